@@ -0,0 +1,17 @@
+        // Must match normalize_answer() and question_key_passphrase() in questions.rs
+        function normalizeAnswer(answer) {
+            return answer
+                .normalize('NFKC')
+                .toLowerCase()
+                .replace(/[^\p{Alphabetic}\p{N}\p{White_Space}]/gu, '')
+                .replace(/\p{White_Space}+/gu, ' ')
+                .trim();
+        }
+
+        function questionKeyPassphrase(answers) {
+            const encoder = new TextEncoder();
+            return answers.map(a => {
+                const normalized = normalizeAnswer(a);
+                return encoder.encode(normalized).length + ':' + normalized;
+            }).join('');
+        }
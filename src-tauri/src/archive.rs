@@ -0,0 +1,271 @@
+use crate::models::{FileAttachment, LegacyDocument};
+use crate::{calendar, markdown, vcard};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use std::io::{Cursor, Read, Write};
+use zip::write::SimpleFileOptions;
+use zip::{AesMode, CompressionMethod, ZipArchive, ZipWriter};
+
+const DOCUMENT_FILE: &str = "document.json";
+const MARKDOWN_FILE: &str = "document.md";
+const CONTACTS_FILE: &str = "contacts.vcf";
+const CALENDAR_FILE: &str = "calendar.ics";
+const README_FILE: &str = "README.txt";
+
+/// Upper bound on the total uncompressed size read back from an archive
+const MAX_UNCOMPRESSED_SIZE: u64 = 512 * 1024 * 1024;
+
+/// Unencrypted so whoever receives the archive knows how to open it. Contains no document data.
+const README: &str = "This archive was created by Honey Did and is protected with AES-256 encryption.\r\n\
+\r\n\
+To open it you need the passphrase chosen when it was exported, and an unzip tool\r\n\
+that supports AES-encrypted ZIP files, such as 7-Zip (Windows), Keka or The\r\n\
+Unarchiver (macOS), or `7z x` on Linux. The built-in Windows and macOS\r\n\
+extractors cannot open AES-encrypted files.\r\n\
+\r\n\
+Contents:\r\n\
+  document.md    - the full document as readable text\r\n\
+  document.json  - the same data in machine-readable form (Honey Did can import this archive)\r\n\
+  attachments/   - the attached files by section, named as listed in document.md\r\n\
+  contacts.vcf   - every contact, for import into an address book\r\n\
+  calendar.ics   - recurring bills and maintenance tasks, for import into a calendar\r\n\
+\r\n\
+Note: file names inside the archive are not encrypted, only their contents, so\r\n\
+attachments are stored under their id instead of their original name.\r\n";
+
+#[derive(Debug)]
+pub enum ArchiveError {
+    ZipError(String),
+    SerializationError(String),
+    MissingFile(String),
+    WrongPassphrase,
+    TooLarge,
+}
+
+impl std::fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArchiveError::ZipError(msg) => write!(f, "Archive error: {}", msg),
+            ArchiveError::SerializationError(msg) => write!(f, "Serialization error: {}", msg),
+            ArchiveError::MissingFile(name) => write!(f, "Archive is missing {}", name),
+            ArchiveError::WrongPassphrase => write!(f, "Decryption failed - incorrect passphrase"),
+            ArchiveError::TooLarge => write!(f, "Archive contents are too large"),
+        }
+    }
+}
+
+impl std::error::Error for ArchiveError {}
+
+impl From<zip::result::ZipError> for ArchiveError {
+    fn from(e: zip::result::ZipError) -> Self {
+        match e {
+            zip::result::ZipError::InvalidPassword => ArchiveError::WrongPassphrase,
+            other => ArchiveError::ZipError(other.to_string()),
+        }
+    }
+}
+
+impl From<std::io::Error> for ArchiveError {
+    fn from(e: std::io::Error) -> Self {
+        ArchiveError::ZipError(e.to_string())
+    }
+}
+
+/// Replaces characters that are unsafe in file names on common platforms
+fn sanitize_file_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| if c.is_control() || matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') { '_' } else { c })
+        .collect();
+    let cleaned = cleaned.trim().trim_start_matches('.');
+    let cleaned: String = cleaned.chars().take(100).collect();
+    if cleaned.is_empty() { "file".to_string() } else { cleaned }
+}
+
+/// Path of an attachment inside the archive. ZIP encryption leaves entry names
+/// readable, so files are named by id and keep only their extension; the
+/// original names are in document.json and document.md.
+pub fn attachment_path(section_key: &str, attachment: &FileAttachment) -> String {
+    let extension = attachment.name.rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+        .filter(|ext| (1..=8).contains(&ext.len()) && ext.chars().all(|c| c.is_ascii_alphanumeric()));
+    match extension {
+        Some(ext) => format!("attachments/{}/{}.{}", section_key, sanitize_file_name(&attachment.id), ext),
+        None => format!("attachments/{}/{}", section_key, sanitize_file_name(&attachment.id)),
+    }
+}
+
+/// Every attachment list in the document with the folder it is stored under
+fn attachment_lists_mut(document: &mut LegacyDocument) -> Vec<(String, &mut Vec<FileAttachment>)> {
+    let mut lists: Vec<(String, &mut Vec<FileAttachment>)> = vec![
+        ("financial".into(), &mut document.financial.attachments),
+        ("insurance".into(), &mut document.insurance.attachments),
+        ("bills".into(), &mut document.bills.attachments),
+        ("property".into(), &mut document.property.attachments),
+        ("legal".into(), &mut document.legal.attachments),
+        ("digital".into(), &mut document.digital.attachments),
+        ("household".into(), &mut document.household.attachments),
+        ("personal".into(), &mut document.personal.attachments),
+        ("contacts".into(), &mut document.contacts.attachments),
+        ("medical".into(), &mut document.medical.attachments),
+        ("pets".into(), &mut document.pets.attachments),
+    ];
+    for section in document.custom_sections.iter_mut() {
+        lists.push((format!("custom/{}", sanitize_file_name(&section.id)), &mut section.attachments));
+    }
+    lists
+}
+
+/// Builds an AES-256 encrypted ZIP with the document as JSON and Markdown,
+/// the original attachment files, a vCard bundle and a calendar file.
+pub fn export_archive(document: &LegacyDocument, passphrase: &str) -> Result<Vec<u8>, ArchiveError> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let plain = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let encrypted = plain.with_aes_encryption(AesMode::Aes256, passphrase);
+
+    zip.start_file(README_FILE, plain)?;
+    zip.write_all(README.as_bytes())?;
+
+    // Attachments are stored as files; the JSON keeps their metadata without the base64 data
+    let mut stripped = document.clone();
+    for (section_key, attachments) in attachment_lists_mut(&mut stripped) {
+        for attachment in attachments.iter_mut() {
+            let bytes = BASE64
+                .decode(&attachment.data)
+                .map_err(|e| ArchiveError::SerializationError(format!("Attachment '{}': {}", attachment.name, e)))?;
+            // Attachments are mostly PDFs and photos, which deflate cannot shrink
+            zip.start_file(attachment_path(&section_key, attachment), encrypted.compression_method(CompressionMethod::Stored))?;
+            zip.write_all(&bytes)?;
+            attachment.data.clear();
+        }
+    }
+
+    let json = serde_json::to_string_pretty(&stripped).map_err(|e| ArchiveError::SerializationError(e.to_string()))?;
+    zip.start_file(DOCUMENT_FILE, encrypted)?;
+    zip.write_all(json.as_bytes())?;
+
+    zip.start_file(MARKDOWN_FILE, encrypted)?;
+    zip.write_all(markdown::render_markdown(document).as_bytes())?;
+
+    let vcf = vcard::document_to_vcf(document);
+    if !vcf.is_empty() {
+        zip.start_file(CONTACTS_FILE, encrypted)?;
+        zip.write_all(vcf.as_bytes())?;
+    }
+
+    let (ics, _) = calendar::generate_ics(document, chrono::Local::now().date_naive());
+    if !ics.is_empty() {
+        zip.start_file(CALENDAR_FILE, encrypted)?;
+        zip.write_all(ics.as_bytes())?;
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
+
+/// Reads one encrypted entry, counting its size against `budget`
+fn read_entry(archive: &mut ZipArchive<Cursor<&[u8]>>, name: &str, passphrase: &str, budget: &mut u64) -> Result<Vec<u8>, ArchiveError> {
+    let file = match archive.by_name_decrypt(name, passphrase.as_bytes()) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Err(ArchiveError::MissingFile(name.to_string())),
+        Err(e) => return Err(e.into()),
+    };
+    let mut bytes = Vec::new();
+    file.take(*budget + 1).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 > *budget {
+        return Err(ArchiveError::TooLarge);
+    }
+    *budget -= bytes.len() as u64;
+    Ok(bytes)
+}
+
+/// Reads a document back from an archive created by `export_archive`
+pub fn import_from_archive(bytes: &[u8], passphrase: &str) -> Result<LegacyDocument, ArchiveError> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+    let mut budget = MAX_UNCOMPRESSED_SIZE;
+
+    let json = read_entry(&mut archive, DOCUMENT_FILE, passphrase, &mut budget)?;
+    let mut document: LegacyDocument =
+        serde_json::from_slice(&json).map_err(|e| ArchiveError::SerializationError(format!("Invalid document format: {}", e)))?;
+
+    for (section_key, attachments) in attachment_lists_mut(&mut document) {
+        for attachment in attachments.iter_mut() {
+            let data = read_entry(&mut archive, &attachment_path(&section_key, attachment), passphrase, &mut budget)?;
+            attachment.size = data.len() as u64;
+            attachment.data = BASE64.encode(data);
+        }
+    }
+
+    Ok(document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Contact, CustomSection};
+
+    fn sample_document() -> LegacyDocument {
+        let mut doc = LegacyDocument::default();
+        doc.meta.creator_name = "Pat".into();
+        doc.contacts.family.push(Contact { name: "Ann Lee".into(), ..Default::default() });
+        doc.legal.attachments.push(FileAttachment {
+            id: "w1".into(),
+            name: "will.pdf".into(),
+            mime_type: "application/pdf".into(),
+            size: 9,
+            data: BASE64.encode(b"%PDF-will"),
+            group: "".into(),
+        });
+        doc.custom_sections.push(CustomSection {
+            id: "c1".into(),
+            name: "Storage".into(),
+            attachments: vec![FileAttachment { id: "k1".into(), name: "../key photo.jpg".into(), data: BASE64.encode([0xff, 0xd8]), ..Default::default() }],
+            ..Default::default()
+        });
+        doc
+    }
+
+    #[test]
+    fn test_archive_roundtrip() {
+        let doc = sample_document();
+        let bytes = export_archive(&doc, "correct horse").unwrap();
+        let imported = import_from_archive(&bytes, "correct horse").unwrap();
+
+        assert_eq!(imported.meta.creator_name, "Pat");
+        assert_eq!(imported.legal.attachments[0].data, doc.legal.attachments[0].data);
+        assert_eq!(imported.custom_sections[0].attachments[0].data, doc.custom_sections[0].attachments[0].data);
+    }
+
+    #[test]
+    fn test_archive_hides_attachment_names() {
+        let mut doc = sample_document();
+        doc.legal.attachments.push(FileAttachment { id: "d1".into(), name: "Divorce settlement".into(), data: BASE64.encode(b"terms"), ..Default::default() });
+        let bytes = export_archive(&doc, "correct horse").unwrap();
+        let archive = ZipArchive::new(Cursor::new(bytes.as_slice())).unwrap();
+        let names: Vec<&str> = archive.file_names().collect();
+        assert!(names.iter().all(|name| !name.contains("will") && !name.contains("key photo") && !name.contains("Divorce")), "{:?}", names);
+        assert!(names.contains(&"attachments/legal/d1"));
+
+        // The original names come back from document.json
+        let imported = import_from_archive(&bytes, "correct horse").unwrap();
+        let imported_names: Vec<&str> = imported.legal.attachments.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(imported_names, ["will.pdf", "Divorce settlement"]);
+        assert_eq!(imported.legal.attachments[1].data, BASE64.encode(b"terms"));
+        assert_eq!(imported.custom_sections[0].attachments[0].name, "../key photo.jpg");
+    }
+
+    #[test]
+    fn test_archive_layout_and_encryption() {
+        let bytes = export_archive(&sample_document(), "correct horse").unwrap();
+        let mut archive = ZipArchive::new(Cursor::new(bytes.as_slice())).unwrap();
+        let names: Vec<String> = archive.file_names().map(String::from).collect();
+
+        assert!(names.contains(&"attachments/legal/w1.pdf".to_string()));
+        assert!(names.contains(&"attachments/custom/c1/k1.jpg".to_string()));
+        assert!(names.contains(&CONTACTS_FILE.to_string()));
+        assert!(!names.contains(&CALENDAR_FILE.to_string()), "no bills or maintenance, no calendar");
+
+        // Everything but the README needs the passphrase
+        assert!(archive.by_name(README_FILE).is_ok());
+        assert!(archive.by_name(DOCUMENT_FILE).is_err());
+        assert!(matches!(import_from_archive(&bytes, "wrong"), Err(ArchiveError::WrongPassphrase)));
+    }
+}
@@ -0,0 +1,290 @@
+;; Argon2id (version 0x13, one lane) for the browser exports.
+;;
+;; Compiled to the checked-in argon2.wasm (test_argon2_wasm_matches_source in
+;; encryption.rs keeps the two in sync), which export.rs embeds in the HTML.
+;; argon2_kdf.js drives it: BLAKE2b (for H0, H' and the final tag) runs
+;; through blake2b_init/blake2b_compress, and fill() does the memory-hard part.
+;; The result must match the argon2 crate byte for byte; both sides are
+;; checked against argon2_kdf_vectors.json.
+;;
+;; Memory layout (byte offsets):
+;;   0      BLAKE2b state h, 8 x i64
+;;   64     BLAKE2b message block, 128 bytes
+;;   192    BLAKE2b IV, 8 x i64
+;;   256    BLAKE2b message schedule, 12 rounds x 16 word offsets
+;;   512    BLAKE2b working vector, 16 x i64
+;;   1024   compression scratch R
+;;   2048   compression scratch (R, then R xor the old block)
+;;   3072   zero block for address generation
+;;   4096   address generation input block
+;;   5120   address block
+;;   65536  Argon2 memory, one 1 KiB block after another
+(module
+  (memory (export "memory") 2)
+
+  (data (i32.const 256)
+    "\00\08\10\18\20\28\30\38\40\48\50\58\60\68\70\78"
+    "\70\50\20\40\48\78\68\30\08\60\00\10\58\38\28\18"
+    "\58\40\60\00\28\10\78\68\50\70\18\30\38\08\48\20"
+    "\38\48\18\08\68\60\58\70\10\30\28\50\20\00\78\40"
+    "\48\00\28\38\10\20\50\78\70\08\58\60\30\40\18\68"
+    "\10\60\30\50\00\58\40\18\20\68\38\28\78\70\08\48"
+    "\60\28\08\78\70\68\20\50\00\38\30\18\48\10\40\58"
+    "\68\58\38\70\60\08\18\48\28\00\78\20\40\30\10\50"
+    "\30\78\70\48\58\18\00\40\60\10\68\38\08\20\50\28"
+    "\50\10\40\20\38\30\08\28\78\58\48\70\18\60\68\00"
+    "\00\08\10\18\20\28\30\38\40\48\50\58\60\68\70\78"
+    "\70\50\20\40\48\78\68\30\08\60\00\10\58\38\28\18")
+
+  ;; Starts an unkeyed BLAKE2b hash with the given digest length (1-64)
+  (func (export "blake2b_init") (param $outlen i32)
+    (i64.store (i32.const 192) (i64.const 0x6a09e667f3bcc908))
+    (i64.store (i32.const 200) (i64.const 0xbb67ae8584caa73b))
+    (i64.store (i32.const 208) (i64.const 0x3c6ef372fe94f82b))
+    (i64.store (i32.const 216) (i64.const 0xa54ff53a5f1d36f1))
+    (i64.store (i32.const 224) (i64.const 0x510e527fade682d1))
+    (i64.store (i32.const 232) (i64.const 0x9b05688c2b3e6c1f))
+    (i64.store (i32.const 240) (i64.const 0x1f83d9abfb41bd6b))
+    (i64.store (i32.const 248) (i64.const 0x5be0cd19137e2179))
+    (memory.copy (i32.const 0) (i32.const 192) (i32.const 64))
+    (i64.store (i32.const 0)
+      (i64.xor (i64.load (i32.const 0))
+               (i64.extend_i32_u (i32.or (i32.const 0x01010000) (local.get $outlen))))))
+
+  ;; BLAKE2b mixing function on working-vector words a, b, c, d
+  (func $g (param $a i32) (param $b i32) (param $c i32) (param $d i32) (param $x i64) (param $y i64)
+    (local $va i64) (local $vb i64) (local $vc i64) (local $vd i64)
+    (local.set $va (i64.load (local.get $a)))
+    (local.set $vb (i64.load (local.get $b)))
+    (local.set $vc (i64.load (local.get $c)))
+    (local.set $vd (i64.load (local.get $d)))
+    (local.set $va (i64.add (i64.add (local.get $va) (local.get $vb)) (local.get $x)))
+    (local.set $vd (i64.rotr (i64.xor (local.get $vd) (local.get $va)) (i64.const 32)))
+    (local.set $vc (i64.add (local.get $vc) (local.get $vd)))
+    (local.set $vb (i64.rotr (i64.xor (local.get $vb) (local.get $vc)) (i64.const 24)))
+    (local.set $va (i64.add (i64.add (local.get $va) (local.get $vb)) (local.get $y)))
+    (local.set $vd (i64.rotr (i64.xor (local.get $vd) (local.get $va)) (i64.const 16)))
+    (local.set $vc (i64.add (local.get $vc) (local.get $vd)))
+    (local.set $vb (i64.rotr (i64.xor (local.get $vb) (local.get $vc)) (i64.const 63)))
+    (i64.store (local.get $a) (local.get $va))
+    (i64.store (local.get $b) (local.get $vb))
+    (i64.store (local.get $c) (local.get $vc))
+    (i64.store (local.get $d) (local.get $vd)))
+
+  ;; Message word j of round r's schedule
+  (func $msg (param $r i32) (param $j i32) (result i64)
+    (i64.load
+      (i32.add (i32.const 64)
+        (i32.load8_u (i32.add (i32.const 256) (i32.add (i32.shl (local.get $r) (i32.const 4)) (local.get $j)))))))
+
+  ;; Compresses the message block at 64 into h. $counter is the number of
+  ;; bytes hashed so far including this block; $last marks the final block.
+  (func (export "blake2b_compress") (param $counter i32) (param $last i32)
+    (local $r i32)
+    (memory.copy (i32.const 512) (i32.const 0) (i32.const 64))
+    (memory.copy (i32.const 576) (i32.const 192) (i32.const 64))
+    (i64.store (i32.const 608)
+      (i64.xor (i64.load (i32.const 608)) (i64.extend_i32_u (local.get $counter))))
+    (if (local.get $last)
+      (then (i64.store (i32.const 624) (i64.xor (i64.load (i32.const 624)) (i64.const -1)))))
+    (local.set $r (i32.const 0))
+    (loop $rounds
+      (call $g (i32.const 512) (i32.const 544) (i32.const 576) (i32.const 608)
+        (call $msg (local.get $r) (i32.const 0)) (call $msg (local.get $r) (i32.const 1)))
+      (call $g (i32.const 520) (i32.const 552) (i32.const 584) (i32.const 616)
+        (call $msg (local.get $r) (i32.const 2)) (call $msg (local.get $r) (i32.const 3)))
+      (call $g (i32.const 528) (i32.const 560) (i32.const 592) (i32.const 624)
+        (call $msg (local.get $r) (i32.const 4)) (call $msg (local.get $r) (i32.const 5)))
+      (call $g (i32.const 536) (i32.const 568) (i32.const 600) (i32.const 632)
+        (call $msg (local.get $r) (i32.const 6)) (call $msg (local.get $r) (i32.const 7)))
+      (call $g (i32.const 512) (i32.const 552) (i32.const 592) (i32.const 632)
+        (call $msg (local.get $r) (i32.const 8)) (call $msg (local.get $r) (i32.const 9)))
+      (call $g (i32.const 520) (i32.const 560) (i32.const 600) (i32.const 608)
+        (call $msg (local.get $r) (i32.const 10)) (call $msg (local.get $r) (i32.const 11)))
+      (call $g (i32.const 528) (i32.const 568) (i32.const 576) (i32.const 616)
+        (call $msg (local.get $r) (i32.const 12)) (call $msg (local.get $r) (i32.const 13)))
+      (call $g (i32.const 536) (i32.const 544) (i32.const 584) (i32.const 624)
+        (call $msg (local.get $r) (i32.const 14)) (call $msg (local.get $r) (i32.const 15)))
+      (local.set $r (i32.add (local.get $r) (i32.const 1)))
+      (br_if $rounds (i32.lt_u (local.get $r) (i32.const 12))))
+    (local.set $r (i32.const 0))
+    (loop $fold
+      (i64.store (local.get $r)
+        (i64.xor (i64.load (local.get $r))
+          (i64.xor (i64.load (i32.add (i32.const 512) (local.get $r)))
+                   (i64.load (i32.add (i32.const 576) (local.get $r))))))
+      (local.set $r (i32.add (local.get $r) (i32.const 8)))
+      (br_if $fold (i32.lt_u (local.get $r) (i32.const 64)))))
+
+  ;; x + y + 2 * lo32(x) * lo32(y), Argon2's multiplication-hardened addition
+  (func $blamka (param $x i64) (param $y i64) (result i64)
+    (i64.add (i64.add (local.get $x) (local.get $y))
+      (i64.shl
+        (i64.mul (i64.and (local.get $x) (i64.const 0xffffffff))
+                 (i64.and (local.get $y) (i64.const 0xffffffff)))
+        (i64.const 1))))
+
+  (func $gb (param $a i32) (param $b i32) (param $c i32) (param $d i32)
+    (local $va i64) (local $vb i64) (local $vc i64) (local $vd i64)
+    (local.set $va (i64.load (local.get $a)))
+    (local.set $vb (i64.load (local.get $b)))
+    (local.set $vc (i64.load (local.get $c)))
+    (local.set $vd (i64.load (local.get $d)))
+    (local.set $va (call $blamka (local.get $va) (local.get $vb)))
+    (local.set $vd (i64.rotr (i64.xor (local.get $vd) (local.get $va)) (i64.const 32)))
+    (local.set $vc (call $blamka (local.get $vc) (local.get $vd)))
+    (local.set $vb (i64.rotr (i64.xor (local.get $vb) (local.get $vc)) (i64.const 24)))
+    (local.set $va (call $blamka (local.get $va) (local.get $vb)))
+    (local.set $vd (i64.rotr (i64.xor (local.get $vd) (local.get $va)) (i64.const 16)))
+    (local.set $vc (call $blamka (local.get $vc) (local.get $vd)))
+    (local.set $vb (i64.rotr (i64.xor (local.get $vb) (local.get $vc)) (i64.const 63)))
+    (i64.store (local.get $a) (local.get $va))
+    (i64.store (local.get $b) (local.get $vb))
+    (i64.store (local.get $c) (local.get $vc))
+    (i64.store (local.get $d) (local.get $vd)))
+
+  ;; BLAKE2 round without message on 16 words, taken as 8 pairs of adjacent
+  ;; words $stride bytes apart: 16 for a row of the block, 128 for a column
+  (func $permute (param $base i32) (param $stride i32)
+    (local $w0 i32) (local $w2 i32) (local $w4 i32) (local $w6 i32)
+    (local $w8 i32) (local $w10 i32) (local $w12 i32) (local $w14 i32)
+    (local.set $w0 (local.get $base))
+    (local.set $w2 (i32.add (local.get $w0) (local.get $stride)))
+    (local.set $w4 (i32.add (local.get $w2) (local.get $stride)))
+    (local.set $w6 (i32.add (local.get $w4) (local.get $stride)))
+    (local.set $w8 (i32.add (local.get $w6) (local.get $stride)))
+    (local.set $w10 (i32.add (local.get $w8) (local.get $stride)))
+    (local.set $w12 (i32.add (local.get $w10) (local.get $stride)))
+    (local.set $w14 (i32.add (local.get $w12) (local.get $stride)))
+    (call $gb (local.get $w0) (local.get $w4)
+              (local.get $w8) (local.get $w12))
+    (call $gb (i32.add (local.get $w0) (i32.const 8)) (i32.add (local.get $w4) (i32.const 8))
+              (i32.add (local.get $w8) (i32.const 8)) (i32.add (local.get $w12) (i32.const 8)))
+    (call $gb (local.get $w2) (local.get $w6)
+              (local.get $w10) (local.get $w14))
+    (call $gb (i32.add (local.get $w2) (i32.const 8)) (i32.add (local.get $w6) (i32.const 8))
+              (i32.add (local.get $w10) (i32.const 8)) (i32.add (local.get $w14) (i32.const 8)))
+    (call $gb (local.get $w0) (i32.add (local.get $w4) (i32.const 8))
+              (local.get $w10) (i32.add (local.get $w14) (i32.const 8)))
+    (call $gb (i32.add (local.get $w0) (i32.const 8)) (local.get $w6)
+              (i32.add (local.get $w10) (i32.const 8)) (local.get $w12))
+    (call $gb (local.get $w2) (i32.add (local.get $w6) (i32.const 8))
+              (local.get $w8) (i32.add (local.get $w12) (i32.const 8)))
+    (call $gb (i32.add (local.get $w2) (i32.const 8)) (local.get $w4)
+              (i32.add (local.get $w8) (i32.const 8)) (local.get $w14)))
+
+  ;; Argon2 compression G: next = P(prev xor ref) xor prev xor ref, further
+  ;; xored with the old contents of next on passes after the first
+  (func $fill_block (param $prev i32) (param $ref i32) (param $next i32) (param $with_xor i32)
+    (local $k i32) (local $r i64)
+    (local.set $k (i32.const 0))
+    (loop $load
+      (local.set $r
+        (i64.xor (i64.load (i32.add (local.get $prev) (local.get $k)))
+                 (i64.load (i32.add (local.get $ref) (local.get $k)))))
+      (i64.store (i32.add (i32.const 1024) (local.get $k)) (local.get $r))
+      (if (local.get $with_xor)
+        (then (local.set $r (i64.xor (local.get $r) (i64.load (i32.add (local.get $next) (local.get $k)))))))
+      (i64.store (i32.add (i32.const 2048) (local.get $k)) (local.get $r))
+      (local.set $k (i32.add (local.get $k) (i32.const 8)))
+      (br_if $load (i32.lt_u (local.get $k) (i32.const 1024))))
+    (local.set $k (i32.const 0))
+    (loop $rows
+      (call $permute (i32.add (i32.const 1024) (i32.shl (local.get $k) (i32.const 7))) (i32.const 16))
+      (local.set $k (i32.add (local.get $k) (i32.const 1)))
+      (br_if $rows (i32.lt_u (local.get $k) (i32.const 8))))
+    (local.set $k (i32.const 0))
+    (loop $columns
+      (call $permute (i32.add (i32.const 1024) (i32.shl (local.get $k) (i32.const 4))) (i32.const 128))
+      (local.set $k (i32.add (local.get $k) (i32.const 1)))
+      (br_if $columns (i32.lt_u (local.get $k) (i32.const 8))))
+    (local.set $k (i32.const 0))
+    (loop $store
+      (i64.store (i32.add (local.get $next) (local.get $k))
+        (i64.xor (i64.load (i32.add (i32.const 1024) (local.get $k)))
+                 (i64.load (i32.add (i32.const 2048) (local.get $k)))))
+      (local.set $k (i32.add (local.get $k) (i32.const 8)))
+      (br_if $store (i32.lt_u (local.get $k) (i32.const 1024)))))
+
+  ;; Next 128 reference positions for the data-independent half of Argon2id
+  (func $next_addresses
+    (i64.store (i32.const 4144) (i64.add (i64.load (i32.const 4144)) (i64.const 1)))
+    (call $fill_block (i32.const 3072) (i32.const 4096) (i32.const 5120) (i32.const 0))
+    (call $fill_block (i32.const 3072) (i32.const 5120) (i32.const 5120) (i32.const 0)))
+
+  (func $block (param $index i32) (result i32)
+    (i32.add (i32.const 65536) (i32.shl (local.get $index) (i32.const 10))))
+
+  ;; Fills $blocks blocks (a multiple of 4) over $passes passes. The caller
+  ;; has already grown memory and written blocks 0 and 1 from H0.
+  (func (export "fill") (param $blocks i32) (param $passes i32)
+    (local $segment i32) (local $pass i32) (local $slice i32) (local $i i32)
+    (local $independent i32) (local $curr i32) (local $prev i32)
+    (local $rand i64) (local $area i64) (local $rel i64) (local $start i32)
+    (local.set $segment (i32.shr_u (local.get $blocks) (i32.const 2)))
+    (memory.fill (i32.const 3072) (i32.const 0) (i32.const 1024))
+    (local.set $pass (i32.const 0))
+    (loop $passes_loop
+      (local.set $slice (i32.const 0))
+      (loop $slices_loop
+        (local.set $independent
+          (i32.and (i32.eqz (local.get $pass)) (i32.lt_u (local.get $slice) (i32.const 2))))
+        (if (local.get $independent)
+          (then
+            (memory.fill (i32.const 4096) (i32.const 0) (i32.const 1024))
+            (i64.store (i32.const 4096) (i64.extend_i32_u (local.get $pass)))
+            (i64.store (i32.const 4112) (i64.extend_i32_u (local.get $slice)))
+            (i64.store (i32.const 4120) (i64.extend_i32_u (local.get $blocks)))
+            (i64.store (i32.const 4128) (i64.extend_i32_u (local.get $passes)))
+            (i64.store (i32.const 4136) (i64.const 2))))
+        (local.set $i (i32.const 0))
+        (if (i32.and (i32.eqz (local.get $pass)) (i32.eqz (local.get $slice)))
+          (then
+            (local.set $i (i32.const 2))
+            (if (local.get $independent) (then (call $next_addresses)))))
+        (block $segment_done
+          (br_if $segment_done (i32.ge_u (local.get $i) (local.get $segment)))
+          (loop $blocks_loop
+            (local.set $curr (i32.add (i32.mul (local.get $slice) (local.get $segment)) (local.get $i)))
+            (local.set $prev
+              (select (i32.sub (local.get $blocks) (i32.const 1))
+                      (i32.sub (local.get $curr) (i32.const 1))
+                      (i32.eqz (local.get $curr))))
+            (if (local.get $independent)
+              (then
+                (if (i32.eqz (i32.and (local.get $i) (i32.const 127)))
+                  (then (call $next_addresses)))
+                (local.set $rand
+                  (i64.load (i32.add (i32.const 5120) (i32.shl (i32.and (local.get $i) (i32.const 127)) (i32.const 3))))))
+              (else
+                (local.set $rand (i64.load (call $block (local.get $prev))))))
+            ;; Blocks this one may reference: everything finished so far in
+            ;; the first pass, otherwise the last three segments
+            (local.set $area
+              (i64.extend_i32_u
+                (if (result i32) (i32.eqz (local.get $pass))
+                  (then (i32.sub (i32.add (i32.mul (local.get $slice) (local.get $segment)) (local.get $i)) (i32.const 1)))
+                  (else (i32.sub (i32.add (i32.sub (local.get $blocks) (local.get $segment)) (local.get $i)) (i32.const 1))))))
+            (local.set $rel (i64.and (local.get $rand) (i64.const 0xffffffff)))
+            (local.set $rel (i64.shr_u (i64.mul (local.get $rel) (local.get $rel)) (i64.const 32)))
+            (local.set $rel
+              (i64.sub (i64.sub (local.get $area) (i64.const 1))
+                       (i64.shr_u (i64.mul (local.get $area) (local.get $rel)) (i64.const 32))))
+            (local.set $start
+              (if (result i32) (i32.or (i32.eqz (local.get $pass)) (i32.eq (local.get $slice) (i32.const 3)))
+                (then (i32.const 0))
+                (else (i32.mul (i32.add (local.get $slice) (i32.const 1)) (local.get $segment)))))
+            (call $fill_block
+              (call $block (local.get $prev))
+              (call $block
+                (i32.wrap_i64
+                  (i64.rem_u (i64.add (i64.extend_i32_u (local.get $start)) (local.get $rel))
+                             (i64.extend_i32_u (local.get $blocks)))))
+              (call $block (local.get $curr))
+              (i32.ne (local.get $pass) (i32.const 0)))
+            (local.set $i (i32.add (local.get $i) (i32.const 1)))
+            (br_if $blocks_loop (i32.lt_u (local.get $i) (local.get $segment)))))
+        (local.set $slice (i32.add (local.get $slice) (i32.const 1)))
+        (br_if $slices_loop (i32.lt_u (local.get $slice) (i32.const 4))))
+      (local.set $pass (i32.add (local.get $pass) (i32.const 1)))
+      (br_if $passes_loop (i32.lt_u (local.get $pass) (local.get $passes))))))
@@ -0,0 +1,108 @@
+        // Key derivation for exported files. Files record their KDF; those
+        // without one are PBKDF2. Argon2id runs in the embedded WebAssembly
+        // module (argon2.wat) and must match encryption.rs derive_browser_key,
+        // which argon2_kdf_vectors.json checks on both sides.
+        const PBKDF2_ITERATIONS = 600000;
+        const ARGON2_MEMORY_OFFSET = 65536;
+        const ARGON2_BLOCK_SIZE = 1024;
+        let argon2Exports = null;
+
+        async function loadArgon2() {
+            if (!argon2Exports) {
+                const bytes = Uint8Array.from(atob(ARGON2_WASM), c => c.charCodeAt(0));
+                argon2Exports = (await WebAssembly.instantiate(bytes)).instance.exports;
+            }
+            return argon2Exports;
+        }
+
+        function le32(value) {
+            const bytes = new Uint8Array(4);
+            new DataView(bytes.buffer).setUint32(0, value, true);
+            return bytes;
+        }
+
+        function concatBytes(...parts) {
+            const out = new Uint8Array(parts.reduce((n, p) => n + p.length, 0));
+            let offset = 0;
+            for (const part of parts) {
+                out.set(part, offset);
+                offset += part.length;
+            }
+            return out;
+        }
+
+        function blake2b(wasm, input, outLength) {
+            wasm.blake2b_init(outLength);
+            let offset = 0;
+            do {
+                const memory = new Uint8Array(wasm.memory.buffer);
+                const chunk = input.subarray(offset, offset + 128);
+                memory.fill(0, 64, 192);
+                memory.set(chunk, 64);
+                offset += chunk.length;
+                wasm.blake2b_compress(offset, offset >= input.length ? 1 : 0);
+            } while (offset < input.length);
+            return new Uint8Array(wasm.memory.buffer).slice(0, outLength);
+        }
+
+        // Argon2's variable-length hash H'
+        function argon2Hash(wasm, input, outLength) {
+            const prefixed = concatBytes(le32(outLength), input);
+            if (outLength <= 64) return blake2b(wasm, prefixed, outLength);
+            const out = new Uint8Array(outLength);
+            let v = blake2b(wasm, prefixed, 64);
+            out.set(v.subarray(0, 32), 0);
+            let position = 32;
+            while (outLength - position > 64) {
+                v = blake2b(wasm, v, 64);
+                out.set(v.subarray(0, 32), position);
+                position += 32;
+            }
+            out.set(blake2b(wasm, v, outLength - position), position);
+            return out;
+        }
+
+        async function argon2id(password, salt, kdf) {
+            if (kdf.parallelism !== 1) throw new Error('Unsupported Argon2id parallelism');
+            const wasm = await loadArgon2();
+            const blocks = kdf.memory_kib - kdf.memory_kib % 4;
+            const needed = ARGON2_MEMORY_OFFSET + blocks * ARGON2_BLOCK_SIZE;
+            const available = wasm.memory.buffer.byteLength;
+            if (available < needed) wasm.memory.grow(Math.ceil((needed - available) / 65536));
+
+            const h0 = blake2b(wasm, concatBytes(
+                le32(kdf.parallelism), le32(32), le32(kdf.memory_kib), le32(kdf.iterations), le32(0x13), le32(2),
+                le32(password.length), password, le32(salt.length), salt, le32(0), le32(0)
+            ), 64);
+            const first = argon2Hash(wasm, concatBytes(h0, le32(0), le32(0)), ARGON2_BLOCK_SIZE);
+            const second = argon2Hash(wasm, concatBytes(h0, le32(1), le32(0)), ARGON2_BLOCK_SIZE);
+            let memory = new Uint8Array(wasm.memory.buffer);
+            memory.set(first, ARGON2_MEMORY_OFFSET);
+            memory.set(second, ARGON2_MEMORY_OFFSET + ARGON2_BLOCK_SIZE);
+
+            wasm.fill(blocks, kdf.iterations);
+
+            memory = new Uint8Array(wasm.memory.buffer);
+            const lastBlock = memory.slice(needed - ARGON2_BLOCK_SIZE, needed);
+            // Wipe the scratch blocks and Argon2 memory, keeping the BLAKE2b tables
+            memory.fill(0, ARGON2_BLOCK_SIZE);
+            return argon2Hash(wasm, lastBlock, 32);
+        }
+
+        async function deriveKey(passphrase, salt, kdf) {
+            const encoded = new TextEncoder().encode(passphrase);
+            if (kdf && kdf.algorithm === 'argon2id') {
+                const raw = await argon2id(encoded, salt, kdf);
+                return await crypto.subtle.importKey('raw', raw, { name: 'AES-GCM' }, false, ['decrypt']);
+            }
+            const keyMaterial = await crypto.subtle.importKey(
+                'raw', encoded, 'PBKDF2', false, ['deriveKey']
+            );
+            return await crypto.subtle.deriveKey(
+                { name: 'PBKDF2', salt: salt, iterations: PBKDF2_ITERATIONS, hash: 'SHA-256' },
+                keyMaterial,
+                { name: 'AES-GCM', length: 256 },
+                false,
+                ['decrypt']
+            );
+        }
@@ -0,0 +1,499 @@
+use crate::delivery::{self, DeliveryError};
+use crate::encryption::BrowserKdf;
+use crate::export::{self, ExportError};
+use crate::i18n::Locale;
+use crate::storage::{self, StorageError};
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+// Automatic re-export: after the document changes and then stays untouched
+// for a while, the app writes a fresh encrypted export into a chosen folder,
+// such as one a sync client shares. Older automatic exports beyond the
+// retention count are deleted; nothing else in the folder is touched.
+
+/// Longest wait after the last change, one day
+pub const MAX_DEBOUNCE_MINUTES: u32 = 24 * 60;
+
+/// Most automatic exports kept in the folder
+pub const MAX_KEEP: u32 = 100;
+
+/// Automatic exports are recognised by this prefix when old ones are pruned
+const FILE_PREFIX: &str = "honey-did-auto-";
+
+/// How often the background scheduler looks for pending changes
+const SCHEDULER_INTERVAL: Duration = Duration::from_secs(60);
+
+/// When the document last changed since the previous automatic export
+static LAST_CHANGE: Mutex<Option<DateTime<Utc>>> = Mutex::new(None);
+
+/// Why the last change couldn't be recorded, for when the stored settings
+/// can't take `last_error` either
+static CHANGE_ERROR: Mutex<Option<String>> = Mutex::new(None);
+
+#[derive(Debug)]
+pub enum AutoExportError {
+    NotConfigured,
+    InvalidSettings(String),
+    StorageError(StorageError),
+    ExportError(ExportError),
+    DeliveryError(DeliveryError),
+}
+
+impl std::fmt::Display for AutoExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AutoExportError::NotConfigured => write!(f, "Automatic export is not set up"),
+            AutoExportError::InvalidSettings(e) => write!(f, "{}", e),
+            AutoExportError::StorageError(e) => write!(f, "{}", e),
+            AutoExportError::ExportError(e) => write!(f, "Export failed: {}", e),
+            AutoExportError::DeliveryError(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for AutoExportError {}
+
+impl From<StorageError> for AutoExportError {
+    fn from(e: StorageError) -> Self {
+        AutoExportError::StorageError(e)
+    }
+}
+
+impl From<ExportError> for AutoExportError {
+    fn from(e: ExportError) -> Self {
+        AutoExportError::ExportError(e)
+    }
+}
+
+impl From<DeliveryError> for AutoExportError {
+    fn from(e: DeliveryError) -> Self {
+        AutoExportError::DeliveryError(e)
+    }
+}
+
+/// How often a changed document may be exported again
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoExportSchedule {
+    /// Every time the document settles after a change
+    AfterChanges,
+    /// At most once a day
+    Daily,
+    /// At most once a week
+    Weekly,
+}
+
+impl AutoExportSchedule {
+    fn min_gap(self) -> TimeDelta {
+        match self {
+            AutoExportSchedule::AfterChanges => TimeDelta::zero(),
+            AutoExportSchedule::Daily => TimeDelta::days(1),
+            AutoExportSchedule::Weekly => TimeDelta::days(7),
+        }
+    }
+}
+
+/// Settings from the settings screen. A missing passphrase keeps the stored one.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AutoExportSettings {
+    pub enabled: bool,
+    pub folder: String,
+    pub schedule: AutoExportSchedule,
+    pub debounce_minutes: u32,
+    pub keep: u32,
+    pub passphrase: Option<String>,
+    #[serde(default)]
+    pub memory_hard_kdf: bool,
+    /// Language of the exported file, English when unset
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// The user saw the weak-passphrase warning and wants this passphrase anyway
+    #[serde(default)]
+    pub acknowledge_weak: bool,
+}
+
+/// The configuration as stored, encrypted with the local key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoExport {
+    pub enabled: bool,
+    pub folder: String,
+    pub schedule: AutoExportSchedule,
+    pub debounce_minutes: u32,
+    pub keep: u32,
+    /// Exports run unattended, so the passphrase is kept wrapped by the local key
+    passphrase: String,
+    pub memory_hard_kdf: bool,
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// RFC 3339 time of the last automatic export
+    pub last_export: Option<String>,
+    pub last_file: Option<String>,
+    pub last_error: Option<String>,
+    /// A change is waiting to be exported; survives restarts
+    #[serde(default)]
+    pub pending: bool,
+}
+
+/// What the settings screen shows; never the passphrase
+#[derive(Debug, Clone, Serialize)]
+pub struct AutoExportStatus {
+    pub enabled: bool,
+    pub folder: String,
+    pub schedule: AutoExportSchedule,
+    pub debounce_minutes: u32,
+    pub keep: u32,
+    pub memory_hard_kdf: bool,
+    pub locale: Option<String>,
+    pub last_export: Option<String>,
+    pub last_file: Option<String>,
+    pub last_error: Option<String>,
+    pub pending: bool,
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value).ok().map(|t| t.with_timezone(&Utc))
+}
+
+/// Names sort by time, so pruning can go by name alone
+fn file_name(now: DateTime<Utc>) -> String {
+    format!("{}{}.html", FILE_PREFIX, now.format("%Y%m%d-%H%M%SZ"))
+}
+
+/// Deletes all but the newest `keep` automatic exports, returning how many went
+fn prune(folder: &Path, keep: u32) -> Result<usize, AutoExportError> {
+    let mut exports: Vec<_> = fs::read_dir(folder)
+        .map_err(|e| DeliveryError::IoError(e.to_string()))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.starts_with(FILE_PREFIX) && name.ends_with(".html"))
+        .collect();
+    exports.sort();
+    let excess = exports.len().saturating_sub(keep as usize);
+    for name in &exports[..excess] {
+        fs::remove_file(folder.join(name)).map_err(|e| DeliveryError::IoError(e.to_string()))?;
+    }
+    Ok(excess)
+}
+
+impl AutoExport {
+    pub fn configure(existing: Option<AutoExport>, settings: AutoExportSettings) -> Result<Self, AutoExportError> {
+        if settings.debounce_minutes > MAX_DEBOUNCE_MINUTES {
+            return Err(AutoExportError::InvalidSettings(format!(
+                "Wait at most {} minutes after a change",
+                MAX_DEBOUNCE_MINUTES
+            )));
+        }
+        if settings.keep == 0 || settings.keep > MAX_KEEP {
+            return Err(AutoExportError::InvalidSettings(format!("Keep between 1 and {} exports", MAX_KEEP)));
+        }
+        if settings.folder.trim().is_empty() || !Path::new(settings.folder.trim()).is_dir() {
+            return Err(AutoExportError::InvalidSettings(format!("{} is not a folder", settings.folder)));
+        }
+        Locale::for_export(settings.locale.as_deref()).map_err(|e| AutoExportError::InvalidSettings(e.to_string()))?;
+        let passphrase = match (settings.passphrase.filter(|p| !p.is_empty()), &existing) {
+            (Some(passphrase), _) => passphrase,
+            (None, Some(existing)) => existing.passphrase.clone(),
+            (None, None) => {
+                return Err(AutoExportError::InvalidSettings("A passphrase is required for the exports it writes".into()))
+            }
+        };
+        Ok(AutoExport {
+            enabled: settings.enabled,
+            folder: settings.folder.trim().to_string(),
+            schedule: settings.schedule,
+            debounce_minutes: settings.debounce_minutes,
+            keep: settings.keep,
+            passphrase,
+            memory_hard_kdf: settings.memory_hard_kdf,
+            locale: settings.locale,
+            last_export: existing.as_ref().and_then(|e| e.last_export.clone()),
+            last_file: existing.as_ref().and_then(|e| e.last_file.clone()),
+            last_error: None,
+            // A new setup exports what is already there
+            pending: existing.is_none_or(|e| e.pending),
+        })
+    }
+
+    /// Due once the document has been quiet for the debounce period and the
+    /// schedule allows another export
+    pub fn is_due(&self, now: DateTime<Utc>, last_change: Option<DateTime<Utc>>) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let settled = match last_change {
+            Some(changed) => now - changed >= TimeDelta::minutes(i64::from(self.debounce_minutes)),
+            None => self.pending,
+        };
+        let allowed = self
+            .last_export
+            .as_deref()
+            .and_then(parse_time)
+            .is_none_or(|last| now - last >= self.schedule.min_gap());
+        settled && allowed
+    }
+
+    pub fn status(&self) -> AutoExportStatus {
+        AutoExportStatus {
+            enabled: self.enabled,
+            folder: self.folder.clone(),
+            schedule: self.schedule,
+            debounce_minutes: self.debounce_minutes,
+            keep: self.keep,
+            memory_hard_kdf: self.memory_hard_kdf,
+            locale: self.locale.clone(),
+            last_export: self.last_export.clone(),
+            last_file: self.last_file.clone(),
+            last_error: self.last_error.clone().or_else(|| CHANGE_ERROR.lock().unwrap_or_else(|e| e.into_inner()).clone()),
+            pending: self.pending,
+        }
+    }
+
+    /// Records a change for the scheduler. Returns whether the stored copy needs saving.
+    fn mark_changed(&mut self) -> bool {
+        if self.enabled && !self.pending {
+            self.pending = true;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Writes a fresh export into the folder and prunes old ones
+    pub fn export_now(&mut self, now: DateTime<Utc>) -> Result<String, AutoExportError> {
+        let folder = Path::new(&self.folder);
+        if !folder.is_dir() {
+            return Err(AutoExportError::InvalidSettings(format!("{} is not a folder", self.folder)));
+        }
+        let document = storage::load_document()?.unwrap_or_default();
+        let kdf = BrowserKdf::for_export(self.memory_hard_kdf);
+        let theme = storage::load_export_theme()?;
+        let locale = Locale::for_export(self.locale.as_deref()).map_err(|e| AutoExportError::InvalidSettings(e.to_string()))?;
+        let html = export::generate_encrypted_html(&document, &self.passphrase, true, kdf, &theme, &locale)?;
+        let html = storage::get_or_create_signing_key()?.sign_html(&html).map_err(ExportError::from)?;
+
+        let name = file_name(now);
+        delivery::write_atomically(&folder.join(&name), html.as_bytes())?;
+        prune(folder, self.keep)?;
+
+        self.last_export = Some(now.to_rfc3339());
+        self.last_file = Some(name.clone());
+        self.last_error = None;
+        self.pending = false;
+        Ok(format!("Saved {} to {}", name, folder.display()))
+    }
+}
+
+/// Called whenever the document is saved, imports included; the scheduler
+/// exports it later. A failure is kept as the status's `last_error`.
+pub fn document_changed(now: DateTime<Utc>) -> Result<(), AutoExportError> {
+    let first_change = {
+        let mut last_change = LAST_CHANGE.lock().unwrap_or_else(|e| e.into_inner());
+        last_change.replace(now).is_none()
+    };
+    // Only the first change after an export touches the disk
+    if !first_change {
+        return Ok(());
+    }
+    let result = mark_stored_changed();
+    if let Err(e) = &result {
+        record_change_error(e);
+    }
+    result
+}
+
+fn mark_stored_changed() -> Result<(), AutoExportError> {
+    if let Some(mut auto_export) = storage::load_auto_export()? {
+        if auto_export.mark_changed() {
+            storage::save_auto_export(&auto_export)?;
+        }
+    }
+    Ok(())
+}
+
+fn record_change_error(e: &AutoExportError) {
+    let message = format!("Could not record a change: {}", e);
+    *CHANGE_ERROR.lock().unwrap_or_else(|e| e.into_inner()) = Some(message.clone());
+    // Best effort: the stored settings may be what failed
+    if let Ok(Some(mut auto_export)) = storage::load_auto_export() {
+        auto_export.last_error = Some(message);
+        let _ = storage::save_auto_export(&auto_export);
+    }
+}
+
+/// Exports if a change has settled, recording the outcome. Returns where the
+/// export went, or `None` when nothing was due.
+pub fn run_if_due(now: DateTime<Utc>) -> Result<Option<String>, AutoExportError> {
+    let last_change = *LAST_CHANGE.lock().unwrap_or_else(|e| e.into_inner());
+    match storage::load_auto_export()? {
+        Some(auto_export) if auto_export.is_due(now, last_change) => {
+            export_and_record(auto_export, last_change, now).map(|(_, sent)| Some(sent))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Exports straight away, as the "Export now" button does
+pub fn run_now(now: DateTime<Utc>) -> Result<AutoExportStatus, AutoExportError> {
+    let last_change = *LAST_CHANGE.lock().unwrap_or_else(|e| e.into_inner());
+    let auto_export = storage::load_auto_export()?.ok_or(AutoExportError::NotConfigured)?;
+    export_and_record(auto_export, last_change, now).map(|(status, _)| status)
+}
+
+fn export_and_record(
+    mut auto_export: AutoExport,
+    last_change: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Result<(AutoExportStatus, String), AutoExportError> {
+    let result = auto_export.export_now(now);
+    match &result {
+        Ok(_) => {
+            *CHANGE_ERROR.lock().unwrap_or_else(|e| e.into_inner()) = None;
+            // Keep a change that arrived while exporting for the next round
+            let mut latest = LAST_CHANGE.lock().unwrap_or_else(|e| e.into_inner());
+            if *latest == last_change {
+                *latest = None;
+            } else {
+                auto_export.pending = true;
+            }
+        }
+        // Left pending, so the next run tries again
+        Err(e) => auto_export.last_error = Some(e.to_string()),
+    }
+    storage::save_auto_export(&auto_export)?;
+    result.map(|sent| (auto_export.status(), sent))
+}
+
+/// Looks for settled changes every minute for as long as the app runs
+pub fn spawn_scheduler() {
+    std::thread::spawn(|| loop {
+        if let Err(e) = run_if_due(Utc::now()) {
+            eprintln!("Automatic export: {}", e);
+        }
+        std::thread::sleep(SCHEDULER_INTERVAL);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(value: &str) -> DateTime<Utc> {
+        parse_time(value).unwrap()
+    }
+
+    fn temp_folder(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("honey-did-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn settings(passphrase: Option<&str>) -> AutoExportSettings {
+        AutoExportSettings {
+            enabled: true,
+            folder: std::env::temp_dir().to_string_lossy().into_owned(),
+            schedule: AutoExportSchedule::AfterChanges,
+            debounce_minutes: 10,
+            keep: 3,
+            passphrase: passphrase.map(str::to_string),
+            memory_hard_kdf: false,
+            locale: None,
+            acknowledge_weak: false,
+        }
+    }
+
+    #[test]
+    fn test_waits_for_changes_to_settle() {
+        let mut auto_export = AutoExport::configure(None, settings(Some("long enough passphrase"))).unwrap();
+        auto_export.pending = false;
+        let changed = time("2026-03-01T10:00:00Z");
+        assert!(!auto_export.is_due(time("2026-03-01T12:00:00Z"), None));
+        assert!(!auto_export.is_due(time("2026-03-01T10:09:59Z"), Some(changed)));
+        assert!(auto_export.is_due(time("2026-03-01T10:10:00Z"), Some(changed)));
+    }
+
+    #[test]
+    fn test_schedule_limits_frequency() {
+        let mut config = settings(Some("long enough passphrase"));
+        config.schedule = AutoExportSchedule::Daily;
+        let mut auto_export = AutoExport::configure(None, config).unwrap();
+        auto_export.last_export = Some("2026-03-01T08:00:00+00:00".into());
+        let changed = Some(time("2026-03-01T09:00:00Z"));
+        assert!(!auto_export.is_due(time("2026-03-01T20:00:00Z"), changed));
+        assert!(auto_export.is_due(time("2026-03-02T08:00:00Z"), changed));
+    }
+
+    #[test]
+    fn test_pending_change_survives_restart() {
+        let mut auto_export = AutoExport::configure(None, settings(Some("long enough passphrase"))).unwrap();
+        assert!(auto_export.pending);
+        assert!(auto_export.is_due(time("2026-03-01T10:00:00Z"), None));
+        auto_export.enabled = false;
+        assert!(!auto_export.is_due(time("2026-03-01T10:00:00Z"), None));
+    }
+
+    #[test]
+    fn test_change_marks_pending() {
+        let mut auto_export = AutoExport::configure(None, settings(Some("long enough passphrase"))).unwrap();
+        auto_export.pending = false;
+        assert!(auto_export.mark_changed());
+        assert!(auto_export.pending);
+        assert!(!auto_export.mark_changed(), "already pending, nothing to save");
+
+        auto_export.pending = false;
+        auto_export.enabled = false;
+        assert!(!auto_export.mark_changed());
+        assert!(!auto_export.pending);
+    }
+
+    #[test]
+    fn test_configure_keeps_passphrase_and_history() {
+        assert!(AutoExport::configure(None, settings(None)).is_err());
+        let mut first = AutoExport::configure(None, settings(Some("long enough passphrase"))).unwrap();
+        first.last_export = Some("2026-03-01T08:00:00+00:00".into());
+        first.pending = false;
+        let second = AutoExport::configure(Some(first), settings(None)).unwrap();
+        assert_eq!(second.passphrase, "long enough passphrase");
+        assert_eq!(second.last_export.as_deref(), Some("2026-03-01T08:00:00+00:00"));
+        assert!(!second.pending);
+    }
+
+    #[test]
+    fn test_rejects_bad_settings() {
+        let mut config = settings(Some("long enough passphrase"));
+        config.keep = 0;
+        assert!(matches!(AutoExport::configure(None, config), Err(AutoExportError::InvalidSettings(_))));
+        let mut config = settings(Some("long enough passphrase"));
+        config.folder = "/nonexistent/honey-did".into();
+        assert!(matches!(AutoExport::configure(None, config), Err(AutoExportError::InvalidSettings(_))));
+    }
+
+    #[test]
+    fn test_prune_keeps_newest_exports_only() {
+        let dir = temp_folder("auto-export-prune");
+        for minute in 0..5 {
+            let name = file_name(time(&format!("2026-03-01T10:0{}:00Z", minute)));
+            fs::write(dir.join(name), "export").unwrap();
+        }
+        fs::write(dir.join("notes.txt"), "not ours").unwrap();
+        assert_eq!(prune(&dir, 2).unwrap(), 3);
+        let mut left: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        left.sort();
+        assert_eq!(
+            left,
+            vec![
+                "honey-did-auto-20260301-100300Z.html".to_string(),
+                "honey-did-auto-20260301-100400Z.html".to_string(),
+                "notes.txt".to_string(),
+            ]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
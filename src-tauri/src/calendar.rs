@@ -0,0 +1,515 @@
+use crate::models::{Bill, LegacyDocument, MaintenanceItem};
+use crate::vcard::{escape_text, fold_line};
+use chrono::{Datelike, Days, Months, NaiveDate};
+use ring::digest;
+use serde::Serialize;
+
+const PRODID: &str = "-//scafidi.dev//Honey Did//EN";
+
+/// Days before a manually paid bill is due that the reminder fires
+const BILL_REMINDER_DAYS: u32 = 3;
+
+/// Longest "every N units" interval accepted, e.g. "every 100 years"
+const MAX_INTERVAL: u32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Frequency {
+    fn as_rrule(self) -> &'static str {
+        match self {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        }
+    }
+}
+
+/// A parsed "every N units" schedule
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    pub interval: u32,
+}
+
+impl Recurrence {
+    fn every(frequency: Frequency, interval: u32) -> Self {
+        Recurrence { frequency, interval }
+    }
+
+    /// The date one period after `date`, clamped to the end of shorter months.
+    /// `None` when that is past the last representable date.
+    fn advance(self, date: NaiveDate) -> Option<NaiveDate> {
+        match self.frequency {
+            Frequency::Daily => date.checked_add_days(Days::new(u64::from(self.interval))),
+            Frequency::Weekly => date.checked_add_days(Days::new(7 * u64::from(self.interval))),
+            Frequency::Monthly => date.checked_add_months(Months::new(self.interval)),
+            Frequency::Yearly => date.checked_add_months(Months::new(self.interval.checked_mul(12)?)),
+        }
+    }
+}
+
+/// When in the period a bill is due
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DueDay {
+    /// Day of every month (1-31); months without that day use their last day
+    DayOfMonth(u32),
+    LastDayOfMonth,
+    /// Once a year, e.g. "March 15"
+    DayOfYear { month: u32, day: u32 },
+}
+
+/// Why an item was left out of the calendar
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedItem {
+    pub section: String,
+    pub name: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct CalendarReport {
+    pub events: usize,
+    pub skipped: Vec<SkippedItem>,
+}
+
+fn parse_number_word(word: &str) -> Option<u32> {
+    match word {
+        "one" | "a" | "an" | "once" => Some(1),
+        "two" | "other" | "twice" => Some(2),
+        "three" | "thrice" => Some(3),
+        "four" => Some(4),
+        "five" => Some(5),
+        "six" => Some(6),
+        "seven" => Some(7),
+        "eight" => Some(8),
+        "nine" => Some(9),
+        "ten" => Some(10),
+        "eleven" => Some(11),
+        "twelve" => Some(12),
+        _ => word.parse().ok().filter(|n| (1..=MAX_INTERVAL).contains(n)),
+    }
+}
+
+fn parse_unit(word: &str) -> Option<Frequency> {
+    match word.trim_end_matches('s') {
+        "day" => Some(Frequency::Daily),
+        "week" | "wk" => Some(Frequency::Weekly),
+        "month" | "mo" => Some(Frequency::Monthly),
+        "year" | "yr" => Some(Frequency::Yearly),
+        _ => None,
+    }
+}
+
+/// Parses free-text frequencies such as "monthly", "every 3 months",
+/// "twice a year" or "every other week"
+pub fn parse_frequency(text: &str) -> Option<Recurrence> {
+    let lower = text.trim().to_lowercase().replace(['-', '/'], " ");
+    let words: Vec<&str> = lower.split_whitespace().filter(|w| !matches!(*w, "every" | "per" | "each")).collect();
+    let joined = words.join(" ");
+
+    let named = match joined.as_str() {
+        "daily" | "day" | "nightly" => Some(Recurrence::every(Frequency::Daily, 1)),
+        "weekly" | "week" => Some(Recurrence::every(Frequency::Weekly, 1)),
+        "biweekly" | "bi weekly" | "fortnightly" | "fortnight" => Some(Recurrence::every(Frequency::Weekly, 2)),
+        "monthly" | "month" => Some(Recurrence::every(Frequency::Monthly, 1)),
+        "bimonthly" | "bi monthly" => Some(Recurrence::every(Frequency::Monthly, 2)),
+        "quarterly" | "quarter" => Some(Recurrence::every(Frequency::Monthly, 3)),
+        "semiannually" | "semi annually" | "semiannual" | "semi annual" | "biannually" | "biannual" => {
+            Some(Recurrence::every(Frequency::Monthly, 6))
+        }
+        "annually" | "annual" | "yearly" | "year" => Some(Recurrence::every(Frequency::Yearly, 1)),
+        _ => None,
+    };
+    if named.is_some() {
+        return named;
+    }
+
+    match words.as_slice() {
+        // "3 months", "other week", "two years"
+        [count, unit] => {
+            let interval = parse_number_word(count)?;
+            Some(Recurrence::every(parse_unit(unit)?, interval))
+        }
+        // "twice a year", "4 times a year", "once a month"
+        [count, "a" | "per", unit] | [count, "times", "a" | "per", unit] => {
+            let times = parse_number_word(count)?;
+            match parse_unit(unit)? {
+                Frequency::Yearly if 12 % times == 0 => Some(Recurrence::every(Frequency::Monthly, 12 / times)),
+                Frequency::Monthly if times == 1 => Some(Recurrence::every(Frequency::Monthly, 1)),
+                Frequency::Weekly if times == 1 => Some(Recurrence::every(Frequency::Weekly, 1)),
+                Frequency::Daily if times == 1 => Some(Recurrence::every(Frequency::Daily, 1)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// A full month name or its abbreviation ("mar", "sept"), so "Mark" and "approx." aren't months
+fn parse_month_name(word: &str) -> Option<u32> {
+    const MONTHS: [&str; 12] = [
+        "january", "february", "march", "april", "may", "june", "july", "august", "september", "october", "november", "december",
+    ];
+    let word = word.trim_end_matches(['.', ',']);
+    let word = if word == "sept" { "sep" } else { word };
+    MONTHS.iter().position(|m| word == *m || word == &m[..3]).map(|i| i as u32 + 1)
+}
+
+fn parse_ordinal(word: &str) -> Option<u32> {
+    let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic() || c == '.' || c == ',');
+    digits.parse().ok().filter(|d| (1..=31).contains(d))
+}
+
+/// Parses free-text due days such as "15", "1st", "the 15th of each month",
+/// "end of month" or "March 15"
+pub fn parse_due_day(text: &str) -> Option<DueDay> {
+    let lower = text.trim().to_lowercase();
+    if lower.contains("last") || lower.contains("end of") {
+        return Some(DueDay::LastDayOfMonth);
+    }
+
+    let words: Vec<&str> = lower.split(|c: char| c.is_whitespace() || c == '/').filter(|w| !w.is_empty()).collect();
+
+    // "March 15", "15 March", "3/15"
+    if let Some(month) = words.iter().find_map(|w| parse_month_name(w)) {
+        let day = words.iter().find_map(|w| parse_ordinal(w))?;
+        return Some(DueDay::DayOfYear { month, day });
+    }
+    if words.len() == 2 && lower.contains('/') {
+        let month: u32 = words[0].parse().ok().filter(|m| (1..=12).contains(m))?;
+        return Some(DueDay::DayOfYear { month, day: parse_ordinal(words[1])? });
+    }
+
+    words.iter().find_map(|w| parse_ordinal(w)).map(DueDay::DayOfMonth)
+}
+
+/// Parses a "last done" date. Month-only dates ("March 2024") mean the first of the month.
+pub fn parse_last_done(text: &str) -> Option<NaiveDate> {
+    const FORMATS: &[&str] = &["%Y-%m-%d", "%m/%d/%Y", "%m/%d/%y", "%Y/%m/%d", "%b %d, %Y", "%B %d, %Y", "%b %d %Y", "%B %d %Y", "%d %b %Y", "%d %B %Y"];
+    let text = text.trim();
+    FORMATS
+        .iter()
+        .find_map(|fmt| NaiveDate::parse_from_str(text, fmt).ok())
+        .or_else(|| {
+            ["%B %Y", "%b %Y", "%Y-%m", "%m/%Y"]
+                .iter()
+                .find_map(|fmt| NaiveDate::parse_from_str(&format!("1 {}", text), &format!("%d {}", fmt)).ok())
+        })
+}
+
+fn last_day_of_month(year: i32, month: u32) -> u32 {
+    let first_of_next = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)
+    };
+    first_of_next.and_then(|d| d.pred_opt()).map(|d| d.day()).unwrap_or(28)
+}
+
+/// The first date on or after `today` that matches `due`
+fn next_due_date(due: DueDay, today: NaiveDate) -> Option<NaiveDate> {
+    let in_month = |year: i32, month: u32| {
+        let last = last_day_of_month(year, month);
+        let day = match due {
+            DueDay::DayOfMonth(d) => d.min(last),
+            DueDay::LastDayOfMonth => last,
+            DueDay::DayOfYear { day, .. } => day.min(last),
+        };
+        NaiveDate::from_ymd_opt(year, month, day)
+    };
+
+    match due {
+        DueDay::DayOfYear { month, .. } => {
+            let this_year = in_month(today.year(), month)?;
+            if this_year >= today { Some(this_year) } else { in_month(today.year() + 1, month) }
+        }
+        _ => {
+            let this_month = in_month(today.year(), today.month())?;
+            if this_month >= today {
+                Some(this_month)
+            } else {
+                let next = today.with_day(1)? + Months::new(1);
+                in_month(next.year(), next.month())
+            }
+        }
+    }
+}
+
+/// RRULE for a bill. Days past the 28th use BYSETPOS so short months fall back to their last day
+/// instead of being skipped (RFC 5545 drops invalid BYMONTHDAY dates).
+fn bill_rrule(due: DueDay) -> String {
+    match due {
+        DueDay::DayOfMonth(day) if day > 28 => {
+            let days: Vec<String> = (28..=day).map(|d| d.to_string()).collect();
+            format!("FREQ=MONTHLY;BYMONTHDAY={};BYSETPOS=-1", days.join(","))
+        }
+        DueDay::DayOfMonth(day) => format!("FREQ=MONTHLY;BYMONTHDAY={}", day),
+        DueDay::LastDayOfMonth => "FREQ=MONTHLY;BYMONTHDAY=-1".to_string(),
+        DueDay::DayOfYear { month, day } if day > 28 => {
+            let days: Vec<String> = (28..=day).map(|d| d.to_string()).collect();
+            format!("FREQ=YEARLY;BYMONTH={};BYMONTHDAY={};BYSETPOS=-1", month, days.join(","))
+        }
+        DueDay::DayOfYear { month, day } => format!("FREQ=YEARLY;BYMONTH={};BYMONTHDAY={}", month, day),
+    }
+}
+
+fn maintenance_rrule(recurrence: Recurrence) -> String {
+    if recurrence.interval == 1 {
+        format!("FREQ={}", recurrence.frequency.as_rrule())
+    } else {
+        format!("FREQ={};INTERVAL={}", recurrence.frequency.as_rrule(), recurrence.interval)
+    }
+}
+
+/// Stable UID so re-importing an updated export replaces events instead of duplicating them
+fn event_uid(kind: &str, parts: &[&str]) -> String {
+    let mut ctx = digest::Context::new(&digest::SHA256);
+    ctx.update(kind.as_bytes());
+    for part in parts {
+        ctx.update(b"\x1f");
+        ctx.update(part.trim().to_lowercase().as_bytes());
+    }
+    let hex: String = ctx.finish().as_ref()[..12].iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}-{}@honey-did", kind, hex)
+}
+
+struct Event {
+    uid: String,
+    summary: String,
+    description: String,
+    category: &'static str,
+    start: NaiveDate,
+    rrule: String,
+    reminder_days: Option<u32>,
+}
+
+fn write_event(event: &Event, stamp: &str, out: &mut String) {
+    let mut line = |l: String| fold_line(&l, out);
+    line("BEGIN:VEVENT".into());
+    line(format!("UID:{}", event.uid));
+    line(format!("DTSTAMP:{}", stamp));
+    line(format!("DTSTART;VALUE=DATE:{}", event.start.format("%Y%m%d")));
+    line(format!("RRULE:{}", event.rrule));
+    line(format!("SUMMARY:{}", escape_text(&event.summary)));
+    if !event.description.is_empty() {
+        line(format!("DESCRIPTION:{}", escape_text(&event.description)));
+    }
+    line(format!("CATEGORIES:{}", escape_text(event.category)));
+    line("TRANSP:TRANSPARENT".into());
+    if let Some(days) = event.reminder_days {
+        line("BEGIN:VALARM".into());
+        line("ACTION:DISPLAY".into());
+        line(format!("TRIGGER:-P{}D", days));
+        line(format!("DESCRIPTION:{}", escape_text(&event.summary)));
+        line("END:VALARM".into());
+    }
+    line("END:VEVENT".into());
+}
+
+fn bill_event(bill: &Bill, today: NaiveDate) -> Result<Event, String> {
+    if bill.due_day.trim().is_empty() {
+        return Err("No due day".into());
+    }
+    let due = parse_due_day(&bill.due_day).ok_or_else(|| format!("Could not understand due day '{}'", bill.due_day))?;
+    let start = next_due_date(due, today).ok_or_else(|| format!("Invalid due day '{}'", bill.due_day))?;
+
+    let mut details = Vec::new();
+    if !bill.provider.is_empty() {
+        details.push(format!("Provider: {}", bill.provider));
+    }
+    if !bill.amount.is_empty() {
+        details.push(format!("Amount: {}", bill.amount));
+    }
+    details.push(if bill.autopay { "Paid automatically (auto-pay)".to_string() } else { "Paid manually".to_string() });
+    if !bill.notes.is_empty() {
+        details.push(bill.notes.clone());
+    }
+
+    Ok(Event {
+        uid: event_uid("bill", &[&bill.name, &bill.provider]),
+        summary: if bill.autopay { format!("{} (auto-pay)", bill.name) } else { format!("Pay {}", bill.name) },
+        description: details.join("\n"),
+        category: "Bills",
+        start,
+        rrule: bill_rrule(due),
+        reminder_days: if bill.autopay { None } else { Some(BILL_REMINDER_DAYS) },
+    })
+}
+
+fn maintenance_event(item: &MaintenanceItem, today: NaiveDate) -> Result<Event, String> {
+    if item.frequency.trim().is_empty() {
+        return Err("No frequency".into());
+    }
+    let recurrence =
+        parse_frequency(&item.frequency).ok_or_else(|| format!("Could not understand frequency '{}'", item.frequency))?;
+
+    // Next due is one period after it was last done; overdue tasks start today
+    let next = match parse_last_done(&item.last_done) {
+        Some(last) => Some(recurrence.advance(last).ok_or_else(|| format!("Frequency '{}' is too far apart", item.frequency))?),
+        None => None,
+    };
+    let start = next.filter(|next| *next >= today).unwrap_or(today);
+
+    let mut details = vec![format!("Frequency: {}", item.frequency)];
+    if !item.last_done.is_empty() {
+        details.push(format!("Last done: {}", item.last_done));
+    }
+    if !item.notes.is_empty() {
+        details.push(item.notes.clone());
+    }
+
+    Ok(Event {
+        uid: event_uid("maintenance", &[&item.name]),
+        summary: item.name.clone(),
+        description: details.join("\n"),
+        category: "Household Maintenance",
+        start,
+        rrule: maintenance_rrule(recurrence),
+        reminder_days: None,
+    })
+}
+
+/// Builds an iCalendar file with a recurring all-day event for every bill and
+/// maintenance task whose schedule can be understood. Items that can't be
+/// scheduled are listed in the report.
+pub fn generate_ics(document: &LegacyDocument, today: NaiveDate) -> (String, CalendarReport) {
+    let mut report = CalendarReport::default();
+    let mut events = Vec::new();
+
+    let mut collect = |section: &str, name: &str, result: Result<Event, String>| match result {
+        Ok(event) => events.push(event),
+        Err(reason) => report.skipped.push(SkippedItem { section: section.into(), name: name.into(), reason }),
+    };
+
+    for bill in document.bills.bills.iter().filter(|b| !b.name.trim().is_empty()) {
+        collect("Bills", &bill.name, bill_event(bill, today));
+    }
+    for item in document.household.maintenance_items.iter().filter(|m| !m.name.trim().is_empty()) {
+        collect("Household", &item.name, maintenance_event(item, today));
+    }
+
+    report.events = events.len();
+    if events.is_empty() {
+        return (String::new(), report);
+    }
+
+    let stamp = format!("{}T000000Z", today.format("%Y%m%d"));
+    let mut out = String::new();
+    fold_line("BEGIN:VCALENDAR", &mut out);
+    fold_line("VERSION:2.0", &mut out);
+    fold_line(&format!("PRODID:{}", PRODID), &mut out);
+    fold_line("CALSCALE:GREGORIAN", &mut out);
+    fold_line("X-WR-CALNAME:Honey Did", &mut out);
+    for event in &events {
+        write_event(event, &stamp, &mut out);
+    }
+    fold_line("END:VCALENDAR", &mut out);
+
+    (out, report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_parse_frequency() {
+        let cases = [
+            ("Monthly", Frequency::Monthly, 1),
+            ("every 3 months", Frequency::Monthly, 3),
+            ("Quarterly", Frequency::Monthly, 3),
+            ("twice a year", Frequency::Monthly, 6),
+            ("every other week", Frequency::Weekly, 2),
+            ("Annually", Frequency::Yearly, 1),
+            ("every two years", Frequency::Yearly, 2),
+            ("4 times a year", Frequency::Monthly, 3),
+        ];
+        for (text, frequency, interval) in cases {
+            assert_eq!(parse_frequency(text), Some(Recurrence { frequency, interval }), "{}", text);
+        }
+        assert_eq!(parse_frequency("when it looks dirty"), None);
+    }
+
+    #[test]
+    fn test_parse_due_day() {
+        assert_eq!(parse_due_day("15"), Some(DueDay::DayOfMonth(15)));
+        assert_eq!(parse_due_day("the 1st of each month"), Some(DueDay::DayOfMonth(1)));
+        assert_eq!(parse_due_day("End of month"), Some(DueDay::LastDayOfMonth));
+        assert_eq!(parse_due_day("March 15th"), Some(DueDay::DayOfYear { month: 3, day: 15 }));
+        assert_eq!(parse_due_day("Sept. 3"), Some(DueDay::DayOfYear { month: 9, day: 3 }));
+        assert_eq!(parse_due_day("approx. the 10th"), Some(DueDay::DayOfMonth(10)));
+        assert_eq!(parse_due_day("paid by Mark on the 5th"), Some(DueDay::DayOfMonth(5)));
+        assert_eq!(parse_due_day("4/30"), Some(DueDay::DayOfYear { month: 4, day: 30 }));
+        assert_eq!(parse_due_day("varies"), None);
+        assert_eq!(parse_due_day("45"), None);
+    }
+
+    #[test]
+    fn test_next_due_date_clamps_short_months() {
+        let today = date(2025, 2, 10);
+        assert_eq!(next_due_date(DueDay::DayOfMonth(31), today), Some(date(2025, 2, 28)));
+        assert_eq!(next_due_date(DueDay::DayOfMonth(5), today), Some(date(2025, 3, 5)));
+        assert_eq!(next_due_date(DueDay::DayOfYear { month: 1, day: 15 }, today), Some(date(2026, 1, 15)));
+        assert_eq!(bill_rrule(DueDay::DayOfMonth(30)), "FREQ=MONTHLY;BYMONTHDAY=28,29,30;BYSETPOS=-1");
+    }
+
+    #[test]
+    fn test_generate_ics() {
+        let mut doc = LegacyDocument::default();
+        doc.bills.bills.push(Bill { name: "Electric".into(), amount: "$120".into(), due_day: "15th".into(), ..Default::default() });
+        doc.bills.bills.push(Bill { name: "Mortgage".into(), due_day: "1".into(), autopay: true, ..Default::default() });
+        doc.bills.bills.push(Bill { name: "Water".into(), due_day: "varies".into(), ..Default::default() });
+        doc.household.maintenance_items.push(MaintenanceItem {
+            name: "Replace HVAC filter".into(),
+            frequency: "every 3 months".into(),
+            last_done: "2025-01-20".into(),
+            ..Default::default()
+        });
+
+        let (ics, report) = generate_ics(&doc, date(2025, 2, 10));
+
+        assert_eq!(report.events, 3);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].name, "Water");
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20250215\r\nRRULE:FREQ=MONTHLY;BYMONTHDAY=15\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20250420\r\nRRULE:FREQ=MONTHLY;INTERVAL=3\r\n"));
+        assert_eq!(ics.matches("BEGIN:VALARM").count(), 1, "only manually paid bills get a reminder");
+        assert!(ics.contains("SUMMARY:Mortgage (auto-pay)"));
+
+        // UIDs are stable across exports
+        let (again, _) = generate_ics(&doc, date(2025, 3, 1));
+        let uids = |s: &str| s.lines().filter(|l| l.starts_with("UID:")).map(String::from).collect::<Vec<_>>();
+        assert_eq!(uids(&ics), uids(&again));
+    }
+
+    #[test]
+    fn test_huge_intervals_are_skipped() {
+        assert_eq!(parse_frequency("every 100 years"), Some(Recurrence { frequency: Frequency::Yearly, interval: 100 }));
+        assert_eq!(parse_frequency("every 1000000 years"), None);
+        assert_eq!(Recurrence { frequency: Frequency::Yearly, interval: u32::MAX }.advance(date(2025, 1, 1)), None);
+        assert_eq!(Recurrence { frequency: Frequency::Daily, interval: u32::MAX }.advance(date(2025, 1, 1)), None);
+
+        let mut doc = LegacyDocument::default();
+        doc.household.maintenance_items.push(MaintenanceItem {
+            name: "Repaint the pyramids".into(),
+            frequency: "every 1000000 years".into(),
+            last_done: "2025-01-20".into(),
+            ..Default::default()
+        });
+        let (ics, report) = generate_ics(&doc, date(2025, 2, 10));
+        assert!(ics.is_empty());
+        assert_eq!(report.skipped[0].name, "Repaint the pyramids");
+    }
+}
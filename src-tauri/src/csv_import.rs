@@ -0,0 +1,668 @@
+use crate::models::{
+    BankAccount, Bill, Contact, CustomItem, CustomSubsection, FieldType, FormElement, LegacyDocument, Medication,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Maximum number of rows returned in a preview sample
+const PREVIEW_ROWS: usize = 5;
+
+/// Reserved custom item key used by the frontend for per-item notes
+const CUSTOM_NOTES_FIELD: &str = "_notes";
+
+#[derive(Debug)]
+pub enum CsvImportError {
+    ParseError(String),
+    InvalidTarget(String),
+    InvalidMapping(String),
+    EmptyFile,
+}
+
+impl std::fmt::Display for CsvImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CsvImportError::ParseError(msg) => write!(f, "Could not read CSV file: {}", msg),
+            CsvImportError::InvalidTarget(msg) => write!(f, "Invalid import target: {}", msg),
+            CsvImportError::InvalidMapping(msg) => write!(f, "Invalid column mapping: {}", msg),
+            CsvImportError::EmptyFile => write!(f, "The CSV file has no header row"),
+        }
+    }
+}
+
+impl std::error::Error for CsvImportError {}
+
+/// The list a CSV file is imported into
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CsvTarget {
+    BankAccounts,
+    Bills,
+    EmergencyContacts,
+    FamilyContacts,
+    ProfessionalContacts,
+    /// Medications of `medical.family_members[family_member]`
+    Medications { family_member: usize },
+    /// Medications of `pets.pets[pet]`
+    PetMedications { pet: usize },
+    CustomSubsection { section_id: String, subsection_id: String },
+}
+
+/// A field of the target list that a CSV column can be mapped to
+#[derive(Debug, Clone, Serialize)]
+pub struct TargetField {
+    pub id: String,
+    pub label: String,
+    pub field_type: FieldType,
+    pub required: bool,
+}
+
+/// Maps target field ids to zero-based CSV column indexes
+pub type ColumnMapping = HashMap<String, usize>;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueSeverity {
+    /// The value was imported, possibly adjusted
+    Warning,
+    /// The value (or the whole row) was not imported
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CsvIssue {
+    /// Line number in the CSV file (the header is line 1)
+    pub line: usize,
+    pub column: Option<String>,
+    pub severity: IssueSeverity,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct CsvImportReport {
+    pub rows_read: usize,
+    pub imported: usize,
+    pub skipped: usize,
+    pub issues: Vec<CsvIssue>,
+}
+
+/// Column-mapping preview shown before the user commits an import
+#[derive(Debug, Clone, Serialize)]
+pub struct CsvPreview {
+    pub headers: Vec<String>,
+    pub fields: Vec<TargetField>,
+    pub mapping: ColumnMapping,
+    pub unmapped_columns: Vec<String>,
+    /// First rows as they would be imported (field id -> value)
+    pub sample: Vec<HashMap<String, String>>,
+    /// Validation report for the whole file with the current mapping
+    pub report: CsvImportReport,
+}
+
+pub(crate) struct ParsedCsv {
+    pub headers: Vec<String>,
+    pub rows: Vec<(usize, Vec<String>)>,
+}
+
+/// Picks the delimiter that splits the header line into the most columns
+fn detect_delimiter(content: &str) -> u8 {
+    let header = content.lines().next().unwrap_or("");
+    [b',', b';', b'\t']
+        .into_iter()
+        .max_by_key(|d| header.matches(*d as char).count())
+        .filter(|d| header.contains(*d as char))
+        .unwrap_or(b',')
+}
+
+pub(crate) fn parse_csv(content: &str) -> Result<ParsedCsv, CsvImportError> {
+    let content = content.trim_start_matches('\u{feff}');
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(detect_delimiter(content))
+        .flexible(true)
+        .has_headers(true)
+        .from_reader(content.as_bytes());
+
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| CsvImportError::ParseError(e.to_string()))?
+        .iter()
+        .map(|h| h.trim().to_string())
+        .collect();
+
+    if headers.iter().all(|h| h.is_empty()) {
+        return Err(CsvImportError::EmptyFile);
+    }
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| CsvImportError::ParseError(e.to_string()))?;
+        let line = record.position().map(|p| p.line() as usize).unwrap_or(0);
+        rows.push((line, record.iter().map(|v| v.trim().to_string()).collect()));
+    }
+
+    Ok(ParsedCsv { headers, rows })
+}
+
+fn field(id: &str, label: &str, field_type: FieldType, required: bool) -> TargetField {
+    TargetField { id: id.to_string(), label: label.to_string(), field_type, required }
+}
+
+fn contact_fields() -> Vec<TargetField> {
+    vec![
+        field("name", "Name", FieldType::Text, true),
+        field("relationship", "Relationship", FieldType::Text, false),
+        field("phone", "Phone", FieldType::Text, false),
+        field("email", "Email", FieldType::Text, false),
+        field("notes", "Notes", FieldType::Text, false),
+    ]
+}
+
+fn medication_fields() -> Vec<TargetField> {
+    vec![
+        field("name", "Medication", FieldType::Text, true),
+        field("dosage", "Dosage", FieldType::Text, false),
+        field("frequency", "Frequency", FieldType::Text, false),
+        field("prescriber", "Prescriber", FieldType::Text, false),
+        field("notes", "Notes", FieldType::Text, false),
+    ]
+}
+
+fn find_custom_subsection<'a>(
+    document: &'a LegacyDocument,
+    section_id: &str,
+    subsection_id: &str,
+) -> Result<&'a CustomSubsection, CsvImportError> {
+    document
+        .custom_sections
+        .iter()
+        .find(|s| s.id == section_id)
+        .and_then(|s| s.subsections.iter().find(|sub| sub.id == subsection_id))
+        .ok_or_else(|| CsvImportError::InvalidTarget("Custom subsection not found".into()))
+}
+
+/// Returns the fields a CSV column can be mapped to for the given target
+pub fn target_fields(document: &LegacyDocument, target: &CsvTarget) -> Result<Vec<TargetField>, CsvImportError> {
+    let fields = match target {
+        CsvTarget::BankAccounts => vec![
+            field("name", "Account Name", FieldType::Text, true),
+            field("institution", "Institution", FieldType::Text, false),
+            field("account_type", "Account Type", FieldType::Text, false),
+            field("last_four", "Last 4 Digits", FieldType::Text, false),
+            field("notes", "Notes", FieldType::Text, false),
+        ],
+        CsvTarget::Bills => vec![
+            field("name", "Bill Name", FieldType::Text, true),
+            field("provider", "Provider", FieldType::Text, false),
+            field("amount", "Amount", FieldType::Text, false),
+            field("due_day", "Due Day", FieldType::Text, false),
+            field("autopay", "Auto-pay", FieldType::Boolean, false),
+            field("notes", "Notes", FieldType::Text, false),
+        ],
+        CsvTarget::EmergencyContacts | CsvTarget::FamilyContacts | CsvTarget::ProfessionalContacts => contact_fields(),
+        CsvTarget::Medications { family_member } => {
+            if *family_member >= document.medical.family_members.len() {
+                return Err(CsvImportError::InvalidTarget("Family member not found".into()));
+            }
+            medication_fields()
+        }
+        CsvTarget::PetMedications { pet } => {
+            if *pet >= document.pets.pets.len() {
+                return Err(CsvImportError::InvalidTarget("Pet not found".into()));
+            }
+            medication_fields()
+        }
+        CsvTarget::CustomSubsection { section_id, subsection_id } => {
+            let subsection = find_custom_subsection(document, section_id, subsection_id)?;
+            let mut fields: Vec<TargetField> = if subsection.form_elements.is_empty() {
+                subsection
+                    .field_definitions
+                    .iter()
+                    .map(|fd| field(&fd.id, &fd.name, fd.field_type.clone(), false))
+                    .collect()
+            } else {
+                subsection
+                    .form_elements
+                    .iter()
+                    .filter_map(|el| match el {
+                        FormElement::Field { id, name, field_type } => Some(field(id, name, field_type.clone(), false)),
+                        _ => None,
+                    })
+                    .collect()
+            };
+            fields.push(field(CUSTOM_NOTES_FIELD, "Notes", FieldType::Text, false));
+            fields
+        }
+    };
+    Ok(fields)
+}
+
+/// Lowercases and strips everything but letters and digits, so "Due Date" matches "due_date"
+pub(crate) fn normalize_header(header: &str) -> String {
+    header.chars().filter(|c| c.is_alphanumeric()).flat_map(|c| c.to_lowercase()).collect()
+}
+
+/// Common spreadsheet column names for built-in fields
+fn header_aliases(field_id: &str) -> &'static [&'static str] {
+    match field_id {
+        "name" => &["name", "title", "account", "accountname", "nickname", "bill", "billname", "payee", "description", "fullname", "contact", "medication", "drug"],
+        "institution" => &["institution", "bank", "bankname", "financialinstitution"],
+        "account_type" => &["type", "accounttype", "kind"],
+        "last_four" => &["lastfour", "last4", "accountnumber", "number", "acct"],
+        "provider" => &["provider", "company", "vendor", "utility", "merchant"],
+        "amount" => &["amount", "payment", "cost", "price", "monthly", "monthlyamount"],
+        "due_day" => &["dueday", "due", "duedate", "dayofmonth"],
+        "autopay" => &["autopay", "automatic", "autopayment", "auto"],
+        "relationship" => &["relationship", "relation", "role"],
+        "phone" => &["phone", "phonenumber", "mobile", "cell", "telephone", "tel"],
+        "email" => &["email", "emailaddress", "mail"],
+        "dosage" => &["dosage", "dose", "strength"],
+        "frequency" => &["frequency", "schedule", "directions", "howoften"],
+        "prescriber" => &["prescriber", "doctor", "prescribedby", "physician"],
+        "notes" | CUSTOM_NOTES_FIELD => &["notes", "note", "memo", "comments", "comment"],
+        _ => &[],
+    }
+}
+
+/// Suggests a mapping by matching header names against field labels, ids and aliases
+pub fn suggest_mapping(headers: &[String], fields: &[TargetField]) -> ColumnMapping {
+    let normalized: Vec<String> = headers.iter().map(|h| normalize_header(h)).collect();
+    let mut mapping = ColumnMapping::new();
+    let mut used = vec![false; headers.len()];
+
+    for f in fields {
+        let mut candidates = vec![normalize_header(&f.label), normalize_header(&f.id)];
+        candidates.extend(header_aliases(&f.id).iter().map(|a| a.to_string()));
+
+        let found = candidates.iter().find_map(|candidate| {
+            normalized
+                .iter()
+                .enumerate()
+                .find(|(i, h)| !used[*i] && !h.is_empty() && *h == candidate)
+                .map(|(i, _)| i)
+        });
+
+        if let Some(col) = found {
+            used[col] = true;
+            mapping.insert(f.id.clone(), col);
+        }
+    }
+
+    mapping
+}
+
+const TRUE_VALUES: &[&str] = &["yes", "y", "true", "t", "1", "x", "on", "auto", "autopay"];
+const FALSE_VALUES: &[&str] = &["no", "n", "false", "f", "0", "off", "manual", ""];
+
+fn parse_boolean(value: &str) -> Option<bool> {
+    let lower = value.trim().to_lowercase();
+    if TRUE_VALUES.contains(&lower.as_str()) {
+        Some(true)
+    } else if FALSE_VALUES.contains(&lower.as_str()) {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Strips currency symbols and thousands separators before parsing
+fn parse_number(value: &str) -> Option<String> {
+    let cleaned: String = value
+        .chars()
+        .filter(|c| !matches!(c, '$' | '€' | '£' | '¥' | ',' | ' '))
+        .collect();
+    cleaned.parse::<f64>().ok().map(|_| cleaned)
+}
+
+/// Normalizes common spreadsheet date formats to YYYY-MM-DD (the format of date inputs)
+fn parse_date(value: &str) -> Option<String> {
+    use chrono::NaiveDate;
+    const FORMATS: &[&str] = &["%Y-%m-%d", "%m/%d/%Y", "%m/%d/%y", "%Y/%m/%d", "%b %d, %Y", "%B %d, %Y", "%d %b %Y", "%d %B %Y"];
+    FORMATS
+        .iter()
+        .find_map(|fmt| NaiveDate::parse_from_str(value.trim(), fmt).ok())
+        .map(|d| d.format("%Y-%m-%d").to_string())
+}
+
+/// One CSV row converted to field values, with any issues found along the way
+struct ConvertedRow {
+    values: HashMap<String, String>,
+    issues: Vec<CsvIssue>,
+}
+
+fn convert_row(
+    line: usize,
+    row: &[String],
+    headers: &[String],
+    fields: &[TargetField],
+    mapping: &ColumnMapping,
+    target: &CsvTarget,
+) -> ConvertedRow {
+    let mut values = HashMap::new();
+    let mut issues = Vec::new();
+
+    for f in fields {
+        let Some(&col) = mapping.get(&f.id) else { continue };
+        let raw = row.get(col).map(|s| s.as_str()).unwrap_or("");
+        let column = headers.get(col).cloned();
+        let mut issue = |severity: IssueSeverity, message: String| {
+            issues.push(CsvIssue { line, column: column.clone(), severity, message });
+        };
+
+        let value = match f.field_type {
+            FieldType::Boolean => match parse_boolean(raw) {
+                Some(b) => b.to_string(),
+                None => {
+                    issue(IssueSeverity::Warning, format!("'{}' is not yes/no; imported as No", raw));
+                    "false".to_string()
+                }
+            },
+            FieldType::Number if !raw.is_empty() => match parse_number(raw) {
+                Some(n) => n,
+                None => {
+                    issue(IssueSeverity::Error, format!("'{}' is not a number", raw));
+                    continue;
+                }
+            },
+            FieldType::Date if !raw.is_empty() => match parse_date(raw) {
+                Some(d) => d,
+                None => {
+                    issue(IssueSeverity::Error, format!("'{}' is not a recognized date", raw));
+                    continue;
+                }
+            },
+            _ => raw.to_string(),
+        };
+
+        let value = if *target == CsvTarget::BankAccounts && f.id == "last_four" && !value.is_empty() {
+            let digits: String = value.chars().filter(|c| c.is_ascii_digit()).collect();
+            if digits.len() > 4 {
+                issue(IssueSeverity::Warning, "Only the last four digits of the account number were kept".into());
+                digits[digits.len() - 4..].to_string()
+            } else if digits.len() < 4 {
+                issue(IssueSeverity::Warning, format!("'{}' has fewer than four digits", value));
+                value
+            } else {
+                digits
+            }
+        } else {
+            value
+        };
+
+        values.insert(f.id.clone(), value);
+    }
+
+    ConvertedRow { values, issues }
+}
+
+fn validate_mapping(headers: &[String], fields: &[TargetField], mapping: &ColumnMapping) -> Result<(), CsvImportError> {
+    for (field_id, col) in mapping {
+        if !fields.iter().any(|f| &f.id == field_id) {
+            return Err(CsvImportError::InvalidMapping(format!("Unknown field '{}'", field_id)));
+        }
+        if *col >= headers.len() {
+            return Err(CsvImportError::InvalidMapping(format!("Column {} does not exist", col + 1)));
+        }
+    }
+    if let Some(missing) = fields.iter().find(|f| f.required && !mapping.contains_key(&f.id)) {
+        return Err(CsvImportError::InvalidMapping(format!("'{}' must be mapped to a column", missing.label)));
+    }
+    Ok(())
+}
+
+fn take(values: &mut HashMap<String, String>, key: &str) -> String {
+    values.remove(key).unwrap_or_default()
+}
+
+fn contact_from(mut v: HashMap<String, String>) -> Contact {
+    Contact {
+        name: take(&mut v, "name"),
+        relationship: take(&mut v, "relationship"),
+        phone: take(&mut v, "phone"),
+        email: take(&mut v, "email"),
+        notes: take(&mut v, "notes"),
+    }
+}
+
+fn medication_from(mut v: HashMap<String, String>) -> Medication {
+    Medication {
+        name: take(&mut v, "name"),
+        dosage: take(&mut v, "dosage"),
+        frequency: take(&mut v, "frequency"),
+        prescriber: take(&mut v, "prescriber"),
+        notes: take(&mut v, "notes"),
+    }
+}
+
+fn generate_item_id() -> String {
+    use rand::Rng;
+    const ALPHABET: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+    let mut rng = rand::thread_rng();
+    (0..7).map(|_| ALPHABET[rng.gen_range(0..ALPHABET.len())] as char).collect()
+}
+
+/// Appends one converted row to the target list
+fn append_row(document: &mut LegacyDocument, target: &CsvTarget, mut v: HashMap<String, String>) -> Result<(), CsvImportError> {
+    match target {
+        CsvTarget::BankAccounts => document.financial.bank_accounts.push(BankAccount {
+            name: take(&mut v, "name"),
+            institution: take(&mut v, "institution"),
+            account_type: take(&mut v, "account_type"),
+            last_four: take(&mut v, "last_four"),
+            notes: take(&mut v, "notes"),
+        }),
+        CsvTarget::Bills => document.bills.bills.push(Bill {
+            name: take(&mut v, "name"),
+            provider: take(&mut v, "provider"),
+            amount: take(&mut v, "amount"),
+            due_day: take(&mut v, "due_day"),
+            autopay: take(&mut v, "autopay") == "true",
+            notes: take(&mut v, "notes"),
+        }),
+        CsvTarget::EmergencyContacts => document.contacts.emergency_contacts.push(contact_from(v)),
+        CsvTarget::FamilyContacts => document.contacts.family.push(contact_from(v)),
+        CsvTarget::ProfessionalContacts => document.contacts.professionals.push(contact_from(v)),
+        CsvTarget::Medications { family_member } => document
+            .medical
+            .family_members
+            .get_mut(*family_member)
+            .ok_or_else(|| CsvImportError::InvalidTarget("Family member not found".into()))?
+            .medications
+            .push(medication_from(v)),
+        CsvTarget::PetMedications { pet } => document
+            .pets
+            .pets
+            .get_mut(*pet)
+            .ok_or_else(|| CsvImportError::InvalidTarget("Pet not found".into()))?
+            .medications
+            .push(medication_from(v)),
+        CsvTarget::CustomSubsection { section_id, subsection_id } => {
+            let subsection = document
+                .custom_sections
+                .iter_mut()
+                .find(|s| &s.id == section_id)
+                .and_then(|s| s.subsections.iter_mut().find(|sub| &sub.id == subsection_id))
+                .ok_or_else(|| CsvImportError::InvalidTarget("Custom subsection not found".into()))?;
+            v.retain(|_, value| !value.is_empty());
+            subsection.items.push(CustomItem { id: generate_item_id(), values: v });
+        }
+    }
+    Ok(())
+}
+
+/// Imports CSV rows into the target list of `document` and reports what happened
+pub fn import_csv(
+    document: &mut LegacyDocument,
+    content: &str,
+    target: &CsvTarget,
+    mapping: &ColumnMapping,
+) -> Result<CsvImportReport, CsvImportError> {
+    let parsed = parse_csv(content)?;
+    let fields = target_fields(document, target)?;
+    validate_mapping(&parsed.headers, &fields, mapping)?;
+
+    let mut report = CsvImportReport::default();
+    for (line, row) in &parsed.rows {
+        if row.iter().all(|v| v.is_empty()) {
+            continue;
+        }
+        report.rows_read += 1;
+
+        let converted = convert_row(*line, row, &parsed.headers, &fields, mapping, target);
+        report.issues.extend(converted.issues);
+
+        if let Some(missing) = fields
+            .iter()
+            .find(|f| f.required && converted.values.get(&f.id).is_none_or(|v| v.is_empty()))
+        {
+            report.skipped += 1;
+            report.issues.push(CsvIssue {
+                line: *line,
+                column: mapping.get(&missing.id).and_then(|c| parsed.headers.get(*c)).cloned(),
+                severity: IssueSeverity::Error,
+                message: format!("Row skipped: '{}' is empty", missing.label),
+            });
+            continue;
+        }
+
+        append_row(document, target, converted.values)?;
+        report.imported += 1;
+    }
+
+    Ok(report)
+}
+
+/// Builds the column-mapping preview and a dry-run validation report.
+/// When `mapping` is `None`, a mapping is suggested from the header row.
+pub fn preview_csv(
+    document: &LegacyDocument,
+    content: &str,
+    target: &CsvTarget,
+    mapping: Option<ColumnMapping>,
+) -> Result<CsvPreview, CsvImportError> {
+    let parsed = parse_csv(content)?;
+    let fields = target_fields(document, target)?;
+    let mapping = mapping.unwrap_or_else(|| suggest_mapping(&parsed.headers, &fields));
+
+    let sample = parsed
+        .rows
+        .iter()
+        .filter(|(_, row)| row.iter().any(|v| !v.is_empty()))
+        .take(PREVIEW_ROWS)
+        .map(|(line, row)| convert_row(*line, row, &parsed.headers, &fields, &mapping, target).values)
+        .collect();
+
+    let unmapped_columns = parsed
+        .headers
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !mapping.values().any(|c| c == i))
+        .map(|(_, h)| h.clone())
+        .collect();
+
+    // Dry run against a copy so the preview reports exactly what an import would do
+    let report = match validate_mapping(&parsed.headers, &fields, &mapping) {
+        Ok(()) => import_csv(&mut document.clone(), content, target, &mapping)?,
+        Err(e) => CsvImportReport {
+            rows_read: parsed.rows.len(),
+            imported: 0,
+            skipped: parsed.rows.len(),
+            issues: vec![CsvIssue { line: 1, column: None, severity: IssueSeverity::Error, message: e.to_string() }],
+        },
+    };
+
+    Ok(CsvPreview { headers: parsed.headers, fields, mapping, unmapped_columns, sample, report })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CustomSection, FamilyMedical};
+
+    #[test]
+    fn test_suggested_mapping_imports_bills() {
+        let csv = "Payee,Company,Monthly Amount,Due Date,Autopay\n\
+                   Electric,City Power,$120.00,15,yes\n\
+                   Internet,Fiber Co,\"$1,080.00\",1st,no\n";
+        let mut doc = LegacyDocument::default();
+        let preview = preview_csv(&doc, csv, &CsvTarget::Bills, None).unwrap();
+        assert_eq!(preview.mapping.get("name"), Some(&0));
+        assert_eq!(preview.mapping.get("autopay"), Some(&4));
+        assert_eq!(preview.report.imported, 2);
+        assert!(doc.bills.bills.is_empty(), "preview must not modify the document");
+
+        let report = import_csv(&mut doc, csv, &CsvTarget::Bills, &preview.mapping).unwrap();
+        assert_eq!(report.imported, 2);
+        assert_eq!(doc.bills.bills[0].provider, "City Power");
+        assert!(doc.bills.bills[0].autopay);
+        assert_eq!(doc.bills.bills[1].amount, "$1,080.00");
+        assert!(!doc.bills.bills[1].autopay);
+    }
+
+    #[test]
+    fn test_rows_without_required_field_are_skipped() {
+        let csv = "Name;Phone\n;555-0100\nJane Doe;555-0101\n;\n";
+        let mut doc = LegacyDocument::default();
+        let mapping = suggest_mapping(&["Name".into(), "Phone".into()], &contact_fields());
+        let report = import_csv(&mut doc, csv, &CsvTarget::FamilyContacts, &mapping).unwrap();
+
+        assert_eq!(report.rows_read, 2);
+        assert_eq!(report.imported, 1);
+        assert_eq!(report.skipped, 1);
+        assert_eq!(report.issues[0].line, 2);
+        assert_eq!(doc.contacts.family[0].phone, "555-0101");
+    }
+
+    #[test]
+    fn test_account_numbers_are_truncated_to_last_four() {
+        let csv = "Account,Bank,Account Number\nChecking,First Bank,123456789\n";
+        let mut doc = LegacyDocument::default();
+        let preview = preview_csv(&doc, csv, &CsvTarget::BankAccounts, None).unwrap();
+        let report = import_csv(&mut doc, csv, &CsvTarget::BankAccounts, &preview.mapping).unwrap();
+
+        assert_eq!(doc.financial.bank_accounts[0].last_four, "6789");
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].severity, IssueSeverity::Warning);
+    }
+
+    #[test]
+    fn test_custom_subsection_validates_field_types() {
+        let mut doc = LegacyDocument::default();
+        doc.custom_sections.push(CustomSection {
+            id: "s1".into(),
+            name: "Storage Units".into(),
+            subsections: vec![CustomSubsection {
+                id: "sub1".into(),
+                name: "Units".into(),
+                form_elements: vec![
+                    FormElement::Field { id: "f1".into(), name: "Unit".into(), field_type: FieldType::Text },
+                    FormElement::Field { id: "f2".into(), name: "Rent".into(), field_type: FieldType::Number },
+                    FormElement::Field { id: "f3".into(), name: "Paid Through".into(), field_type: FieldType::Date },
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        });
+        let target = CsvTarget::CustomSubsection { section_id: "s1".into(), subsection_id: "sub1".into() };
+        let csv = "Unit,Rent,Paid Through\nA12,$95,03/31/2025\nB7,ninety,sometime\n";
+        let mapping = suggest_mapping(&["Unit".into(), "Rent".into(), "Paid Through".into()], &target_fields(&doc, &target).unwrap());
+        let report = import_csv(&mut doc, csv, &target, &mapping).unwrap();
+
+        let items = &doc.custom_sections[0].subsections[0].items;
+        assert_eq!(report.imported, 2);
+        assert_eq!(items[0].values.get("f2").map(String::as_str), Some("95"));
+        assert_eq!(items[0].values.get("f3").map(String::as_str), Some("2025-03-31"));
+        assert!(!items[1].values.contains_key("f2"));
+        assert_eq!(report.issues.iter().filter(|i| i.severity == IssueSeverity::Error).count(), 2);
+    }
+
+    #[test]
+    fn test_invalid_targets_and_mappings_are_rejected() {
+        let mut doc = LegacyDocument::default();
+        let target = CsvTarget::Medications { family_member: 0 };
+        assert!(matches!(target_fields(&doc, &target), Err(CsvImportError::InvalidTarget(_))));
+
+        doc.medical.family_members.push(FamilyMedical { name: "Dad".into(), ..Default::default() });
+        let no_name = ColumnMapping::from([("dosage".to_string(), 1)]);
+        let result = import_csv(&mut doc, "Drug,Dose\nLisinopril,10mg\n", &target, &no_name);
+        assert!(matches!(result, Err(CsvImportError::InvalidMapping(_))));
+    }
+}
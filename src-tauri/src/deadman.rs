@@ -0,0 +1,378 @@
+use crate::delivery::{Delivery, DeliveryError, HintEmail, TransportConfig};
+use crate::encryption::BrowserKdf;
+use crate::export::{self, ExportError};
+use crate::i18n::Locale;
+use crate::storage::{self, StorageError};
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// Dead man's switch: if the owner stops checking in for long enough, the app
+// prepares the encrypted export on its own and sends it through the chosen
+// transport. Unlocking the app or pressing "Check in" restarts the clock, and
+// re-arms the switch after it has fired.
+
+/// Longest silence period, about ten years
+pub const MAX_SILENCE_DAYS: u32 = 3650;
+
+/// How often the background scheduler looks at the switch
+const SCHEDULER_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+#[derive(Debug)]
+pub enum DeadManError {
+    NotConfigured,
+    InvalidSettings(String),
+    StorageError(StorageError),
+    ExportError(ExportError),
+    DeliveryError(DeliveryError),
+}
+
+impl std::fmt::Display for DeadManError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeadManError::NotConfigured => write!(f, "The dead man's switch is not set up"),
+            DeadManError::InvalidSettings(e) => write!(f, "{}", e),
+            DeadManError::StorageError(e) => write!(f, "{}", e),
+            DeadManError::ExportError(e) => write!(f, "Export failed: {}", e),
+            DeadManError::DeliveryError(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for DeadManError {}
+
+impl From<StorageError> for DeadManError {
+    fn from(e: StorageError) -> Self {
+        DeadManError::StorageError(e)
+    }
+}
+
+impl From<ExportError> for DeadManError {
+    fn from(e: ExportError) -> Self {
+        DeadManError::ExportError(e)
+    }
+}
+
+impl From<DeliveryError> for DeadManError {
+    fn from(e: DeliveryError) -> Self {
+        DeadManError::DeliveryError(e)
+    }
+}
+
+/// Settings from the settings screen. A missing passphrase keeps the stored one.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeadManSettings {
+    pub enabled: bool,
+    pub silence_days: u32,
+    pub transport: TransportConfig,
+    pub passphrase: Option<String>,
+    #[serde(default)]
+    pub memory_hard_kdf: bool,
+    #[serde(default)]
+    pub message: String,
+    /// Emailed separately from the file when the transport is SMTP
+    #[serde(default)]
+    pub passphrase_hint: String,
+    /// Language of the exported file and the default email, English when unset
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// The user saw the weak-passphrase warning and wants this passphrase anyway
+    #[serde(default)]
+    pub acknowledge_weak: bool,
+}
+
+/// The switch as stored, encrypted with the local key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadManSwitch {
+    pub enabled: bool,
+    pub silence_days: u32,
+    pub transport: TransportConfig,
+    /// Exports are made unattended, so the passphrase has to be kept
+    passphrase: String,
+    pub memory_hard_kdf: bool,
+    pub message: String,
+    #[serde(default)]
+    pub passphrase_hint: String,
+    #[serde(default)]
+    pub locale: Option<String>,
+    pub last_check_in: String,
+    /// Set when the switch fires; cleared by the next check-in
+    pub delivered_on: Option<String>,
+    pub last_error: Option<String>,
+}
+
+/// What the settings screen shows; never the passphrase or transport secrets
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadManStatus {
+    pub enabled: bool,
+    pub silence_days: u32,
+    pub transport: TransportConfig,
+    pub memory_hard_kdf: bool,
+    pub message: String,
+    pub passphrase_hint: String,
+    pub locale: Option<String>,
+    pub last_check_in: String,
+    pub due_on: Option<String>,
+    pub delivered_on: Option<String>,
+    pub last_error: Option<String>,
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, DATE_FORMAT).ok()
+}
+
+fn format_date(date: NaiveDate) -> String {
+    date.format(DATE_FORMAT).to_string()
+}
+
+impl DeadManSwitch {
+    /// Creates or updates the switch. Saving settings counts as a check-in.
+    pub fn configure(existing: Option<DeadManSwitch>, settings: DeadManSettings, today: NaiveDate) -> Result<Self, DeadManError> {
+        if settings.silence_days == 0 || settings.silence_days > MAX_SILENCE_DAYS {
+            return Err(DeadManError::InvalidSettings(format!(
+                "Silence period must be between 1 and {} days",
+                MAX_SILENCE_DAYS
+            )));
+        }
+        Locale::for_export(settings.locale.as_deref()).map_err(|e| DeadManError::InvalidSettings(e.to_string()))?;
+        let mut transport = settings.transport;
+        if let Some(existing) = &existing {
+            transport.keep_secrets(&existing.transport);
+        }
+        if let TransportConfig::Smtp(smtp) = &transport {
+            if !settings.passphrase_hint.trim().is_empty() && !smtp.has_hint_recipients() {
+                return Err(DeadManError::InvalidSettings("Add who should get the passphrase hint".into()));
+            }
+        }
+        let passphrase = match (settings.passphrase.filter(|p| !p.is_empty()), existing) {
+            (Some(passphrase), _) => passphrase,
+            (None, Some(existing)) => existing.passphrase,
+            (None, None) => {
+                return Err(DeadManError::InvalidSettings("A passphrase is required for the exports it sends".into()))
+            }
+        };
+        Ok(DeadManSwitch {
+            enabled: settings.enabled,
+            silence_days: settings.silence_days,
+            transport,
+            passphrase,
+            memory_hard_kdf: settings.memory_hard_kdf,
+            message: settings.message,
+            passphrase_hint: settings.passphrase_hint,
+            locale: settings.locale,
+            last_check_in: format_date(today),
+            delivered_on: None,
+            last_error: None,
+        })
+    }
+
+    /// Records how delivering `fired` went. A check-in since `fired` was loaded
+    /// re-armed the switch, so the outcome no longer applies to it.
+    fn record_delivery(&mut self, fired: &DeadManSwitch, today: NaiveDate, result: &Result<String, DeadManError>) {
+        if self.last_check_in != fired.last_check_in {
+            return;
+        }
+        match result {
+            Ok(_) => {
+                self.delivered_on = Some(format_date(today));
+                self.last_error = None;
+            }
+            // Left armed, so the next run tries again
+            Err(e) => self.last_error = Some(e.to_string()),
+        }
+    }
+
+    pub fn check_in(&mut self, today: NaiveDate) {
+        self.last_check_in = format_date(today);
+        self.delivered_on = None;
+        self.last_error = None;
+    }
+
+    /// The first day the switch fires if nobody checks in
+    pub fn due_on(&self) -> Option<NaiveDate> {
+        parse_date(&self.last_check_in)?.checked_add_days(Days::new(u64::from(self.silence_days)))
+    }
+
+    pub fn is_due(&self, today: NaiveDate) -> bool {
+        self.enabled && self.delivered_on.is_none() && self.due_on().is_some_and(|due| today >= due)
+    }
+
+    pub fn status(&self) -> DeadManStatus {
+        DeadManStatus {
+            enabled: self.enabled,
+            silence_days: self.silence_days,
+            transport: self.transport.redacted(),
+            memory_hard_kdf: self.memory_hard_kdf,
+            message: self.message.clone(),
+            passphrase_hint: self.passphrase_hint.clone(),
+            locale: self.locale.clone(),
+            last_check_in: self.last_check_in.clone(),
+            due_on: self.due_on().map(format_date),
+            delivered_on: self.delivered_on.clone(),
+            last_error: self.last_error.clone(),
+        }
+    }
+
+    /// Builds the export from the saved document and hands it to the transport
+    pub fn deliver(&self, today: NaiveDate) -> Result<String, DeadManError> {
+        let document = storage::load_document()?.unwrap_or_default();
+        let kdf = BrowserKdf::for_export(self.memory_hard_kdf);
+        let theme = storage::load_export_theme()?;
+        let locale = Locale::for_export(self.locale.as_deref()).map_err(|e| DeadManError::InvalidSettings(e.to_string()))?;
+        let html = export::generate_encrypted_html(&document, &self.passphrase, true, kdf, &theme, &locale)?;
+        let html = storage::get_or_create_signing_key()?.sign_html(&html).map_err(ExportError::from)?;
+
+        let creator = document.meta.creator_name.trim();
+        let creator = if creator.is_empty() { locale.t("email.owner") } else { creator };
+        let message = if self.message.trim().is_empty() {
+            locale.format("email.dead_man_body", &[("name", creator)])
+        } else {
+            self.message.clone()
+        };
+        let file_name = format!("honey-did-{}.html", format_date(today));
+        let subject = locale.format("email.subject", &[("name", creator)]);
+        let delivery = Delivery {
+            passphrase_hint: HintEmail::new(&locale, &subject, &file_name, Some(&self.passphrase_hint)),
+            file_name,
+            html,
+            subject,
+            message,
+        };
+        Ok(self.transport.transport().deliver(&delivery)?)
+    }
+}
+
+/// Fires the switch if it is due, recording the outcome. Returns where the
+/// export went, or `None` when nothing was due.
+pub fn run_if_due(today: NaiveDate) -> Result<Option<String>, DeadManError> {
+    let switch = match storage::load_dead_man_switch()? {
+        Some(switch) if switch.is_due(today) => switch,
+        _ => return Ok(None),
+    };
+    let result = switch.deliver(today);
+    // Delivery can take a while; reload so a check-in or settings change made
+    // meanwhile isn't overwritten with the copy loaded above
+    let Some(mut current) = storage::load_dead_man_switch()? else {
+        return result.map(Some);
+    };
+    current.record_delivery(&switch, today, &result);
+    storage::save_dead_man_switch(&current)?;
+    result.map(Some)
+}
+
+/// Checks the switch now and then for as long as the app runs
+pub fn spawn_scheduler() {
+    std::thread::spawn(|| loop {
+        if let Err(e) = run_if_due(chrono::Local::now().date_naive()) {
+            eprintln!("Dead man's switch: {}", e);
+        }
+        std::thread::sleep(SCHEDULER_INTERVAL);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::delivery::FolderTransport;
+
+    fn date(value: &str) -> NaiveDate {
+        parse_date(value).unwrap()
+    }
+
+    fn settings(passphrase: Option<&str>) -> DeadManSettings {
+        DeadManSettings {
+            enabled: true,
+            silence_days: 30,
+            transport: TransportConfig::Folder(FolderTransport { path: "/tmp".into() }),
+            passphrase: passphrase.map(str::to_string),
+            memory_hard_kdf: false,
+            message: String::new(),
+            passphrase_hint: String::new(),
+            locale: None,
+            acknowledge_weak: false,
+        }
+    }
+
+    #[test]
+    fn test_due_after_silence() {
+        let switch = DeadManSwitch::configure(None, settings(Some("long enough passphrase")), date("2026-03-01")).unwrap();
+        assert_eq!(switch.status().due_on.as_deref(), Some("2026-03-31"));
+        assert!(!switch.is_due(date("2026-03-30")));
+        assert!(switch.is_due(date("2026-03-31")));
+    }
+
+    #[test]
+    fn test_check_in_rearms() {
+        let mut switch = DeadManSwitch::configure(None, settings(Some("long enough passphrase")), date("2026-03-01")).unwrap();
+        switch.delivered_on = Some("2026-03-31".into());
+        assert!(!switch.is_due(date("2026-04-15")));
+        switch.check_in(date("2026-04-15"));
+        assert!(!switch.is_due(date("2026-05-14")));
+        assert!(switch.is_due(date("2026-05-15")));
+    }
+
+    #[test]
+    fn test_disabled_never_due() {
+        let mut config = settings(Some("long enough passphrase"));
+        config.enabled = false;
+        let switch = DeadManSwitch::configure(None, config, date("2026-03-01")).unwrap();
+        assert!(!switch.is_due(date("2030-01-01")));
+    }
+
+    #[test]
+    fn test_configure_keeps_passphrase() {
+        assert!(DeadManSwitch::configure(None, settings(None), date("2026-03-01")).is_err());
+        let first = DeadManSwitch::configure(None, settings(Some("long enough passphrase")), date("2026-03-01")).unwrap();
+        let second = DeadManSwitch::configure(Some(first), settings(None), date("2026-03-02")).unwrap();
+        assert_eq!(second.passphrase, "long enough passphrase");
+        assert_eq!(second.last_check_in, "2026-03-02");
+    }
+
+    #[test]
+    fn test_rejects_bad_silence_period() {
+        let mut config = settings(Some("long enough passphrase"));
+        config.silence_days = 0;
+        assert!(matches!(
+            DeadManSwitch::configure(None, config, date("2026-03-01")),
+            Err(DeadManError::InvalidSettings(_))
+        ));
+    }
+
+    #[test]
+    fn test_hint_needs_its_own_recipients() {
+        let mut smtp: crate::delivery::SmtpSettings = serde_json::from_str(
+            r#"{"host":"mail.example.com","port":587,"from":"a@example.com","recipients":["b@example.com"]}"#,
+        )
+        .unwrap();
+        let mut config = settings(Some("long enough passphrase"));
+        config.passphrase_hint = "Ask Grandma".into();
+        config.transport = TransportConfig::Smtp(smtp.clone());
+        assert!(matches!(
+            DeadManSwitch::configure(None, config.clone(), date("2026-03-01")),
+            Err(DeadManError::InvalidSettings(_))
+        ));
+
+        smtp.hint_recipients = vec!["c@example.com".into()];
+        config.transport = TransportConfig::Smtp(smtp);
+        assert!(DeadManSwitch::configure(None, config, date("2026-03-01")).is_ok());
+    }
+
+    #[test]
+    fn test_check_in_during_delivery_is_kept() {
+        let fired = DeadManSwitch::configure(None, settings(Some("long enough passphrase")), date("2026-03-01")).unwrap();
+        let mut current = fired.clone();
+        current.check_in(date("2026-03-31"));
+        current.record_delivery(&fired, date("2026-03-31"), &Ok("Saved".into()));
+        assert_eq!(current.last_check_in, "2026-03-31");
+        assert_eq!(current.delivered_on, None);
+
+        let mut unchanged = fired.clone();
+        unchanged.record_delivery(&fired, date("2026-03-31"), &Err(DeadManError::InvalidSettings("offline".into())));
+        assert!(unchanged.last_error.is_some());
+        unchanged.record_delivery(&fired, date("2026-04-01"), &Ok("Saved".into()));
+        assert_eq!(unchanged.delivered_on.as_deref(), Some("2026-04-01"));
+        assert_eq!(unchanged.last_error, None);
+    }
+}
@@ -0,0 +1,605 @@
+use crate::i18n::Locale;
+use lettre::message::{header::ContentType, Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+// Ways to hand an encrypted export to someone without the owner doing it by
+// hand. Every transport takes the same `Delivery`, so callers such as the
+// dead man's switch don't care where the file ends up.
+
+const NETWORK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An encrypted export on its way somewhere
+pub struct Delivery {
+    pub file_name: String,
+    pub html: String,
+    pub subject: String,
+    /// Plain-text note that travels with the file; never the passphrase
+    pub message: String,
+    /// Only the email transport sends this, as a separate message
+    pub passphrase_hint: Option<HintEmail>,
+}
+
+/// The passphrase hint email, worded in the export's language
+pub struct HintEmail {
+    pub subject: String,
+    pub body: String,
+}
+
+impl HintEmail {
+    /// `None` when there is no hint to send
+    pub fn new(locale: &Locale, subject: &str, file_name: &str, hint: Option<&str>) -> Option<HintEmail> {
+        let hint = hint.map(str::trim).filter(|h| !h.is_empty())?;
+        Some(HintEmail {
+            subject: locale.format("email.hint_subject", &[("subject", subject)]),
+            // The hint goes last so braces in it are never taken for placeholders
+            body: locale.format("email.hint_body", &[("file", file_name), ("hint", hint)]),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum DeliveryError {
+    InvalidConfig(String),
+    IoError(String),
+    SmtpError(String),
+    HttpError(String),
+}
+
+impl std::fmt::Display for DeliveryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeliveryError::InvalidConfig(e) => write!(f, "Invalid delivery settings: {}", e),
+            DeliveryError::IoError(e) => write!(f, "Could not write the export: {}", e),
+            DeliveryError::SmtpError(e) => write!(f, "Could not send the email: {}", e),
+            DeliveryError::HttpError(e) => write!(f, "Webhook request failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for DeliveryError {}
+
+pub trait Transport {
+    /// Sends the export, returning a short description of where it went
+    fn deliver(&self, delivery: &Delivery) -> Result<String, DeliveryError>;
+}
+
+/// A transport as the settings screen stores it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TransportConfig {
+    Folder(FolderTransport),
+    Smtp(SmtpSettings),
+    Webhook(WebhookTransport),
+}
+
+impl TransportConfig {
+    pub fn transport(&self) -> &dyn Transport {
+        match self {
+            TransportConfig::Folder(t) => t,
+            TransportConfig::Smtp(t) => t,
+            TransportConfig::Webhook(t) => t,
+        }
+    }
+
+    /// A copy that is safe to show in the settings screen, without passwords or tokens
+    pub fn redacted(&self) -> TransportConfig {
+        match self {
+            TransportConfig::Smtp(t) => TransportConfig::Smtp(t.redacted()),
+            TransportConfig::Webhook(t) => TransportConfig::Webhook(WebhookTransport { bearer_token: None, ..t.clone() }),
+            other => other.clone(),
+        }
+    }
+
+    /// Fills in secrets left blank from the previously saved transport
+    pub fn keep_secrets(&mut self, previous: &TransportConfig) {
+        match (self, previous) {
+            (TransportConfig::Smtp(t), TransportConfig::Smtp(previous)) => t.keep_password(previous),
+            (TransportConfig::Webhook(t), TransportConfig::Webhook(previous))
+                if t.bearer_token.as_deref().unwrap_or("").is_empty() && t.url == previous.url =>
+            {
+                t.bearer_token = previous.bearer_token.clone();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Writes the export into a folder, e.g. one a sync client shares with family
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderTransport {
+    pub path: String,
+}
+
+/// Writes `contents` next to `path` first and renames it into place, so
+/// nothing watching the folder ever sees a half-written file
+pub fn write_atomically(path: &Path, contents: &[u8]) -> Result<(), DeliveryError> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| DeliveryError::InvalidConfig("missing file name".into()))?;
+    let temp_path = path.with_file_name(format!(".{}.partial", file_name.to_string_lossy()));
+    fs::write(&temp_path, contents).map_err(|e| DeliveryError::IoError(e.to_string()))?;
+    fs::rename(&temp_path, path).map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        DeliveryError::IoError(e.to_string())
+    })
+}
+
+impl Transport for FolderTransport {
+    fn deliver(&self, delivery: &Delivery) -> Result<String, DeliveryError> {
+        let folder = Path::new(self.path.trim());
+        if self.path.trim().is_empty() || !folder.is_dir() {
+            return Err(DeliveryError::InvalidConfig(format!("{} is not a folder", self.path)));
+        }
+        let path = folder.join(&delivery.file_name);
+        write_atomically(&path, delivery.html.as_bytes())?;
+        Ok(format!("Saved to {}", path.display()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    /// No encryption; only for a relay on this machine or a test server
+    None,
+    /// Upgrade the connection with STARTTLS, usually on port 587
+    #[default]
+    StartTls,
+    /// TLS from the start, usually on port 465
+    Tls,
+}
+
+/// Accepts a single address as well as a list, so older settings still load
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(address) => vec![address],
+        OneOrMany::Many(addresses) => addresses,
+    })
+}
+
+/// Emails the export as an attachment. A passphrase hint, if any, goes out as
+/// a second email so one intercepted message isn't enough.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    #[serde(default)]
+    pub security: SmtpSecurity,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    pub from: String,
+    #[serde(alias = "to", deserialize_with = "one_or_many")]
+    pub recipients: Vec<String>,
+    /// Who gets the passphrase hint. Required when there is a hint, so it never
+    /// lands in the same inbox as the file by default.
+    #[serde(default)]
+    pub hint_recipients: Vec<String>,
+    /// When set, writes the messages here as .eml files instead of sending them
+    #[serde(default)]
+    pub dry_run_dir: Option<String>,
+}
+
+fn mailbox(address: &str) -> Result<Mailbox, DeliveryError> {
+    address
+        .trim()
+        .parse::<Mailbox>()
+        .map_err(|e| DeliveryError::InvalidConfig(format!("{}: {}", address, e)))
+}
+
+fn mailboxes(addresses: &[String]) -> Result<Vec<Mailbox>, DeliveryError> {
+    addresses.iter().filter(|a| !a.trim().is_empty()).map(|a| mailbox(a)).collect()
+}
+
+impl SmtpSettings {
+    fn redacted(&self) -> SmtpSettings {
+        SmtpSettings { password: None, ..self.clone() }
+    }
+
+    /// Keeps the saved password when the form leaves it blank for the same account
+    pub fn keep_password(&mut self, previous: &SmtpSettings) {
+        if self.password.as_deref().unwrap_or("").is_empty()
+            && self.host == previous.host
+            && self.username == previous.username
+        {
+            self.password = previous.password.clone();
+        }
+    }
+
+    pub fn has_hint_recipients(&self) -> bool {
+        self.hint_recipients.iter().any(|a| !a.trim().is_empty())
+    }
+
+    fn build_message(&self, to: &[Mailbox], subject: String, body: MultiPart) -> Result<Message, DeliveryError> {
+        let mut builder = Message::builder().from(mailbox(&self.from)?).subject(subject);
+        for recipient in to {
+            builder = builder.to(recipient.clone());
+        }
+        builder.multipart(body).map_err(|e| DeliveryError::SmtpError(e.to_string()))
+    }
+
+    /// The export email, followed by the hint email when there is a hint
+    fn messages(&self, delivery: &Delivery) -> Result<Vec<Message>, DeliveryError> {
+        let recipients = mailboxes(&self.recipients)?;
+        if recipients.is_empty() {
+            return Err(DeliveryError::InvalidConfig("Add at least one recipient".into()));
+        }
+
+        let attachment = Attachment::new(delivery.file_name.clone()).body(
+            delivery.html.clone(),
+            ContentType::parse("text/html; charset=utf-8").map_err(|e| DeliveryError::SmtpError(e.to_string()))?,
+        );
+        let export = MultiPart::mixed()
+            .singlepart(SinglePart::plain(delivery.message.clone()))
+            .singlepart(attachment);
+        let mut messages = vec![self.build_message(&recipients, delivery.subject.clone(), export)?];
+
+        if let Some(hint) = &delivery.passphrase_hint {
+            let hint_recipients = mailboxes(&self.hint_recipients)?;
+            if hint_recipients.is_empty() {
+                return Err(DeliveryError::InvalidConfig("Add who should get the passphrase hint".into()));
+            }
+            let hint_message = MultiPart::mixed().singlepart(SinglePart::plain(hint.body.clone()));
+            messages.push(self.build_message(&hint_recipients, hint.subject.clone(), hint_message)?);
+        }
+        Ok(messages)
+    }
+
+    fn mailer(&self) -> Result<SmtpTransport, DeliveryError> {
+        let host = self.host.trim();
+        if host.is_empty() {
+            return Err(DeliveryError::InvalidConfig("SMTP server is required".into()));
+        }
+        let builder = match self.security {
+            SmtpSecurity::None => SmtpTransport::builder_dangerous(host),
+            SmtpSecurity::StartTls => SmtpTransport::starttls_relay(host).map_err(|e| DeliveryError::SmtpError(e.to_string()))?,
+            SmtpSecurity::Tls => SmtpTransport::relay(host).map_err(|e| DeliveryError::SmtpError(e.to_string()))?,
+        };
+        let mut builder = builder.port(self.port).timeout(Some(NETWORK_TIMEOUT));
+        if let Some(username) = self.username.as_deref().filter(|u| !u.is_empty()) {
+            if self.security == SmtpSecurity::None {
+                return Err(DeliveryError::InvalidConfig("Use STARTTLS or TLS to sign in to the server".into()));
+            }
+            builder = builder.credentials(Credentials::new(username.to_string(), self.password.clone().unwrap_or_default()));
+        }
+        Ok(builder.build())
+    }
+
+    /// Writes each message as an RFC 5322 .eml file instead of sending it
+    fn write_dry_run(&self, dir: &str, delivery: &Delivery, messages: &[Message]) -> Result<String, DeliveryError> {
+        let folder = Path::new(dir.trim());
+        if !folder.is_dir() {
+            return Err(DeliveryError::InvalidConfig(format!("{} is not a folder", dir)));
+        }
+        let stem = delivery.file_name.trim_end_matches(".html");
+        for (i, message) in messages.iter().enumerate() {
+            let name = if i == 0 { format!("{}.eml", stem) } else { format!("{}-hint.eml", stem) };
+            write_atomically(&folder.join(name), &message.formatted())?;
+        }
+        Ok(format!("Dry run: wrote {} message(s) to {}", messages.len(), folder.display()))
+    }
+}
+
+impl Transport for SmtpSettings {
+    fn deliver(&self, delivery: &Delivery) -> Result<String, DeliveryError> {
+        use lettre::Transport as _;
+
+        let messages = self.messages(delivery)?;
+        if let Some(dir) = self.dry_run_dir.as_deref().filter(|d| !d.trim().is_empty()) {
+            return self.write_dry_run(dir, delivery, &messages);
+        }
+        let mailer = self.mailer()?;
+        for message in &messages {
+            mailer.send(message).map_err(|e| DeliveryError::SmtpError(e.to_string()))?;
+        }
+        Ok(format!("Emailed to {}", self.recipients.join(", ")))
+    }
+}
+
+/// POSTs the export to a URL, for home automation or a self-hosted service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookTransport {
+    pub url: String,
+    /// Sent as `Authorization: Bearer ...` when set
+    #[serde(default)]
+    pub bearer_token: Option<String>,
+}
+
+impl Transport for WebhookTransport {
+    fn deliver(&self, delivery: &Delivery) -> Result<String, DeliveryError> {
+        let url = self.url.trim();
+        if !(url.starts_with("https://") || url.starts_with("http://")) {
+            return Err(DeliveryError::InvalidConfig("Webhook URL must start with http:// or https://".into()));
+        }
+        let mut request = ureq::post(url)
+            .timeout(NETWORK_TIMEOUT)
+            .set("Content-Type", "text/html; charset=utf-8")
+            .set("X-Honey-Did-File-Name", &delivery.file_name)
+            .set("X-Honey-Did-Subject", &delivery.subject);
+        if let Some(token) = self.bearer_token.as_deref().filter(|t| !t.is_empty()) {
+            request = request.set("Authorization", &format!("Bearer {}", token));
+        }
+        let response = request
+            .send_string(&delivery.html)
+            .map_err(|e| DeliveryError::HttpError(e.to_string()))?;
+        Ok(format!("Posted to {} ({})", url, response.status()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    fn hint(locale: &str, text: &str) -> Option<HintEmail> {
+        HintEmail::new(&Locale::load(locale).unwrap(), "A document for you", "honey-did-2026-01-01.html", Some(text))
+    }
+
+    fn delivery() -> Delivery {
+        Delivery {
+            file_name: "honey-did-2026-01-01.html".into(),
+            html: "<html>encrypted</html>".into(),
+            subject: "A document for you".into(),
+            message: "Open the attached file in a browser.".into(),
+            passphrase_hint: None,
+        }
+    }
+
+    fn smtp_settings(port: u16) -> SmtpSettings {
+        SmtpSettings {
+            host: "127.0.0.1".into(),
+            port,
+            security: SmtpSecurity::None,
+            username: None,
+            password: None,
+            from: "Owner <owner@example.com>".into(),
+            recipients: vec!["executor@example.com".into()],
+            hint_recipients: Vec::new(),
+            dry_run_dir: None,
+        }
+    }
+
+    /// Just enough of an SMTP server to accept one message per connection
+    fn fake_smtp_server(connections: usize) -> (u16, std::thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let mut messages = Vec::new();
+            for _ in 0..connections {
+                let (stream, _) = listener.accept().unwrap();
+                let mut writer = stream.try_clone().unwrap();
+                let mut reader = BufReader::new(stream);
+                writer.write_all(b"220 test ESMTP\r\n").unwrap();
+                let mut data = String::new();
+                let mut in_data = false;
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap() == 0 {
+                        break;
+                    }
+                    if in_data {
+                        if line == ".\r\n" {
+                            in_data = false;
+                            writer.write_all(b"250 queued\r\n").unwrap();
+                        } else {
+                            data.push_str(&line);
+                        }
+                        continue;
+                    }
+                    let command = line.to_ascii_uppercase();
+                    if command.starts_with("EHLO") {
+                        writer.write_all(b"250 test\r\n").unwrap();
+                    } else if command.starts_with("RCPT") {
+                        data.push_str(&line);
+                        writer.write_all(b"250 ok\r\n").unwrap();
+                    } else if command.starts_with("DATA") {
+                        in_data = true;
+                        writer.write_all(b"354 go ahead\r\n").unwrap();
+                    } else if command.starts_with("QUIT") {
+                        writer.write_all(b"221 bye\r\n").unwrap();
+                        break;
+                    } else {
+                        writer.write_all(b"250 ok\r\n").unwrap();
+                    }
+                }
+                messages.push(data);
+            }
+            messages
+        });
+        (port, server)
+    }
+
+    fn temp_folder(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("honey-did-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_config_is_tagged_by_kind() {
+        let config: TransportConfig = serde_json::from_str(r#"{"kind":"folder","path":"/tmp"}"#).unwrap();
+        assert!(matches!(config, TransportConfig::Folder(ref f) if f.path == "/tmp"));
+        let json = serde_json::to_string(&TransportConfig::Webhook(WebhookTransport {
+            url: "https://example.com".into(),
+            bearer_token: None,
+        }))
+        .unwrap();
+        assert!(json.contains(r#""kind":"webhook""#));
+    }
+
+    #[test]
+    fn test_folder_delivery() {
+        let dir = temp_folder("folder-delivery");
+        let transport = FolderTransport { path: dir.to_string_lossy().into_owned() };
+        transport.deliver(&delivery()).unwrap();
+        let written = fs::read_to_string(dir.join("honey-did-2026-01-01.html")).unwrap();
+        assert_eq!(written, "<html>encrypted</html>");
+        // No temporary file is left behind
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_folder_must_exist() {
+        let transport = FolderTransport { path: "/nonexistent/honey-did".into() };
+        assert!(matches!(transport.deliver(&delivery()), Err(DeliveryError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn test_webhook_posts_export() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut head = String::new();
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+                head.push_str(&line);
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            reader.get_mut().write_all(b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n").unwrap();
+            (head, String::from_utf8(body).unwrap())
+        });
+
+        let transport = WebhookTransport {
+            url: format!("http://127.0.0.1:{}/hook", port),
+            bearer_token: Some("secret".into()),
+        };
+        transport.deliver(&delivery()).unwrap();
+        let (head, body) = server.join().unwrap();
+        assert!(head.starts_with("POST /hook"));
+        assert!(head.contains("Authorization: Bearer secret"));
+        assert!(head.contains("X-Honey-Did-File-Name: honey-did-2026-01-01.html"));
+        assert_eq!(body, "<html>encrypted</html>");
+    }
+
+    #[test]
+    fn test_smtp_sends_attachment() {
+        let (port, server) = fake_smtp_server(1);
+        let mut transport = smtp_settings(port);
+        transport.recipients.push("sibling@example.com".into());
+        transport.deliver(&delivery()).unwrap();
+        let data = server.join().unwrap().remove(0);
+        assert!(data.contains("Subject: A document for you"));
+        assert!(data.contains("RCPT TO:<executor@example.com>"));
+        assert!(data.contains("RCPT TO:<sibling@example.com>"));
+        assert!(data.contains("filename=\"honey-did-2026-01-01.html\""));
+        assert!(data.contains("Open the attached file in a browser."));
+    }
+
+    #[test]
+    fn test_smtp_sends_hint_separately() {
+        let (port, server) = fake_smtp_server(2);
+        let mut transport = smtp_settings(port);
+        transport.hint_recipients = vec!["spouse@example.com".into()];
+        let mut with_hint = delivery();
+        with_hint.passphrase_hint = hint("en", "The street we first lived on");
+        transport.deliver(&with_hint).unwrap();
+        let messages = server.join().unwrap();
+        assert!(messages[0].contains("honey-did-2026-01-01.html"));
+        assert!(!messages[0].contains("first lived on"));
+        assert!(messages[1].contains("RCPT TO:<spouse@example.com>"));
+        assert!(messages[1].contains("The street we first lived on"));
+        assert!(!messages[1].contains("<html>encrypted</html>"));
+    }
+
+    #[test]
+    fn test_hint_email_follows_the_locale() {
+        assert!(hint("en", "  ").is_none());
+        let english = hint("en", "Ask Grandma").unwrap();
+        assert_eq!(english.subject, "A document for you (passphrase hint)");
+        assert!(english.body.contains("honey-did-2026-01-01.html"));
+        let spanish = hint("es", "Pregunta a la abuela {file}").unwrap();
+        assert_eq!(spanish.subject, "A document for you (pista de la frase de contraseña)");
+        assert!(spanish.body.starts_with("Una pista"));
+        assert!(spanish.body.ends_with("Pregunta a la abuela {file}"));
+    }
+
+    #[test]
+    fn test_smtp_dry_run_writes_eml() {
+        let dir = temp_folder("smtp-dry-run");
+        let mut transport = smtp_settings(1);
+        transport.dry_run_dir = Some(dir.to_string_lossy().into_owned());
+        transport.hint_recipients = vec!["spouse@example.com".into()];
+        let mut with_hint = delivery();
+        with_hint.passphrase_hint = hint("en", "Ask Grandma");
+        transport.deliver(&with_hint).unwrap();
+        let export = fs::read_to_string(dir.join("honey-did-2026-01-01.eml")).unwrap();
+        assert!(export.contains("To: executor@example.com"));
+        assert!(export.contains("Subject: A document for you"));
+        let hint = fs::read_to_string(dir.join("honey-did-2026-01-01-hint.eml")).unwrap();
+        assert!(hint.contains("To: spouse@example.com"));
+        assert!(hint.contains("Ask Grandma"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_smtp_hint_needs_its_own_recipients() {
+        let dir = temp_folder("smtp-hint-recipients");
+        let mut transport = smtp_settings(1);
+        transport.dry_run_dir = Some(dir.to_string_lossy().into_owned());
+        let mut with_hint = delivery();
+        with_hint.passphrase_hint = hint("en", "Ask Grandma");
+        assert!(matches!(transport.deliver(&with_hint), Err(DeliveryError::InvalidConfig(_))));
+        assert!(!dir.join("honey-did-2026-01-01.eml").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_smtp_rejects_bad_address() {
+        let mut transport = smtp_settings(25);
+        transport.from = "not an address".into();
+        assert!(matches!(transport.deliver(&delivery()), Err(DeliveryError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn test_smtp_credentials_need_tls() {
+        let mut transport = smtp_settings(25);
+        transport.username = Some("owner".into());
+        transport.password = Some("hunter2".into());
+        assert!(matches!(transport.deliver(&delivery()), Err(DeliveryError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn test_smtp_loads_single_recipient_and_keeps_password() {
+        let config: TransportConfig = serde_json::from_str(
+            r#"{"kind":"smtp","host":"mail.example.com","port":587,"from":"a@example.com","to":"b@example.com"}"#,
+        )
+        .unwrap();
+        let TransportConfig::Smtp(ref smtp) = config else { panic!("expected smtp") };
+        assert_eq!(smtp.recipients, vec!["b@example.com".to_string()]);
+        assert_eq!(smtp.security, SmtpSecurity::StartTls);
+
+        let mut saved = smtp.clone();
+        saved.password = Some("app password".into());
+        let saved = TransportConfig::Smtp(saved);
+        let shown = saved.redacted();
+        assert!(!serde_json::to_string(&shown).unwrap().contains("app password"));
+        let mut edited = shown.clone();
+        edited.keep_secrets(&saved);
+        assert!(matches!(edited, TransportConfig::Smtp(ref s) if s.password.as_deref() == Some("app password")));
+    }
+}
@@ -13,11 +13,68 @@ const ARGON2_PARALLELISM: u32 = 4;
 // PBKDF2 iterations - high enough for security, compatible with Web Crypto
 const PBKDF2_ITERATIONS: u32 = 600_000;
 
+// Argon2id for browser exports: same memory and passes as local storage, but a
+// single lane because the exported page computes it on one thread
+const EXPORT_ARGON2_MEMORY_COST: u32 = 65536; // 64 MB
+const EXPORT_ARGON2_TIME_COST: u32 = 3;
+const EXPORT_ARGON2_PARALLELISM: u32 = 1;
+
+// Ceilings for Argon2id parameters read from an imported file, so a crafted
+// file can't make the app allocate gigabytes or run for hours
+const MAX_BROWSER_ARGON2_MEMORY_COST: u32 = 4 * EXPORT_ARGON2_MEMORY_COST;
+const MAX_BROWSER_ARGON2_TIME_COST: u32 = 4 * EXPORT_ARGON2_TIME_COST;
+
+// Each Argon2id wrapping holds 64 MB while it runs
+const MAX_ARGON2_WRAPPING_THREADS: usize = 4;
+
+/// Argon2id for exported pages, compiled from argon2.wat. The tests check it
+/// is still in sync with the source and regenerate it with UPDATE_ARGON2_WASM=1.
+pub const ARGON2_WASM: &[u8] = include_bytes!("argon2.wasm");
+
+/// Browser-side twin of `derive_browser_key`, driving `ARGON2_WASM`
+pub const ARGON2_KDF_JS: &str = include_str!("argon2_kdf.js");
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct EncryptedPayload {
     pub salt: String,      // Base64-encoded Argon2 salt
     pub nonce: String,     // Base64-encoded AES-GCM nonce
     pub ciphertext: String, // Base64-encoded encrypted data
+    /// Key derivation for browser payloads. Left out for PBKDF2, so exports
+    /// made before Argon2id was an option still read as PBKDF2.
+    #[serde(default, skip_serializing_if = "BrowserKdf::is_pbkdf2")]
+    pub kdf: BrowserKdf,
+}
+
+/// How the key for a browser-decryptable payload is derived from the passphrase
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "algorithm", rename_all = "lowercase")]
+pub enum BrowserKdf {
+    /// PBKDF2-HMAC-SHA256, built into Web Crypto
+    #[default]
+    Pbkdf2,
+    /// Argon2id v1.3, computed in the page by the embedded WebAssembly module
+    Argon2id {
+        memory_kib: u32,
+        iterations: u32,
+        parallelism: u32,
+    },
+}
+
+impl BrowserKdf {
+    /// Argon2id at the cost used for exports
+    pub const ARGON2ID: BrowserKdf = BrowserKdf::Argon2id {
+        memory_kib: EXPORT_ARGON2_MEMORY_COST,
+        iterations: EXPORT_ARGON2_TIME_COST,
+        parallelism: EXPORT_ARGON2_PARALLELISM,
+    };
+
+    pub fn for_export(memory_hard: bool) -> Self {
+        if memory_hard { Self::ARGON2ID } else { Self::Pbkdf2 }
+    }
+
+    pub fn is_pbkdf2(&self) -> bool {
+        *self == BrowserKdf::Pbkdf2
+    }
 }
 
 #[derive(Debug)]
@@ -111,27 +168,54 @@ pub fn encrypt(plaintext: &str, passphrase: &str) -> Result<EncryptedPayload, En
         salt: BASE64.encode(salt),
         nonce: BASE64.encode(nonce_bytes),
         ciphertext: BASE64.encode(in_out),
+        kdf: BrowserKdf::default(), // Storage always derives with derive_key
     })
 }
 
-/// Encrypts using PBKDF2 key derivation (compatible with Web Crypto API)
-/// Use this for data that needs to be decrypted in a browser
-pub fn encrypt_for_browser(plaintext: &str, passphrase: &str) -> Result<EncryptedPayload, EncryptionError> {
+/// Derives the AES key for a browser payload. PBKDF2 goes through ring; Argon2id
+/// through the argon2 crate, which the page's WebAssembly module must match
+/// (see `argon2_kdf_vectors.json`).
+pub fn derive_browser_key(passphrase: &str, salt: &[u8], kdf: BrowserKdf) -> Result<[u8; 32], EncryptionError> {
+    let mut key_bytes = [0u8; 32];
+    match kdf {
+        BrowserKdf::Pbkdf2 => pbkdf2::derive(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            NonZeroU32::new(PBKDF2_ITERATIONS).unwrap(),
+            salt,
+            passphrase.as_bytes(),
+            &mut key_bytes,
+        ),
+        BrowserKdf::Argon2id { memory_kib, iterations, parallelism } => {
+            // The page only implements a single lane
+            if parallelism != 1 {
+                return Err(EncryptionError::KeyDerivation("Browser Argon2id supports one lane only".into()));
+            }
+            if memory_kib > MAX_BROWSER_ARGON2_MEMORY_COST || iterations > MAX_BROWSER_ARGON2_TIME_COST {
+                return Err(EncryptionError::KeyDerivation("Argon2id parameters are above the supported maximum".into()));
+            }
+            let params = argon2::Params::new(memory_kib, iterations, parallelism, Some(32))
+                .map_err(|e| EncryptionError::KeyDerivation(e.to_string()))?;
+            Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+                .hash_password_into(passphrase.as_bytes(), salt, &mut key_bytes)
+                .map_err(|e| EncryptionError::KeyDerivation(e.to_string()))?;
+        }
+    }
+    Ok(key_bytes)
+}
+
+/// Encrypts with a Web Crypto-compatible key derivation (PBKDF2, or Argon2id
+/// via the WebAssembly module embedded in the export).
+/// Use this for data that needs to be decrypted in a browser. `aad` is
+/// authenticated but not encrypted; decrypting needs the same bytes.
+pub fn encrypt_for_browser(plaintext: &str, passphrase: &str, kdf: BrowserKdf, aad: &[u8]) -> Result<EncryptedPayload, EncryptionError> {
     // Generate random salt and nonce
     let salt = generate_salt();
     let mut nonce_bytes = [0u8; 12];
     use rand::RngCore;
     OsRng.fill_bytes(&mut nonce_bytes);
 
-    // Derive key using PBKDF2 (Web Crypto compatible)
-    let mut key_bytes = [0u8; 32];
-    pbkdf2::derive(
-        pbkdf2::PBKDF2_HMAC_SHA256,
-        NonZeroU32::new(PBKDF2_ITERATIONS).unwrap(),
-        &salt,
-        passphrase.as_bytes(),
-        &mut key_bytes,
-    );
+    // Derive key the way the exported page will
+    let key_bytes = derive_browser_key(passphrase, &salt, kdf)?;
 
     // Create AES-256-GCM key
     let unbound_key = UnboundKey::new(&AES_256_GCM, &key_bytes)
@@ -141,19 +225,20 @@ pub fn encrypt_for_browser(plaintext: &str, passphrase: &str) -> Result<Encrypte
     // Encrypt
     let nonce = Nonce::assume_unique_for_key(nonce_bytes);
     let mut in_out = plaintext.as_bytes().to_vec();
-    key.seal_in_place_append_tag(nonce, Aad::empty(), &mut in_out)
+    key.seal_in_place_append_tag(nonce, Aad::from(aad), &mut in_out)
         .map_err(|_| EncryptionError::Encryption("Encryption failed".into()))?;
 
     Ok(EncryptedPayload {
         salt: BASE64.encode(salt),
         nonce: BASE64.encode(nonce_bytes),
         ciphertext: BASE64.encode(in_out),
+        kdf,
     })
 }
 
-/// Decrypts using PBKDF2 key derivation (compatible with Web Crypto API)
-/// Use this for data encrypted with encrypt_for_browser
-pub fn decrypt_from_browser(payload: &EncryptedPayload, passphrase: &str) -> Result<String, EncryptionError> {
+/// Decrypts using the key derivation recorded in the payload
+/// Use this for data encrypted with encrypt_for_browser, with the same `aad`
+pub fn decrypt_from_browser(payload: &EncryptedPayload, passphrase: &str, aad: &[u8]) -> Result<String, EncryptionError> {
     // Decode base64 values
     let salt = BASE64
         .decode(&payload.salt)
@@ -167,15 +252,8 @@ pub fn decrypt_from_browser(payload: &EncryptedPayload, passphrase: &str) -> Res
         .decode(&payload.ciphertext)
         .map_err(|_| EncryptionError::InvalidData("Invalid ciphertext".into()))?;
 
-    // Derive key using PBKDF2 (Web Crypto compatible)
-    let mut key_bytes = [0u8; 32];
-    pbkdf2::derive(
-        pbkdf2::PBKDF2_HMAC_SHA256,
-        NonZeroU32::new(PBKDF2_ITERATIONS).unwrap(),
-        &salt,
-        passphrase.as_bytes(),
-        &mut key_bytes,
-    );
+    // Derive key the way the payload records
+    let key_bytes = derive_browser_key(passphrase, &salt, payload.kdf)?;
 
     // Create AES-256-GCM key
     let unbound_key = UnboundKey::new(&AES_256_GCM, &key_bytes)
@@ -185,7 +263,7 @@ pub fn decrypt_from_browser(payload: &EncryptedPayload, passphrase: &str) -> Res
     // Decrypt
     let nonce = Nonce::assume_unique_for_key(nonce_bytes);
     let plaintext = key
-        .open_in_place(nonce, Aad::empty(), &mut ciphertext)
+        .open_in_place(nonce, Aad::from(aad), &mut ciphertext)
         .map_err(|_| EncryptionError::Decryption("Decryption failed - wrong passphrase?".into()))?;
 
     String::from_utf8(plaintext.to_vec())
@@ -200,8 +278,8 @@ pub fn generate_document_key() -> [u8; 32] {
     key
 }
 
-/// Encrypts raw bytes with a raw key using AES-256-GCM
-pub fn encrypt_with_raw_key(plaintext: &[u8], key: &[u8; 32]) -> Result<EncryptedPayload, EncryptionError> {
+/// Encrypts raw bytes with a raw key using AES-256-GCM, authenticating `aad`
+pub fn encrypt_with_raw_key(plaintext: &[u8], key: &[u8; 32], aad: &[u8]) -> Result<EncryptedPayload, EncryptionError> {
     let mut nonce_bytes = [0u8; 12];
     use rand::RngCore;
     OsRng.fill_bytes(&mut nonce_bytes);
@@ -212,32 +290,60 @@ pub fn encrypt_with_raw_key(plaintext: &[u8], key: &[u8; 32]) -> Result<Encrypte
 
     let nonce = Nonce::assume_unique_for_key(nonce_bytes);
     let mut in_out = plaintext.to_vec();
-    aead_key.seal_in_place_append_tag(nonce, Aad::empty(), &mut in_out)
+    aead_key.seal_in_place_append_tag(nonce, Aad::from(aad), &mut in_out)
         .map_err(|_| EncryptionError::Encryption("Encryption failed".into()))?;
 
     Ok(EncryptedPayload {
         salt: String::new(), // Not used for raw key encryption
         nonce: BASE64.encode(nonce_bytes),
         ciphertext: BASE64.encode(in_out),
+        kdf: BrowserKdf::default(), // Not used for raw key encryption
     })
 }
 
-/// Encrypts the document key with a passphrase-derived key using PBKDF2
-pub fn encrypt_key_with_passphrase(document_key: &[u8; 32], passphrase: &str) -> Result<EncryptedPayload, EncryptionError> {
+/// Encrypts the document key with a passphrase-derived key
+pub fn encrypt_key_with_passphrase(document_key: &[u8; 32], passphrase: &str, kdf: BrowserKdf, aad: &[u8]) -> Result<EncryptedPayload, EncryptionError> {
+    wrap_key_with_salt(document_key, passphrase, &generate_salt(), kdf, aad)
+}
+
+/// Encrypts the document key once per passphrase, all sharing one salt.
+/// The browser then derives a single key from what was typed and tries it
+/// against every wrapping, instead of deriving once per wrapping.
+pub fn encrypt_key_with_passphrases(document_key: &[u8; 32], passphrases: &[String], kdf: BrowserKdf, aad: &[u8]) -> Result<Vec<EncryptedPayload>, EncryptionError> {
     let salt = generate_salt();
+    let mut threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    if !kdf.is_pbkdf2() {
+        threads = threads.min(MAX_ARGON2_WRAPPING_THREADS);
+    }
+    let chunk_size = passphrases.len().div_ceil(threads).max(1);
+
+    // Key derivation dominates the cost, so spread the wrappings across cores
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = passphrases
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let salt = &salt;
+                scope.spawn(move || {
+                    chunk.iter().map(|p| wrap_key_with_salt(document_key, p, salt, kdf, aad)).collect::<Result<Vec<_>, _>>()
+                })
+            })
+            .collect();
+
+        let mut wrapped = Vec::with_capacity(passphrases.len());
+        for handle in handles {
+            let chunk = handle.join().map_err(|_| EncryptionError::Encryption("Key wrapping thread failed".into()))??;
+            wrapped.extend(chunk);
+        }
+        Ok(wrapped)
+    })
+}
+
+fn wrap_key_with_salt(document_key: &[u8; 32], passphrase: &str, salt: &[u8], kdf: BrowserKdf, aad: &[u8]) -> Result<EncryptedPayload, EncryptionError> {
     let mut nonce_bytes = [0u8; 12];
     use rand::RngCore;
     OsRng.fill_bytes(&mut nonce_bytes);
 
-    // Derive key using PBKDF2
-    let mut key_bytes = [0u8; 32];
-    pbkdf2::derive(
-        pbkdf2::PBKDF2_HMAC_SHA256,
-        NonZeroU32::new(PBKDF2_ITERATIONS).unwrap(),
-        &salt,
-        passphrase.as_bytes(),
-        &mut key_bytes,
-    );
+    let key_bytes = derive_browser_key(passphrase, salt, kdf)?;
 
     let unbound_key = UnboundKey::new(&AES_256_GCM, &key_bytes)
         .map_err(|_| EncryptionError::Encryption("Failed to create key".into()))?;
@@ -245,18 +351,19 @@ pub fn encrypt_key_with_passphrase(document_key: &[u8; 32], passphrase: &str) ->
 
     let nonce = Nonce::assume_unique_for_key(nonce_bytes);
     let mut in_out = document_key.to_vec();
-    aead_key.seal_in_place_append_tag(nonce, Aad::empty(), &mut in_out)
+    aead_key.seal_in_place_append_tag(nonce, Aad::from(aad), &mut in_out)
         .map_err(|_| EncryptionError::Encryption("Encryption failed".into()))?;
 
     Ok(EncryptedPayload {
         salt: BASE64.encode(salt),
         nonce: BASE64.encode(nonce_bytes),
         ciphertext: BASE64.encode(in_out),
+        kdf,
     })
 }
 
-/// Decrypts the document key from an encrypted payload using PBKDF2 + passphrase
-pub fn decrypt_key_with_passphrase(payload: &EncryptedPayload, passphrase: &str) -> Result<[u8; 32], EncryptionError> {
+/// Decrypts the document key from an encrypted payload using its recorded key derivation + passphrase
+pub fn decrypt_key_with_passphrase(payload: &EncryptedPayload, passphrase: &str, aad: &[u8]) -> Result<[u8; 32], EncryptionError> {
     let salt = BASE64
         .decode(&payload.salt)
         .map_err(|_| EncryptionError::InvalidData("Invalid salt".into()))?;
@@ -269,15 +376,7 @@ pub fn decrypt_key_with_passphrase(payload: &EncryptedPayload, passphrase: &str)
         .decode(&payload.ciphertext)
         .map_err(|_| EncryptionError::InvalidData("Invalid ciphertext".into()))?;
 
-    // Derive key using PBKDF2
-    let mut key_bytes = [0u8; 32];
-    pbkdf2::derive(
-        pbkdf2::PBKDF2_HMAC_SHA256,
-        NonZeroU32::new(PBKDF2_ITERATIONS).unwrap(),
-        &salt,
-        passphrase.as_bytes(),
-        &mut key_bytes,
-    );
+    let key_bytes = derive_browser_key(passphrase, &salt, payload.kdf)?;
 
     let unbound_key = UnboundKey::new(&AES_256_GCM, &key_bytes)
         .map_err(|_| EncryptionError::Decryption("Failed to create key".into()))?;
@@ -285,15 +384,15 @@ pub fn decrypt_key_with_passphrase(payload: &EncryptedPayload, passphrase: &str)
 
     let nonce = Nonce::assume_unique_for_key(nonce_bytes);
     let plaintext = key
-        .open_in_place(nonce, Aad::empty(), &mut ciphertext)
+        .open_in_place(nonce, Aad::from(aad), &mut ciphertext)
         .map_err(|_| EncryptionError::Decryption("Decryption failed - wrong passphrase?".into()))?;
 
     plaintext.try_into()
         .map_err(|_| EncryptionError::Decryption("Decrypted key has wrong length".into()))
 }
 
-/// Decrypts data using a raw 32-byte key (no salt needed)
-pub fn decrypt_with_raw_key(nonce: &str, ciphertext: &str, key: &[u8; 32]) -> Result<String, EncryptionError> {
+/// Decrypts data using a raw 32-byte key (no salt needed) and the `aad` it was encrypted with
+pub fn decrypt_with_raw_key(nonce: &str, ciphertext: &str, key: &[u8; 32], aad: &[u8]) -> Result<String, EncryptionError> {
     let nonce_bytes: [u8; 12] = BASE64
         .decode(nonce)
         .map_err(|_| EncryptionError::InvalidData("Invalid nonce".into()))?
@@ -309,7 +408,7 @@ pub fn decrypt_with_raw_key(nonce: &str, ciphertext: &str, key: &[u8; 32]) -> Re
 
     let nonce = Nonce::assume_unique_for_key(nonce_bytes);
     let plaintext = aead_key
-        .open_in_place(nonce, Aad::empty(), &mut ciphertext_bytes)
+        .open_in_place(nonce, Aad::from(aad), &mut ciphertext_bytes)
         .map_err(|_| EncryptionError::Decryption("Decryption failed".into()))?;
 
     String::from_utf8(plaintext.to_vec())
@@ -410,9 +509,9 @@ mod tests {
         let plaintext = "Browser-compatible encryption test!";
         let passphrase = "browser-test-passphrase";
 
-        let encrypted = encrypt_for_browser(plaintext, passphrase)
+        let encrypted = encrypt_for_browser(plaintext, passphrase, BrowserKdf::Pbkdf2, &[])
             .expect("browser encryption should succeed");
-        let decrypted = decrypt_from_browser(&encrypted, passphrase)
+        let decrypted = decrypt_from_browser(&encrypted, passphrase, &[])
             .expect("browser decryption should succeed");
 
         assert_eq!(decrypted, plaintext);
@@ -421,10 +520,10 @@ mod tests {
     #[test]
     fn test_browser_wrong_passphrase_fails() {
         let plaintext = "Secret data";
-        let encrypted = encrypt_for_browser(plaintext, "correct")
+        let encrypted = encrypt_for_browser(plaintext, "correct", BrowserKdf::Pbkdf2, &[])
             .expect("encryption should succeed");
 
-        let result = decrypt_from_browser(&encrypted, "wrong");
+        let result = decrypt_from_browser(&encrypted, "wrong", &[]);
         assert!(result.is_err());
     }
 
@@ -433,12 +532,119 @@ mod tests {
         let plaintext = "Same message";
         let passphrase = "same-pass";
 
-        let enc1 = encrypt_for_browser(plaintext, passphrase).unwrap();
-        let enc2 = encrypt_for_browser(plaintext, passphrase).unwrap();
+        let enc1 = encrypt_for_browser(plaintext, passphrase, BrowserKdf::Pbkdf2, &[]).unwrap();
+        let enc2 = encrypt_for_browser(plaintext, passphrase, BrowserKdf::Pbkdf2, &[]).unwrap();
 
         // Salt and nonce should differ
         assert_ne!(enc1.salt, enc2.salt);
         assert_ne!(enc1.nonce, enc2.nonce);
         assert_ne!(enc1.ciphertext, enc2.ciphertext);
     }
+
+    #[test]
+    fn test_key_wrapped_for_several_passphrases_shares_salt() {
+        let key = generate_document_key();
+        let passphrases = vec!["6:buster".to_string(), "9:bustopher".to_string(), "3:bus".to_string()];
+        let wrapped = encrypt_key_with_passphrases(&key, &passphrases, BrowserKdf::Pbkdf2, b"questions").unwrap();
+
+        assert_eq!(wrapped.len(), 3);
+        assert!(wrapped.iter().all(|w| w.salt == wrapped[0].salt));
+        for (payload, passphrase) in wrapped.iter().zip(&passphrases) {
+            assert_eq!(decrypt_key_with_passphrase(payload, passphrase, b"questions").unwrap(), key);
+        }
+        assert!(decrypt_key_with_passphrase(&wrapped[0], &passphrases[1], b"questions").is_err());
+    }
+
+    #[test]
+    fn test_associated_data_must_match() {
+        let key = generate_document_key();
+        let document = encrypt_with_raw_key(b"document", &key, b"document").unwrap();
+        assert_eq!(decrypt_with_raw_key(&document.nonce, &document.ciphertext, &key, b"document").unwrap(), "document");
+        assert!(decrypt_with_raw_key(&document.nonce, &document.ciphertext, &key, b"passphrase").is_err());
+        assert!(decrypt_with_raw_key(&document.nonce, &document.ciphertext, &key, &[]).is_err());
+
+        let wrapped = encrypt_key_with_passphrase(&key, "passphrase", BrowserKdf::Pbkdf2, b"passphrase").unwrap();
+        assert_eq!(decrypt_key_with_passphrase(&wrapped, "passphrase", b"passphrase").unwrap(), key);
+        assert!(decrypt_key_with_passphrase(&wrapped, "passphrase", b"questions").is_err());
+
+        let encrypted = encrypt_for_browser("payload", "passphrase", BrowserKdf::Pbkdf2, b"document").unwrap();
+        assert!(decrypt_from_browser(&encrypted, "passphrase", b"document").is_ok());
+        assert!(decrypt_from_browser(&encrypted, "passphrase", &[]).is_err());
+    }
+
+    #[test]
+    fn test_browser_argon2id_roundtrip() {
+        let kdf = BrowserKdf::Argon2id { memory_kib: 256, iterations: 2, parallelism: 1 };
+        let encrypted = encrypt_for_browser("memory-hard", "passphrase", kdf, &[]).unwrap();
+        assert_eq!(encrypted.kdf, kdf);
+
+        // The parameters travel with the payload, so decryption needs nothing else
+        let json = serde_json::to_string(&encrypted).unwrap();
+        assert!(json.contains(r#""kdf":{"algorithm":"argon2id","memory_kib":256,"iterations":2,"parallelism":1}"#));
+        let parsed: EncryptedPayload = serde_json::from_str(&json).unwrap();
+        assert_eq!(decrypt_from_browser(&parsed, "passphrase", &[]).unwrap(), "memory-hard");
+        assert!(decrypt_from_browser(&parsed, "wrong", &[]).is_err());
+    }
+
+    #[test]
+    fn test_oversized_argon2id_parameters_are_rejected() {
+        let salt = generate_salt();
+        let too_much_memory = BrowserKdf::Argon2id { memory_kib: MAX_BROWSER_ARGON2_MEMORY_COST + 1, iterations: 1, parallelism: 1 };
+        let too_many_passes = BrowserKdf::Argon2id { memory_kib: 256, iterations: MAX_BROWSER_ARGON2_TIME_COST + 1, parallelism: 1 };
+        for kdf in [too_much_memory, too_many_passes] {
+            assert!(matches!(derive_browser_key("passphrase", &salt, kdf), Err(EncryptionError::KeyDerivation(_))));
+        }
+        assert!(derive_browser_key("passphrase", &salt, BrowserKdf::Argon2id { memory_kib: 256, iterations: MAX_BROWSER_ARGON2_TIME_COST, parallelism: 1 }).is_ok());
+    }
+
+    #[test]
+    fn test_payload_without_kdf_is_pbkdf2() {
+        let encrypted = encrypt_for_browser("older export", "passphrase", BrowserKdf::Pbkdf2, &[]).unwrap();
+        let json = serde_json::to_string(&encrypted).unwrap();
+        assert!(!json.contains("kdf"));
+
+        let parsed: EncryptedPayload = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.kdf, BrowserKdf::Pbkdf2);
+        assert_eq!(decrypt_from_browser(&parsed, "passphrase", &[]).unwrap(), "older export");
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Argon2Vectors {
+        argon2id: Vec<Argon2Vector>,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Argon2Vector {
+        passphrase: String,
+        salt: String,
+        memory_kib: u32,
+        iterations: u32,
+        parallelism: u32,
+        key: String,
+    }
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_argon2id_vectors() {
+        // The same vectors are checked against the WebAssembly module in argon2Kdf.test.ts
+        let vectors: Argon2Vectors = serde_json::from_str(include_str!("argon2_kdf_vectors.json")).unwrap();
+        for v in vectors.argon2id {
+            let kdf = BrowserKdf::Argon2id { memory_kib: v.memory_kib, iterations: v.iterations, parallelism: v.parallelism };
+            let key = derive_browser_key(&v.passphrase, &from_hex(&v.salt), kdf).unwrap();
+            assert_eq!(key.to_vec(), from_hex(&v.key), "passphrase {:?}", v.passphrase);
+        }
+    }
+
+    #[test]
+    fn test_argon2_wasm_matches_source() {
+        let compiled = wat::parse_str(include_str!("argon2.wat")).unwrap();
+        if std::env::var_os("UPDATE_ARGON2_WASM").is_some() {
+            std::fs::write(concat!(env!("CARGO_MANIFEST_DIR"), "/src/argon2.wasm"), &compiled).unwrap();
+            return;
+        }
+        assert!(compiled == ARGON2_WASM, "argon2.wasm is stale; rerun this test with UPDATE_ARGON2_WASM=1");
+    }
 }
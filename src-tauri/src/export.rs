@@ -1,26 +1,27 @@
-use crate::encryption::{decrypt_from_browser, decrypt_key_with_passphrase, decrypt_with_raw_key, encrypt_for_browser, encrypt_key_with_passphrase, encrypt_with_raw_key, generate_document_key, EncryptedPayload, EncryptionError};
+use crate::encryption::{decrypt_from_browser, decrypt_key_with_passphrase, decrypt_with_raw_key, encrypt_for_browser, encrypt_key_with_passphrase, encrypt_key_with_passphrases, encrypt_with_raw_key, generate_document_key, BrowserKdf, EncryptedPayload, EncryptionError, ARGON2_KDF_JS, ARGON2_WASM};
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use ring::digest;
 use serde::Deserialize;
-use crate::models::{FieldType, LegacyDocument, SlideType};
+use crate::models::{LegacyDocument, SlideType};
+use crate::questions::{
+    accepted_answers, answer_combinations, answer_subsets, question_key_passphrase, MAX_ANSWER_COMBINATIONS, QUESTION_JS_NORMALIZE,
+};
+use crate::timelock::{TimeLock, TimeLockInfo};
+use crate::i18n::Locale;
+use crate::render::{RenderError, RenderedDocument, Theme, ViewerLinks};
+use crate::search::{SearchIndex, SEARCH_JS_MATCHING};
+use crate::signing::{self, SigningError, SIGNATURE_PLACEHOLDER};
+use crate::{calendar, render, vcard};
 use serde::Serialize;
-
-/// The app logo PNG, embedded at compile time.
-const LOGO_PNG_BYTES: &[u8] = include_bytes!("../icons/icon-no-text.png");
-
-/// Returns the logo as a base64 data-URI `<img>` tag.
-fn logo_img_tag(css_class: &str, size_px: u32) -> String {
-    let b64 = BASE64.encode(LOGO_PNG_BYTES);
-    format!(
-        r#"<img class="{}" src="data:image/png;base64,{}" alt="Honey Did" style="width:{}px;height:{}px;" />"#,
-        css_class, b64, size_px, size_px
-    )
-}
+use std::collections::BTreeMap;
 
 #[derive(Debug)]
 pub enum ExportError {
     EncryptionError(EncryptionError),
     SerializationError(String),
     ParseError(String),
+    RenderError(RenderError),
+    SignatureError(SigningError),
 }
 
 impl std::fmt::Display for ExportError {
@@ -29,6 +30,8 @@ impl std::fmt::Display for ExportError {
             ExportError::EncryptionError(e) => write!(f, "Encryption error: {}", e),
             ExportError::SerializationError(msg) => write!(f, "Serialization error: {}", msg),
             ExportError::ParseError(msg) => write!(f, "Parse error: {}", msg),
+            ExportError::RenderError(e) => write!(f, "{}", e),
+            ExportError::SignatureError(e) => write!(f, "{}", e),
         }
     }
 }
@@ -41,54 +44,225 @@ impl From<EncryptionError> for ExportError {
     }
 }
 
+impl From<RenderError> for ExportError {
+    fn from(e: RenderError) -> Self {
+        ExportError::RenderError(e)
+    }
+}
+
+impl From<SigningError> for ExportError {
+    fn from(e: SigningError) -> Self {
+        ExportError::SignatureError(e)
+    }
+}
+
+/// Format of files with an `ExportHeader`. Files without one are format 1,
+/// whose ciphertexts have no associated data; format 2 headers have no lock screen.
+const EXPORT_FORMAT: u32 = 3;
+
+/// Key slots: the part of a file a ciphertext belongs to. Every key wrapped
+/// from answers, including "any K of N" keys, is in the questions slot.
+const SLOT_DOCUMENT: &str = "document";
+const SLOT_QUESTIONS: &str = "questions";
+const SLOT_PASSPHRASE: &str = "passphrase";
+
+/// Browser-side twin of `ExportHeader::associated_data`.
+/// Both implementations are checked against `export_aad_vectors.json`.
+const EXPORT_AAD_JS: &str = include_str!("export_aad.js");
+
+/// Metadata stored in the clear with an export's encrypted data. Every
+/// AES-GCM ciphertext in the file authenticates it, together with its key
+/// slot, so editing it or moving a wrapped key to another slot or another
+/// export makes decryption fail.
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportHeader {
+    format: u32,
+    /// When the file was exported, RFC 3339 in UTC
+    created: String,
+    creator: String,
+    /// `LockScreen` as JSON. The page renders its lock screen from this copy only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lock_screen: Option<String>,
+}
+
+impl ExportHeader {
+    fn new(document: &LegacyDocument, lock_screen: &LockScreen) -> Result<Self, ExportError> {
+        Ok(ExportHeader {
+            format: EXPORT_FORMAT,
+            created: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            creator: document.meta.creator_name.clone(),
+            lock_screen: Some(serde_json::to_string(lock_screen).map_err(|e| ExportError::SerializationError(e.to_string()))?),
+        })
+    }
+
+    /// Each field prefixed with its UTF-8 length, as in `question_key_passphrase`
+    fn associated_data(&self, slot: &str) -> String {
+        let format = self.format.to_string();
+        let mut fields = vec!["honey-did-export", &format, &self.created, &self.creator];
+        fields.extend(self.lock_screen.as_deref());
+        fields.push(slot);
+        fields.iter().map(|field| format!("{}:{}", field.len(), field)).collect()
+    }
+}
+
+/// Everything the page shows or asks before it can decrypt. It is bound to the
+/// ciphertexts through the header, so a file edited to show other questions,
+/// another sender or a different release date no longer opens.
+#[derive(Serialize, Default)]
+struct LockScreen<'a> {
+    /// The lock message as HTML, for passphrase files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+    slides: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    attempt_limit: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    threshold: Option<AnswerThreshold>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    time_lock: Option<&'a TimeLockInfo>,
+}
+
+/// "Any K of N": how many answers are needed, and the questions behind each subset key
+#[derive(Serialize)]
+struct AnswerThreshold {
+    required: usize,
+    /// Slide ids per subset, in slide order and in the order of `ThresholdKeys::subsets`
+    subsets: Vec<Vec<String>>,
+}
+
+/// Associated data for one slot of an imported file; none for files from before headers
+fn import_associated_data(header: Option<&ExportHeader>, slot: &str) -> Vec<u8> {
+    header.map_or_else(Vec::new, |h| h.associated_data(slot).into_bytes())
+}
+
+/// Derived files bundled into the encrypted payload for the recipient to download.
+/// Import reads the payload as a plain `LegacyDocument` and ignores these.
+#[derive(Serialize)]
+struct ExportExtras {
+    /// All contacts as one vCard bundle
+    contacts_vcf: String,
+    /// Single-contact vCards keyed by `vcard::contact_card_key`
+    contact_cards: BTreeMap<String, String>,
+    /// Recurring bills and maintenance tasks as an iCalendar file
+    calendar_ics: String,
+    /// Words, stems and phonetic codes, so the page doesn't compute them on open
+    search_index: SearchIndex,
+    /// Section HTML from the templates in `render.rs`
+    rendered: RenderedDocument,
+}
+
+/// The JSON that gets encrypted: the document plus `export_extras`
+#[derive(Serialize)]
+struct ExportPayload<'a> {
+    #[serde(flatten)]
+    document: &'a LegacyDocument,
+    export_extras: ExportExtras,
+}
+
+/// Serializes the document and its derived files for encryption
+fn serialize_export_payload(document: &LegacyDocument, theme: &Theme, locale: &Locale) -> Result<String, ExportError> {
+    let contacts_vcf = vcard::document_to_vcf(document);
+    let contact_cards = vcard::document_contact_cards(document);
+    let calendar_ics = calendar::generate_ics(document, chrono::Local::now().date_naive()).0;
+    let links = ViewerLinks {
+        contact_cards: contact_cards.keys().map(String::as_str).collect(),
+        contacts_bundle: !contacts_vcf.is_empty(),
+        calendar: !calendar_ics.is_empty(),
+    };
+    let rendered = render::render_viewer(document, theme, locale, &links)?;
+    let payload = ExportPayload {
+        document,
+        export_extras: ExportExtras {
+            contacts_vcf,
+            contact_cards,
+            calendar_ics,
+            search_index: SearchIndex::from_document(document),
+            rendered,
+        },
+    };
+    serde_json::to_string(&payload).map_err(|e| ExportError::SerializationError(e.to_string()))
+}
+
 /// Generates the encrypted HTML file content
 pub fn generate_encrypted_html(
     document: &LegacyDocument,
     passphrase: &str,
     include_welcome_screen: bool,
+    kdf: BrowserKdf,
+    theme: &Theme,
+    locale: &Locale,
 ) -> Result<String, ExportError> {
-    // Serialize document to JSON
-    let json = serde_json::to_string(document)
-        .map_err(|e| ExportError::SerializationError(e.to_string()))?;
+    // Serialize document (with derived files) to JSON
+    let json = serialize_export_payload(document, theme, locale)?;
+
+    // Welcome slides, if enabled
+    let welcome_slides = match document.welcome_screen {
+        Some(ref welcome) if include_welcome_screen && welcome.enabled && !welcome.slides.is_empty() => {
+            serde_json::to_value(&welcome.slides).map_err(|e| ExportError::SerializationError(e.to_string()))?
+        }
+        _ => serde_json::json!([]),
+    };
+    let lock_screen = LockScreen {
+        message: Some(theme.lock_message_html(&document.meta.creator_name, locale)),
+        slides: welcome_slides,
+        ..Default::default()
+    };
 
-    // Encrypt the JSON
-    let encrypted = encrypt_for_browser(&json, passphrase)?;
+    // Encrypt the JSON, bound to the header
+    let header = ExportHeader::new(document, &lock_screen)?;
+    let encrypted = PassphraseEncryptedData {
+        payload: encrypt_for_browser(&json, passphrase, kdf, header.associated_data(SLOT_DOCUMENT).as_bytes())?,
+        header: Some(header),
+    };
 
     // Serialize encrypted payload
     let encrypted_json = serde_json::to_string(&encrypted)
         .map_err(|e| ExportError::SerializationError(e.to_string()))?;
 
-    // Prepare welcome screen data if enabled
-    let welcome_screen_json = if include_welcome_screen {
-        if let Some(ref welcome) = document.welcome_screen {
-            if welcome.enabled && !welcome.slides.is_empty() {
-                serde_json::to_string(&welcome.slides)
-                    .map_err(|e| ExportError::SerializationError(e.to_string()))?
-            } else {
-                "[]".to_string()
-            }
-        } else {
-            "[]".to_string()
-        }
-    } else {
-        "[]".to_string()
-    };
-
     // Generate the HTML
-    let html = generate_html_template(&encrypted_json, &document.meta.creator_name, &welcome_screen_json);
+    let html = generate_html_template(&encrypted_json, kdf, theme, locale);
 
     Ok(html)
 }
 
+/// Passphrase-based encrypted data: the payload's fields plus the header
+#[derive(Serialize, Deserialize)]
+struct PassphraseEncryptedData {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    header: Option<ExportHeader>,
+    #[serde(flatten)]
+    payload: EncryptedPayload,
+}
+
 /// Structure for dual-key encrypted data (question-based unlock)
 #[derive(Serialize)]
 struct DualKeyEncryptedData {
+    header: ExportHeader,
     question_key: EncryptedPayload,
+    /// Same salt as `question_key`, wrapped for alternative accepted answers
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    question_key_alternates: Vec<EncryptedPayload>,
     #[serde(skip_serializing_if = "Option::is_none")]
     passphrase_key: Option<EncryptedPayload>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    threshold: Option<ThresholdKeys>,
     document: DocumentPayload,
 }
 
+/// Document key wrapped once per combination of `required` answers. The
+/// questions of each subset are in the lock screen's `AnswerThreshold`.
+#[derive(Serialize)]
+struct ThresholdKeys {
+    subsets: Vec<SubsetKey>,
+}
+
+#[derive(Serialize)]
+struct SubsetKey {
+    key: EncryptedPayload,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    alternates: Vec<EncryptedPayload>,
+}
+
 #[derive(Serialize)]
 struct DocumentPayload {
     nonce: String,
@@ -105,11 +279,16 @@ struct ExportSlide {
     transition: serde_json::Value,
 }
 
-/// Generates encrypted HTML with question-based unlock
+/// Generates encrypted HTML with question-based unlock. With a time lock, the
+/// answers also need the release code; the backup passphrase does not.
 pub fn generate_encrypted_html_with_questions(
     document: &LegacyDocument,
     passphrase: &str,
     include_welcome_screen: bool,
+    kdf: BrowserKdf,
+    theme: &Theme,
+    locale: &Locale,
+    time_lock: Option<&TimeLock>,
 ) -> Result<String, ExportError> {
     let welcome = document.welcome_screen.as_ref()
         .ok_or_else(|| ExportError::SerializationError("Welcome screen not configured".into()))?;
@@ -131,41 +310,135 @@ pub fn generate_encrypted_html_with_questions(
         return Err(ExportError::SerializationError("Maximum 5 questions allowed".into()));
     }
 
-    // Concatenate answers (normalized to lowercase) to form the question key passphrase
-    let question_passphrase: String = question_slides.iter()
-        .filter_map(|s| s.answer.as_ref())
-        .map(|a| a.to_lowercase().trim().to_string())
-        .collect::<Vec<_>>()
-        .join("");
+    // Each question accepts its main answer plus any alternatives
+    let options: Vec<Vec<&str>> = question_slides.iter()
+        .map(|s| accepted_answers(s.answer.as_deref().unwrap_or(""), &s.alternate_answers))
+        .collect();
 
-    if question_passphrase.is_empty() {
+    if options.iter().any(|o| o.is_empty()) {
         return Err(ExportError::SerializationError("All questions must have answers".into()));
     }
 
-    // Serialize document to JSON
-    let json = serde_json::to_string(document)
-        .map_err(|e| ExportError::SerializationError(e.to_string()))?;
+    let required = welcome.required_answers.unwrap_or(options.len()).min(options.len());
+    if required < 2 {
+        return Err(ExportError::SerializationError("At least 2 answers must be required".into()));
+    }
+
+    if time_lock.is_some() && passphrase.is_empty() {
+        return Err(ExportError::SerializationError("Time-locked exports need a backup passphrase".into()));
+    }
+
+    match welcome.attempt_limit {
+        Some(0) => return Err(ExportError::SerializationError("The attempt limit must be at least 1".into())),
+        Some(_) if passphrase.is_empty() => {
+            return Err(ExportError::SerializationError("An attempt limit needs a backup passphrase".into()));
+        }
+        _ => {}
+    }
+
+    // Question key passphrases (normalized, length-prefixed answers) for every
+    // combination of accepted answers to the given questions, main answers first
+    let passphrases_for = |questions: &[usize]| -> Vec<String> {
+        let question_options: Vec<Vec<&str>> = questions.iter().map(|&i| options[i].clone()).collect();
+        answer_combinations(&question_options).iter().map(|c| question_key_passphrase(c)).collect()
+    };
+
+    let main_answers: Vec<&str> = options.iter().map(|o| o[0]).collect();
+    let subsets = if required < options.len() { answer_subsets(options.len(), required) } else { Vec::new() };
+
+    // With "any K of N" the subset keys already cover every full set of answers,
+    // so alternatives only need wrapping per subset
+    let full_passphrases = if subsets.is_empty() {
+        passphrases_for(&(0..options.len()).collect::<Vec<_>>())
+    } else {
+        vec![question_key_passphrase(&main_answers)]
+    };
+    let subset_passphrases: Vec<Vec<String>> = subsets.iter().map(|subset| passphrases_for(subset)).collect();
+
+    let wrappings = full_passphrases.len() + subset_passphrases.iter().map(Vec::len).sum::<usize>();
+    if wrappings > MAX_ANSWER_COMBINATIONS {
+        return Err(ExportError::SerializationError(format!(
+            "Too many combinations of accepted answers ({}, limit {}). Remove some alternative answers.",
+            wrappings, MAX_ANSWER_COMBINATIONS
+        )));
+    }
+
+    // Serialize document (with derived files) to JSON
+    let json = serialize_export_payload(document, theme, locale)?;
+
+    // Prepare slides for export (strip answers from question slides)
+    let export_slides: Vec<ExportSlide> = if include_welcome_screen {
+        welcome.slides.iter().map(|s| ExportSlide {
+            id: s.id.clone(),
+            slide_type: match s.slide_type {
+                SlideType::Message => "message".to_string(),
+                SlideType::Question => "question".to_string(),
+            },
+            text: s.text.clone(),
+            transition: serde_json::to_value(&s.transition).unwrap_or(serde_json::json!({"type": "click"})),
+        }).collect()
+    } else {
+        vec![]
+    };
+
+    // Everything the page shows before unlocking goes into the header. With a time
+    // lock, the question keys wrap the document key XOR an escrowed release key.
+    let lock_screen = LockScreen {
+        message: None,
+        slides: serde_json::to_value(&export_slides).map_err(|e| ExportError::SerializationError(e.to_string()))?,
+        attempt_limit: welcome.attempt_limit,
+        threshold: (!subsets.is_empty()).then(|| AnswerThreshold {
+            required,
+            subsets: subsets.iter().map(|subset| subset.iter().map(|&i| question_slides[i].id.clone()).collect()).collect(),
+        }),
+        time_lock: time_lock.map(|lock| &lock.info),
+    };
 
     // Generate random document key
     let doc_key = generate_document_key();
 
     // Encrypt document with document key
-    let doc_encrypted = encrypt_with_raw_key(json.as_bytes(), &doc_key)?;
+    let header = ExportHeader::new(document, &lock_screen)?;
+    let doc_encrypted = encrypt_with_raw_key(json.as_bytes(), &doc_key, header.associated_data(SLOT_DOCUMENT).as_bytes())?;
+
+    // Encrypt document key once per accepted-answer combination, all in one batch.
+    // Time-locked exports give the answers only a share that needs the release key.
+    let question_share = time_lock.map_or(doc_key, |lock| lock.question_share(&doc_key));
+    let all_passphrases: Vec<String> = full_passphrases.iter().chain(subset_passphrases.iter().flatten()).cloned().collect();
+    let mut wrapped = encrypt_key_with_passphrases(&question_share, &all_passphrases, kdf, header.associated_data(SLOT_QUESTIONS).as_bytes())?.into_iter();
+
+    let question_key_encrypted = wrapped.next()
+        .ok_or_else(|| ExportError::SerializationError("No question key was produced".into()))?;
+    let question_key_alternates: Vec<EncryptedPayload> = wrapped.by_ref().take(full_passphrases.len() - 1).collect();
 
-    // Encrypt document key with question-derived key
-    let question_key_encrypted = encrypt_key_with_passphrase(&doc_key, &question_passphrase)?;
+    // With "any K of N", the document key is also wrapped for every K-answer combination
+    let threshold = if subsets.is_empty() {
+        None
+    } else {
+        let subsets = subset_passphrases.iter()
+            .map(|passphrases| {
+                let mut keys = wrapped.by_ref().take(passphrases.len());
+                let key = keys.next().ok_or_else(|| ExportError::SerializationError("Missing subset key".into()))?;
+                Ok(SubsetKey { key, alternates: keys.collect() })
+            })
+            .collect::<Result<Vec<_>, ExportError>>()?;
+        Some(ThresholdKeys { subsets })
+    };
 
     // Encrypt document key with the export passphrase (backup to questions)
     let passphrase_key_encrypted = if !passphrase.is_empty() {
-        Some(encrypt_key_with_passphrase(&doc_key, passphrase)?)
+        Some(encrypt_key_with_passphrase(&doc_key, passphrase, kdf, header.associated_data(SLOT_PASSPHRASE).as_bytes())?)
     } else {
         None
     };
 
     // Build the encrypted data structure
     let encrypted_data = DualKeyEncryptedData {
+        header,
         question_key: question_key_encrypted,
+        question_key_alternates,
         passphrase_key: passphrase_key_encrypted,
+        threshold,
         document: DocumentPayload {
             nonce: doc_encrypted.nonce,
             ciphertext: doc_encrypted.ciphertext,
@@ -175,32 +448,10 @@ pub fn generate_encrypted_html_with_questions(
     let encrypted_json = serde_json::to_string(&encrypted_data)
         .map_err(|e| ExportError::SerializationError(e.to_string()))?;
 
-    // Prepare slides for export (strip answers from question slides)
-    let export_slides: Vec<ExportSlide> = if include_welcome_screen {
-        welcome.slides.iter().map(|s| ExportSlide {
-            id: s.id.clone(),
-            slide_type: match s.slide_type {
-                SlideType::Message => "message".to_string(),
-                SlideType::Question => "question".to_string(),
-            },
-            text: s.text.clone(),
-            transition: serde_json::to_value(&s.transition).unwrap_or(serde_json::json!({"type": "click"})),
-        }).collect()
-    } else {
-        vec![]
-    };
-
-    let slides_json = serde_json::to_string(&export_slides)
-        .map_err(|e| ExportError::SerializationError(e.to_string()))?;
-
     let has_passphrase_fallback = !passphrase.is_empty();
 
     // Generate the HTML with question-based unlock
-    let html = generate_question_html_template(
-        &encrypted_json,
-        &slides_json,
-        has_passphrase_fallback,
-    );
+    let html = generate_question_html_template(&encrypted_json, has_passphrase_fallback, kdf, theme, locale);
 
     Ok(html)
 }
@@ -209,6 +460,8 @@ pub fn generate_encrypted_html_with_questions(
 #[derive(Deserialize)]
 #[allow(dead_code)] // question_key is part of the format but only used for browser-side unlock
 struct QuestionBasedEncryptedData {
+    #[serde(default)]
+    header: Option<ExportHeader>,
     question_key: EncryptedPayload,
     passphrase_key: Option<EncryptedPayload>,
     document: DocumentPayloadImport,
@@ -261,8 +514,11 @@ fn extract_json_from_html(html: &str, marker: &str) -> Result<String, ExportErro
     Ok(html[json_start..json_end].to_string())
 }
 
-/// Imports a legacy document from an encrypted HTML file
+/// Imports a legacy document from an encrypted HTML file. A signed file must
+/// still match its signature, and a file with a header must match its header;
+/// files from before signing or headers import as they are.
 pub fn import_from_html(html: &str, passphrase: &str) -> Result<LegacyDocument, ExportError> {
+    signing::verify_html(html)?;
     let encrypted_json = extract_json_from_html(html, "const ENCRYPTED_DATA = ")?;
 
     // Try to detect if this is a question-based export by checking for question_key field
@@ -280,10 +536,16 @@ pub fn import_from_html(html: &str, passphrase: &str) -> Result<LegacyDocument,
         })?;
 
         // Decrypt the document key using the passphrase
-        let doc_key = decrypt_key_with_passphrase(&passphrase_key, passphrase)?;
+        let header = data.header.as_ref();
+        let doc_key = decrypt_key_with_passphrase(&passphrase_key, passphrase, &import_associated_data(header, SLOT_PASSPHRASE))?;
 
         // Decrypt the document using the document key
-        let decrypted_json = decrypt_with_raw_key(&data.document.nonce, &data.document.ciphertext, &doc_key)?;
+        let decrypted_json = decrypt_with_raw_key(
+            &data.document.nonce,
+            &data.document.ciphertext,
+            &doc_key,
+            &import_associated_data(header, SLOT_DOCUMENT),
+        )?;
 
         // Parse the decrypted JSON into a LegacyDocument
         let document: LegacyDocument = serde_json::from_str(&decrypted_json)
@@ -292,11 +554,12 @@ pub fn import_from_html(html: &str, passphrase: &str) -> Result<LegacyDocument,
         Ok(document)
     } else {
         // This is a passphrase-based export (original format)
-        let payload: EncryptedPayload = serde_json::from_str(&encrypted_json)
+        let data: PassphraseEncryptedData = serde_json::from_str(&encrypted_json)
             .map_err(|e| ExportError::ParseError(format!("Invalid encrypted data format: {}", e)))?;
 
         // Decrypt the payload
-        let decrypted_json = decrypt_from_browser(&payload, passphrase)?;
+        let aad = import_associated_data(data.header.as_ref(), SLOT_DOCUMENT);
+        let decrypted_json = decrypt_from_browser(&data.payload, passphrase, &aad)?;
 
         // Parse the decrypted JSON into a LegacyDocument
         let document: LegacyDocument = serde_json::from_str(&decrypted_json)
@@ -310,103 +573,135 @@ pub fn import_from_html(html: &str, passphrase: &str) -> Result<LegacyDocument,
 // SHARED TEMPLATE COMPONENTS
 // ============================================================================
 
-// LOGO_SVG removed — replaced by logo_img_tag() using the embedded PNG.
+// Colors come from `render::Theme` as --hd-* custom properties, declared ahead of these styles.
 
 /// Shared CSS styles used by both templates
 const SHARED_CSS: &str = r##"
         * { box-sizing: border-box; margin: 0; padding: 0; }
-        body { font-family: 'Inter', -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif; line-height: 1.6; background: #F0EFEB; color: #283618; }
+        body { font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif; line-height: 1.6; background: var(--hd-background); color: var(--hd-primary); }
         .content { display: none; width: 100%; }
         .content.visible { display: block; width: 100%; }
         .layout { display: flex; min-height: 100vh; width: 100%; }
         .container { width: 100%; }
-        .sidebar { width: 280px; min-width: 280px; background: #FFFFFF; border-right: 1px solid #D4D4D4; height: 100vh; position: fixed; left: 0; top: 0; overflow-y: auto; display: flex; flex-direction: column; z-index: 100; }
-        .sidebar-header { padding: 16px 20px; border-bottom: 1px solid #D4D4D4; background: #283618; display: flex; align-items: center; gap: 12px; }
+        .sidebar { width: 280px; min-width: 280px; background: #FFFFFF; border-inline-end: 1px solid var(--hd-border); height: 100vh; position: fixed; inset-inline-start: 0; top: 0; overflow-y: auto; display: flex; flex-direction: column; z-index: 100; }
+        .sidebar-header { padding: 16px 20px; border-bottom: 1px solid var(--hd-border); background: var(--hd-primary); display: flex; align-items: center; gap: 12px; }
         .logo-icon { width: 40px; height: 40px; flex-shrink: 0; }
         .logo-text { flex: 1; }
-        .sidebar-title { font-size: 1.25rem; font-weight: 600; color: #F0EFEB; margin-bottom: 0.25rem; }
-        .sidebar-subtitle { font-size: 0.8rem; color: #B7B7A4; }
-        .sidebar-search { padding: 16px; border-bottom: 1px solid #D4D4D4; }
+        .sidebar-title { font-size: 1.25rem; font-weight: 600; color: var(--hd-background); margin-bottom: 0.25rem; }
+        .sidebar-subtitle { font-size: 0.8rem; color: var(--hd-muted); }
+        .sidebar-search { padding: 16px; border-bottom: 1px solid var(--hd-border); }
         .search-wrapper { position: relative; display: flex; align-items: center; }
-        .search-input { width: 100%; padding: 10px 36px 10px 14px; border: 1px solid #D4D4D4; border-radius: 8px; font-size: 0.9rem; background: #F0EFEB; transition: border-color 0.2s, box-shadow 0.2s; }
-        .search-input:focus { outline: none; border-color: #283618; box-shadow: 0 0 0 3px rgba(40, 54, 24, 0.1); background: white; }
-        .search-clear { position: absolute; right: 10px; background: none; border: none; cursor: pointer; color: #B7B7A4; font-size: 1.1rem; padding: 0 4px; line-height: 1; transition: color 0.2s; }
-        .search-clear:hover { color: #283618; }
+        .search-input { width: 100%; padding: 10px 14px; padding-inline-end: 36px; border: 1px solid var(--hd-border); border-radius: 8px; font-size: 0.9rem; background: var(--hd-background); transition: border-color 0.2s, box-shadow 0.2s; }
+        .search-input:focus { outline: none; border-color: var(--hd-primary); box-shadow: 0 0 0 3px rgba(40, 54, 24, 0.1); background: white; }
+        .search-clear { position: absolute; inset-inline-end: 10px; background: none; border: none; cursor: pointer; color: var(--hd-accent); font-size: 1.1rem; padding: 0 4px; line-height: 1; transition: color 0.2s; }
+        .search-clear:hover { color: var(--hd-primary); }
         .search-clear.hidden { display: none; }
-        .search-controls { padding: 12px 16px; border-bottom: 1px solid #D4D4D4; display: none; background: #F0EFEB; }
+        .search-controls { padding: 12px 16px; border-bottom: 1px solid var(--hd-border); display: none; background: var(--hd-background); }
         .search-controls.visible { display: block; }
         .search-nav { display: flex; align-items: center; gap: 0.5rem; margin-bottom: 0.75rem; }
-        .search-nav button { padding: 6px 12px; border: 1px solid #D4D4D4; background: white; border-radius: 6px; cursor: pointer; font-size: 0.9rem; transition: all 0.2s; }
-        .search-nav button:hover:not(:disabled) { background: #F0EFEB; border-color: #B7B7A4; }
+        .search-nav button { padding: 6px 12px; border: 1px solid var(--hd-border); background: white; border-radius: 6px; cursor: pointer; font-size: 0.9rem; transition: all 0.2s; }
+        .search-nav button:hover:not(:disabled) { background: var(--hd-background); border-color: var(--hd-muted); }
         .search-nav button:disabled { opacity: 0.4; cursor: not-allowed; }
-        .search-counter { color: #606C38; font-size: 0.85rem; font-weight: 500; }
+        .search-counter { color: var(--hd-accent); font-size: 0.85rem; font-weight: 500; }
         .search-filters { display: flex; gap: 6px; flex-wrap: wrap; }
-        .search-filter { padding: 4px 8px; border: 1px solid #D4D4D4; background: white; border-radius: 6px; font-size: 0.75rem; font-weight: 500; cursor: pointer; user-select: none; transition: all 0.2s; }
-        .search-filter.active { background: #283618; color: #F0EFEB; border-color: #283618; }
+        .search-filter { padding: 4px 8px; border: 1px solid var(--hd-border); background: white; color: var(--hd-primary); border-radius: 6px; font-family: inherit; font-size: 0.75rem; font-weight: 500; cursor: pointer; user-select: none; transition: all 0.2s; }
+        .search-filter.active { background: var(--hd-primary); color: var(--hd-background); border-color: var(--hd-primary); }
         .search-filter.disabled { opacity: 0.4; cursor: not-allowed; }
         .sidebar-nav { flex: 1; overflow-y: auto; padding: 16px; }
-        .nav-title { font-weight: 600; font-size: 0.7rem; text-transform: uppercase; color: #B7B7A4; margin-bottom: 0.75rem; letter-spacing: 0.05em; }
+        .nav-title { font-weight: 600; font-size: 0.7rem; text-transform: uppercase; color: var(--hd-accent); margin-bottom: 0.75rem; letter-spacing: 0.05em; }
         .nav-list { list-style: none; }
         .nav-list li { margin: 2px 0; }
-        .nav-list a { color: #283618; text-decoration: none; display: block; padding: 8px 12px; border-radius: 6px; font-size: 0.9rem; font-weight: 500; transition: all 0.2s; }
-        .nav-list a:hover { background: #F0EFEB; color: #606C38; }
-        .sidebar-footer { padding: 16px; border-top: 1px solid #D4D4D4; }
-        .print-btn { width: 100%; padding: 12px 16px; background: #B7B7A4; color: #283618; border: none; border-radius: 8px; cursor: pointer; font-size: 0.9rem; font-weight: 500; transition: background 0.2s; }
+        .nav-list a { color: var(--hd-primary); text-decoration: none; display: block; padding: 8px 12px; border-radius: 6px; font-size: 0.9rem; font-weight: 500; transition: all 0.2s; }
+        .nav-list a:hover { background: var(--hd-background); color: var(--hd-accent); }
+        .sidebar-footer { padding: 16px; border-top: 1px solid var(--hd-border); }
+        .print-btn { width: 100%; padding: 12px 16px; background: var(--hd-muted); color: var(--hd-primary); border: none; border-radius: 8px; cursor: pointer; font-size: 0.9rem; font-weight: 500; transition: background 0.2s; }
         .print-btn:hover { background: #a3a392; }
-        .legal-line { text-align: center; font-size: 0.75rem; color: #B7B7A4; margin-top: 12px; opacity: 0.7; }
-        .legal-line a { color: #B7B7A4; text-decoration: underline; }
-        .legal-line a:hover { color: #F0EFEB; }
-        .main-content { flex: 1; margin-left: 280px; padding: 24px 40px; }
-        .section { background: white; padding: 24px; border-radius: 12px; margin-bottom: 20px; box-shadow: 0 1px 3px rgba(40,54,24,0.08), 0 1px 2px rgba(40,54,24,0.04); border: 1px solid #D4D4D4; }
-        .section-title { font-size: 1.15rem; font-weight: 600; color: #283618; border-bottom: 2px solid #283618; padding-bottom: 0.75rem; margin-bottom: 1.25rem; }
-        .item { background: #F0EFEB; padding: 16px; border-radius: 8px; margin-bottom: 12px; border: 1px solid #D4D4D4; }
-        .item-title { font-weight: 600; color: #283618; margin-bottom: 0.5rem; }
-        .item-detail { color: #606C38; font-size: 0.9rem; }
-        .notes { background: #F0EFEB; padding: 12px 14px; border-radius: 8px; margin-top: 1rem; font-style: italic; color: #283618; border-left: 3px solid #B7B7A4; }
-        .attachments-section { margin-top: 1rem; padding: 14px; background: #F0EFEB; border-radius: 8px; border-left: 3px solid #606C38; }
-        .attachments-heading { margin: 0 0 10px 0; font-size: 0.9rem; color: #606C38; font-weight: 600; }
+        .legal-line { text-align: center; font-size: 0.75rem; color: var(--hd-accent); margin-top: 12px; }
+        .legal-line a { color: inherit; text-decoration: underline; }
+        .legal-line a:hover { color: var(--hd-primary); }
+        .display-options { display: flex; gap: 8px; margin-top: 8px; }
+        .display-toggle { flex: 1; padding: 8px; border: 1px solid var(--hd-border); background: white; color: var(--hd-primary); border-radius: 8px; cursor: pointer; font-family: inherit; font-size: 0.8rem; }
+        .display-toggle[aria-pressed="true"] { background: var(--hd-primary); color: var(--hd-background); border-color: var(--hd-primary); }
+        .theme-footer { text-align: center; font-size: 0.8rem; color: var(--hd-accent); margin-top: 12px; }
+        .custom-divider { border: none; border-top: 1px solid var(--hd-border); margin: 8px 0; }
+        .custom-header { font-weight: bold; margin: 8px 0 4px; }
+        .main-content { flex: 1; margin-inline-start: 280px; padding: 24px 40px; }
+        .main-content:focus { outline: none; }
+        .section { background: white; padding: 24px; border-radius: 12px; margin-bottom: 20px; box-shadow: 0 1px 3px rgba(40,54,24,0.08), 0 1px 2px rgba(40,54,24,0.04); border: 1px solid var(--hd-border); }
+        .section-title { font-size: 1.15rem; font-weight: 600; color: var(--hd-primary); border-bottom: 2px solid var(--hd-primary); padding-bottom: 0.75rem; margin-bottom: 1.25rem; }
+        .item { background: var(--hd-background); padding: 16px; border-radius: 8px; margin-bottom: 12px; border: 1px solid var(--hd-border); }
+        .item-title { font-weight: 600; color: var(--hd-primary); margin-bottom: 0.5rem; }
+        .item-detail { color: var(--hd-accent); font-size: 0.9rem; }
+        .notes { background: var(--hd-background); padding: 12px 14px; border-radius: 8px; margin-top: 1rem; font-style: italic; color: var(--hd-primary); border-inline-start: 3px solid var(--hd-muted); }
+        .attachments-section { margin-top: 1rem; padding: 14px; background: var(--hd-background); border-radius: 8px; border-inline-start: 3px solid var(--hd-accent); }
+        .attachments-heading { margin: 0 0 10px 0; font-size: 0.9rem; color: var(--hd-accent); font-weight: 600; }
         .attachment-export { margin-bottom: 10px; }
         .attachment-export-name { font-size: 0.85rem; color: #555; }
-        .attachment-export-link { color: #283618; font-weight: 500; }
-        .attachment-export-size { font-size: 0.8rem; color: #999; }
-        .match-badge { font-size: 0.65rem; font-weight: 500; color: #606C38; background: #D4D4D4; padding: 2px 6px; border-radius: 4px; margin-left: 4px; vertical-align: middle; text-transform: lowercase; }
+        .attachment-export-link { color: var(--hd-primary); font-weight: 500; }
+        .attachment-export-size { font-size: 0.8rem; color: #666; }
+        .vcard-link { display: inline-block; margin-top: 6px; font-size: 0.85rem; color: var(--hd-primary); font-weight: 500; }
+        .vcard-bundle { margin-bottom: 16px; }
+        .calendar-link { display: inline-block; margin-bottom: 16px; font-size: 0.85rem; color: var(--hd-primary); font-weight: 500; }
+        .match-badge { font-size: 0.65rem; font-weight: 500; color: var(--hd-primary); background: var(--hd-border); padding: 2px 6px; border-radius: 4px; margin-inline-start: 4px; vertical-align: middle; text-transform: lowercase; }
         .highlight { background: #DDE5B6; padding: 1px 2px; border-radius: 2px; }
-        .highlight.current { background: #ADC178; outline: 2px solid #283618; }
-        .mobile-toolbar { display: none; position: sticky; top: 0; left: 0; right: 0; z-index: 200; background: #283618; padding: 10px 12px; box-shadow: 0 2px 8px rgba(40,54,24,0.3); }
+        .highlight.current { background: #ADC178; outline: 2px solid var(--hd-primary); }
+        .mobile-toolbar { display: none; position: sticky; top: 0; left: 0; right: 0; z-index: 200; background: var(--hd-primary); padding: 10px 12px; box-shadow: 0 2px 8px rgba(40,54,24,0.3); }
         .mobile-toolbar-default { display: flex; align-items: center; justify-content: space-between; }
         .mobile-toolbar.search-mode .mobile-toolbar-default { display: none; }
-        .mobile-toolbar-btn { background: none; border: none; color: #F0EFEB; cursor: pointer; font-weight: 500; font-size: 0.95rem; padding: 8px 12px; border-radius: 6px; display: flex; align-items: center; gap: 6px; min-height: 44px; }
+        .mobile-toolbar-btn { background: none; border: none; color: var(--hd-background); cursor: pointer; font-weight: 500; font-size: 0.95rem; padding: 8px 12px; border-radius: 6px; display: flex; align-items: center; gap: 6px; min-height: 44px; }
         .mobile-toolbar-btn:hover { background: rgba(240,239,235,0.1); }
         .mobile-search-inline { display: none; flex-direction: column; gap: 8px; }
         .mobile-toolbar.search-mode .mobile-search-inline { display: flex; }
         .mobile-search-row { display: flex; align-items: center; gap: 8px; }
-        .mobile-search-row .search-input { flex: 1; padding: 12px 14px; border: none; border-radius: 8px; font-size: 1rem; background: #F0EFEB; min-width: 0; }
+        .mobile-search-row .search-input { flex: 1; padding: 12px 14px; border: none; border-radius: 8px; font-size: 1rem; background: var(--hd-background); min-width: 0; }
         .mobile-search-row .search-input:focus { outline: none; background: white; }
-        .mobile-search-row .search-close-btn { background: none; border: none; color: #F0EFEB; font-size: 1.4rem; cursor: pointer; padding: 8px; min-width: 44px; min-height: 44px; display: flex; align-items: center; justify-content: center; flex-shrink: 0; }
+        .mobile-search-row .search-close-btn { background: none; border: none; color: var(--hd-background); font-size: 1.4rem; cursor: pointer; padding: 8px; min-width: 44px; min-height: 44px; display: flex; align-items: center; justify-content: center; flex-shrink: 0; }
         .mobile-search-nav { display: flex; align-items: center; justify-content: space-between; gap: 8px; }
-        .mobile-search-nav .search-counter { color: #B7B7A4; font-size: 0.85rem; white-space: nowrap; }
-        .mobile-search-nav .search-nav-btn { padding: 10px 18px; border: none; background: #606C38; color: #F0EFEB; border-radius: 8px; cursor: pointer; font-size: 1rem; min-height: 44px; min-width: 56px; flex-shrink: 0; }
+        .mobile-search-nav .search-counter { color: var(--hd-muted); font-size: 0.85rem; white-space: nowrap; }
+        .mobile-search-buttons { display: flex; gap: 8px; }
+        .mobile-search-nav .search-nav-btn { padding: 10px 18px; border: none; background: var(--hd-accent); color: var(--hd-background); border-radius: 8px; cursor: pointer; font-size: 1rem; min-height: 44px; min-width: 56px; flex-shrink: 0; }
         .mobile-search-nav .search-nav-btn:disabled { opacity: 0.4; cursor: not-allowed; }
-        .mobile-search-filters-bar { display: none; position: sticky; top: 0; left: 0; right: 0; z-index: 199; background: #283618; padding: 6px 12px 10px; }
+        .mobile-search-filters-bar { display: none; position: sticky; top: 0; left: 0; right: 0; z-index: 199; background: var(--hd-primary); padding: 6px 12px 10px; }
         .mobile-search-filters-bar.visible { display: flex; gap: 8px; flex-wrap: wrap; align-items: center; }
-        .mobile-search-filters-bar .search-filter { padding: 8px 12px; border: 1px solid #606C38; background: transparent; color: #B7B7A4; border-radius: 8px; font-size: 0.85rem; font-weight: 500; cursor: pointer; user-select: none; min-height: 38px; display: flex; align-items: center; }
-        .mobile-search-filters-bar .search-filter.active { background: #606C38; color: #F0EFEB; border-color: #606C38; }
+        .mobile-search-filters-bar .search-filter { padding: 8px 12px; border: 1px solid var(--hd-accent); background: transparent; color: var(--hd-muted); border-radius: 8px; font-family: inherit; font-size: 0.85rem; font-weight: 500; cursor: pointer; user-select: none; min-height: 38px; display: flex; align-items: center; }
+        .mobile-search-filters-bar .search-filter.active { background: var(--hd-accent); color: var(--hd-background); border-color: var(--hd-accent); }
         .mobile-search-filters-bar .search-filter.disabled { opacity: 0.4; cursor: not-allowed; }
-        .menu-toggle { display: none; }
+        /* Accessibility: keyboard focus, the skip link, and the display modes from the sidebar */
+        .visually-hidden { position: absolute; width: 1px; height: 1px; margin: -1px; overflow: hidden; clip: rect(0, 0, 0, 0); white-space: nowrap; border: 0; }
+        .skip-link { position: absolute; top: -60px; inset-inline-start: 8px; z-index: 1000; padding: 10px 16px; background: var(--hd-primary); color: var(--hd-background); border-radius: 8px; font-weight: 500; }
+        .signature-note { position: fixed; top: 8px; inset-inline: 0; width: fit-content; max-width: calc(100% - 16px); margin: 0 auto; z-index: 2100; padding: 4px 12px; border-radius: 999px; background: rgba(255,255,255,0.92); color: var(--hd-primary); font-size: 0.75rem; }
+        .skip-link:focus { top: 8px; }
+        :focus-visible { outline: 3px solid var(--hd-accent); outline-offset: 2px; }
+        html.hd-large { font-size: 125%; }
+        html.hd-contrast { --hd-primary: #000000; --hd-primary-dark: #000000; --hd-accent: #000000; --hd-background: #FFFFFF; --hd-border: #000000; --hd-muted: #FFFFFF; }
+        html.hd-contrast .nav-title, html.hd-contrast .lock-version, html.hd-contrast .search-clear, html.hd-contrast .attachment-export-size, html.hd-contrast .attachment-export-name { color: #000000; }
+        html.hd-contrast .print-btn { border: 2px solid #000000; }
+        html.hd-contrast .match-badge { background: #000000; color: #FFFFFF; }
+        html.hd-contrast .highlight { background: #FFFF00; color: #000000; }
+        html.hd-contrast .highlight.current { background: #FFD700; }
+        html.hd-contrast :focus-visible { outline-color: #0000EE; }
+        html.hd-contrast .mobile-search-nav .search-nav-btn, html.hd-contrast .mobile-search-filters-bar .search-filter { border: 1px solid #FFFFFF; }
+        html.hd-contrast .mobile-search-filters-bar .search-filter.active { background: #FFFFFF; color: #000000; }
+        @media (prefers-reduced-motion: reduce) {
+            *, *::before, *::after { transition-duration: 0.01ms !important; animation: none !important; scroll-behavior: auto !important; }
+        }
         @media (max-width: 768px) {
             .mobile-toolbar { display: block; }
-            .sidebar { transform: translateX(-100%); transition: transform 0.3s ease; position: fixed; top: 0; height: 100vh; z-index: 300; }
-            .sidebar.open { transform: translateX(0); box-shadow: 4px 0 20px rgba(40,54,24,0.15); }
+            /* Hidden as well as moved, so a closed menu is out of the tab order */
+            .sidebar { transform: translateX(-100%); visibility: hidden; transition: transform 0.3s ease, visibility 0.3s; position: fixed; top: 0; height: 100vh; z-index: 300; }
+            .sidebar.open { transform: translateX(0); visibility: visible; box-shadow: 4px 0 20px rgba(40,54,24,0.15); }
+            [dir="rtl"] .sidebar { transform: translateX(100%); }
+            [dir="rtl"] .sidebar.open { transform: translateX(0); box-shadow: -4px 0 20px rgba(40,54,24,0.15); }
             .sidebar .sidebar-search, .sidebar .search-controls { display: none; }
             .sidebar-overlay { display: none; position: fixed; inset: 0; background: rgba(0,0,0,0.4); z-index: 250; }
             .sidebar-overlay.visible { display: block; }
-            .main-content { margin-left: 0; padding: 16px; }
+            .main-content { margin-inline-start: 0; padding: 16px; }
         }
         @media print {
-            .sidebar, .menu-toggle, .mobile-toolbar, .mobile-search-filters-bar, .sidebar-overlay { display: none !important; }
+            .sidebar, .skip-link, .signature-note, .mobile-toolbar, .mobile-search-filters-bar, .sidebar-overlay { display: none !important; }
             body { background: white !important; font-size: 11pt; line-height: 1.4; }
             .layout { display: block !important; }
-            .main-content { margin-left: 0 !important; padding: 20px !important; max-width: 100% !important; }
+            .main-content { margin-inline-start: 0 !important; padding: 20px !important; max-width: 100% !important; }
             .section { box-shadow: none !important; border: none !important; border-radius: 0 !important; background: white !important; padding: 0 !important; margin: 0 0 20px 0 !important; page-break-inside: auto; }
             .section-title { font-size: 13pt; font-weight: 700; color: #000; border-bottom: 2px solid #000; padding-bottom: 4px; margin-bottom: 10px; page-break-after: avoid; }
             h3 { font-size: 11pt; color: #333; page-break-after: avoid; margin-top: 12px; }
@@ -415,10 +710,11 @@ const SHARED_CSS: &str = r##"
             .item-title { font-size: 10pt; font-weight: 600; }
             .item-detail { font-size: 9.5pt; }
             .contact-info { page-break-inside: avoid; }
-            .notes { page-break-inside: avoid; background: #f0f0f0 !important; border-left: 3px solid #999 !important; font-size: 9.5pt; }
+            .notes { page-break-inside: avoid; background: #f0f0f0 !important; border-inline-start: 3px solid #999 !important; font-size: 9.5pt; }
             .attachments-section { page-break-inside: avoid; font-size: 9.5pt; }
             .attachment-export-link { color: #000 !important; text-decoration: none !important; }
-            .attachment-export-link::after { content: " (attached file)"; font-style: italic; color: #666; font-size: 8.5pt; }
+            .attachment-export-link::after { content: " (" attr(data-attached) ")"; font-style: italic; color: #666; font-size: 8.5pt; }
+            .vcard-link, .vcard-bundle, .calendar-link { display: none !important; }
         }
 "##;
 
@@ -431,30 +727,210 @@ const SHARED_JS_UTILS: &str = r##"
             return div.innerHTML;
         }
 
-        // Escape for use in HTML attributes (href, id, etc.)
-        // Only allows alphanumeric, dash, underscore for safety
         function escapeAttr(text) {
-            if (!text) return '';
-            return String(text).replace(/[^a-zA-Z0-9_-]/g, '');
+            return escapeHtml(text).replace(/"/g, '&quot;');
+        }
+
+        // Text in the export's language; LOCALE comes from i18n::Locale::script_json
+        function fillMessage(text, params) {
+            if (params) Object.keys(params).forEach(name => { text = text.split('{' + name + '}').join(params[name]); });
+            return text;
+        }
+
+        function t(key, params) {
+            return fillMessage(LOCALE.messages[key] || key, params);
+        }
+
+        // Same output as i18n::Locale::format_date
+        function formatDate(value) {
+            const parts = /^(\d{4})-(\d{2})-(\d{2})$/.exec(String(value || '').trim());
+            const month = parts && LOCALE.months[Number(parts[2]) - 1];
+            if (!month) return value;
+            return fillMessage(LOCALE.date_format, { day: Number(parts[3]), month: month, year: parts[1] });
+        }
+
+        function menuButtonLabel(open) {
+            return open ? '&#10005; ' + escapeHtml(t('viewer.close')) : '&#9776; ' + escapeHtml(t('viewer.menu'));
+        }
+
+        function setMenuButton(open) {
+            var btn = document.getElementById('toolbarMenuBtn');
+            if (!btn) return;
+            btn.innerHTML = menuButtonLabel(open);
+            btn.setAttribute('aria-expanded', String(open));
+        }
+
+        // High-contrast and larger-text modes, remembered in this browser. Without a
+        // saved choice, high contrast follows the system's "increase contrast" setting.
+        const DISPLAY_MODES = ['contrast', 'large'];
+
+        function loadDisplayModes() {
+            let saved = null;
+            try { saved = JSON.parse(localStorage.getItem('honey-did-display')); } catch (e) {}
+            if (!saved) saved = { contrast: !!(window.matchMedia && matchMedia('(prefers-contrast: more)').matches), large: false };
+            DISPLAY_MODES.forEach(mode => document.documentElement.classList.toggle('hd-' + mode, !!saved[mode]));
+        }
+
+        function syncDisplayToggles() {
+            document.querySelectorAll('.display-toggle').forEach(el => {
+                el.setAttribute('aria-pressed', String(document.documentElement.classList.contains('hd-' + el.dataset.mode)));
+            });
+        }
+
+        function toggleDisplayMode(el) {
+            document.documentElement.classList.toggle('hd-' + el.dataset.mode);
+            syncDisplayToggles();
+            const saved = {};
+            DISPLAY_MODES.forEach(mode => { saved[mode] = document.documentElement.classList.contains('hd-' + mode); });
+            try { localStorage.setItem('honey-did-display', JSON.stringify(saved)); } catch (e) {}
+        }
+
+        loadDisplayModes();
+
+        function prefersReducedMotion() {
+            return !!(window.matchMedia && matchMedia('(prefers-reduced-motion: reduce)').matches);
+        }
+
+        // Once unlocked, keyboard and screen reader users start at the document
+        function focusDocument() {
+            var main = document.getElementById('mainContent');
+            if (main) main.focus();
+        }
+
+        // AES-GCM parameters for one key slot. Files with a header authenticate it
+        // with every ciphertext; older files have no associated data.
+        function aesGcmParams(iv, slot) {
+            const params = { name: 'AES-GCM', iv: iv };
+            if (ENCRYPTED_DATA.header) {
+                params.additionalData = new TextEncoder().encode(exportAssociatedData(ENCRYPTED_DATA.header, slot));
+            }
+            return params;
+        }
+
+        // Each wrong passphrase or set of answers makes the next try wait longer. The count is
+        // kept in this browser per file (by its nonce), so reloading the page doesn't reset it.
+        // It only slows down someone guessing at the lock screen, not an offline attack.
+        const UNLOCK_DELAYS = [0, 0, 0, 5, 15, 30, 60, 120, 300];
+        const UNLOCK_KINDS = ['passphrase', 'questions'];
+        const unlockRecords = {};
+        const unlockWaits = {};
+
+        function unlockRecordKey(kind) {
+            const payload = ENCRYPTED_DATA.document || ENCRYPTED_DATA;
+            return 'honey-did-unlock:' + payload.nonce + ':' + kind;
+        }
+
+        function unlockRecord(kind) {
+            if (!unlockRecords[kind]) {
+                let saved = null;
+                try { saved = JSON.parse(localStorage.getItem(unlockRecordKey(kind))); } catch (e) {}
+                unlockRecords[kind] = saved && typeof saved.failures === 'number' ? saved : { failures: 0, until: 0 };
+            }
+            return unlockRecords[kind];
+        }
+
+        // Returns how many tries of this kind have failed so far
+        function recordFailedUnlock(kind) {
+            const failures = unlockRecord(kind).failures + 1;
+            const delay = UNLOCK_DELAYS[Math.min(failures, UNLOCK_DELAYS.length - 1)];
+            unlockRecords[kind] = { failures: failures, until: Date.now() + delay * 1000 };
+            try { localStorage.setItem(unlockRecordKey(kind), JSON.stringify(unlockRecords[kind])); } catch (e) {}
+            return failures;
+        }
+
+        function clearFailedUnlocks() {
+            UNLOCK_KINDS.forEach(kind => {
+                delete unlockRecords[kind];
+                try { localStorage.removeItem(unlockRecordKey(kind)); } catch (e) {}
+            });
+        }
+
+        function unlockWaitSeconds(kind) {
+            return Math.max(0, Math.ceil((unlockRecord(kind).until - Date.now()) / 1000));
+        }
+
+        // While a wait is running, says how long in the error element and disables the
+        // unlock buttons until it is over. Returns false when there is nothing to wait for.
+        function showUnlockWait(kind, errorId, buttonIds) {
+            const seconds = unlockWaitSeconds(kind);
+            if (seconds === 0) return false;
+            const errorEl = document.getElementById(errorId);
+            const buttons = buttonIds.map(id => document.getElementById(id));
+            errorEl.textContent = seconds > 60
+                ? t('unlock.wait_minutes', { minutes: Math.ceil(seconds / 60) })
+                : t('unlock.wait_seconds', { seconds: seconds });
+            errorEl.style.display = 'block';
+            buttons.forEach(button => { button.disabled = true; });
+            clearTimeout(unlockWaits[kind]);
+            unlockWaits[kind] = setTimeout(() => {
+                buttons.forEach(button => { button.disabled = false; });
+                errorEl.textContent = t('unlock.ready');
+            }, seconds * 1000);
+            return true;
         }
 
-        function renderContact(contact) {
-            if (!contact || !contact.name) return '';
-            let html = '<div class="contact-info">';
-            if (contact.name) html += '<div><strong>' + escapeHtml(contact.name) + '</strong></div>';
-            if (contact.relationship) html += '<div>' + escapeHtml(contact.relationship) + '</div>';
-            if (contact.phone) html += '<div>Phone: ' + escapeHtml(contact.phone) + '</div>';
-            if (contact.email) html += '<div>Email: ' + escapeHtml(contact.email) + '</div>';
-            if (contact.notes) html += '<div class="notes">' + escapeHtml(contact.notes) + '</div>';
-            html += '</div>';
-            return html;
+        // The fingerprint of the key in the signature tag, grouped like signing::fingerprint.
+        // Only the app can check the signature itself; this is for comparing over the phone.
+        async function showSignatureFingerprint() {
+            const note = document.getElementById('signatureNote');
+            const tag = document.querySelector('meta[name="honey-did-signature"]');
+            const parts = tag ? tag.content.split(':') : [];
+            if (!note || parts.length !== 3 || !window.crypto || !crypto.subtle) return;
+            try {
+                const key = Uint8Array.from(atob(parts[1]), c => c.charCodeAt(0));
+                const hash = new Uint8Array(await crypto.subtle.digest('SHA-256', key));
+                const hex = Array.from(hash.slice(0, 16), b => b.toString(16).padStart(2, '0')).join('').toUpperCase();
+                note.textContent = t('signature.fingerprint', { fingerprint: hex.match(/.{4}/g).join(' ') });
+                note.hidden = false;
+            } catch (e) {}
+        }
+
+        document.addEventListener('DOMContentLoaded', showSignatureFingerprint);
+
+        // Files bundled with the document at export time (see ExportExtras)
+        var exportExtras = {};
+
+        function vcardFileName(name) {
+            return (String(name || 'contact').replace(/[^a-zA-Z0-9 _-]/g, '').trim() || 'contact') + '.vcf';
+        }
+
+        function downloadText(mime, text, name) {
+            try {
+                var blob = new Blob([text], { type: mime });
+                var url = URL.createObjectURL(blob);
+                var a = document.createElement('a');
+                a.href = url;
+                a.download = name;
+                document.body.appendChild(a);
+                a.click();
+                document.body.removeChild(a);
+                setTimeout(function() { URL.revokeObjectURL(url); }, 100);
+            } catch(e) { console.error('Download failed', e); }
         }
 
-        function formatSize(bytes) {
-            if (!bytes) return '';
-            if (bytes < 1024) return bytes + ' B';
-            if (bytes < 1048576) return (bytes / 1024).toFixed(1) + ' KB';
-            return (bytes / 1048576).toFixed(1) + ' MB';
+        function bindExtraDownloads() {
+            document.querySelectorAll('.vcard-link[data-vcard]').forEach(function(el) {
+                el.addEventListener('click', function(e) {
+                    e.preventDefault();
+                    // The key is "name|phone|email", see vcard::contact_card_key
+                    var key = el.getAttribute('data-vcard');
+                    var card = exportExtras.contact_cards && exportExtras.contact_cards[key];
+                    if (card) downloadText('text/vcard', card, vcardFileName(key.split('|')[0]));
+                });
+            });
+            document.querySelectorAll('.calendar-link').forEach(function(el) {
+                el.addEventListener('click', function(e) {
+                    e.preventDefault();
+                    downloadText('text/calendar', exportExtras.calendar_ics, 'honey-did-calendar.ics');
+                });
+            });
+            var bundle = document.getElementById('vcardBundle');
+            if (bundle) {
+                bundle.addEventListener('click', function(e) {
+                    e.preventDefault();
+                    downloadText('text/vcard', exportExtras.contacts_vcf, 'contacts.vcf');
+                });
+            }
         }
 
         function downloadAttachment(mime, data, name) {
@@ -474,18 +950,6 @@ const SHARED_JS_UTILS: &str = r##"
             } catch(e) { console.error('Download failed', e); }
         }
 
-        function renderAttachments(attachments) {
-            if (!attachments || !attachments.length) return '';
-            var html = '<div class="attachments-section"><h4 class="attachments-heading">Attachments</h4>';
-            attachments.forEach(function(a, idx) {
-                var dlId = 'att-dl-' + (a.id || idx);
-                var attId = 'att-row-' + (a.id || idx);
-                html += '<div class="attachment-export" id="' + attId + '"><a class="attachment-export-link" href="#" id="' + dlId + '">' + escapeHtml(a.name) + '</a> <span class="attachment-export-size">(' + formatSize(a.size) + ')</span></div>';
-            });
-            html += '</div>';
-            return html;
-        }
-
         function bindAttachmentDownloads(attachments) {
             if (!attachments) return;
             attachments.forEach(function(a, idx) {
@@ -500,41 +964,6 @@ const SHARED_JS_UTILS: &str = r##"
             });
         }
 
-        function renderSection(title, id, content) {
-            if (!content) return '';
-            return '<div class="section" id="' + escapeAttr(id) + '"><h2 class="section-title">' + escapeHtml(title) + '</h2>' + content + '</div>';
-        }
-
-        function renderCustomSubsection(subsection) {
-            if (!subsection.items || !subsection.items.length) return '';
-            var elements = subsection.form_elements && subsection.form_elements.length
-                ? subsection.form_elements
-                : (subsection.field_definitions || []).map(function(fd) { return { type: 'field', id: fd.id, name: fd.name, field_type: fd.field_type }; });
-            let html = '<h3>' + escapeHtml(subsection.name) + '</h3>';
-            subsection.items.forEach(item => {
-                html += '<div class="item">';
-                elements.forEach(el => {
-                    if (el.type === 'field') {
-                        const value = item.values && item.values[el.id];
-                        if (value) {
-                            let displayValue = value;
-                            if (el.field_type === 'boolean') displayValue = value === 'true' ? 'Yes' : 'No';
-                            html += '<div class="item-detail"><strong>' + escapeHtml(el.name) + ':</strong> ' + escapeHtml(displayValue) + '</div>';
-                        }
-                    } else if (el.type === 'divider') {
-                        html += '<hr style="border:none;border-top:1px solid #D4D4D4;margin:8px 0;">';
-                    } else if (el.type === 'header') {
-                        html += '<div style="font-weight:bold;margin:8px 0 4px;">' + escapeHtml(el.text || '') + '</div>';
-                    }
-                });
-                if (item.values && item.values['_notes']) {
-                    html += '<div class="notes">' + escapeHtml(item.values['_notes']) + '</div>';
-                }
-                html += '</div>';
-            });
-            return html;
-        }
-
         function toggleSidebar() {
             var sidebar = document.getElementById('sidebar');
             var overlay = document.getElementById('sidebarOverlay');
@@ -545,10 +974,14 @@ const SHARED_JS_UTILS: &str = r##"
             }
             sidebar.classList.toggle('open');
             if (overlay) overlay.classList.toggle('visible', sidebar.classList.contains('open'));
-            // Update menu button text
-            var btn = document.getElementById('toolbarMenuBtn');
-            if (btn) {
-                btn.innerHTML = sidebar.classList.contains('open') ? '&#10005; Close' : '&#9776; Menu';
+            setMenuButton(sidebar.classList.contains('open'));
+            // Move focus into the menu, and back to its button once it closes
+            if (sidebar.classList.contains('open')) {
+                var firstLink = sidebar.querySelector('.nav-list a');
+                if (firstLink) firstLink.focus();
+            } else {
+                var btn = document.getElementById('toolbarMenuBtn');
+                if (btn && window.innerWidth <= 768) btn.focus();
             }
         }
 
@@ -557,8 +990,7 @@ const SHARED_JS_UTILS: &str = r##"
                 document.getElementById('sidebar').classList.remove('open');
                 var overlay = document.getElementById('sidebarOverlay');
                 if (overlay) overlay.classList.remove('visible');
-                var btn = document.getElementById('toolbarMenuBtn');
-                if (btn) btn.innerHTML = '&#9776; Menu';
+                setMenuButton(false);
             }
         }
 
@@ -571,8 +1003,7 @@ const SHARED_JS_UTILS: &str = r##"
                 document.getElementById('sidebar').classList.remove('open');
                 var overlay = document.getElementById('sidebarOverlay');
                 if (overlay) overlay.classList.remove('visible');
-                var btn = document.getElementById('toolbarMenuBtn');
-                if (btn) btn.innerHTML = '&#9776; Menu';
+                setMenuButton(false);
                 // Open search inline in toolbar
                 if (toolbar) toolbar.classList.add('search-mode');
                 var filters = document.getElementById('mobileFiltersBar');
@@ -598,100 +1029,58 @@ const SHARED_JS_UTILS: &str = r##"
             if (filters) filters.classList.remove('visible');
         }
 
+        // The Content-Security-Policy blocks inline event handlers, so markup
+        // names the function to call in data-* attributes instead
+        document.addEventListener('click', (e) => {
+            const el = e.target.closest && e.target.closest('[data-click]');
+            if (el && typeof window[el.dataset.click] === 'function') window[el.dataset.click](el);
+        });
+        document.addEventListener('submit', (e) => {
+            const form = e.target.closest('[data-submit]');
+            if (form && typeof window[form.dataset.submit] === 'function') window[form.dataset.submit](e);
+        });
+        document.addEventListener('input', (e) => {
+            if (e.target.dataset && e.target.dataset.search) debounceSearch(e.target.value, e.target.dataset.search === 'mobile');
+        });
+        document.addEventListener('keydown', (e) => {
+            if (e.key !== 'Escape' || !e.target.dataset || !e.target.dataset.escape) return;
+            if (typeof window[e.target.dataset.escape] === 'function') window[e.target.dataset.escape]();
+        });
+        // Escape closes the mobile menu
+        document.addEventListener('keydown', (e) => {
+            var sidebar = document.getElementById('sidebar');
+            if (e.key === 'Escape' && sidebar && sidebar.classList.contains('open')) toggleSidebar();
+        });
+
 "##;
 
 /// Shared JavaScript for search functionality
 const SHARED_JS_SEARCH: &str = r##"
-        function levenshtein(a, b) {
-            if (a.length === 0) return b.length;
-            if (b.length === 0) return a.length;
-            const matrix = [];
-            for (let i = 0; i <= b.length; i++) matrix[i] = [i];
-            for (let j = 0; j <= a.length; j++) matrix[0][j] = j;
-            for (let i = 1; i <= b.length; i++) {
-                for (let j = 1; j <= a.length; j++) {
-                    if (b.charAt(i - 1) === a.charAt(j - 1)) {
-                        matrix[i][j] = matrix[i - 1][j - 1];
-                    } else {
-                        matrix[i][j] = Math.min(
-                            matrix[i - 1][j - 1] + 1,
-                            matrix[i][j - 1] + 1,
-                            matrix[i - 1][j] + 1
-                        );
-                    }
-                }
-            }
-            return matrix[b.length][a.length];
+        // Words from the index built at export time; anything else on the page
+        // (labels, headings) is added the first time a search meets it.
+        let searchVocabulary = new Map();
+
+        function loadSearchVocabulary(index) {
+            searchVocabulary = new Map();
+            if (!index || index.version !== SEARCH_INDEX_VERSION) return;
+            index.words.forEach((word, i) => {
+                const codes = index.phonetic[i].split('|');
+                searchVocabulary.set(word, {
+                    word: word,
+                    stem: index.stems[i] || word,
+                    primary: codes[0],
+                    alternate: codes.length > 1 ? codes[1] : codes[0]
+                });
+            });
         }
 
-        function metaphone(word) {
-            if (!word) return '';
-            word = word.toUpperCase();
-            let result = '';
-            const vowels = 'AEIOU';
-            let i = 0;
-
-            if (word.length >= 2) {
-                const first2 = word.substring(0, 2);
-                if (['KN', 'GN', 'PN', 'AE', 'WR'].includes(first2)) {
-                    word = word.substring(1);
-                }
-            }
-
-            while (i < word.length && result.length < 6) {
-                const c = word[i];
-                const prev = i > 0 ? word[i - 1] : '';
-                const next = i < word.length - 1 ? word[i + 1] : '';
-
-                if (c === prev && c !== 'C') { i++; continue; }
-                if (vowels.includes(c) && i === 0) { result += c; i++; continue; }
-                if (vowels.includes(c)) { i++; continue; }
-
-                switch (c) {
-                    case 'B': if (prev !== 'M') result += 'P'; break;
-                    case 'C':
-                        if (next === 'H') { result += 'X'; i++; }
-                        else if (['I', 'E', 'Y'].includes(next)) result += 'S';
-                        else result += 'K';
-                        break;
-                    case 'D':
-                        if (next === 'G' && ['I', 'E', 'Y'].includes(word[i + 2] || '')) { result += 'J'; i++; }
-                        else result += 'T';
-                        break;
-                    case 'F': result += 'F'; break;
-                    case 'G':
-                        if (next === 'H') { if (!vowels.includes(word[i + 2] || '')) i++; }
-                        else if (next === 'N' && word[i + 2] === undefined) { }
-                        else if (['I', 'E', 'Y'].includes(next)) result += 'J';
-                        else result += 'K';
-                        break;
-                    case 'H': if (vowels.includes(next) && !vowels.includes(prev)) result += 'H'; break;
-                    case 'J': result += 'J'; break;
-                    case 'K': if (prev !== 'C') result += 'K'; break;
-                    case 'L': result += 'L'; break;
-                    case 'M': result += 'M'; break;
-                    case 'N': result += 'N'; break;
-                    case 'P': if (next === 'H') { result += 'F'; i++; } else result += 'P'; break;
-                    case 'Q': result += 'K'; break;
-                    case 'R': result += 'R'; break;
-                    case 'S':
-                        if (next === 'H') { result += 'X'; i++; }
-                        else if (next === 'I' && ['O', 'A'].includes(word[i + 2] || '')) { result += 'X'; i++; }
-                        else result += 'S';
-                        break;
-                    case 'T':
-                        if (next === 'H') { result += '0'; i++; }
-                        else if (next === 'I' && ['O', 'A'].includes(word[i + 2] || '')) { result += 'X'; i++; }
-                        else result += 'T';
-                        break;
-                    case 'V': result += 'F'; break;
-                    case 'W': case 'Y': if (vowels.includes(next)) result += c; break;
-                    case 'X': result += 'KS'; break;
-                    case 'Z': result += 'S'; break;
-                }
-                i++;
+        function searchTerm(word) {
+            let term = searchVocabulary.get(word);
+            if (!term) {
+                term = makeSearchTerm(word);
+                searchVocabulary.set(word, term);
             }
-            return result;
+            return term;
         }
 
         let searchIndex = [];
@@ -714,22 +1103,35 @@ const SHARED_JS_SEARCH: &str = r##"
 
             let node;
             while (node = walker.nextNode()) {
-                const text = node.textContent;
-                const words = text.match(/\b[\w']+\b/g) || [];
-                words.forEach(word => {
-                    if (word.length >= 2) {
-                        searchIndex.push({
-                            text: word,
-                            lowerText: word.toLowerCase(),
-                            metaphone: metaphone(word),
-                            node: node,
-                            fullText: text
-                        });
-                    }
+                const current = node;
+                searchTokens(current.textContent).forEach(token => {
+                    searchIndex.push({ ...token, node: current });
                 });
             }
         }
 
+        // One entry per distinct word in each text node, best match types first
+        function findSearchMatches(term) {
+            const query = searchQuery(term);
+            if (!query) return [];
+            const types = new Map();
+            const matchMap = new Map();
+
+            searchIndex.forEach(entry => {
+                const key = entry.node.textContent + '|' + entry.text;
+                if (matchMap.has(key)) return;
+                if (!types.has(entry.word)) {
+                    types.set(entry.word, classifySearchMatch(query, searchTerm(entry.word)));
+                }
+                const type = types.get(entry.word);
+                if (type) matchMap.set(key, { ...entry, type: type });
+            });
+
+            const typeOrder = { exact: 0, contains: 1, spelling: 2, phonetic: 3 };
+            return Array.from(matchMap.values())
+                .sort((a, b) => typeOrder[a.type] - typeOrder[b.type]);
+        }
+
         let searchTimeout;
         function debounceSearch(term, fromMobile) {
             clearTimeout(searchTimeout);
@@ -776,51 +1178,14 @@ const SHARED_JS_SEARCH: &str = r##"
             searchState.currentIndex = -1;
             searchState.filters = { exact: true, contains: true, spelling: true, phonetic: true };
 
-            if (!term || term.length < 2) {
+            if (!searchQuery(term)) {
                 document.getElementById('searchControls').classList.remove('visible');
                 updateSearchUI();
                 return;
             }
 
             document.getElementById('searchControls').classList.add('visible');
-            const termMeta = metaphone(term);
-            const matchMap = new Map();
-
-            searchIndex.forEach(entry => {
-                const key = entry.node.textContent + '|' + entry.text;
-                if (matchMap.has(key)) return;
-
-                const wordLower = entry.lowerText;
-                const termLower = term.toLowerCase();
-
-                if (wordLower === termLower) {
-                    matchMap.set(key, { ...entry, type: 'exact' });
-                    return;
-                }
-
-                if (term.length >= 3 && wordLower.includes(termLower)) {
-                    matchMap.set(key, { ...entry, type: 'contains' });
-                    return;
-                }
-
-                // Spelling match: require first char match and limit edit distance
-                if (wordLower[0] === termLower[0]) {
-                    const maxDist = term.length >= 8 ? 3 : (term.length >= 5 ? 2 : 1);
-                    const dist = levenshtein(wordLower, termLower);
-                    if (dist > 0 && dist <= maxDist) {
-                        matchMap.set(key, { ...entry, type: 'spelling', distance: dist });
-                        return;
-                    }
-                }
-
-                if (term.length >= 3 && termMeta && entry.metaphone === termMeta) {
-                    matchMap.set(key, { ...entry, type: 'phonetic' });
-                }
-            });
-
-            const typeOrder = { exact: 0, contains: 1, spelling: 2, phonetic: 3 };
-            searchState.matches = Array.from(matchMap.values())
-                .sort((a, b) => typeOrder[a.type] - typeOrder[b.type]);
+            searchState.matches = findSearchMatches(term);
 
             highlightMatches();
             updateSearchUI();
@@ -861,22 +1226,14 @@ const SHARED_JS_SEARCH: &str = r##"
 
                 const text = node.textContent;
 
-                // Find positions of all matches in this node
-                const highlights = [];
-                for (const m of matches) {
-                    // Use word boundaries to match whole words only
-                    const regex = new RegExp('\\b' + m.text.replace(/[.*+?^${}()|[\]\\]/g, '\\$&') + '\\b', 'i');
-                    const result = regex.exec(text);
-                    if (result) {
-                        highlights.push({
-                            start: result.index,
-                            end: result.index + result[0].length,
-                            matchedText: result[0],
-                            type: m.type,
-                            idx: m.visibleIdx
-                        });
-                    }
-                }
+                // Matches carry the word's offsets from searchTokens
+                const highlights = matches.map(m => ({
+                    start: m.start,
+                    end: m.end,
+                    matchedText: text.slice(m.start, m.end),
+                    type: m.type,
+                    idx: m.visibleIdx
+                }));
 
                 if (highlights.length === 0) continue;
 
@@ -911,8 +1268,7 @@ const SHARED_JS_SEARCH: &str = r##"
 
                     const badge = document.createElement('span');
                     badge.className = 'match-badge';
-                    badge.textContent = h.type === 'spelling' ? '~spelling' :
-                                       h.type === 'phonetic' ? 'sounds like' : h.type;
+                    badge.textContent = t('search.badge.' + h.type);
                     fragment.appendChild(badge);
 
                     pos = h.end;
@@ -941,14 +1297,7 @@ const SHARED_JS_SEARCH: &str = r##"
 
             ['exact', 'contains', 'spelling', 'phonetic'].forEach(type => {
                 const el = document.querySelector('.sidebar .search-filter[data-type="' + type + '"]');
-                if (!el) return;
-                if (counts[type] === 0) {
-                    el.classList.add('disabled');
-                    el.classList.remove('active');
-                } else {
-                    el.classList.remove('disabled');
-                    el.classList.toggle('active', searchState.filters[type]);
-                }
+                if (el) updateFilterButton(el, counts[type]);
             });
 
             const visible = getVisibleMatches();
@@ -957,12 +1306,12 @@ const SHARED_JS_SEARCH: &str = r##"
             const nextBtn = document.getElementById('nextBtn');
 
             if (visible.length === 0) {
-                counter.textContent = 'No matches';
+                counter.textContent = t('search.no_matches');
                 prevBtn.disabled = true;
                 nextBtn.disabled = true;
             } else {
                 const current = searchState.currentIndex + 1;
-                counter.textContent = 'Match ' + current + ' of ' + visible.length;
+                counter.textContent = t('search.counter', { current: current, total: visible.length });
                 prevBtn.disabled = visible.length <= 1;
                 nextBtn.disabled = visible.length <= 1;
             }
@@ -981,14 +1330,7 @@ const SHARED_JS_SEARCH: &str = r##"
             document.querySelectorAll('.mobilePhoneticCount').forEach(el => el.textContent = counts.phonetic);
 
             document.querySelectorAll('.mobile-search-filters-bar .search-filter').forEach(el => {
-                var type = el.dataset.type;
-                if (counts[type] === 0) {
-                    el.classList.add('disabled');
-                    el.classList.remove('active');
-                } else {
-                    el.classList.remove('disabled');
-                    el.classList.toggle('active', searchState.filters[type]);
-                }
+                updateFilterButton(el, counts[el.dataset.type]);
             });
 
             var visible = getVisibleMatches();
@@ -997,18 +1339,26 @@ const SHARED_JS_SEARCH: &str = r##"
             var mNext = document.getElementById('mobileNextBtn');
             if (mCounter) {
                 if (visible.length === 0) {
-                    mCounter.textContent = searchState.term ? 'No matches' : '';
+                    mCounter.textContent = searchState.term ? t('search.no_matches') : '';
                     if (mPrev) mPrev.disabled = true;
                     if (mNext) mNext.disabled = true;
                 } else {
                     var current = searchState.currentIndex + 1;
-                    mCounter.textContent = 'Match ' + current + ' of ' + visible.length;
+                    mCounter.textContent = t('search.counter', { current: current, total: visible.length });
                     if (mPrev) mPrev.disabled = visible.length <= 1;
                     if (mNext) mNext.disabled = visible.length <= 1;
                 }
             }
         }
 
+        function updateFilterButton(el, count) {
+            const active = count > 0 && searchState.filters[el.dataset.type];
+            el.disabled = count === 0;
+            el.classList.toggle('disabled', count === 0);
+            el.classList.toggle('active', active);
+            el.setAttribute('aria-pressed', String(active));
+        }
+
         function updateCurrentHighlight() {
             document.querySelectorAll('mark.highlight.current').forEach(el => el.classList.remove('current'));
             const visible = getVisibleMatches();
@@ -1023,7 +1373,7 @@ const SHARED_JS_SEARCH: &str = r##"
             closeSidebarOnMobile();
             const mark = document.querySelector('mark.highlight.current');
             if (mark) {
-                mark.scrollIntoView({ behavior: 'smooth', block: 'center' });
+                mark.scrollIntoView({ behavior: prefersReducedMotion() ? 'auto' : 'smooth', block: 'center' });
             }
             updateSearchUI();
         }
@@ -1053,6 +1403,7 @@ const SHARED_JS_SEARCH: &str = r##"
             // Sync all filter buttons of this type
             document.querySelectorAll('.search-filter[data-type="' + type + '"]').forEach(function(btn) {
                 btn.classList.toggle('active', searchState.filters[type]);
+                btn.setAttribute('aria-pressed', String(searchState.filters[type]));
             });
 
             // clearHighlights destroys the DOM nodes referenced by matches,
@@ -1060,42 +1411,7 @@ const SHARED_JS_SEARCH: &str = r##"
             clearHighlights();
             buildSearchIndex();
 
-            const term = searchState.term;
-            const termMeta = metaphone(term);
-            const savedFilters = { ...searchState.filters };
-            const matchMap = new Map();
-
-            searchIndex.forEach(entry => {
-                const key = entry.node.textContent + '|' + entry.text;
-                if (matchMap.has(key)) return;
-
-                const wordLower = entry.lowerText;
-
-                if (wordLower === term) {
-                    matchMap.set(key, { ...entry, type: 'exact' });
-                    return;
-                }
-                if (term.length >= 3 && wordLower.includes(term)) {
-                    matchMap.set(key, { ...entry, type: 'contains' });
-                    return;
-                }
-                if (wordLower[0] === term[0]) {
-                    const maxDist = term.length >= 8 ? 3 : (term.length >= 5 ? 2 : 1);
-                    const dist = levenshtein(wordLower, term);
-                    if (dist > 0 && dist <= maxDist) {
-                        matchMap.set(key, { ...entry, type: 'spelling', distance: dist });
-                        return;
-                    }
-                }
-                if (term.length >= 3 && termMeta && entry.metaphone === termMeta) {
-                    matchMap.set(key, { ...entry, type: 'phonetic' });
-                }
-            });
-
-            const typeOrder = { exact: 0, contains: 1, spelling: 2, phonetic: 3 };
-            searchState.matches = Array.from(matchMap.values())
-                .sort((a, b) => typeOrder[a.type] - typeOrder[b.type]);
-            searchState.filters = savedFilters;
+            searchState.matches = findSearchMatches(searchState.term);
 
             highlightMatches();
 
@@ -1178,479 +1494,15 @@ const SHARED_JS_SEARCH: &str = r##"
 const SHARED_JS_RENDER_DOCUMENT: &str = r##"
         function renderDocument(data) {
             const container = document.getElementById('documentContent');
-            let html = '';
-
-            html += '<button class="menu-toggle" onclick="toggleSidebar()">&#9776; Menu</button>';
-            html += '<div class="mobile-toolbar" id="mobileToolbar">';
-            html += '<div class="mobile-toolbar-default">';
-            html += '<button class="mobile-toolbar-btn" id="toolbarMenuBtn" onclick="toggleSidebar()">&#9776; Menu</button>';
-            html += '<button class="mobile-toolbar-btn" onclick="toggleMobileSearch()"><svg width="16" height="16" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2.5" stroke-linecap="round"><circle cx="11" cy="11" r="7"/><line x1="16.5" y1="16.5" x2="21" y2="21"/></svg> Search</button>';
-            html += '</div>';
-            html += '<div class="mobile-search-inline">';
-            html += '<div class="mobile-search-row">';
-            html += '<input type="text" id="mobileSearchInput" class="search-input" placeholder="Search..." oninput="debounceSearch(this.value, true)" onkeydown="if(event.key===\'Escape\')closeMobileSearch()">';
-            html += '<button class="search-close-btn" onclick="closeMobileSearch()">&#10005;</button>';
-            html += '</div>';
-            html += '<div class="mobile-search-nav">';
-            html += '<span class="search-counter" id="mobileSearchCounter"></span>';
-            html += '<div style="display:flex;gap:8px;">';
-            html += '<button class="search-nav-btn" onclick="prevMatch()" id="mobilePrevBtn" disabled>&#9650; Prev</button>';
-            html += '<button class="search-nav-btn" onclick="nextMatch()" id="mobileNextBtn" disabled>&#9660; Next</button>';
-            html += '</div>';
-            html += '</div>';
-            html += '</div>';
-            html += '</div>';
-            html += '<div class="mobile-search-filters-bar" id="mobileFiltersBar">';
-            html += '<span class="search-filter active" data-type="exact" onclick="toggleFilter(this)">Exact (<span class="mobileExactCount">0</span>)</span>';
-            html += '<span class="search-filter active" data-type="contains" onclick="toggleFilter(this)">Contains (<span class="mobileContainsCount">0</span>)</span>';
-            html += '<span class="search-filter active" data-type="spelling" onclick="toggleFilter(this)">Spelling (<span class="mobileSpellingCount">0</span>)</span>';
-            html += '<span class="search-filter active" data-type="phonetic" onclick="toggleFilter(this)">Sounds-like (<span class="mobilePhoneticCount">0</span>)</span>';
-            html += '</div>';
-            html += '<div class="sidebar-overlay" id="sidebarOverlay" onclick="toggleSidebar()"></div>';
-            html += '<div class="layout">';
-            html += '<div class="sidebar" id="sidebar">';
-            html += '<div class="sidebar-header">';
-            html += '<img class="logo-icon" src="' + LOGO_DATA_URI + '" alt="Honey Did" />';
-            html += '<div class="logo-text"><div class="sidebar-title">Honey Did</div>';
-            if (data.meta && data.meta.creator_name) {
-                html += '<div class="sidebar-subtitle">By ' + escapeHtml(data.meta.creator_name) + '</div>';
-            }
-            html += '</div></div>';
-            html += '<div class="sidebar-search">';
-            html += '<div class="search-wrapper">';
-            html += '<input type="text" id="searchInput" class="search-input" placeholder="Search..." oninput="debounceSearch(this.value)" onkeydown="if(event.key===\'Escape\')clearSearch()">';
-            html += '<button class="search-clear hidden" id="searchClear" onclick="clearSearch()" title="Clear search (Esc)">✕</button>';
-            html += '</div>';
-            html += '</div>';
-            html += '<div class="search-controls" id="searchControls">';
-            html += '<div class="search-nav">';
-            html += '<button onclick="prevMatch()" id="prevBtn" disabled>◀</button>';
-            html += '<button onclick="nextMatch()" id="nextBtn" disabled>▶</button>';
-            html += '<span class="search-counter" id="searchCounter"></span>';
-            html += '</div>';
-            html += '<div class="search-filters">';
-            html += '<span class="search-filter active" data-type="exact" onclick="toggleFilter(this)">Exact (<span id="exactCount">0</span>)</span>';
-            html += '<span class="search-filter active" data-type="contains" onclick="toggleFilter(this)">Contains (<span id="containsCount">0</span>)</span>';
-            html += '<span class="search-filter active" data-type="spelling" onclick="toggleFilter(this)">Spelling (<span id="spellingCount">0</span>)</span>';
-            html += '<span class="search-filter active" data-type="phonetic" onclick="toggleFilter(this)">Sounds-like (<span id="phoneticCount">0</span>)</span>';
-            html += '</div>';
-            html += '</div>';
-            html += '<div class="sidebar-nav"><div class="nav-title">Contents</div><ul class="nav-list">';
-
-            // Check which sections have content
-            const sectionLabels = {'financial': '💰 Financial', 'insurance': '🛡️ Insurance', 'bills': '📄 Bills', 'property': '🏠 Property', 'legal': '⚖️ Legal', 'digital': '💻 Digital Life', 'household': '🔧 Household', 'personal': '👤 Personal', 'contacts': '📇 Contacts', 'medical': '🏥 Medical', 'pets': '🐾 Pets'};
-
-            function hasContent(section) {
-                if (!data[section]) return false;
-                const d = data[section];
-                switch(section) {
-                    case 'financial': return (d.bank_accounts?.length || d.credit_cards?.length || d.investments?.length || d.debts?.length || d.notes);
-                    case 'insurance': return (d.policies?.length || d.notes);
-                    case 'bills': return (d.bills?.length || d.notes);
-                    case 'property': return (d.properties?.length || d.vehicles?.length || d.valuables?.length || d.notes);
-                    case 'legal': return (d.will_location || d.power_of_attorney || d.trusts?.length || d.attorney?.name || d.notes);
-                    case 'digital': return (d.email_accounts?.length || d.social_media?.length || d.password_manager?.name || d.notes);
-                    case 'household': return (d.maintenance_items?.length || d.contractors?.length || d.how_things_work?.length || d.notes);
-                    case 'personal': return (d.funeral_preferences || d.obituary_notes || d.messages?.length || d.notes);
-                    case 'contacts': return (d.emergency_contacts?.length || d.family?.length || d.professionals?.length || d.notes);
-                    case 'medical': return (d.family_members?.length || d.notes);
-                    case 'pets': return (d.pets?.length || d.notes);
-                    default: return false;
-                }
-            }
-
-            const sections = ['financial', 'insurance', 'bills', 'property', 'legal', 'digital', 'household', 'personal', 'contacts', 'medical', 'pets'];
-            sections.forEach(s => {
-                if (hasContent(s)) {
-                    html += '<li><a href="#' + s + '" onclick="closeSidebarOnMobile()">' + sectionLabels[s] + '</a></li>';
-                }
-            });
-
-            // Add custom sections to nav
-            if (data.custom_sections && data.custom_sections.length) {
-                const topLevel = data.custom_sections.filter(s => !s.parent);
-                topLevel.forEach(section => {
-                    if (section.subsections && section.subsections.some(sub => sub.items && sub.items.length)) {
-                        html += '<li><a href="#custom-' + escapeAttr(section.id) + '" onclick="closeSidebarOnMobile()">📋 ' + escapeHtml(section.name) + '</a></li>';
-                    }
-                });
-            }
-            html += '</ul></div>';
-            html += '<div class="sidebar-footer"><button class="print-btn" onclick="window.print()">Print Document</button><div class="legal-line">&copy; scafidi.dev &middot; <a href="https://opensource.org/licenses/MIT" target="_blank" rel="noopener">MIT License</a></div></div>';
-            html += '</div>';
-            html += '<div class="main-content" id="mainContent">';
-
-            // Financial Section
-            if (data.financial) {
-                let content = '';
-                if (data.financial.bank_accounts && data.financial.bank_accounts.length) {
-                    content += '<h3>Bank Accounts</h3>';
-                    data.financial.bank_accounts.forEach(a => {
-                        content += '<div class="item"><div class="item-title">' + escapeHtml(a.name) + '</div>';
-                        content += '<div class="item-detail">Institution: ' + escapeHtml(a.institution) + '</div>';
-                        content += '<div class="item-detail">Type: ' + escapeHtml(a.account_type) + '</div>';
-                        if (a.notes) content += '<div class="notes">' + escapeHtml(a.notes) + '</div>';
-                        content += '</div>';
-                    });
-                }
-                if (data.financial.credit_cards && data.financial.credit_cards.length) {
-                    content += '<h3>Credit Cards</h3>';
-                    data.financial.credit_cards.forEach(c => {
-                        content += '<div class="item"><div class="item-title">' + escapeHtml(c.name) + '</div>';
-                        content += '<div class="item-detail">Issuer: ' + escapeHtml(c.issuer) + '</div>';
-                        if (c.notes) content += '<div class="notes">' + escapeHtml(c.notes) + '</div>';
-                        content += '</div>';
-                    });
-                }
-                if (data.financial.investments && data.financial.investments.length) {
-                    content += '<h3>Investments</h3>';
-                    data.financial.investments.forEach(inv => {
-                        content += '<div class="item"><div class="item-title">' + escapeHtml(inv.name) + '</div>';
-                        content += '<div class="item-detail">Institution: ' + escapeHtml(inv.institution) + '</div>';
-                        content += '<div class="item-detail">Type: ' + escapeHtml(inv.account_type) + '</div>';
-                        if (inv.notes) content += '<div class="notes">' + escapeHtml(inv.notes) + '</div>';
-                        content += '</div>';
-                    });
-                }
-                if (data.financial.debts && data.financial.debts.length) {
-                    content += '<h3>Debts & Loans</h3>';
-                    data.financial.debts.forEach(d => {
-                        content += '<div class="item"><div class="item-title">' + escapeHtml(d.name) + '</div>';
-                        content += '<div class="item-detail">Lender: ' + escapeHtml(d.lender) + '</div>';
-                        if (d.notes) content += '<div class="notes">' + escapeHtml(d.notes) + '</div>';
-                        content += '</div>';
-                    });
-                }
-                if (data.financial.notes) content += '<div class="notes">' + escapeHtml(data.financial.notes) + '</div>';
-                content += renderAttachments(data.financial.attachments);
-                html += renderSection('💰 Financial Information', 'financial', content);
-            }
-
-            // Insurance Section
-            if (data.insurance && ((data.insurance.policies && data.insurance.policies.length) || data.insurance.notes)) {
-                let content = '';
-                if (data.insurance.policies && data.insurance.policies.length) {
-                    data.insurance.policies.forEach(p => {
-                        content += '<div class="item"><div class="item-title">' + escapeHtml(p.policy_type) + '</div>';
-                        content += '<div class="item-detail">Provider: ' + escapeHtml(p.provider) + '</div>';
-                        content += '<div class="item-detail">Policy #: ' + escapeHtml(p.policy_number) + '</div>';
-                        if (p.contact) content += '<div class="item-detail">Contact: ' + escapeHtml(p.contact) + '</div>';
-                        if (p.notes) content += '<div class="notes">' + escapeHtml(p.notes) + '</div>';
-                        content += '</div>';
-                    });
-                }
-                if (data.insurance.notes) content += '<div class="notes">' + escapeHtml(data.insurance.notes) + '</div>';
-                content += renderAttachments(data.insurance.attachments);
-                html += renderSection('🛡️ Insurance', 'insurance', content);
-            }
-
-            // Bills Section
-            if (data.bills && ((data.bills.bills && data.bills.bills.length) || data.bills.notes)) {
-                let content = '';
-                if (data.bills.bills && data.bills.bills.length) {
-                    data.bills.bills.forEach(b => {
-                        content += '<div class="item"><div class="item-title">' + escapeHtml(b.name) + '</div>';
-                        content += '<div class="item-detail">Provider: ' + escapeHtml(b.provider) + '</div>';
-                        content += '<div class="item-detail">Amount: ' + escapeHtml(b.amount) + '</div>';
-                        content += '<div class="item-detail">Due Day: ' + escapeHtml(b.due_day) + '</div>';
-                        content += '<div class="item-detail">Auto-pay: ' + (b.autopay ? 'Yes' : 'No') + '</div>';
-                        if (b.notes) content += '<div class="notes">' + escapeHtml(b.notes) + '</div>';
-                        content += '</div>';
-                    });
-                }
-                if (data.bills.notes) content += '<div class="notes">' + escapeHtml(data.bills.notes) + '</div>';
-                content += renderAttachments(data.bills.attachments);
-                html += renderSection('📄 Bills', 'bills', content);
-            }
-
-            // Property Section
-            if (data.property) {
-                let content = '';
-                if (data.property.properties && data.property.properties.length) {
-                    content += '<h3>Properties</h3>';
-                    data.property.properties.forEach(p => {
-                        content += '<div class="item"><div class="item-title">' + escapeHtml(p.name) + '</div>';
-                        content += '<div class="item-detail">Address: ' + escapeHtml(p.address) + '</div>';
-                        if (p.notes) content += '<div class="notes">' + escapeHtml(p.notes) + '</div>';
-                        content += '</div>';
-                    });
-                }
-                if (data.property.vehicles && data.property.vehicles.length) {
-                    content += '<h3>Vehicles</h3>';
-                    data.property.vehicles.forEach(v => {
-                        content += '<div class="item"><div class="item-title">' + escapeHtml(v.name) + '</div>';
-                        content += '<div class="item-detail">' + escapeHtml(v.details) + '</div>';
-                        if (v.notes) content += '<div class="notes">' + escapeHtml(v.notes) + '</div>';
-                        content += '</div>';
-                    });
-                }
-                if (data.property.valuables && data.property.valuables.length) {
-                    content += '<h3>Valuables</h3>';
-                    data.property.valuables.forEach(v => {
-                        content += '<div class="item"><div class="item-title">' + escapeHtml(v.name) + '</div>';
-                        content += '<div class="item-detail">Location: ' + escapeHtml(v.location) + '</div>';
-                        if (v.notes) content += '<div class="notes">' + escapeHtml(v.notes) + '</div>';
-                        content += '</div>';
-                    });
-                }
-                if (data.property.notes) content += '<div class="notes">' + escapeHtml(data.property.notes) + '</div>';
-                content += renderAttachments(data.property.attachments);
-                html += renderSection('🏠 Property', 'property', content);
-            }
-
-            // Legal Section
-            if (data.legal) {
-                let content = '';
-                if (data.legal.will_location) content += '<div class="item-detail"><strong>Will Location:</strong> ' + escapeHtml(data.legal.will_location) + '</div>';
-                if (data.legal.power_of_attorney) content += '<div class="item-detail"><strong>Power of Attorney:</strong> ' + escapeHtml(data.legal.power_of_attorney) + '</div>';
-                if (data.legal.attorney && data.legal.attorney.name) {
-                    content += '<h3>Attorney</h3>' + renderContact(data.legal.attorney);
-                }
-                if (data.legal.trusts && data.legal.trusts.length) {
-                    content += '<h3>Trusts</h3>';
-                    data.legal.trusts.forEach(t => {
-                        content += '<div class="item"><div class="item-title">' + escapeHtml(t.name) + '</div>';
-                        content += '<div class="item-detail">Trustee: ' + escapeHtml(t.trustee) + '</div>';
-                        if (t.notes) content += '<div class="notes">' + escapeHtml(t.notes) + '</div>';
-                        content += '</div>';
-                    });
-                }
-                if (data.legal.notes) content += '<div class="notes">' + escapeHtml(data.legal.notes) + '</div>';
-                content += renderAttachments(data.legal.attachments);
-                html += renderSection('⚖️ Legal Documents', 'legal', content);
-            }
-
-            // Digital Section
-            if (data.digital) {
-                let content = '';
-                if (data.digital.password_manager && data.digital.password_manager.name) {
-                    content += '<h3>Password Manager</h3>';
-                    content += '<div class="item"><div class="item-title">' + escapeHtml(data.digital.password_manager.name) + '</div>';
-                    if (data.digital.password_manager.master_password_hint) content += '<div class="item-detail">Hint: ' + escapeHtml(data.digital.password_manager.master_password_hint) + '</div>';
-                    if (data.digital.password_manager.recovery_method) content += '<div class="item-detail">Recovery: ' + escapeHtml(data.digital.password_manager.recovery_method) + '</div>';
-                    content += '</div>';
-                }
-                if (data.digital.email_accounts && data.digital.email_accounts.length) {
-                    content += '<h3>Email Accounts</h3>';
-                    data.digital.email_accounts.forEach(e => {
-                        content += '<div class="item"><div class="item-title">' + escapeHtml(e.name) + '</div>';
-                        content += '<div class="item-detail">Username: ' + escapeHtml(e.username) + '</div>';
-                        if (e.recovery_hint) content += '<div class="item-detail">Recovery: ' + escapeHtml(e.recovery_hint) + '</div>';
-                        if (e.notes) content += '<div class="notes">' + escapeHtml(e.notes) + '</div>';
-                        content += '</div>';
-                    });
-                }
-                if (data.digital.social_media && data.digital.social_media.length) {
-                    content += '<h3>Social Media</h3>';
-                    data.digital.social_media.forEach(s => {
-                        content += '<div class="item"><div class="item-title">' + escapeHtml(s.name) + '</div>';
-                        content += '<div class="item-detail">Username: ' + escapeHtml(s.username) + '</div>';
-                        if (s.notes) content += '<div class="notes">' + escapeHtml(s.notes) + '</div>';
-                        content += '</div>';
-                    });
-                }
-                if (data.digital.notes) content += '<div class="notes">' + escapeHtml(data.digital.notes) + '</div>';
-                content += renderAttachments(data.digital.attachments);
-                html += renderSection('💻 Digital Life', 'digital', content);
-            }
-
-            // Household Section
-            if (data.household) {
-                let content = '';
-                if (data.household.maintenance_items && data.household.maintenance_items.length) {
-                    content += '<h3>Maintenance</h3>';
-                    data.household.maintenance_items.forEach(m => {
-                        content += '<div class="item"><div class="item-title">' + escapeHtml(m.name) + '</div>';
-                        content += '<div class="item-detail">Frequency: ' + escapeHtml(m.frequency) + '</div>';
-                        if (m.notes) content += '<div class="notes">' + escapeHtml(m.notes) + '</div>';
-                        content += '</div>';
-                    });
-                }
-                if (data.household.contractors && data.household.contractors.length) {
-                    content += '<h3>Contractors</h3>';
-                    data.household.contractors.forEach(c => {
-                        content += '<div class="item">' + renderContact(c) + '</div>';
-                    });
-                }
-                if (data.household.how_things_work && data.household.how_things_work.length) {
-                    content += '<h3>How Things Work</h3>';
-                    data.household.how_things_work.forEach(h => {
-                        content += '<div class="item"><div class="item-title">' + escapeHtml(h.name) + '</div>';
-                        content += '<div class="item-detail">' + escapeHtml(h.instructions) + '</div>';
-                        content += '</div>';
-                    });
-                }
-                if (data.household.notes) content += '<div class="notes">' + escapeHtml(data.household.notes) + '</div>';
-                content += renderAttachments(data.household.attachments);
-                html += renderSection('🔧 Household', 'household', content);
-            }
-
-            // Personal Section
-            if (data.personal) {
-                let content = '';
-                if (data.personal.funeral_preferences) {
-                    content += '<h3>Funeral Preferences</h3><div class="item">' + escapeHtml(data.personal.funeral_preferences) + '</div>';
-                }
-                if (data.personal.obituary_notes) {
-                    content += '<h3>Obituary Notes</h3><div class="item">' + escapeHtml(data.personal.obituary_notes) + '</div>';
-                }
-                if (data.personal.messages && data.personal.messages.length) {
-                    content += '<h3>Personal Messages</h3>';
-                    data.personal.messages.forEach(m => {
-                        content += '<div class="item"><div class="item-title">To: ' + escapeHtml(m.recipient) + '</div>';
-                        content += '<div class="item-detail">' + escapeHtml(m.message) + '</div>';
-                        content += '</div>';
-                    });
-                }
-                if (data.personal.notes) content += '<div class="notes">' + escapeHtml(data.personal.notes) + '</div>';
-                content += renderAttachments(data.personal.attachments);
-                html += renderSection('👤 Personal Wishes', 'personal', content);
-            }
-
-            // Contacts Section
-            if (data.contacts) {
-                let content = '';
-                if (data.contacts.emergency_contacts && data.contacts.emergency_contacts.length) {
-                    content += '<h3>Emergency Contacts</h3>';
-                    data.contacts.emergency_contacts.forEach(c => {
-                        content += '<div class="item">' + renderContact(c) + '</div>';
-                    });
-                }
-                if (data.contacts.family && data.contacts.family.length) {
-                    content += '<h3>Family</h3>';
-                    data.contacts.family.forEach(c => {
-                        content += '<div class="item">' + renderContact(c) + '</div>';
-                    });
-                }
-                if (data.contacts.professionals && data.contacts.professionals.length) {
-                    content += '<h3>Professional Contacts</h3>';
-                    data.contacts.professionals.forEach(c => {
-                        content += '<div class="item">' + renderContact(c) + '</div>';
-                    });
-                }
-                if (data.contacts.notes) content += '<div class="notes">' + escapeHtml(data.contacts.notes) + '</div>';
-                content += renderAttachments(data.contacts.attachments);
-                html += renderSection('📇 Important Contacts', 'contacts', content);
-            }
-
-            // Medical Section
-            if (data.medical && ((data.medical.family_members && data.medical.family_members.length) || data.medical.notes)) {
-                let content = '';
-                if (data.medical.family_members && data.medical.family_members.length) {
-                    data.medical.family_members.forEach(m => {
-                        content += '<div class="item"><div class="item-title">' + escapeHtml(m.name) + '</div>';
-                        if (m.conditions && m.conditions.length) content += '<div class="item-detail"><strong>Conditions:</strong> ' + m.conditions.map(c => escapeHtml(c)).join(', ') + '</div>';
-                        if (m.allergies && m.allergies.length) content += '<div class="item-detail"><strong>Allergies:</strong> ' + m.allergies.map(a => escapeHtml(a)).join(', ') + '</div>';
-                        if (m.doctors && m.doctors.length) {
-                            content += '<div class="item-detail"><strong>Doctors:</strong></div>';
-                            m.doctors.forEach(doc => {
-                                let docInfo = escapeHtml(doc.name);
-                                if (doc.specialty) docInfo += ' (' + escapeHtml(doc.specialty) + ')';
-                                if (doc.phone) docInfo += ' - ' + escapeHtml(doc.phone);
-                                content += '<div class="item-detail">&nbsp;&nbsp;' + docInfo + '</div>';
-                                if (doc.notes) content += '<div class="item-detail">&nbsp;&nbsp;&nbsp;&nbsp;<em>' + escapeHtml(doc.notes) + '</em></div>';
-                            });
-                        }
-                        if (m.medications && m.medications.length) {
-                            content += '<div class="item-detail"><strong>Medications:</strong></div>';
-                            m.medications.forEach(med => {
-                                content += '<div class="item-detail">&nbsp;&nbsp;' + escapeHtml(med.name) + ' - ' + escapeHtml(med.dosage) + ' (' + escapeHtml(med.frequency) + ')</div>';
-                            });
-                        }
-                        if (m.pharmacy && m.pharmacy.name) content += '<div class="item-detail"><strong>Pharmacy:</strong> ' + escapeHtml(m.pharmacy.name) + ' ' + escapeHtml(m.pharmacy.phone || '') + '</div>';
-                        if (m.notes) content += '<div class="notes">' + escapeHtml(m.notes) + '</div>';
-                        content += '</div>';
-                    });
-                }
-                if (data.medical.notes) content += '<div class="notes">' + escapeHtml(data.medical.notes) + '</div>';
-                content += renderAttachments(data.medical.attachments);
-                html += renderSection('🏥 Medical Information', 'medical', content);
-            }
-
-            // Pets Section
-            if (data.pets && ((data.pets.pets && data.pets.pets.length) || data.pets.notes)) {
-                let content = '';
-                if (data.pets.pets && data.pets.pets.length) {
-                    data.pets.pets.forEach(p => {
-                        content += '<div class="item"><div class="item-title">' + escapeHtml(p.name) + '</div>';
-                        content += '<div class="item-detail">Species: ' + escapeHtml(p.species) + '</div>';
-                        content += '<div class="item-detail">Breed: ' + escapeHtml(p.breed) + '</div>';
-                        if (p.vet && p.vet.name) content += '<div class="item-detail"><strong>Vet:</strong> ' + escapeHtml(p.vet.name) + ' ' + escapeHtml(p.vet.phone || '') + '</div>';
-                        if (p.medications && p.medications.length) {
-                            content += '<div class="item-detail"><strong>Medications:</strong></div>';
-                            p.medications.forEach(med => {
-                                content += '<div class="item-detail">&nbsp;&nbsp;' + escapeHtml(med.name) + ' - ' + escapeHtml(med.dosage) + '</div>';
-                            });
-                        }
-                        if (p.feeding) content += '<div class="item-detail"><strong>Feeding:</strong> ' + escapeHtml(p.feeding) + '</div>';
-                        if (p.care_notes) content += '<div class="notes">' + escapeHtml(p.care_notes) + '</div>';
-                        content += '</div>';
-                    });
-                }
-                if (data.pets.notes) content += '<div class="notes">' + escapeHtml(data.pets.notes) + '</div>';
-                content += renderAttachments(data.pets.attachments);
-                html += renderSection('🐾 Pets', 'pets', content);
-            }
-
-            // Custom Sections
-            if (data.custom_sections && data.custom_sections.length) {
-                // Separate top-level from preset subsections
-                const topLevel = data.custom_sections.filter(s => !s.parent);
-                const presetSubs = data.custom_sections.filter(s => s.parent);
-
-                // Render top-level custom sections
-                topLevel.forEach(section => {
-                    if (!section.subsections || !section.subsections.length) return;
-                    let content = '';
-                    section.subsections.forEach(sub => {
-                        content += renderCustomSubsection(sub);
-                    });
-                    content += renderAttachments(section.attachments);
-                    if (content) html += renderSection('📋 ' + escapeHtml(section.name), 'custom-' + section.id, content);
-                });
-
-                // Render preset subsections grouped by parent
-                const byParent = {};
-                presetSubs.forEach(section => {
-                    if (!byParent[section.parent]) byParent[section.parent] = [];
-                    byParent[section.parent].push(section);
-                });
-
-                const parentLabels = {
-                    'financial': 'Financial (Custom)',
-                    'insurance': 'Insurance (Custom)',
-                    'bills': 'Bills (Custom)',
-                    'property': 'Property (Custom)',
-                    'legal': 'Legal (Custom)',
-                    'digital': 'Digital Life (Custom)',
-                    'household': 'Household (Custom)',
-                    'personal': 'Personal Wishes (Custom)',
-                    'contacts': 'Contacts (Custom)',
-                    'medical': 'Medical (Custom)',
-                    'pets': 'Pets (Custom)'
-                };
-
-                Object.keys(byParent).forEach(parentName => {
-                    let content = '';
-                    byParent[parentName].forEach(section => {
-                        if (section.subsections) {
-                            section.subsections.forEach(sub => {
-                                content += renderCustomSubsection(sub);
-                            });
-                        }
-                    });
-                    if (content) {
-                        const label = parentLabels[parentName] || parentName + ' (Custom)';
-                        html += renderSection('📋 ' + escapeHtml(label), 'custom-' + parentName, content);
-                    }
-                });
-            }
-
-            html += '</div>'; // End main-content
-            html += '</div>'; // End layout
-
-            container.innerHTML = html;
+            // The fingerprint belongs to the lock screens
+            document.getElementById('signatureNote').hidden = true;
+            exportExtras = data.export_extras || {};
+            loadSearchVocabulary(exportExtras.search_index);
+            // The viewer is rendered from templates at export time (see render.rs)
+            container.innerHTML = exportExtras.rendered.page;
+            syncDisplayToggles();
             buildSearchIndex();
+            bindExtraDownloads();
 
             // Bind download handlers for all attachments
             var allSections = ['financial','insurance','bills','property','legal','digital','household','personal','contacts','medical','pets'];
@@ -1671,61 +1523,52 @@ const SHARED_JS_RENDER_DOCUMENT: &str = r##"
 
 /// CSS specific to passphrase-based unlock
 const PASSPHRASE_CSS: &str = r##"
-        .lock-screen { display: flex; flex-direction: column; align-items: center; justify-content: center; min-height: 100vh; text-align: center; background: linear-gradient(145deg, #F0EFEB 0%, #D4D4D4 100%); }
-        .lock-version { color: #B7B7A4; font-size: 0.8rem; margin-top: 2rem; }
-        .lock-title { font-size: 1.75rem; font-weight: 600; color: #283618; margin-bottom: 0.5rem; }
-        .lock-subtitle { color: #606C38; margin-bottom: 2rem; font-size: 0.95rem; }
+        .lock-screen { display: flex; flex-direction: column; align-items: center; justify-content: center; min-height: 100vh; text-align: center; background: linear-gradient(145deg, var(--hd-background) 0%, var(--hd-border) 100%); }
+        .lock-version { color: var(--hd-accent); font-size: 0.8rem; margin-top: 2rem; }
+        .lock-title { font-size: 1.75rem; font-weight: 600; color: var(--hd-primary); margin-bottom: 0.5rem; }
+        .lock-subtitle { color: var(--hd-accent); margin-bottom: 2rem; font-size: 0.95rem; }
         .password-form { display: flex; flex-direction: column; gap: 1rem; width: 100%; max-width: 320px; }
-        .password-input { padding: 14px 16px; font-size: 1rem; border: 2px solid #D4D4D4; border-radius: 10px; text-align: center; background: white; transition: border-color 0.2s, box-shadow 0.2s; }
-        .password-input:focus { outline: none; border-color: #283618; box-shadow: 0 0 0 3px rgba(40, 54, 24, 0.1); }
-        .unlock-btn { padding: 14px 28px; font-size: 1rem; font-weight: 500; background: #283618; color: #F0EFEB; border: none; border-radius: 10px; cursor: pointer; transition: background 0.2s, transform 0.1s; }
-        .unlock-btn:hover { background: #1a2410; }
+        .password-input { padding: 14px 16px; font-size: 1rem; border: 2px solid var(--hd-border); border-radius: 10px; text-align: center; background: white; transition: border-color 0.2s, box-shadow 0.2s; }
+        .password-input:focus { outline: none; border-color: var(--hd-primary); box-shadow: 0 0 0 3px rgba(40, 54, 24, 0.1); }
+        .unlock-btn { padding: 14px 28px; font-size: 1rem; font-weight: 500; background: var(--hd-primary); color: var(--hd-background); border: none; border-radius: 10px; cursor: pointer; transition: background 0.2s, transform 0.1s; }
+        .unlock-btn:hover:not(:disabled) { background: var(--hd-primary-dark); }
         .unlock-btn:active { transform: scale(0.98); }
+        .unlock-btn:disabled { opacity: 0.6; cursor: not-allowed; }
         .error { color: #9B2C2C; font-size: 0.9rem; margin-top: 1rem; }
         /* Welcome Screen Styles */
-        .welcome-screen { position: fixed; inset: 0; background: linear-gradient(145deg, #283618 0%, #1a2410 100%); display: flex; flex-direction: column; align-items: center; justify-content: center; z-index: 2000; opacity: 1; transition: opacity 0.5s ease; }
-        .welcome-screen.hidden { opacity: 0; pointer-events: none; }
+        .welcome-screen { position: fixed; inset: 0; background: linear-gradient(145deg, var(--hd-primary) 0%, var(--hd-primary-dark) 100%); display: flex; flex-direction: column; align-items: center; justify-content: center; z-index: 2000; opacity: 1; transition: opacity 0.5s ease; }
+        .welcome-screen.hidden { opacity: 0; visibility: hidden; pointer-events: none; transition: opacity 0.5s ease, visibility 0.5s; }
+        .welcome-pause { margin-top: 16px; padding: 8px 20px; background: none; color: var(--hd-background); border: 1px solid rgba(240, 239, 235, 0.5); border-radius: 8px; cursor: pointer; font-size: 0.9rem; }
+        .welcome-pause[hidden] { display: none; }
         .welcome-slide { max-width: 600px; padding: 40px; text-align: center; opacity: 0; transform: translateY(20px); transition: opacity 0.5s ease, transform 0.5s ease; }
         .welcome-slide.visible { opacity: 1; transform: translateY(0); }
-        .welcome-slide-text { font-size: 1.5rem; line-height: 1.8; color: #F0EFEB; font-weight: 400; white-space: pre-wrap; }
-        .welcome-continue { margin-top: 40px; padding: 14px 32px; background: rgba(240, 239, 235, 0.15); color: #F0EFEB; border: 2px solid rgba(240, 239, 235, 0.3); border-radius: 10px; cursor: pointer; font-size: 1rem; font-weight: 500; transition: all 0.2s; }
+        .welcome-slide-text { font-size: 1.5rem; line-height: 1.8; color: var(--hd-background); font-weight: 400; white-space: pre-wrap; }
+        .welcome-continue { margin-top: 40px; padding: 14px 32px; background: rgba(240, 239, 235, 0.15); color: var(--hd-background); border: 2px solid rgba(240, 239, 235, 0.3); border-radius: 10px; cursor: pointer; font-size: 1rem; font-weight: 500; transition: all 0.2s; }
         .welcome-continue:hover { background: rgba(240, 239, 235, 0.25); border-color: rgba(240, 239, 235, 0.5); }
         .welcome-progress { position: absolute; bottom: 40px; display: flex; gap: 8px; }
         .welcome-dot { width: 8px; height: 8px; border-radius: 50%; background: rgba(240, 239, 235, 0.3); transition: background 0.3s; }
-        .welcome-dot.active { background: #F0EFEB; }
+        .welcome-dot.active { background: var(--hd-background); }
         .welcome-timer { position: absolute; bottom: 20px; width: 200px; height: 3px; background: rgba(240, 239, 235, 0.2); border-radius: 2px; overflow: hidden; }
-        .welcome-timer-bar { height: 100%; background: #F0EFEB; width: 0%; transition: width linear; }
+        .welcome-timer-bar { height: 100%; background: var(--hd-background); width: 0%; transition: width linear; }
 "##;
 
 /// JavaScript specific to passphrase-based decryption
 const PASSPHRASE_JS_DECRYPT: &str = r##"
-        const PBKDF2_ITERATIONS = 600000;
-
         async function unlock(event) {
             event.preventDefault();
             const passphrase = document.getElementById('passphrase').value;
             if (!passphrase) return false;
+            if (showUnlockWait('passphrase', 'error', ['unlockBtn'])) return false;
 
             try {
                 const salt = Uint8Array.from(atob(ENCRYPTED_DATA.salt), c => c.charCodeAt(0));
                 const nonce = Uint8Array.from(atob(ENCRYPTED_DATA.nonce), c => c.charCodeAt(0));
                 const ciphertext = Uint8Array.from(atob(ENCRYPTED_DATA.ciphertext), c => c.charCodeAt(0));
 
-                const encoder = new TextEncoder();
-                const keyMaterial = await crypto.subtle.importKey(
-                    'raw', encoder.encode(passphrase), 'PBKDF2', false, ['deriveKey']
-                );
-
-                const key = await crypto.subtle.deriveKey(
-                    { name: 'PBKDF2', salt: salt, iterations: PBKDF2_ITERATIONS, hash: 'SHA-256' },
-                    keyMaterial,
-                    { name: 'AES-GCM', length: 256 },
-                    false,
-                    ['decrypt']
-                );
+                const key = await deriveKey(passphrase, salt, ENCRYPTED_DATA.kdf);
 
                 const decrypted = await crypto.subtle.decrypt(
-                    { name: 'AES-GCM', iv: nonce },
+                    aesGcmParams(nonce, 'document'),
                     key,
                     ciphertext
                 );
@@ -1734,12 +1577,16 @@ const PASSPHRASE_JS_DECRYPT: &str = r##"
                 const json = decoder.decode(decrypted);
                 const data = JSON.parse(json);
 
+                clearFailedUnlocks();
                 renderDocument(data);
                 document.getElementById('lockScreen').style.display = 'none';
                 document.getElementById('content').classList.add('visible');
+                focusDocument();
             } catch (err) {
-                document.getElementById('error').textContent = 'Incorrect passphrase. Please try again.';
+                recordFailedUnlock('passphrase');
+                document.getElementById('error').textContent = t('lock.incorrect');
                 document.getElementById('error').style.display = 'block';
+                showUnlockWait('passphrase', 'error', ['unlockBtn']);
             }
             return false;
         }
@@ -1749,8 +1596,15 @@ const PASSPHRASE_JS_DECRYPT: &str = r##"
 const PASSPHRASE_JS_WELCOME: &str = r##"
         let currentWelcomeSlide = 0;
         let welcomeTimer = null;
+        // Auto-advancing slides can be paused, and start paused for anyone who asks for less motion
+        let welcomePaused = prefersReducedMotion();
+        let welcomeDuration = 0;
+        let welcomeRemaining = 0;
+        let welcomeStarted = 0;
 
         function initWelcome() {
+            // Built and escaped by the app; a changed message fails to decrypt
+            document.getElementById('lockMessage').innerHTML = LOCK_SCREEN.message;
             if (!WELCOME_SLIDES || WELCOME_SLIDES.length === 0) {
                 document.getElementById('welcomeScreen').classList.add('hidden');
                 document.getElementById('lockScreen').style.display = 'flex';
@@ -1768,13 +1622,52 @@ const PASSPHRASE_JS_WELCOME: &str = r##"
             });
 
             showWelcomeSlide(0);
+            document.getElementById('welcomeContinue').focus();
         }
 
-        function showWelcomeSlide(index) {
+        function clearWelcomeTimer() {
             if (welcomeTimer) {
                 clearTimeout(welcomeTimer);
                 welcomeTimer = null;
             }
+        }
+
+        function setWelcomeTimerBar(percent, seconds) {
+            const timerBar = document.getElementById('welcomeTimerBar');
+            timerBar.style.transition = seconds ? `width ${seconds}s linear` : 'none';
+            timerBar.style.width = percent + '%';
+        }
+
+        function startWelcomeTimer() {
+            welcomeStarted = Date.now();
+            welcomeTimer = setTimeout(() => nextWelcomeSlide(), welcomeRemaining);
+            setTimeout(() => {
+                if (welcomeTimer) setWelcomeTimerBar(100, welcomeRemaining / 1000);
+            }, 50);
+        }
+
+        function updateWelcomePause(isAuto) {
+            const pauseEl = document.getElementById('welcomePause');
+            pauseEl.hidden = !isAuto;
+            pauseEl.textContent = t(welcomePaused ? 'slides.play' : 'slides.pause');
+            // Announce each slide unless they are changing on their own
+            document.getElementById('welcomeText').setAttribute('aria-live', isAuto && !welcomePaused ? 'off' : 'polite');
+        }
+
+        function toggleWelcomePause() {
+            welcomePaused = !welcomePaused;
+            if (welcomePaused) {
+                if (welcomeTimer) welcomeRemaining = Math.max(0, welcomeRemaining - (Date.now() - welcomeStarted));
+                clearWelcomeTimer();
+                setWelcomeTimerBar(100 * (1 - welcomeRemaining / welcomeDuration), 0);
+            } else {
+                startWelcomeTimer();
+            }
+            updateWelcomePause(true);
+        }
+
+        function showWelcomeSlide(index) {
+            clearWelcomeTimer();
 
             currentWelcomeSlide = index;
             const slide = WELCOME_SLIDES[index];
@@ -1782,33 +1675,29 @@ const PASSPHRASE_JS_WELCOME: &str = r##"
             const slideEl = document.getElementById('welcomeSlide');
             const btnEl = document.getElementById('welcomeContinue');
             const timerEl = document.getElementById('welcomeTimer');
-            const timerBar = document.getElementById('welcomeTimerBar');
 
             slideEl.classList.remove('visible');
 
             setTimeout(() => {
                 textEl.textContent = slide.text;
+                slideEl.setAttribute('aria-label', t('slides.position', { current: index + 1, total: WELCOME_SLIDES.length }));
 
                 const isLastSlide = index === WELCOME_SLIDES.length - 1;
-                btnEl.textContent = isLastSlide ? 'Continue to Document' : 'Continue';
+                btnEl.textContent = isLastSlide ? t('welcome.continue_to_document') : t('welcome.continue');
 
                 const dots = document.querySelectorAll('.welcome-dot');
                 dots.forEach((dot, i) => dot.classList.toggle('active', i === index));
 
                 slideEl.classList.add('visible');
 
-                if (slide.transition && slide.transition.type === 'auto') {
-                    const duration = slide.transition.seconds || 5;
+                const isAuto = !!(slide.transition && slide.transition.type === 'auto');
+                updateWelcomePause(isAuto);
+                if (isAuto) {
+                    welcomeDuration = (slide.transition.seconds || 5) * 1000;
+                    welcomeRemaining = welcomeDuration;
                     timerEl.style.display = 'block';
-                    timerBar.style.width = '0%';
-                    timerBar.style.transition = 'none';
-
-                    setTimeout(() => {
-                        timerBar.style.transition = `width ${duration}s linear`;
-                        timerBar.style.width = '100%';
-                    }, 50);
-
-                    welcomeTimer = setTimeout(() => nextWelcomeSlide(), duration * 1000);
+                    setWelcomeTimerBar(0, 0);
+                    if (!welcomePaused) startWelcomeTimer();
                 } else {
                     timerEl.style.display = 'none';
                 }
@@ -1816,10 +1705,7 @@ const PASSPHRASE_JS_WELCOME: &str = r##"
         }
 
         function nextWelcomeSlide() {
-            if (welcomeTimer) {
-                clearTimeout(welcomeTimer);
-                welcomeTimer = null;
-            }
+            clearWelcomeTimer();
 
             if (currentWelcomeSlide < WELCOME_SLIDES.length - 1) {
                 showWelcomeSlide(currentWelcomeSlide + 1);
@@ -1832,9 +1718,9 @@ const PASSPHRASE_JS_WELCOME: &str = r##"
 
         document.addEventListener('DOMContentLoaded', initWelcome);
 
-        // Allow Enter key to advance welcome slides
+        // Allow Enter key to advance welcome slides; buttons handle their own Enter
         document.addEventListener('keydown', (e) => {
-            if (e.key === 'Enter') {
+            if (e.key === 'Enter' && e.target.tagName !== 'BUTTON') {
                 const welcomeScreen = document.getElementById('welcomeScreen');
                 if (welcomeScreen && !welcomeScreen.classList.contains('hidden')) {
                     e.preventDefault();
@@ -1851,90 +1737,156 @@ const PASSPHRASE_JS_WELCOME: &str = r##"
 /// CSS specific to question-based unlock
 const QUESTION_CSS: &str = r##"
         /* Slide Screen */
-        .slide-screen { position: fixed; inset: 0; background: linear-gradient(145deg, #283618 0%, #1a2410 100%); display: flex; flex-direction: column; align-items: center; justify-content: center; z-index: 2000; }
+        .slide-screen { position: fixed; inset: 0; background: linear-gradient(145deg, var(--hd-primary) 0%, var(--hd-primary-dark) 100%); display: flex; flex-direction: column; align-items: center; justify-content: center; z-index: 2000; }
         .slide-screen.hidden { display: none; }
         .slide-container { max-width: 600px; padding: 40px; text-align: center; display: flex; flex-direction: column; align-items: center; }
-        .slide-text { font-size: 1.5rem; line-height: 1.8; color: #F0EFEB; font-weight: 400; white-space: pre-wrap; margin-bottom: 24px; }
-        .slide-input { width: 100%; max-width: 400px; padding: 14px 16px; font-size: 1.1rem; border: 2px solid rgba(240, 239, 235, 0.3); border-radius: 10px; text-align: center; background: rgba(255,255,255,0.1); color: #F0EFEB; margin-bottom: 16px; }
+        .slide-text { font-size: 1.5rem; line-height: 1.8; color: var(--hd-background); font-weight: 400; white-space: pre-wrap; margin-bottom: 24px; }
+        .slide-input { width: 100%; max-width: 400px; padding: 14px 16px; font-size: 1.1rem; border: 2px solid rgba(240, 239, 235, 0.3); border-radius: 10px; text-align: center; background: rgba(255,255,255,0.1); color: var(--hd-background); margin-bottom: 16px; }
         .slide-input::placeholder { color: rgba(240, 239, 235, 0.5); }
+        .slide-hint { color: rgba(240, 239, 235, 0.7); font-size: 0.9rem; margin: -4px 0 16px 0; }
         .slide-input:focus { outline: none; border-color: rgba(240, 239, 235, 0.6); background: rgba(255,255,255,0.15); }
-        .slide-btn { padding: 14px 32px; background: rgba(240, 239, 235, 0.15); color: #F0EFEB; border: 2px solid rgba(240, 239, 235, 0.3); border-radius: 10px; cursor: pointer; font-size: 1rem; font-weight: 500; transition: all 0.2s; }
+        .slide-btn { padding: 14px 32px; background: rgba(240, 239, 235, 0.15); color: var(--hd-background); border: 2px solid rgba(240, 239, 235, 0.3); border-radius: 10px; cursor: pointer; font-size: 1rem; font-weight: 500; transition: all 0.2s; }
         .slide-btn:hover { background: rgba(240, 239, 235, 0.25); border-color: rgba(240, 239, 235, 0.5); }
         .slide-progress { position: absolute; bottom: 40px; display: flex; gap: 8px; }
         .slide-dot { width: 10px; height: 10px; border-radius: 50%; background: rgba(240, 239, 235, 0.3); transition: background 0.3s; }
-        .slide-dot.active { background: #F0EFEB; }
+        .slide-dot.active { background: var(--hd-background); }
         .slide-dot.question { border: 2px solid rgba(240, 239, 235, 0.5); }
 
         /* Retry Screen */
-        .retry-screen { position: fixed; inset: 0; background: linear-gradient(145deg, #F0EFEB 0%, #D4D4D4 100%); display: flex; flex-direction: column; align-items: center; justify-content: center; z-index: 2000; padding: 20px; }
+        .retry-screen { position: fixed; inset: 0; background: linear-gradient(145deg, var(--hd-background) 0%, var(--hd-border) 100%); display: flex; flex-direction: column; align-items: center; justify-content: center; z-index: 2000; padding: 20px; }
         .retry-screen.hidden { display: none; }
         .retry-container { max-width: 500px; width: 100%; text-align: center; }
-        .retry-title { font-size: 1.25rem; color: #283618; margin-bottom: 8px; }
-        .retry-subtitle { color: #606C38; margin-bottom: 24px; }
-        .retry-questions { text-align: left; margin-bottom: 24px; }
-        .retry-question { background: white; border: 1px solid #D4D4D4; border-radius: 8px; padding: 16px; margin-bottom: 12px; }
-        .retry-question label { display: block; font-weight: 500; color: #283618; margin-bottom: 8px; }
-        .retry-question input { width: 100%; padding: 10px 12px; border: 1px solid #D4D4D4; border-radius: 6px; font-size: 1rem; }
-        .retry-question input:focus { outline: none; border-color: #283618; }
-        .retry-btn { padding: 14px 28px; font-size: 1rem; font-weight: 500; background: #283618; color: #F0EFEB; border: none; border-radius: 10px; cursor: pointer; transition: background 0.2s; }
-        .retry-btn:hover { background: #1a2410; }
-        .fallback-link { background: none; border: none; color: #606C38; font-size: 0.9rem; margin-top: 16px; cursor: pointer; text-decoration: underline; }
-        .fallback-link:hover { color: #283618; }
+        .retry-title { font-size: 1.25rem; color: var(--hd-primary); margin-bottom: 8px; }
+        .retry-subtitle { color: var(--hd-accent); margin-bottom: 24px; }
+        .retry-questions { text-align: start; margin-bottom: 24px; }
+        .retry-question { background: white; border: 1px solid var(--hd-border); border-radius: 8px; padding: 16px; margin-bottom: 12px; }
+        .retry-question label { display: block; font-weight: 500; color: var(--hd-primary); margin-bottom: 8px; }
+        .retry-question input { width: 100%; padding: 10px 12px; border: 1px solid var(--hd-border); border-radius: 6px; font-size: 1rem; }
+        .retry-question input:focus { outline: none; border-color: var(--hd-primary); }
+        .retry-btn { padding: 14px 28px; font-size: 1rem; font-weight: 500; background: var(--hd-primary); color: var(--hd-background); border: none; border-radius: 10px; cursor: pointer; transition: background 0.2s; }
+        .retry-btn:hover:not(:disabled) { background: var(--hd-primary-dark); }
+        .retry-btn:disabled { opacity: 0.6; cursor: not-allowed; }
+        .fallback-link { background: none; border: none; color: var(--hd-accent); font-size: 0.9rem; margin-top: 16px; cursor: pointer; text-decoration: underline; }
+        .fallback-link:hover { color: var(--hd-primary); }
         .error-msg { color: #9B2C2C; margin-top: 16px; }
 
         /* Passphrase Screen */
-        .passphrase-screen { position: fixed; inset: 0; background: linear-gradient(145deg, #F0EFEB 0%, #D4D4D4 100%); display: flex; flex-direction: column; align-items: center; justify-content: center; z-index: 2000; }
+        .passphrase-screen { position: fixed; inset: 0; background: linear-gradient(145deg, var(--hd-background) 0%, var(--hd-border) 100%); display: flex; flex-direction: column; align-items: center; justify-content: center; z-index: 2000; }
         .passphrase-screen.hidden { display: none; }
         .passphrase-container { max-width: 320px; text-align: center; }
-        .lock-version { color: #B7B7A4; font-size: 0.8rem; margin-top: 2rem; }
-        .passphrase-title { font-size: 1.5rem; font-weight: 600; color: #283618; margin-bottom: 2rem; }
-        .passphrase-input { width: 100%; padding: 14px 16px; font-size: 1rem; border: 2px solid #D4D4D4; border-radius: 10px; text-align: center; background: white; margin-bottom: 16px; }
-        .passphrase-input:focus { outline: none; border-color: #283618; }
-        .back-link { background: none; border: none; color: #606C38; font-size: 0.9rem; margin-top: 16px; cursor: pointer; }
-        .back-link:hover { color: #283618; text-decoration: underline; }
+        .lock-version { color: var(--hd-accent); font-size: 0.8rem; margin-top: 2rem; }
+        .passphrase-title { font-size: 1.5rem; font-weight: 600; color: var(--hd-primary); margin-bottom: 2rem; }
+        .passphrase-input { width: 100%; padding: 14px 16px; font-size: 1rem; border: 2px solid var(--hd-border); border-radius: 10px; text-align: center; background: white; margin-bottom: 16px; }
+        .passphrase-input:focus { outline: none; border-color: var(--hd-primary); }
+        .back-link { background: none; border: none; color: var(--hd-accent); font-size: 0.9rem; margin-top: 16px; cursor: pointer; }
+        .back-link:hover { color: var(--hd-primary); text-decoration: underline; }
+        .release-note { color: var(--hd-accent); font-size: 0.95rem; line-height: 1.5; margin-bottom: 1.5rem; }
 
         /* Unlocking screen */
-        .unlocking-screen { position: fixed; inset: 0; background: linear-gradient(145deg, #F0EFEB 0%, #D4D4D4 100%); display: flex; flex-direction: column; align-items: center; justify-content: center; z-index: 2000; }
+        .unlocking-screen { position: fixed; inset: 0; background: linear-gradient(145deg, var(--hd-background) 0%, var(--hd-border) 100%); display: flex; flex-direction: column; align-items: center; justify-content: center; z-index: 2000; }
         .unlocking-screen.hidden { display: none; }
         .unlocking-screen svg { animation: pulse 1.5s infinite; }
         @keyframes pulse { 0%, 100% { opacity: 1; } 50% { opacity: 0.5; } }
-        .unlocking-text { color: #283618; font-size: 1.1rem; }
+        .unlocking-text { color: var(--hd-primary); font-size: 1.1rem; }
 "##;
 
 /// JavaScript specific to question-based decryption
 const QUESTION_JS_DECRYPT: &str = r##"
-        const PBKDF2_ITERATIONS = 600000;
+        // Tries the passphrase against each wrapped copy of the document key.
+        // Copies for alternative answers share a salt, so the key is derived once per salt.
+        async function unwrapDocumentKey(wrappings, passphrase, slot) {
+            const keysBySalt = {};
+            for (const wrapped of wrappings) {
+                if (!keysBySalt[wrapped.salt]) {
+                    const salt = Uint8Array.from(atob(wrapped.salt), c => c.charCodeAt(0));
+                    keysBySalt[wrapped.salt] = await deriveKey(passphrase, salt, wrapped.kdf);
+                }
+                const nonce = Uint8Array.from(atob(wrapped.nonce), c => c.charCodeAt(0));
+                const ciphertext = Uint8Array.from(atob(wrapped.ciphertext), c => c.charCodeAt(0));
+                try {
+                    const docKeyBytes = await crypto.subtle.decrypt(
+                        aesGcmParams(nonce, slot),
+                        keysBySalt[wrapped.salt],
+                        ciphertext
+                    );
+                    return new Uint8Array(docKeyBytes);
+                } catch (err) {
+                    // Not this copy; try the next one
+                }
+            }
+            throw new Error('Incorrect answers or passphrase');
+        }
 
         async function decryptWithQuestionKey(passphrase) {
-            const qk = ENCRYPTED_DATA.question_key;
-            const salt = Uint8Array.from(atob(qk.salt), c => c.charCodeAt(0));
-            const nonce = Uint8Array.from(atob(qk.nonce), c => c.charCodeAt(0));
-            const ciphertext = Uint8Array.from(atob(qk.ciphertext), c => c.charCodeAt(0));
-
-            const key = await deriveKey(passphrase, salt);
-            const docKeyBytes = await crypto.subtle.decrypt(
-                { name: 'AES-GCM', iv: nonce },
-                key,
-                ciphertext
-            );
-
-            await decryptDocument(new Uint8Array(docKeyBytes));
+            const wrappings = [ENCRYPTED_DATA.question_key].concat(ENCRYPTED_DATA.question_key_alternates || []);
+            await finishQuestionUnlock(await unwrapDocumentKey(wrappings, passphrase, 'questions'));
         }
 
         async function decryptWithPassphraseKey(passphrase) {
-            const pk = ENCRYPTED_DATA.passphrase_key;
-            const salt = Uint8Array.from(atob(pk.salt), c => c.charCodeAt(0));
-            const nonce = Uint8Array.from(atob(pk.nonce), c => c.charCodeAt(0));
-            const ciphertext = Uint8Array.from(atob(pk.ciphertext), c => c.charCodeAt(0));
-
-            const key = await deriveKey(passphrase, salt);
-            const docKeyBytes = await crypto.subtle.decrypt(
-                { name: 'AES-GCM', iv: nonce },
-                key,
-                ciphertext
-            );
+            await decryptDocument(await unwrapDocumentKey([ENCRYPTED_DATA.passphrase_key], passphrase, 'passphrase'));
+        }
+
+        // "Any K of N" files: try each key whose questions have all been answered
+        async function decryptWithAnswerSubsets(answers) {
+            const answered = id => normalizeAnswer(answers[id] || '') !== '';
+            for (const [i, questions] of THRESHOLD.subsets.entries()) {
+                if (!questions.every(answered)) continue;
+                const subset = ENCRYPTED_DATA.threshold.subsets[i];
+                const wrappings = [subset.key].concat(subset.alternates || []);
+                let docKey;
+                try {
+                    docKey = await unwrapDocumentKey(wrappings, questionKeyPassphrase(questions.map(id => answers[id])), 'questions');
+                } catch (err) {
+                    continue;
+                }
+                await finishQuestionUnlock(docKey);
+                return;
+            }
+            throw new Error('No combination of answers unlocked the document');
+        }
+
+        // Time-locked files: the answers only recover the document key XOR the
+        // release key, which the release service hands out as a release code
+        let questionShare = null;
 
-            await decryptDocument(new Uint8Array(docKeyBytes));
+        async function finishQuestionUnlock(key) {
+            if (!LOCK_SCREEN.time_lock) {
+                await decryptDocument(key);
+                return;
+            }
+            questionShare = key;
+            showReleaseScreen();
+        }
+
+        // Crockford base32, forgiving case, spacing and look-alike letters
+        function decodeReleaseCode(code) {
+            const alphabet = '0123456789ABCDEFGHJKMNPQRSTVWXYZ';
+            const symbols = code.toUpperCase().replace(/[\s-]/g, '').replace(/O/g, '0').replace(/[IL]/g, '1');
+            if (symbols.length !== 52) return null;
+            const key = new Uint8Array(32);
+            let buffer = 0, bits = 0, index = 0;
+            for (const symbol of symbols) {
+                const value = alphabet.indexOf(symbol);
+                if (value < 0) return null;
+                buffer = (buffer << 5) | value;
+                bits += 5;
+                if (bits >= 8) {
+                    bits -= 8;
+                    key[index++] = (buffer >> bits) & 0xff;
+                }
+                buffer &= (1 << bits) - 1;
+            }
+            return buffer === 0 ? key : null;
+        }
+
+        async function decryptWithReleaseCode(code) {
+            const releaseKey = decodeReleaseCode(code);
+            if (!releaseKey || !questionShare) throw new Error('Invalid release code');
+            const digest = new Uint8Array(await crypto.subtle.digest('SHA-256', releaseKey));
+            if (btoa(String.fromCharCode(...digest.slice(0, 8))) !== LOCK_SCREEN.time_lock.check) {
+                throw new Error('Invalid release code');
+            }
+            await decryptDocument(questionShare.map((byte, i) => byte ^ releaseKey[i]));
         }
 
         async function decryptDocument(docKey) {
@@ -1947,7 +1899,7 @@ const QUESTION_JS_DECRYPT: &str = r##"
             );
 
             const decrypted = await crypto.subtle.decrypt(
-                { name: 'AES-GCM', iv: nonce },
+                aesGcmParams(nonce, 'document'),
                 cryptoKey,
                 ciphertext
             );
@@ -1956,35 +1908,53 @@ const QUESTION_JS_DECRYPT: &str = r##"
             const jsonString = decoder.decode(decrypted);
             const data = JSON.parse(jsonString);
 
+            clearFailedUnlocks();
             renderDocument(data);
             document.getElementById('unlockingScreen').classList.add('hidden');
             document.getElementById('content').classList.add('visible');
+            focusDocument();
         }
 
-        async function deriveKey(passphrase, salt) {
-            const encoder = new TextEncoder();
-            const keyMaterial = await crypto.subtle.importKey(
-                'raw', encoder.encode(passphrase), 'PBKDF2', false, ['deriveKey']
-            );
-            return await crypto.subtle.deriveKey(
-                { name: 'PBKDF2', salt: salt, iterations: PBKDF2_ITERATIONS, hash: 'SHA-256' },
-                keyMaterial,
-                { name: 'AES-GCM', length: 256 },
-                false,
-                ['decrypt']
-            );
-        }
 "##;
 
 /// JavaScript for slide navigation (question-based template)
 const QUESTION_JS_SLIDES: &str = r##"
         let currentSlide = 0;
         let answers = {};
-        let attempts = 0;
-        const MAX_ATTEMPTS = 5;
+        let attempts = unlockRecord('questions').failures;
+        const MAX_ATTEMPTS = ATTEMPT_LIMIT || 5;
+        const THRESHOLD = LOCK_SCREEN.threshold || null;
+
+        // With an attempt limit, enough wrong answers leave only the backup passphrase
+        function questionsLockedOut() {
+            return ATTEMPT_LIMIT !== null && attempts >= ATTEMPT_LIMIT;
+        }
+
+        function questionSlidesList() {
+            return SLIDES.filter(s => s.type === 'question');
+        }
+
+        function unansweredQuestions() {
+            return questionSlidesList().filter(s => normalizeAnswer(answers[s.id] || '') === '');
+        }
+
+        // How many more answers are needed before an unlock attempt makes sense
+        function answersStillNeeded() {
+            if (!THRESHOLD) return 0;
+            const answeredCount = questionSlidesList().length - unansweredQuestions().length;
+            return Math.max(0, THRESHOLD.required - answeredCount);
+        }
+
+        async function unlockWithAnswers() {
+            if (THRESHOLD) {
+                await decryptWithAnswerSubsets(answers);
+            } else {
+                await decryptWithQuestionKey(questionKeyPassphrase(questionSlidesList().map(s => answers[s.id] || '')));
+            }
+        }
 
         function initSlides() {
-            if (SLIDES.length === 0) {
+            if (SLIDES.length === 0 || questionsLockedOut()) {
                 showRetryScreen();
                 return;
             }
@@ -2013,15 +1983,23 @@ const QUESTION_JS_SLIDES: &str = r##"
             const btnEl = document.getElementById('slideBtn');
 
             textEl.textContent = slide.text;
+            document.getElementById('slideGroup').setAttribute('aria-label', t('slides.position', { current: index + 1, total: SLIDES.length }));
 
+            const hintEl = document.getElementById('slideHint');
             if (slide.type === 'question') {
                 inputEl.style.display = 'block';
                 inputEl.value = answers[slide.id] || '';
                 inputEl.focus();
-                btnEl.textContent = 'Continue';
+                btnEl.textContent = t('welcome.continue');
+                if (THRESHOLD) {
+                    hintEl.textContent = t('questions.threshold_hint', { required: THRESHOLD.required, total: questionSlidesList().length });
+                    hintEl.style.display = 'block';
+                }
             } else {
                 inputEl.style.display = 'none';
-                btnEl.textContent = 'Continue';
+                hintEl.style.display = 'none';
+                btnEl.textContent = t('welcome.continue');
+                btnEl.focus();
             }
 
             // Update progress dots
@@ -2033,22 +2011,25 @@ const QUESTION_JS_SLIDES: &str = r##"
             const slide = SLIDES[currentSlide];
             if (slide.type === 'question') {
                 const input = document.getElementById('slideInput');
-                answers[slide.id] = input.value.toLowerCase().trim();
+                answers[slide.id] = input.value;
             }
             showSlide(currentSlide + 1);
         }
 
         async function attemptUnlock() {
-            const questionSlides = SLIDES.filter(s => s.type === 'question');
-            const passphrase = questionSlides.map(s => answers[s.id] || '').join('');
-
             document.getElementById('slideScreen').classList.add('hidden');
+
+            if (answersStillNeeded() > 0 || questionsLockedOut() || unlockWaitSeconds('questions') > 0) {
+                showRetryScreen();
+                return;
+            }
+
             document.getElementById('unlockingScreen').classList.remove('hidden');
 
             try {
-                await decryptWithQuestionKey(passphrase);
+                await unlockWithAnswers();
             } catch (err) {
-                attempts++;
+                attempts = recordFailedUnlock('questions');
                 showRetryScreen();
             }
         }
@@ -2057,44 +2038,65 @@ const QUESTION_JS_SLIDES: &str = r##"
             document.getElementById('slideScreen').classList.add('hidden');
             document.getElementById('unlockingScreen').classList.add('hidden');
             document.getElementById('passphraseScreen').classList.add('hidden');
+            document.getElementById('releaseScreen').classList.add('hidden');
             document.getElementById('retryScreen').classList.remove('hidden');
 
+            // Too few answers to try yet: only ask for the ones that were skipped
+            const needed = answersStillNeeded();
+            const lockedOut = questionsLockedOut();
+            const title = document.getElementById('retryTitle');
             const counter = document.getElementById('attemptCounter');
-            if (attempts >= MAX_ATTEMPTS) {
-                counter.textContent = 'Having trouble? You can keep trying, or use the passphrase if you have it.';
+            document.getElementById('retryBtn').style.display = lockedOut ? 'none' : '';
+            if (lockedOut) {
+                title.textContent = t('questions.locked_out');
+                counter.textContent = t('questions.locked_out_note');
+                document.getElementById('retryError').style.display = 'none';
+            } else if (needed > 0) {
+                title.textContent = t('questions.more_needed');
+                counter.textContent = t(needed === 1 ? 'questions.need_one' : 'questions.need_other', { count: needed });
+            } else if (attempts >= MAX_ATTEMPTS) {
+                title.textContent = t('questions.wrong');
+                counter.textContent = t('questions.having_trouble');
             } else {
-                counter.textContent = 'Attempt ' + attempts + ' of ' + MAX_ATTEMPTS;
+                title.textContent = t('questions.wrong');
+                counter.textContent = t('questions.attempt', { attempt: attempts, max: MAX_ATTEMPTS });
             }
 
             // Build retry questions form
             const container = document.getElementById('retryQuestions');
             container.innerHTML = '';
-            const questionSlides = SLIDES.filter(s => s.type === 'question');
+            const questionSlides = lockedOut ? [] : needed > 0 ? unansweredQuestions() : questionSlidesList();
             questionSlides.forEach((slide, i) => {
                 const div = document.createElement('div');
                 div.className = 'retry-question';
-                div.innerHTML = '<label>' + escapeHtml(slide.text) + '</label><input type="text" id="retry-' + slide.id + '" value="' + escapeHtml(answers[slide.id] || '') + '">';
+                const inputId = 'retry-' + escapeAttr(slide.id);
+                div.innerHTML = '<label for="' + inputId + '">' + escapeHtml(slide.text) + '</label><input type="text" id="' + inputId + '" value="' + escapeAttr(answers[slide.id] || '') + '">';
                 container.appendChild(div);
             });
+            if (!lockedOut) showUnlockWait('questions', 'retryError', ['retryBtn']);
+            // Read out what went wrong, then let Tab continue to the answers
+            title.focus();
         }
 
         async function retryUnlock() {
-            const questionSlides = SLIDES.filter(s => s.type === 'question');
-            questionSlides.forEach(slide => {
+            questionSlidesList().forEach(slide => {
                 const input = document.getElementById('retry-' + slide.id);
-                if (input) answers[slide.id] = input.value.toLowerCase().trim();
+                if (input) answers[slide.id] = input.value;
             });
 
-            const passphrase = questionSlides.map(s => answers[s.id] || '').join('');
+            if (answersStillNeeded() > 0 || questionsLockedOut() || unlockWaitSeconds('questions') > 0) {
+                showRetryScreen();
+                return;
+            }
 
             document.getElementById('retryScreen').classList.add('hidden');
             document.getElementById('unlockingScreen').classList.remove('hidden');
 
             try {
-                await decryptWithQuestionKey(passphrase);
+                await unlockWithAnswers();
             } catch (err) {
-                attempts++;
-                document.getElementById('retryError').textContent = 'Some answers weren\'t quite right.';
+                attempts = recordFailedUnlock('questions');
+                document.getElementById('retryError').textContent = t('questions.wrong');
                 document.getElementById('retryError').style.display = 'block';
                 showRetryScreen();
             }
@@ -2102,6 +2104,7 @@ const QUESTION_JS_SLIDES: &str = r##"
 
         function showPassphraseScreen() {
             document.getElementById('retryScreen').classList.add('hidden');
+            document.getElementById('releaseScreen').classList.add('hidden');
             document.getElementById('passphraseScreen').classList.remove('hidden');
             document.getElementById('passphraseInput').focus();
         }
@@ -2109,10 +2112,11 @@ const QUESTION_JS_SLIDES: &str = r##"
         async function unlockWithPassphrase() {
             const passphrase = document.getElementById('passphraseInput').value;
             if (!passphrase) {
-                document.getElementById('passphraseError').textContent = 'Please enter a passphrase.';
+                document.getElementById('passphraseError').textContent = t('passphrase.empty');
                 document.getElementById('passphraseError').style.display = 'block';
                 return;
             }
+            if (showUnlockWait('passphrase', 'passphraseError', ['passphraseUnlockBtn'])) return;
 
             document.getElementById('passphraseScreen').classList.add('hidden');
             document.getElementById('unlockingScreen').classList.remove('hidden');
@@ -2120,10 +2124,39 @@ const QUESTION_JS_SLIDES: &str = r##"
             try {
                 await decryptWithPassphraseKey(passphrase);
             } catch (err) {
-                document.getElementById('passphraseError').textContent = 'Incorrect passphrase.';
+                recordFailedUnlock('passphrase');
+                document.getElementById('passphraseError').textContent = t('passphrase.incorrect');
                 document.getElementById('passphraseError').style.display = 'block';
                 document.getElementById('unlockingScreen').classList.add('hidden');
                 document.getElementById('passphraseScreen').classList.remove('hidden');
+                showUnlockWait('passphrase', 'passphraseError', ['passphraseUnlockBtn']);
+            }
+        }
+
+        // The answers were right; a time-locked file still needs its release code
+        function showReleaseScreen() {
+            const lock = LOCK_SCREEN.time_lock;
+            const conditions = [];
+            if (lock.release_after) conditions.push(t('release.on_date', { date: formatDate(lock.release_after) }));
+            if (lock.check_in_days) conditions.push(t('release.after_days', { days: lock.check_in_days }));
+            document.getElementById('releaseNote').textContent = t('release.note', { conditions: conditions.join(t('release.or')) });
+            document.getElementById('unlockingScreen').classList.add('hidden');
+            document.getElementById('releaseScreen').classList.remove('hidden');
+            document.getElementById('releaseInput').focus();
+        }
+
+        async function unlockWithReleaseCode() {
+            const code = document.getElementById('releaseInput').value;
+            document.getElementById('releaseScreen').classList.add('hidden');
+            document.getElementById('unlockingScreen').classList.remove('hidden');
+
+            try {
+                await decryptWithReleaseCode(code);
+            } catch (err) {
+                document.getElementById('releaseError').textContent = t('release.invalid');
+                document.getElementById('releaseError').style.display = 'block';
+                document.getElementById('unlockingScreen').classList.add('hidden');
+                document.getElementById('releaseScreen').classList.remove('hidden');
             }
         }
 
@@ -2131,9 +2164,13 @@ const QUESTION_JS_SLIDES: &str = r##"
             if (e.key === 'Enter') unlockWithPassphrase();
         });
 
-        // Allow Enter key to advance slides
+        document.getElementById('releaseInput').addEventListener('keydown', (e) => {
+            if (e.key === 'Enter') unlockWithReleaseCode();
+        });
+
+        // Allow Enter key to advance slides; buttons handle their own Enter
         document.addEventListener('keydown', (e) => {
-            if (e.key === 'Enter') {
+            if (e.key === 'Enter' && e.target.tagName !== 'BUTTON') {
                 const slideScreen = document.getElementById('slideScreen');
                 if (slideScreen && !slideScreen.classList.contains('hidden')) {
                     e.preventDefault();
@@ -2149,117 +2186,203 @@ const QUESTION_JS_SLIDES: &str = r##"
 // TEMPLATE GENERATION FUNCTIONS
 // ============================================================================
 
-fn generate_html_template(encrypted_data: &str, creator_name: &str, welcome_slides_json: &str) -> String {
+/// The Argon2id module only ships in files that need it
+fn argon2_wasm_base64(kdf: BrowserKdf) -> String {
+    if kdf.is_pbkdf2() { String::new() } else { BASE64.encode(ARGON2_WASM) }
+}
+
+/// Content-Security-Policy for an exported file: only its own inline script
+/// runs, and the page can't load fonts, send requests or submit forms. The
+/// Argon2id module needs WebAssembly compilation; PBKDF2 files don't get it.
+fn content_security_policy(script: &str, kdf: BrowserKdf) -> String {
+    let hash = BASE64.encode(digest::digest(&digest::SHA256, script.as_bytes()));
+    let wasm = if kdf.is_pbkdf2() { "" } else { " 'wasm-unsafe-eval'" };
+    format!(
+        "default-src 'none'; script-src 'sha256-{}'{}; style-src 'unsafe-inline'; img-src data: blob:; \
+         connect-src 'none'; font-src 'none'; media-src 'none'; object-src 'none'; frame-src 'none'; \
+         worker-src 'none'; base-uri 'none'; form-action 'none'",
+        hash, wasm
+    )
+}
+
+/// The encrypted data as a script literal. `<` is escaped so slide text can't close the script.
+fn encrypted_data_script(encrypted_data: &str) -> String {
+    encrypted_data.replace('<', "\\u003c")
+}
+
+fn generate_html_template(encrypted_data: &str, kdf: BrowserKdf, theme: &Theme, locale: &Locale) -> String {
+    let script = format!(
+        r##"
+        const ENCRYPTED_DATA = {encrypted_data};
+        const LOCK_SCREEN = JSON.parse(ENCRYPTED_DATA.header.lock_screen);
+        const WELCOME_SLIDES = LOCK_SCREEN.slides;
+        const ARGON2_WASM = "{argon2_wasm}";
+        const LOCALE = {locale_json};
+{SHARED_JS_UTILS}
+{SEARCH_JS_MATCHING}
+{SHARED_JS_SEARCH}
+{SHARED_JS_RENDER_DOCUMENT}
+{EXPORT_AAD_JS}
+{ARGON2_KDF_JS}
+{PASSPHRASE_JS_DECRYPT}
+{PASSPHRASE_JS_WELCOME}
+    "##,
+        encrypted_data = encrypted_data_script(encrypted_data),
+        argon2_wasm = argon2_wasm_base64(kdf),
+        locale_json = locale.script_json(),
+        SHARED_JS_UTILS = SHARED_JS_UTILS,
+        SEARCH_JS_MATCHING = SEARCH_JS_MATCHING,
+        SHARED_JS_SEARCH = SHARED_JS_SEARCH,
+        SHARED_JS_RENDER_DOCUMENT = SHARED_JS_RENDER_DOCUMENT,
+        EXPORT_AAD_JS = EXPORT_AAD_JS,
+        ARGON2_KDF_JS = ARGON2_KDF_JS,
+        PASSPHRASE_JS_DECRYPT = PASSPHRASE_JS_DECRYPT,
+        PASSPHRASE_JS_WELCOME = PASSPHRASE_JS_WELCOME,
+    );
+
     format!(
         r##"<!DOCTYPE html>
-<html lang="en">
+<html lang="{lang}" dir="{dir}">
 <head>
     <meta charset="UTF-8">
+    <meta http-equiv="Content-Security-Policy" content="{csp}">
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
-    <title>Honey Did - Legacy Document</title>
-    <link rel="preconnect" href="https://fonts.googleapis.com">
-    <link rel="preconnect" href="https://fonts.gstatic.com" crossorigin>
-    <link href="https://fonts.googleapis.com/css2?family=Inter:wght@400;500;600&display=swap" rel="stylesheet">
+    {signature_placeholder}
+    <title>{theme_title}</title>
     <style>
+{theme_css}
 {SHARED_CSS}
 {PASSPHRASE_CSS}
     </style>
 </head>
 <body>
-    <div id="welcomeScreen" class="welcome-screen hidden">
-        <div id="welcomeSlide" class="welcome-slide">
-            <div id="welcomeText" class="welcome-slide-text"></div>
-            <button id="welcomeContinue" class="welcome-continue" onclick="nextWelcomeSlide()">Continue</button>
+    <div id="welcomeScreen" class="welcome-screen hidden" role="region" aria-roledescription="carousel" aria-label="{slides_label}">
+        <div id="welcomeSlide" class="welcome-slide" role="group" aria-roledescription="slide">
+            <div id="welcomeText" class="welcome-slide-text" aria-live="polite"></div>
+            <button type="button" id="welcomeContinue" class="welcome-continue" data-click="nextWelcomeSlide">{continue_label}</button>
         </div>
-        <div id="welcomeProgress" class="welcome-progress"></div>
-        <div id="welcomeTimer" class="welcome-timer"><div id="welcomeTimerBar" class="welcome-timer-bar"></div></div>
+        <button type="button" id="welcomePause" class="welcome-pause" data-click="toggleWelcomePause" aria-controls="welcomeSlide" hidden>{pause_label}</button>
+        <div id="welcomeProgress" class="welcome-progress" aria-hidden="true"></div>
+        <div id="welcomeTimer" class="welcome-timer" aria-hidden="true"><div id="welcomeTimerBar" class="welcome-timer-bar"></div></div>
     </div>
     <div id="lockScreen" class="lock-screen" style="display: none;">
         {logo_svg}
-        <h1 class="lock-title">Honey Did</h1>
-        <p class="lock-subtitle">This document was prepared by {creator_name}<br>to help you in their absence.</p>
-        <form class="password-form" onsubmit="return unlock(event)">
-            <input type="password" id="passphrase" class="password-input" placeholder="Enter passphrase" autofocus>
-            <button type="submit" class="unlock-btn">Unlock</button>
+        <h1 class="lock-title">{theme_name}</h1>
+        <p id="lockMessage" class="lock-subtitle"></p>
+        <form class="password-form" data-submit="unlock">
+            <label for="passphrase" class="visually-hidden">{passphrase_placeholder}</label>
+            <input type="password" id="passphrase" class="password-input" placeholder="{passphrase_placeholder}" autofocus>
+            <button type="submit" id="unlockBtn" class="unlock-btn">{unlock_label}</button>
         </form>
-        <p id="error" class="error" style="display: none;"></p>
+        <p id="error" class="error" role="alert" style="display: none;"></p>
         <p class="lock-version">v{app_version}</p>
     </div>
+    <p id="signatureNote" class="signature-note" hidden></p>
     <div id="content" class="content">
         <div class="container" id="documentContent"></div>
     </div>
-    <script>
-        const LOGO_DATA_URI = "{logo_data_uri}";
-        const ENCRYPTED_DATA = {encrypted_data};
-        const WELCOME_SLIDES = {welcome_slides_json};
-{SHARED_JS_UTILS}
-{SHARED_JS_SEARCH}
-{SHARED_JS_RENDER_DOCUMENT}
-{PASSPHRASE_JS_DECRYPT}
-{PASSPHRASE_JS_WELCOME}
-    </script>
+    <script>{script}</script>
 </body>
 </html>"##,
+        csp = content_security_policy(&script, kdf),
+        signature_placeholder = SIGNATURE_PLACEHOLDER,
         SHARED_CSS = SHARED_CSS,
         PASSPHRASE_CSS = PASSPHRASE_CSS,
-        logo_svg = logo_img_tag("lock-logo", 72),
-        logo_data_uri = format!("data:image/png;base64,{}", BASE64.encode(LOGO_PNG_BYTES)),
+        logo_svg = theme.logo_img_tag("lock-logo", 72),
+        theme_name = theme.escaped_name(),
+        theme_title = theme.escaped_title(locale),
+        theme_css = theme.css_variables(),
+        lang = locale.code,
+        dir = locale.dir(),
+        continue_label = locale.html("welcome.continue", &[]),
+        slides_label = locale.html("slides.label", &[]),
+        pause_label = locale.html("slides.pause", &[]),
+        passphrase_placeholder = locale.html("lock.placeholder", &[]),
+        unlock_label = locale.html("lock.unlock", &[]),
         app_version = env!("CARGO_PKG_VERSION"),
-        creator_name = creator_name,
-        encrypted_data = encrypted_data,
-        welcome_slides_json = welcome_slides_json,
-        SHARED_JS_UTILS = SHARED_JS_UTILS,
-        SHARED_JS_SEARCH = SHARED_JS_SEARCH,
-        SHARED_JS_RENDER_DOCUMENT = SHARED_JS_RENDER_DOCUMENT,
-        PASSPHRASE_JS_DECRYPT = PASSPHRASE_JS_DECRYPT,
-        PASSPHRASE_JS_WELCOME = PASSPHRASE_JS_WELCOME,
+        script = script,
     )
 }
 
-fn generate_question_html_template(encrypted_data: &str, slides_json: &str, has_passphrase_fallback: bool) -> String {
+fn generate_question_html_template(encrypted_data: &str, has_passphrase_fallback: bool, kdf: BrowserKdf, theme: &Theme, locale: &Locale) -> String {
     let fallback_link = if has_passphrase_fallback {
-        r#"<button class="fallback-link" onclick="showPassphraseScreen()">I have the passphrase instead</button>"#
+        format!(r#"<button type="button" class="fallback-link" data-click="showPassphraseScreen">{}</button>"#, locale.html("questions.use_passphrase", &[]))
     } else {
-        ""
+        String::new()
     };
 
+    let script = format!(
+        r##"
+        const ENCRYPTED_DATA = {encrypted_data};
+        const LOCK_SCREEN = JSON.parse(ENCRYPTED_DATA.header.lock_screen);
+        const SLIDES = LOCK_SCREEN.slides;
+        const HAS_PASSPHRASE = {has_passphrase};
+        const ATTEMPT_LIMIT = LOCK_SCREEN.attempt_limit || null;
+        const ARGON2_WASM = "{argon2_wasm}";
+        const LOCALE = {locale_json};
+{SHARED_JS_UTILS}
+{SEARCH_JS_MATCHING}
+{SHARED_JS_SEARCH}
+{SHARED_JS_RENDER_DOCUMENT}
+{QUESTION_JS_NORMALIZE}
+{EXPORT_AAD_JS}
+{ARGON2_KDF_JS}
+{QUESTION_JS_DECRYPT}
+{QUESTION_JS_SLIDES}
+    "##,
+        encrypted_data = encrypted_data_script(encrypted_data),
+        has_passphrase = has_passphrase_fallback,
+        argon2_wasm = argon2_wasm_base64(kdf),
+        locale_json = locale.script_json(),
+        SHARED_JS_UTILS = SHARED_JS_UTILS,
+        SEARCH_JS_MATCHING = SEARCH_JS_MATCHING,
+        SHARED_JS_SEARCH = SHARED_JS_SEARCH,
+        SHARED_JS_RENDER_DOCUMENT = SHARED_JS_RENDER_DOCUMENT,
+        QUESTION_JS_NORMALIZE = QUESTION_JS_NORMALIZE,
+        EXPORT_AAD_JS = EXPORT_AAD_JS,
+        ARGON2_KDF_JS = ARGON2_KDF_JS,
+        QUESTION_JS_DECRYPT = QUESTION_JS_DECRYPT,
+        QUESTION_JS_SLIDES = QUESTION_JS_SLIDES,
+    );
+
     format!(
         r##"<!DOCTYPE html>
-<html lang="en">
+<html lang="{lang}" dir="{dir}">
 <head>
     <meta charset="UTF-8">
+    <meta http-equiv="Content-Security-Policy" content="{csp}">
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
-    <title>Honey Did - Legacy Document</title>
-    <link rel="preconnect" href="https://fonts.googleapis.com">
-    <link rel="preconnect" href="https://fonts.gstatic.com" crossorigin>
-    <link href="https://fonts.googleapis.com/css2?family=Inter:wght@400;500;600&display=swap" rel="stylesheet">
+    {signature_placeholder}
+    <title>{theme_title}</title>
     <style>
+{theme_css}
 {SHARED_CSS}
 {QUESTION_CSS}
     </style>
 </head>
 <body>
-    <div id="slideScreen" class="slide-screen">
-        <div class="slide-container">
-            <div id="slideText" class="slide-text"></div>
-            <input type="text" id="slideInput" class="slide-input" style="display: none;" placeholder="Type your answer...">
-            <button id="slideBtn" class="slide-btn" onclick="nextSlide()">Continue</button>
+    <div id="slideScreen" class="slide-screen" role="region" aria-roledescription="carousel" aria-label="{slides_label}">
+        <div id="slideGroup" class="slide-container" role="group" aria-roledescription="slide">
+            <div id="slideText" class="slide-text" aria-live="polite"></div>
+            <input type="text" id="slideInput" class="slide-input" style="display: none;" placeholder="{answer_placeholder}" aria-labelledby="slideText" aria-describedby="slideHint">
+            <p id="slideHint" class="slide-hint" style="display: none;"></p>
+            <button type="button" id="slideBtn" class="slide-btn" data-click="nextSlide">{continue_label}</button>
         </div>
-        <div id="slideProgress" class="slide-progress"></div>
+        <div id="slideProgress" class="slide-progress" aria-hidden="true"></div>
     </div>
 
-    <div id="unlockingScreen" class="unlocking-screen hidden">
+    <div id="unlockingScreen" class="unlocking-screen hidden" role="status">
         {logo_svg}
-        <div class="unlocking-text">Unlocking...</div>
+        <div class="unlocking-text">{unlocking}</div>
     </div>
 
     <div id="retryScreen" class="retry-screen hidden">
         <div class="retry-container">
-            <h2 class="retry-title">Some answers weren't quite right.</h2>
-            <p class="retry-subtitle">Please try again. <span id="attemptCounter"></span></p>
+            <h2 id="retryTitle" class="retry-title" tabindex="-1" aria-describedby="retrySubtitle">{wrong_answers}</h2>
+            <p id="retrySubtitle" class="retry-subtitle">{try_again_note} <span id="attemptCounter"></span></p>
             <div id="retryQuestions" class="retry-questions"></div>
-            <button class="retry-btn" onclick="retryUnlock()">Try Again</button>
-            <p id="retryError" class="error-msg" style="display: none;"></p>
+            <button type="button" id="retryBtn" class="retry-btn" data-click="retryUnlock">{try_again}</button>
+            <p id="retryError" class="error-msg" role="alert" style="display: none;"></p>
             {fallback_link}
         </div>
     </div>
@@ -2267,46 +2390,60 @@ fn generate_question_html_template(encrypted_data: &str, slides_json: &str, has_
     <div id="passphraseScreen" class="passphrase-screen hidden">
         <div class="passphrase-container">
             {logo_svg}
-            <h2 class="passphrase-title">Enter passphrase</h2>
-            <input type="password" id="passphraseInput" class="passphrase-input" placeholder="Enter passphrase">
-            <button class="retry-btn" onclick="unlockWithPassphrase()">Unlock</button>
-            <p id="passphraseError" class="error-msg" style="display: none;"></p>
-            <button class="back-link" onclick="showRetryScreen()">&#8592; Back to questions</button>
+            <h2 id="passphraseTitle" class="passphrase-title">{passphrase_title}</h2>
+            <input type="password" id="passphraseInput" class="passphrase-input" placeholder="{passphrase_placeholder}" aria-labelledby="passphraseTitle">
+            <button type="button" id="passphraseUnlockBtn" class="retry-btn" data-click="unlockWithPassphrase">{unlock_label}</button>
+            <p id="passphraseError" class="error-msg" role="alert" style="display: none;"></p>
+            <button type="button" class="back-link" data-click="showRetryScreen">{back_label}</button>
             <p class="lock-version">v{app_version}</p>
         </div>
     </div>
 
+    <div id="releaseScreen" class="passphrase-screen hidden">
+        <div class="passphrase-container">
+            {logo_svg}
+            <h2 id="releaseTitle" class="passphrase-title">{release_title}</h2>
+            <p id="releaseNote" class="release-note"></p>
+            <input type="text" id="releaseInput" class="passphrase-input" placeholder="XXXX-XXXX-..." autocomplete="off" spellcheck="false" aria-labelledby="releaseTitle" aria-describedby="releaseNote">
+            <button type="button" class="retry-btn" data-click="unlockWithReleaseCode">{unlock_label}</button>
+            <p id="releaseError" class="error-msg" role="alert" style="display: none;"></p>
+            {fallback_link}
+        </div>
+    </div>
+
+    <p id="signatureNote" class="signature-note" hidden></p>
+
     <div id="content" class="content">
         <div class="container" id="documentContent"></div>
     </div>
 
-    <script>
-        const LOGO_DATA_URI = "{logo_data_uri}";
-        const ENCRYPTED_DATA = {encrypted_data};
-        const SLIDES = {slides_json};
-        const HAS_PASSPHRASE = {has_passphrase};
-{SHARED_JS_UTILS}
-{SHARED_JS_SEARCH}
-{SHARED_JS_RENDER_DOCUMENT}
-{QUESTION_JS_DECRYPT}
-{QUESTION_JS_SLIDES}
-    </script>
+    <script>{script}</script>
 </body>
 </html>"##,
+        csp = content_security_policy(&script, kdf),
+        signature_placeholder = SIGNATURE_PLACEHOLDER,
         SHARED_CSS = SHARED_CSS,
         QUESTION_CSS = QUESTION_CSS,
-        logo_svg = logo_img_tag("lock-logo", 72),
-        logo_data_uri = format!("data:image/png;base64,{}", BASE64.encode(LOGO_PNG_BYTES)),
+        logo_svg = theme.logo_img_tag("lock-logo", 72),
+        theme_title = theme.escaped_title(locale),
+        theme_css = theme.css_variables(),
+        lang = locale.code,
+        dir = locale.dir(),
+        answer_placeholder = locale.html("questions.placeholder", &[]),
+        slides_label = locale.html("slides.label", &[]),
+        continue_label = locale.html("welcome.continue", &[]),
+        unlocking = locale.html("questions.unlocking", &[]),
+        wrong_answers = locale.html("questions.wrong", &[]),
+        try_again_note = locale.html("questions.try_again_note", &[]),
+        try_again = locale.html("questions.try_again", &[]),
+        passphrase_title = locale.html("passphrase.title", &[]),
+        passphrase_placeholder = locale.html("lock.placeholder", &[]),
+        unlock_label = locale.html("lock.unlock", &[]),
+        back_label = locale.html("questions.back", &[]),
+        release_title = locale.html("release.title", &[]),
         app_version = env!("CARGO_PKG_VERSION"),
         fallback_link = fallback_link,
-        encrypted_data = encrypted_data,
-        slides_json = slides_json,
-        has_passphrase = has_passphrase_fallback,
-        SHARED_JS_UTILS = SHARED_JS_UTILS,
-        SHARED_JS_SEARCH = SHARED_JS_SEARCH,
-        SHARED_JS_RENDER_DOCUMENT = SHARED_JS_RENDER_DOCUMENT,
-        QUESTION_JS_DECRYPT = QUESTION_JS_DECRYPT,
-        QUESTION_JS_SLIDES = QUESTION_JS_SLIDES,
+        script = script,
     )
 }
 
@@ -2314,148 +2451,522 @@ fn generate_question_html_template(encrypted_data: &str, slides_json: &str, has_
 // PRINT HTML GENERATION (for non-encrypted output)
 // ============================================================================
 
-/// Generates a printable HTML version of the document (not encrypted)
-pub fn generate_print_html(document: &LegacyDocument) -> String {
-    let mut html = String::from(r#"<!DOCTYPE html>
-<html lang="en">
-<head>
-    <meta charset="UTF-8">
-    <meta name="viewport" content="width=device-width, initial-scale=1.0">
-    <title>Honey Did - Legacy Document</title>
-    <style>
-        * { box-sizing: border-box; margin: 0; padding: 0; }
-        body { font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif; line-height: 1.6; background: #fff; color: #333; max-width: 800px; margin: 0 auto; padding: 40px 20px; }
-        h1 { font-size: 1.75rem; margin-bottom: 0.5rem; color: #283618; }
-        h2 { font-size: 1.25rem; margin: 2rem 0 1rem 0; padding-bottom: 0.5rem; border-bottom: 2px solid #283618; color: #283618; }
-        h3 { font-size: 1rem; margin: 1.5rem 0 0.75rem 0; color: #606C38; }
-        .subtitle { color: #606C38; margin-bottom: 2rem; }
-        .item { background: #f5f5f5; padding: 16px; border-radius: 8px; margin-bottom: 12px; }
-        .item-title { font-weight: 600; margin-bottom: 0.5rem; }
-        .item-detail { color: #666; font-size: 0.9rem; }
-        .notes { background: #f9f9f9; padding: 12px; border-radius: 8px; margin-top: 1rem; font-style: italic; border-left: 3px solid #ccc; }
-        @media print { body { padding: 0; } }
-    </style>
-</head>
-<body>
-    <h1>Honey Did - Legacy Document</h1>
-"#);
-
-    html.push_str(&format!("<p class=\"subtitle\">Prepared by {}</p>\n", document.meta.creator_name));
-
-    // Financial
-    if !document.financial.bank_accounts.is_empty() || !document.financial.credit_cards.is_empty()
-        || !document.financial.investments.is_empty() || !document.financial.debts.is_empty()
-        || !document.financial.notes.is_empty() {
-        html.push_str("<h2>💰 Financial Information</h2>\n");
-
-        if !document.financial.bank_accounts.is_empty() {
-            html.push_str("<h3>Bank Accounts</h3>\n");
-            for account in &document.financial.bank_accounts {
-                html.push_str(&format!(
-                    "<div class=\"item\"><div class=\"item-title\">{}</div><div class=\"item-detail\">Institution: {}</div><div class=\"item-detail\">Type: {}</div></div>\n",
-                    account.name, account.institution, account.account_type
-                ));
+/// Generates a printable HTML version of the document (not encrypted), from the
+/// same section templates as the exported viewer
+pub fn generate_print_html(document: &LegacyDocument, theme: &Theme, locale: &Locale) -> Result<String, ExportError> {
+    Ok(render::render_print(document, theme, locale)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The text between the template's only <script> tags
+    fn inline_script(html: &str) -> &str {
+        let start = html.find("<script>").unwrap() + "<script>".len();
+        let end = html.rfind("</script>").unwrap();
+        &html[start..end]
+    }
+
+    fn policy_hash(html: &str) -> &str {
+        let start = html.find("'sha256-").unwrap() + "'sha256-".len();
+        let len = html[start..].find('\'').unwrap();
+        &html[start..start + len]
+    }
+
+    /// Markup outside <script> and <style>, so strings built by the viewer's JS aren't checked as tags
+    fn markup(html: &str) -> String {
+        regex::Regex::new(r"(?s)<(script|style)[^>]*>.*?</(script|style)>").unwrap().replace_all(html, "").into_owned()
+    }
+
+    /// WCAG basics a screen reader or keyboard user would trip over, one line per problem
+    fn accessibility_problems(html: &str) -> Vec<String> {
+        let html = markup(html);
+        let tag = regex::Regex::new(r"<([a-z][a-z0-9]*)\b([^>]*)>").unwrap();
+        let attr = regex::Regex::new(r#"([a-z:-]+)(?:="([^"]*)")?"#).unwrap();
+        let label_for = regex::Regex::new(r#"<label[^>]*\bfor="([^"]+)""#).unwrap();
+        let any_tag = regex::Regex::new(r"<[^>]*>").unwrap();
+        let labelled: Vec<&str> = label_for.captures_iter(&html).map(|c| c.get(1).unwrap().as_str()).collect();
+
+        let mut ids = std::collections::HashSet::new();
+        let mut references = Vec::new();
+        let mut problems = Vec::new();
+        for found in tag.captures_iter(&html) {
+            let name = &found[1];
+            let attrs: std::collections::HashMap<&str, &str> = attr.captures_iter(found.get(2).unwrap().as_str())
+                .map(|a| (a.get(1).unwrap().as_str(), a.get(2).map_or("", |v| v.as_str())))
+                .collect();
+            if let Some(id) = attrs.get("id") {
+                if !ids.insert(id.to_string()) {
+                    problems.push(format!("duplicate id {}", id));
+                }
+            }
+            for key in ["aria-labelledby", "aria-describedby", "aria-controls"] {
+                if let Some(targets) = attrs.get(key) {
+                    references.extend(targets.split_whitespace().map(|t| (key, t.to_string())));
+                }
+            }
+            let hidden = attrs.get("aria-hidden") == Some(&"true");
+            match name {
+                "img" if !attrs.contains_key("alt") => problems.push("img without alt".into()),
+                "input" if attrs.get("type") != Some(&"hidden")
+                    && !attrs.contains_key("aria-label")
+                    && !attrs.contains_key("aria-labelledby")
+                    && !attrs.get("id").is_some_and(|id| labelled.contains(id)) =>
+                {
+                    problems.push(format!("unlabelled input {}", &found[0]));
+                }
+                "button" => {
+                    let rest = &html[found.get(0).unwrap().end()..];
+                    let inner = &rest[..rest.find("</button>").unwrap_or(0)];
+                    let text = any_tag.replace_all(inner, "");
+                    if text.trim().is_empty() && !attrs.contains_key("aria-label") {
+                        problems.push(format!("button without a name {}", &found[0]));
+                    }
+                }
+                _ => {}
+            }
+            if attrs.contains_key("data-click") && !matches!(name, "button" | "a") && !hidden {
+                problems.push(format!("click handler on a <{}> the keyboard can't reach", name));
             }
         }
-
-        if !document.financial.notes.is_empty() {
-            html.push_str(&format!("<div class=\"notes\">{}</div>\n", document.financial.notes));
+        for (key, target) in references {
+            if !ids.contains(&target) {
+                problems.push(format!("{} points at missing #{}", key, target));
+            }
         }
+        problems
     }
 
-    // Insurance
-    if !document.insurance.policies.is_empty() || !document.insurance.notes.is_empty() {
-        html.push_str("<h2>🛡️ Insurance</h2>\n");
-        for policy in &document.insurance.policies {
-            html.push_str(&format!(
-                "<div class=\"item\"><div class=\"item-title\">{}</div><div class=\"item-detail\">Provider: {}</div><div class=\"item-detail\">Policy #: {}</div></div>\n",
-                policy.policy_type, policy.provider, policy.policy_number
-            ));
-        }
-        if !document.insurance.notes.is_empty() {
-            html.push_str(&format!("<div class=\"notes\">{}</div>\n", document.insurance.notes));
+    /// WCAG relative-luminance contrast ratio of two #RRGGBB colors
+    fn contrast_ratio(a: &str, b: &str) -> f64 {
+        let luminance = |hex: &str| {
+            let channel = |i: usize| {
+                let c = u8::from_str_radix(&hex[i..i + 2], 16).unwrap() as f64 / 255.0;
+                if c <= 0.03928 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+            };
+            0.2126 * channel(1) + 0.7152 * channel(3) + 0.0722 * channel(5)
+        };
+        let (x, y) = (luminance(a), luminance(b));
+        (x.max(y) + 0.05) / (x.min(y) + 0.05)
+    }
+
+    #[test]
+    fn test_export_is_offline_and_csp_locked() {
+        let html = generate_encrypted_html(&LegacyDocument::default(), "correct horse battery", false, BrowserKdf::Pbkdf2, &Theme::default(), &Locale::default()).unwrap();
+        assert_eq!(html.matches("<script>").count(), 1);
+        let expected = BASE64.encode(digest::digest(&digest::SHA256, inline_script(&html).as_bytes()));
+        assert_eq!(policy_hash(&html), expected);
+        assert!(html.contains("connect-src 'none'"));
+        assert!(!html.contains("wasm-unsafe-eval"));
+        assert!(!html.contains("http://") && !html.contains("https://"));
+        assert!(!html.contains(" onclick=") && !html.contains(" onsubmit=") && !html.contains(" oninput="));
+    }
+
+    #[test]
+    fn test_argon2_policy_allows_wasm_only() {
+        let policy = content_security_policy("1 + 1", BrowserKdf::for_export(true));
+        assert!(policy.contains("'wasm-unsafe-eval'"));
+        let script_src = policy.split(';').find(|d| d.trim().starts_with("script-src")).unwrap();
+        assert!(!script_src.contains("unsafe-inline"));
+        assert!(policy.starts_with("default-src 'none';"));
+    }
+
+    #[test]
+    fn test_payload_carries_search_index() {
+        let mut document = LegacyDocument::default();
+        document.financial.notes = "Mortgage payments go through Chase".into();
+        let payload: serde_json::Value = serde_json::from_str(&serialize_export_payload(&document, &Theme::default(), &Locale::default()).unwrap()).unwrap();
+        let index = &payload["export_extras"]["search_index"];
+        assert_eq!(index["version"], crate::search::INDEX_VERSION);
+        let words: Vec<&str> = index["words"].as_array().unwrap().iter().map(|w| w.as_str().unwrap()).collect();
+        assert!(words.contains(&"payments") && words.contains(&"chase"));
+        assert_eq!(index["stems"].as_array().unwrap().len(), words.len());
+        // Import ignores the extras
+        assert_eq!(serde_json::from_value::<LegacyDocument>(payload).unwrap().financial.notes, document.financial.notes);
+    }
+
+    #[test]
+    fn test_theme_brands_lock_screen_and_payload() {
+        let theme = Theme {
+            name: "Smith & Co.".into(),
+            lock_message: Some("Call our office before opening".into()),
+            footer: "Call 555-0199".into(),
+            ..Theme::default()
+        };
+        let mut document = LegacyDocument::default();
+        document.pets.notes = "Feed the cat".into();
+        let html = generate_encrypted_html(&document, "correct horse battery", false, BrowserKdf::Pbkdf2, &theme, &Locale::default()).unwrap();
+        assert!(html.contains("<title>Smith &amp; Co. - Legacy Document</title>"));
+        assert!(html.contains(r#"<h1 class="lock-title">Smith &amp; Co.</h1>"#));
+        assert!(html.contains(r#"<p id="lockMessage" class="lock-subtitle"></p>"#));
+        assert!(inline_script(&html).contains(r#"{\"message\":\"Call our office before opening\","#));
+        assert!(html.contains("--hd-primary: #283618;"));
+
+        let payload: serde_json::Value = serde_json::from_str(&serialize_export_payload(&document, &theme, &Locale::default()).unwrap()).unwrap();
+        let rendered = &payload["export_extras"]["rendered"];
+        assert!(rendered["page"].as_str().unwrap().contains("Feed the cat"));
+        assert!(rendered["page"].as_str().unwrap().contains("Call 555-0199"));
+
+        let invalid = Theme { colors: crate::render::ThemeColors { primary: "red".into(), ..Default::default() }, ..Theme::default() };
+        assert!(generate_encrypted_html(&document, "correct horse battery", false, BrowserKdf::Pbkdf2, &invalid, &Locale::default()).is_err());
+    }
+
+    #[test]
+    fn test_export_in_another_language() {
+        let mut document = LegacyDocument::default();
+        document.meta.creator_name = "Ana".into();
+        let spanish = Locale::load("es").unwrap();
+        let html = generate_encrypted_html(&document, "correct horse battery", false, BrowserKdf::Pbkdf2, &Theme::default(), &spanish).unwrap();
+        assert!(html.contains(r#"<html lang="es" dir="ltr">"#));
+        assert!(html.contains("<title>Honey Did - Documento de legado</title>"));
+        assert!(html.contains(r"Este documento lo preparó Ana\u003cbr>para ayudarte en su ausencia."));
+        assert!(html.contains(r#"placeholder="Escribe la frase de contraseña""#));
+        assert!(inline_script(&html).contains(r#""lock.incorrect":"Frase de contraseña incorrecta. Inténtalo de nuevo.""#));
+        assert_eq!(policy_hash(&html), BASE64.encode(digest::digest(&digest::SHA256, inline_script(&html).as_bytes())));
+
+        let arabic = Locale::load("ar").unwrap();
+        let html = generate_encrypted_html(&document, "correct horse battery", false, BrowserKdf::Pbkdf2, &Theme::default(), &arabic).unwrap();
+        assert!(html.contains(r#"<html lang="ar" dir="rtl">"#));
+        assert!(html.contains(r#"[dir="rtl"] .sidebar"#));
+        assert!(!SHARED_CSS.contains("margin-left") && !SHARED_CSS.contains("border-left"));
+    }
+
+    #[test]
+    fn test_question_attempt_limit_needs_backup_passphrase() {
+        let welcome = crate::models::WelcomeScreen {
+            enabled: true,
+            slides: ["Rex", "Elm"].iter().enumerate().map(|(i, answer)| crate::models::MessageSlide {
+                id: format!("q{}", i),
+                slide_type: SlideType::Question,
+                answer: Some(answer.to_string()),
+                ..Default::default()
+            }).collect(),
+            attempt_limit: Some(3),
+            ..Default::default()
+        };
+        let mut document = LegacyDocument { welcome_screen: Some(welcome), ..Default::default() };
+        let export = |document: &LegacyDocument, passphrase: &str| {
+            generate_encrypted_html_with_questions(document, passphrase, true, BrowserKdf::Pbkdf2, &Theme::default(), &Locale::default(), None)
+        };
+        assert!(export(&document, "").is_err());
+        let html = export(&document, "correct horse battery").unwrap();
+        let data: serde_json::Value = serde_json::from_str(&extract_json_from_html(&html, "const ENCRYPTED_DATA = ").unwrap()).unwrap();
+        assert_eq!(lock_screen(&data)["attempt_limit"], 3);
+
+        document.welcome_screen.as_mut().unwrap().attempt_limit = Some(0);
+        assert!(export(&document, "correct horse battery").is_err());
+
+        document.welcome_screen.as_mut().unwrap().attempt_limit = None;
+        let unlimited = export(&document, "correct horse battery").unwrap();
+        let data: serde_json::Value = serde_json::from_str(&extract_json_from_html(&unlimited, "const ENCRYPTED_DATA = ").unwrap()).unwrap();
+        assert!(lock_screen(&data).get("attempt_limit").is_none());
+    }
+
+    /// A document whose welcome screen asks one question per answer, each with its alternatives
+    fn question_document(answers: &[(&str, &[&str])], required: Option<usize>) -> LegacyDocument {
+        let mut document = LegacyDocument::default();
+        document.pets.notes = "Feed the cat".into();
+        document.welcome_screen = Some(crate::models::WelcomeScreen {
+            enabled: true,
+            slides: answers.iter().enumerate().map(|(i, (answer, alternates))| crate::models::MessageSlide {
+                id: format!("q{}", i),
+                slide_type: SlideType::Question,
+                answer: Some(answer.to_string()),
+                alternate_answers: alternates.iter().map(|a| a.to_string()).collect(),
+                ..Default::default()
+            }).collect(),
+            required_answers: required,
+            ..Default::default()
+        });
+        document
+    }
+
+    /// The ENCRYPTED_DATA of a question export
+    fn question_export_data(document: &LegacyDocument, time_lock: Option<&TimeLock>) -> serde_json::Value {
+        let html = generate_encrypted_html_with_questions(document, "correct horse battery", true, BrowserKdf::Pbkdf2, &Theme::default(), &Locale::default(), time_lock).unwrap();
+        serde_json::from_str(&extract_json_from_html(&html, "const ENCRYPTED_DATA = ").unwrap()).unwrap()
+    }
+
+    /// The lock screen a file's header carries
+    fn lock_screen(data: &serde_json::Value) -> serde_json::Value {
+        serde_json::from_str(data["header"]["lock_screen"].as_str().unwrap()).unwrap()
+    }
+
+    /// Unwraps one of the question key wrappings with the given answers, the way the page does
+    fn unwrap_with_answers(data: &serde_json::Value, wrapping: &serde_json::Value, answers: &[&str]) -> Result<[u8; 32], EncryptionError> {
+        let header: ExportHeader = serde_json::from_value(data["header"].clone()).unwrap();
+        let payload: EncryptedPayload = serde_json::from_value(wrapping.clone()).unwrap();
+        decrypt_key_with_passphrase(&payload, &question_key_passphrase(answers), header.associated_data(SLOT_QUESTIONS).as_bytes())
+    }
+
+    fn open_with_key(data: &serde_json::Value, doc_key: &[u8; 32]) -> Result<LegacyDocument, ExportError> {
+        let header: ExportHeader = serde_json::from_value(data["header"].clone()).unwrap();
+        let document = &data["document"];
+        let json = decrypt_with_raw_key(
+            document["nonce"].as_str().unwrap(),
+            document["ciphertext"].as_str().unwrap(),
+            doc_key,
+            header.associated_data(SLOT_DOCUMENT).as_bytes(),
+        )?;
+        Ok(serde_json::from_str(&json).unwrap())
+    }
+
+    #[test]
+    fn test_every_threshold_subset_opens_the_document() {
+        let answers = ["Rex", "Elm", "Blue"];
+        let document = question_document(&[("Rex", &[]), ("Elm", &[]), ("Blue", &[])], Some(2));
+        let data = question_export_data(&document, None);
+
+        let doc_key = unwrap_with_answers(&data, &data["question_key"], &answers).unwrap();
+        assert_eq!(open_with_key(&data, &doc_key).unwrap().pets.notes, "Feed the cat");
+
+        let subsets = data["threshold"]["subsets"].as_array().unwrap();
+        let threshold = &lock_screen(&data)["threshold"];
+        assert_eq!(threshold["required"], 2);
+        assert_eq!(subsets.len(), 3);
+        for (subset, questions) in subsets.iter().zip(threshold["subsets"].as_array().unwrap()) {
+            let ids: Vec<usize> = questions.as_array().unwrap().iter()
+                .map(|id| id.as_str().unwrap()[1..].parse().unwrap())
+                .collect();
+            let subset_answers: Vec<&str> = ids.iter().map(|&i| answers[i]).collect();
+            let key = unwrap_with_answers(&data, &subset["key"], &subset_answers).unwrap();
+            assert_eq!(key, doc_key, "subset {:?}", ids);
+
+            // The answers to a different pair don't open this subset's key
+            let other: Vec<&str> = (0..answers.len()).filter(|i| !ids.contains(i)).map(|i| answers[i]).chain([subset_answers[0]]).collect();
+            assert!(unwrap_with_answers(&data, &subset["key"], &other).is_err(), "subset {:?}", ids);
         }
     }
 
-    // Bills
-    if !document.bills.bills.is_empty() || !document.bills.notes.is_empty() {
-        html.push_str("<h2>📄 Bills</h2>\n");
-        for bill in &document.bills.bills {
-            html.push_str(&format!(
-                "<div class=\"item\"><div class=\"item-title\">{}</div><div class=\"item-detail\">Provider: {}</div><div class=\"item-detail\">Amount: {}</div><div class=\"item-detail\">Due Day: {}</div></div>\n",
-                bill.name, bill.provider, bill.amount, bill.due_day
-            ));
+    #[test]
+    fn test_every_alternate_answer_opens_the_document() {
+        // All questions required: one wrapping per combination, main answers first
+        let document = question_document(&[("Rex", &["Rexy"]), ("Elm", &["Elm Street", "Elm St"])], None);
+        let data = question_export_data(&document, None);
+        let alternates = data["question_key_alternates"].as_array().unwrap();
+        assert_eq!(alternates.len(), 5);
+        let doc_key = unwrap_with_answers(&data, &data["question_key"], &["Rex", "Elm"]).unwrap();
+        assert_eq!(open_with_key(&data, &doc_key).unwrap().pets.notes, "Feed the cat");
+        let combinations = [["Rex", "Elm Street"], ["Rex", "Elm St"], ["Rexy", "Elm"], ["Rexy", "Elm Street"], ["Rexy", "Elm St"]];
+        for (wrapping, answers) in alternates.iter().zip(combinations) {
+            assert_eq!(unwrap_with_answers(&data, wrapping, &answers).unwrap(), doc_key, "{:?}", answers);
         }
-        if !document.bills.notes.is_empty() {
-            html.push_str(&format!("<div class=\"notes\">{}</div>\n", document.bills.notes));
+        assert!(data.get("threshold").is_none());
+
+        // Any 2 of 3: alternatives are wrapped per subset instead
+        let document = question_document(&[("Rex", &["Rexy"]), ("Elm", &[]), ("Blue", &[])], Some(2));
+        let data = question_export_data(&document, None);
+        assert!(data.get("question_key_alternates").is_none());
+        let doc_key = unwrap_with_answers(&data, &data["question_key"], &["Rex", "Elm", "Blue"]).unwrap();
+        let subsets = data["threshold"]["subsets"].as_array().unwrap();
+        let subset_questions = lock_screen(&data)["threshold"]["subsets"].clone();
+        let expected = [(vec!["q0", "q1"], ["Rexy", "Elm"]), (vec!["q0", "q2"], ["Rexy", "Blue"])];
+        for (i, (subset, (questions, answers))) in subsets.iter().zip(expected).enumerate() {
+            assert_eq!(subset_questions[i], serde_json::json!(questions));
+            let subset_alternates = subset["alternates"].as_array().unwrap();
+            assert_eq!(subset_alternates.len(), 1);
+            assert_eq!(unwrap_with_answers(&data, &subset_alternates[0], &answers).unwrap(), doc_key, "{:?}", answers);
         }
+        assert!(subsets[2].get("alternates").is_none());
+    }
+
+    #[test]
+    fn test_time_locked_question_share_needs_release_key() {
+        let settings = crate::timelock::TimeLockSettings { check_in_days: Some(30), ..Default::default() };
+        let (lock, _) = crate::timelock::create(&settings, chrono::NaiveDate::from_ymd_opt(2026, 3, 1).unwrap()).unwrap();
+        let document = question_document(&[("Rex", &[]), ("Elm", &[])], None);
+        let data = question_export_data(&document, Some(&lock));
+        assert_eq!(lock_screen(&data)["time_lock"]["id"], lock.info.id);
+        assert!(data.get("time_lock").is_none());
+
+        // The answers unwrap only a share, which can't open the document on its own
+        let share = unwrap_with_answers(&data, &data["question_key"], &["Rex", "Elm"]).unwrap();
+        assert!(matches!(open_with_key(&data, &share), Err(ExportError::EncryptionError(_))));
+
+        // Combined with the release key, the share gives back the document key
+        let doc_key = lock.question_share(&share);
+        assert_eq!(open_with_key(&data, &doc_key).unwrap().pets.notes, "Feed the cat");
+        // The backup passphrase still opens it without the release key
+        let html = generate_encrypted_html_with_questions(&document, "correct horse battery", true, BrowserKdf::Pbkdf2, &Theme::default(), &Locale::default(), Some(&lock)).unwrap();
+        assert_eq!(import_from_html(&html, "correct horse battery").unwrap().pets.notes, "Feed the cat");
+    }
+
+    #[test]
+    fn test_signed_export_imports_only_unchanged() {
+        let mut document = LegacyDocument::default();
+        document.pets.notes = "Feed the cat".into();
+        let html = generate_encrypted_html(&document, "correct horse battery", false, BrowserKdf::Pbkdf2, &Theme::default(), &Locale::default()).unwrap();
+        assert_eq!(html.matches(SIGNATURE_PLACEHOLDER).count(), 1);
+        assert!(html.contains(r#"<p id="signatureNote" class="signature-note" hidden></p>"#));
+        // Files from before signing still import
+        assert_eq!(import_from_html(&html, "correct horse battery").unwrap().pets.notes, "Feed the cat");
+
+        let key = signing::SigningKey::generate().unwrap();
+        let signed = key.sign_html(&html).unwrap();
+        assert_eq!(signing::verify_html(&signed).unwrap(), Some(key.fingerprint().unwrap()));
+        assert_eq!(import_from_html(&signed, "correct horse battery").unwrap().pets.notes, "Feed the cat");
+
+        // A lock screen changed to phish the passphrase no longer imports
+        let phishing = signed.replace("connect-src 'none'", "connect-src https://evil.example");
+        assert!(matches!(import_from_html(&phishing, "correct horse battery"), Err(ExportError::SignatureError(SigningError::Mismatch))));
+    }
+
+    #[derive(Deserialize)]
+    struct AssociatedDataVector {
+        header: ExportHeader,
+        slot: String,
+        expected: String,
     }
 
-    // Pets
-    if !document.pets.pets.is_empty() || !document.pets.notes.is_empty() {
-        html.push_str("<h2>🐾 Pets</h2>\n");
-        for pet in &document.pets.pets {
-            html.push_str(&format!(
-                "<div class=\"item\"><div class=\"item-title\">{}</div><div class=\"item-detail\">Species: {}</div><div class=\"item-detail\">Breed: {}</div></div>\n",
-                pet.name, pet.species, pet.breed
-            ));
+    #[test]
+    fn test_associated_data_vectors() {
+        // The same vectors are checked against export_aad.js in exportAad.test.ts
+        #[derive(Deserialize)]
+        struct Vectors {
+            associated_data: Vec<AssociatedDataVector>,
         }
-        if !document.pets.notes.is_empty() {
-            html.push_str(&format!("<div class=\"notes\">{}</div>\n", document.pets.notes));
+        let vectors: Vectors = serde_json::from_str(include_str!("export_aad_vectors.json")).unwrap();
+        let mut seen = std::collections::HashSet::new();
+        for v in vectors.associated_data {
+            assert_eq!(v.header.associated_data(&v.slot), v.expected, "creator {:?}", v.header.creator);
+            // Headers that differ only in their lock screen (slide text, time lock) bind differently
+            assert!(seen.insert(v.expected.clone()), "{:?}", v.expected);
         }
     }
 
-    // Custom Sections
-    for section in &document.custom_sections {
-        html.push_str(&format!("<h2>📋 {}</h2>\n", section.name));
-        for subsection in &section.subsections {
-            html.push_str(&format!("<h3>{}</h3>\n", subsection.name));
-            // Use form_elements if available, otherwise fall back to field_definitions
-            let use_form_elements = !subsection.form_elements.is_empty();
-            for item in &subsection.items {
-                html.push_str("<div class=\"item\">");
-                if use_form_elements {
-                    for el in &subsection.form_elements {
-                        match el {
-                            crate::models::FormElement::Field { id, name, field_type } => {
-                                if let Some(value) = item.values.get(id) {
-                                    let display_value: String = match field_type {
-                                        FieldType::Boolean => if value == "true" { "Yes".to_string() } else { "No".to_string() },
-                                        _ => value.clone(),
-                                    };
-                                    html.push_str(&format!("<div class=\"item-detail\"><strong>{}:</strong> {}</div>", name, display_value));
-                                }
-                            }
-                            crate::models::FormElement::Divider { .. } => {
-                                html.push_str("<hr style=\"border:none;border-top:1px solid #D4D4D4;margin:8px 0;\">");
-                            }
-                            crate::models::FormElement::Header { text, .. } => {
-                                html.push_str(&format!("<div style=\"font-weight:bold;margin:8px 0 4px;\">{}</div>", text));
-                            }
-                        }
-                    }
-                } else {
-                    for field_def in &subsection.field_definitions {
-                        if let Some(value) = item.values.get(&field_def.id) {
-                            let display_value: String = match field_def.field_type {
-                                FieldType::Boolean => if value == "true" { "Yes".to_string() } else { "No".to_string() },
-                                _ => value.clone(),
-                            };
-                            html.push_str(&format!("<div class=\"item-detail\"><strong>{}:</strong> {}</div>", field_def.name, display_value));
-                        }
-                    }
-                }
-                if let Some(notes) = item.values.get("_notes") {
-                    if !notes.is_empty() {
-                        html.push_str(&format!("<div class=\"notes\">{}</div>", notes));
-                    }
-                }
-                html.push_str("</div>\n");
+    #[test]
+    fn test_edited_header_fails_to_decrypt() {
+        let mut document = LegacyDocument::default();
+        document.meta.creator_name = "Ana".into();
+        document.pets.notes = "Feed the cat".into();
+        document.welcome_screen = Some(crate::models::WelcomeScreen {
+            enabled: true,
+            slides: ["Rex", "Elm"].iter().enumerate().map(|(i, answer)| crate::models::MessageSlide {
+                id: format!("q{}", i),
+                slide_type: SlideType::Question,
+                answer: Some(answer.to_string()),
+                ..Default::default()
+            }).collect(),
+            ..Default::default()
+        });
+        let header = regex::Regex::new(r#""header":\{"format":3,"created":"[^"]*","creator":"[^"]*","lock_screen":"(?:[^"\\]|\\.)*"\},"#).unwrap();
+
+        let passphrase_file = generate_encrypted_html(&document, "correct horse battery", false, BrowserKdf::Pbkdf2, &Theme::default(), &Locale::default()).unwrap();
+        let question_file = generate_encrypted_html_with_questions(&document, "correct horse battery", true, BrowserKdf::Pbkdf2, &Theme::default(), &Locale::default(), None).unwrap();
+        for html in [passphrase_file, question_file] {
+            assert!(inline_script(&html).contains(r#""format":3,"#) && inline_script(&html).contains(r#""creator":"Ana","lock_screen":"#));
+            assert_eq!(import_from_html(&html, "correct horse battery").unwrap().pets.notes, "Feed the cat");
+
+            let edits = [
+                html.replace(r#""creator":"Ana""#, r#""creator":"Eve""#),
+                html.replace(r#""format":3,"#, r#""format":4,"#),
+                header.replace(&html, "").into_owned(),
+            ];
+            for edited in edits {
+                assert_ne!(edited, html);
+                assert!(matches!(import_from_html(&edited, "correct horse battery"), Err(ExportError::EncryptionError(_))));
             }
         }
     }
 
-    html.push_str("<div style=\"text-align:center;font-size:0.75rem;color:#B7B7A4;margin-top:2rem;padding-top:1rem;border-top:1px solid #D4D4D4;\">&copy; scafidi.dev &middot; MIT License</div>\n");
-    html.push_str("</body>\n</html>");
-    html
+    #[test]
+    fn test_edited_lock_screen_fails_to_decrypt() {
+        let settings = crate::timelock::TimeLockSettings { check_in_days: Some(30), ..Default::default() };
+        let (lock, _) = crate::timelock::create(&settings, chrono::NaiveDate::from_ymd_opt(2026, 3, 1).unwrap()).unwrap();
+        let mut document = question_document(&[("Rex", &[]), ("Elm", &[]), ("Blue", &[])], Some(2));
+        document.meta.creator_name = "Ana".into();
+        document.welcome_screen.as_mut().unwrap().slides[0].text = "What was our first dog's name?".into();
+        document.welcome_screen.as_mut().unwrap().attempt_limit = Some(3);
+        let question_file = generate_encrypted_html_with_questions(&document, "correct horse battery", true, BrowserKdf::Pbkdf2, &Theme::default(), &Locale::default(), Some(&lock)).unwrap();
+        assert_eq!(import_from_html(&question_file, "correct horse battery").unwrap().pets.notes, "Feed the cat");
+
+        let edits = [
+            question_file.replace("What was our first dog's name?", "What is your bank PIN?"),
+            question_file.replace(r#"check_in_days\":30"#, r#"check_in_days\":1"#),
+            question_file.replace(r#"attempt_limit\":3"#, r#"attempt_limit\":30"#),
+            question_file.replace(r#"required\":2"#, r#"required\":3"#),
+            question_file.replace(r#"[\"q0\",\"q1\"]"#, r#"[\"q1\",\"q0\"]"#),
+        ];
+        for edited in edits {
+            assert_ne!(edited, question_file);
+            assert!(matches!(import_from_html(&edited, "correct horse battery"), Err(ExportError::EncryptionError(_))));
+        }
+
+        // The slides and release date only reach the page through the header
+        let script = inline_script(&question_file);
+        assert!(script.contains("const SLIDES = LOCK_SCREEN.slides;"));
+        assert_eq!(script.matches("What was our first dog's name?").count(), 1);
+
+        let passphrase_file = generate_encrypted_html(&document, "correct horse battery", false, BrowserKdf::Pbkdf2, &Theme::default(), &Locale::default()).unwrap();
+        let edited = passphrase_file.replace("prepared by Ana", "prepared by your bank");
+        assert_ne!(edited, passphrase_file);
+        assert!(matches!(import_from_html(&edited, "correct horse battery"), Err(ExportError::EncryptionError(_))));
+    }
+
+    #[test]
+    fn test_files_without_header_still_import() {
+        let mut document = LegacyDocument::default();
+        document.pets.notes = "Feed the cat".into();
+        let json = serde_json::to_string(&document).unwrap();
+
+        let payload = encrypt_for_browser(&json, "correct horse battery", BrowserKdf::Pbkdf2, &[]).unwrap();
+        let html = generate_html_template(&serde_json::to_string(&payload).unwrap(), BrowserKdf::Pbkdf2, &Theme::default(), &Locale::default());
+        assert_eq!(import_from_html(&html, "correct horse battery").unwrap().pets.notes, "Feed the cat");
+
+        let doc_key = generate_document_key();
+        let encrypted = encrypt_with_raw_key(json.as_bytes(), &doc_key, &[]).unwrap();
+        let wrapped = encrypt_key_with_passphrase(&doc_key, "correct horse battery", BrowserKdf::Pbkdf2, &[]).unwrap();
+        let data = serde_json::json!({
+            "question_key": wrapped,
+            "passphrase_key": wrapped,
+            "document": { "nonce": encrypted.nonce, "ciphertext": encrypted.ciphertext },
+        });
+        let html = generate_question_html_template(&data.to_string(), true, BrowserKdf::Pbkdf2, &Theme::default(), &Locale::default());
+        assert_eq!(import_from_html(&html, "correct horse battery").unwrap().pets.notes, "Feed the cat");
+    }
+
+    #[test]
+    fn test_lock_screens_are_accessible() {
+        let passphrase = generate_html_template("{}", BrowserKdf::Pbkdf2, &Theme::default(), &Locale::default());
+        assert!(passphrase.contains(r#"<html lang="en" dir="ltr">"#));
+        assert_eq!(accessibility_problems(&passphrase), Vec::<String>::new());
+        assert!(passphrase.contains(r#"id="welcomePause" class="welcome-pause" data-click="toggleWelcomePause""#));
+        assert!(passphrase.contains(r#"<p id="error" class="error" role="alert""#));
+
+        let questions = generate_question_html_template("{}", true, BrowserKdf::Pbkdf2, &Theme::default(), &Locale::default());
+        assert_eq!(accessibility_problems(&questions), Vec::<String>::new());
+        assert!(questions.contains(r#"id="slideGroup" class="slide-container" role="group" aria-roledescription="slide""#));
+        assert!(questions.contains(r#"id="retryTitle" class="retry-title" tabindex="-1""#));
+    }
+
+    #[test]
+    fn test_viewer_is_accessible() {
+        let mut document = LegacyDocument::default();
+        document.financial.notes = "Mortgage payments go through Chase".into();
+        document.pets.notes = "Feed the cat".into();
+        let payload: serde_json::Value = serde_json::from_str(&serialize_export_payload(&document, &Theme::default(), &Locale::default()).unwrap()).unwrap();
+        let page = payload["export_extras"]["rendered"]["page"].as_str().unwrap();
+        assert_eq!(accessibility_problems(page), Vec::<String>::new());
+        assert!(page.starts_with(r##"<a class="skip-link" href="#mainContent">"##));
+        assert!(page.contains(r#"<main class="main-content" id="mainContent" tabindex="-1">"#));
+        assert!(page.contains(r#"<nav class="sidebar-nav" aria-labelledby="navTitle">"#));
+        assert_eq!(page.matches(r#"role="search""#).count(), 2);
+        assert_eq!(page.matches(r#"data-click="toggleDisplayMode""#).count(), 2);
+    }
+
+    #[test]
+    fn test_default_theme_meets_contrast_minimum() {
+        let colors = crate::render::ThemeColors::default();
+        let pairs = [
+            (&colors.primary, &colors.background),
+            (&colors.primary, &"#FFFFFF".to_string()),
+            (&colors.accent, &"#FFFFFF".to_string()),
+            (&colors.accent, &colors.background),
+            (&colors.muted, &colors.primary),
+        ];
+        for (text, background) in pairs {
+            assert!(contrast_ratio(text, background) >= 4.5, "{} on {}", text, background);
+        }
+    }
 }
@@ -0,0 +1,10 @@
+        // Must match ExportHeader::associated_data() in export.rs
+        function exportAssociatedData(header, slot) {
+            const encoder = new TextEncoder();
+            const fields = ['honey-did-export', String(header.format), header.created, header.creator];
+            if (header.lock_screen !== undefined) fields.push(header.lock_screen);
+            fields.push(slot);
+            return fields
+                .map(field => encoder.encode(field).length + ':' + field)
+                .join('');
+        }
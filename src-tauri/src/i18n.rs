@@ -0,0 +1,233 @@
+use chrono::{Datelike, NaiveDate};
+use minijinja::HtmlEscape;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Message catalogs for exported and printed documents, embedded at compile time.
+/// English comes first and fills in any message another catalog is missing.
+const CATALOGS: &[(&str, &str)] = &[
+    ("en", include_str!("locales/en.json")),
+    ("es", include_str!("locales/es.json")),
+    ("ar", include_str!("locales/ar.json")),
+];
+
+pub const DEFAULT_LOCALE: &str = "en";
+
+#[derive(Debug)]
+pub enum LocaleError {
+    UnknownLocale(String),
+    InvalidCatalog(String),
+}
+
+impl std::fmt::Display for LocaleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LocaleError::UnknownLocale(code) => write!(f, "Unknown export language: {}", code),
+            LocaleError::InvalidCatalog(msg) => write!(f, "Invalid message catalog: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for LocaleError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Direction {
+    Ltr,
+    Rtl,
+}
+
+/// A catalog file as stored in `src/locales`
+#[derive(Deserialize)]
+struct Catalog {
+    name: String,
+    direction: Direction,
+    /// Uses `{day}`, `{month}` and `{year}`
+    date_format: String,
+    months: Vec<String>,
+    messages: BTreeMap<String, String>,
+}
+
+/// An export language the app offers, for the language picker
+#[derive(Debug, Clone, Serialize)]
+pub struct LocaleInfo {
+    pub code: String,
+    /// The language's own name, e.g. "Español"
+    pub name: String,
+    pub rtl: bool,
+}
+
+/// The language an export is written in
+#[derive(Debug, Clone)]
+pub struct Locale {
+    pub code: String,
+    direction: Direction,
+    date_format: String,
+    months: Vec<String>,
+    messages: BTreeMap<String, String>,
+}
+
+/// The languages exports can be written in
+pub fn available_locales() -> Vec<LocaleInfo> {
+    CATALOGS
+        .iter()
+        .filter_map(|(code, source)| {
+            let catalog: Catalog = serde_json::from_str(source).ok()?;
+            Some(LocaleInfo { code: code.to_string(), name: catalog.name, rtl: catalog.direction == Direction::Rtl })
+        })
+        .collect()
+}
+
+fn parse_catalog(source: &str) -> Result<Catalog, LocaleError> {
+    let catalog: Catalog = serde_json::from_str(source).map_err(|e| LocaleError::InvalidCatalog(e.to_string()))?;
+    if catalog.months.len() != 12 {
+        return Err(LocaleError::InvalidCatalog("a catalog needs 12 month names".into()));
+    }
+    Ok(catalog)
+}
+
+impl Locale {
+    /// Loads a catalog by its code, e.g. "es"
+    pub fn load(code: &str) -> Result<Locale, LocaleError> {
+        let source = CATALOGS
+            .iter()
+            .find(|(c, _)| *c == code)
+            .map(|(_, source)| *source)
+            .ok_or_else(|| LocaleError::UnknownLocale(code.to_string()))?;
+        let catalog = parse_catalog(source)?;
+        let mut messages = parse_catalog(CATALOGS[0].1)?.messages;
+        messages.extend(catalog.messages);
+        Ok(Locale {
+            code: code.to_string(),
+            direction: catalog.direction,
+            date_format: catalog.date_format,
+            months: catalog.months,
+            messages,
+        })
+    }
+
+    /// The language chosen for an export, English when none was chosen
+    pub fn for_export(code: Option<&str>) -> Result<Locale, LocaleError> {
+        Locale::load(code.unwrap_or(DEFAULT_LOCALE))
+    }
+
+    /// "ltr" or "rtl", for the `dir` attribute
+    pub fn dir(&self) -> &'static str {
+        match self.direction {
+            Direction::Ltr => "ltr",
+            Direction::Rtl => "rtl",
+        }
+    }
+
+    /// The message for a key, or the key itself if no catalog has it
+    pub fn t<'a>(&'a self, key: &'a str) -> &'a str {
+        self.messages.get(key).map(String::as_str).unwrap_or(key)
+    }
+
+    /// The message for a key with its `{name}` placeholders filled in
+    pub fn format(&self, key: &str, params: &[(&str, &str)]) -> String {
+        fill(self.t(key), params)
+    }
+
+    /// Like `format`, escaped for HTML, with line breaks kept as `<br>`
+    pub fn html(&self, key: &str, params: &[(&str, &str)]) -> String {
+        self.format(key, params).lines().map(|line| HtmlEscape(line).to_string()).collect::<Vec<_>>().join("<br>")
+    }
+
+    /// A date written out the way the language does, e.g. "5 de marzo de 2026"
+    pub fn format_date(&self, date: NaiveDate) -> String {
+        let month = &self.months[date.month0() as usize];
+        fill(&self.date_format, &[("day", &date.day().to_string()), ("month", month), ("year", &date.year().to_string())])
+    }
+
+    /// Formats a `YYYY-MM-DD` value, leaving anything else as it is
+    pub fn format_date_str(&self, value: &str) -> String {
+        match NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d") {
+            Ok(date) => self.format_date(date),
+            Err(_) => value.to_string(),
+        }
+    }
+
+    /// The messages and date format for the exported page's script, safe inside `<script>`
+    pub fn script_json(&self) -> String {
+        let json = serde_json::json!({
+            "code": self.code,
+            "dir": self.dir(),
+            "date_format": self.date_format,
+            "months": self.months,
+            "messages": self.messages,
+        });
+        json.to_string().replace('<', "\\u003c")
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale::load(DEFAULT_LOCALE).expect("the English catalog is valid")
+    }
+}
+
+fn fill(template: &str, params: &[(&str, &str)]) -> String {
+    params.iter().fold(template.to_string(), |text, (name, value)| text.replace(&format!("{{{}}}", name), value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalogs_have_the_same_messages() {
+        let english = parse_catalog(CATALOGS[0].1).unwrap();
+        for (code, source) in CATALOGS {
+            let catalog = parse_catalog(source).unwrap();
+            let missing: Vec<_> = english.messages.keys().filter(|k| !catalog.messages.contains_key(*k)).collect();
+            let extra: Vec<_> = catalog.messages.keys().filter(|k| !english.messages.contains_key(*k)).collect();
+            assert!(missing.is_empty() && extra.is_empty(), "{}: missing {:?}, extra {:?}", code, missing, extra);
+            // Placeholders have to survive translation
+            for (key, text) in &english.messages {
+                for placeholder in text.split('{').skip(1).filter_map(|rest| rest.split_once('}')).map(|(name, _)| name) {
+                    assert!(catalog.messages[key].contains(&format!("{{{}}}", placeholder)), "{}: {} lost {{{}}}", code, key, placeholder);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_messages_and_fallback() {
+        let spanish = Locale::load("es").unwrap();
+        assert_eq!(spanish.t("field.institution"), "Entidad");
+        assert_eq!(spanish.format("questions.attempt", &[("attempt", "2"), ("max", "5")]), "Intento 2 de 5");
+        assert_eq!(spanish.t("no.such.key"), "no.such.key");
+        assert_eq!(spanish.html("lock.message", &[("name", "Ana <Lee>")]), "Este documento lo preparó Ana &lt;Lee&gt;<br>para ayudarte en su ausencia.");
+        assert!(Locale::load("xx").is_err());
+    }
+
+    #[test]
+    fn test_dates_and_direction() {
+        let date = NaiveDate::from_ymd_opt(2026, 3, 5).unwrap();
+        assert_eq!(Locale::default().format_date(date), "March 5, 2026");
+        assert_eq!(Locale::load("es").unwrap().format_date(date), "5 de marzo de 2026");
+        let arabic = Locale::load("ar").unwrap();
+        assert_eq!(arabic.dir(), "rtl");
+        assert_eq!(arabic.format_date_str("2026-03-05"), "5 مارس 2026");
+        assert_eq!(arabic.format_date_str("next spring"), "next spring");
+        assert_eq!(Locale::default().dir(), "ltr");
+    }
+
+    #[test]
+    fn test_script_json_cannot_close_the_script() {
+        let json = Locale::default().script_json();
+        assert!(!json.contains('<'));
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["messages"]["viewer.contents"], "Contents");
+        assert_eq!(value["months"].as_array().unwrap().len(), 12);
+    }
+
+    #[test]
+    fn test_available_locales() {
+        let locales = available_locales();
+        assert_eq!(locales.len(), CATALOGS.len());
+        assert!(locales.iter().any(|l| l.code == "ar" && l.rtl));
+        assert!(locales.iter().any(|l| l.code == "es" && l.name == "Español" && !l.rtl));
+    }
+}
@@ -1,8 +1,27 @@
+mod archive;
+mod autoexport;
+mod calendar;
+mod csv_import;
+mod deadman;
+mod delivery;
 mod encryption;
 mod export;
+mod i18n;
+mod markdown;
 mod models;
+mod passphrase;
+mod password_import;
+mod questions;
+mod render;
+mod search;
+mod signing;
 mod storage;
+mod strength;
+mod timelock;
+mod vcard;
 
+use encryption::BrowserKdf;
+use strength::ExportCommandError;
 use models::LegacyDocument;
 use std::sync::Mutex;
 use tauri::{Manager, State};
@@ -24,6 +43,8 @@ const MAX_PASSPHRASE_LENGTH: usize = 1024;
 const MIN_PASSWORD_LENGTH: usize = 8;
 const MAX_PASSWORD_LENGTH: usize = 256;
 const MAX_HTML_CONTENT_LENGTH: usize = 50 * 1024 * 1024; // 50MB
+const MAX_TEXT_IMPORT_LENGTH: usize = 10 * 1024 * 1024; // 10MB
+const MAX_ARCHIVE_LENGTH: usize = 256 * 1024 * 1024; // 256MB
 
 /// Validates passphrase input
 fn validate_passphrase(passphrase: &str) -> Result<(), String> {
@@ -58,32 +79,137 @@ fn validate_html_content(html: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Validates text file content (CSV, vCard) for import
+fn validate_text_import(content: &str) -> Result<(), String> {
+    if content.trim().is_empty() {
+        return Err("File content cannot be empty".to_string());
+    }
+    if content.len() > MAX_TEXT_IMPORT_LENGTH {
+        return Err("File is too large".to_string());
+    }
+    Ok(())
+}
+
+/// Validates an encrypted archive for import
+fn validate_archive(bytes: &[u8]) -> Result<(), String> {
+    if bytes.is_empty() {
+        return Err("File content cannot be empty".to_string());
+    }
+    if bytes.len() > MAX_ARCHIVE_LENGTH {
+        return Err("File is too large".to_string());
+    }
+    Ok(())
+}
+
 #[tauri::command]
 fn get_document(state: State<AppState>) -> Result<LegacyDocument, String> {
     let doc = state.document.lock().map_err(|e| e.to_string())?;
     Ok(doc.clone())
 }
 
-#[tauri::command]
-fn update_document(state: State<AppState>, document: LegacyDocument) -> Result<(), String> {
+/// Replaces and saves the open document. Every change goes through here so
+/// automatic export sees it.
+fn replace_document(state: &State<AppState>, document: LegacyDocument) -> Result<(), String> {
     let mut doc = state.document.lock().map_err(|e| e.to_string())?;
     *doc = document;
     storage::save_document(&doc).map_err(|e| e.to_string())?;
+    // Recorded in the automatic export's last_error as well
+    if let Err(e) = autoexport::document_changed(chrono::Utc::now()) {
+        eprintln!("Automatic export: {}", e);
+    }
     Ok(())
 }
 
 #[tauri::command]
-fn export_html(state: State<AppState>, passphrase: String, include_welcome_screen: Option<bool>) -> Result<String, String> {
+fn update_document(state: State<AppState>, document: LegacyDocument) -> Result<(), String> {
+    replace_document(&state, document)
+}
+
+/// Searches the open document. `modes` picks the match kinds ("exact",
+/// "contains", "spelling" or "fuzzy", "phonetic"); all of them when omitted.
+#[tauri::command]
+fn search_document(state: State<AppState>, query: String, modes: Option<Vec<search::MatchKind>>) -> Result<Vec<search::SearchHit>, String> {
+    let Some(query) = search::Query::new(&query) else {
+        return Ok(Vec::new());
+    };
+    let modes = modes.unwrap_or_else(|| search::MatchKind::ALL.to_vec());
+    let doc = state.document.lock().map_err(|e| e.to_string())?;
+    Ok(search::search_document(&doc, &query, &modes))
+}
+
+#[tauri::command]
+fn export_html(
+    state: State<AppState>,
+    passphrase: String,
+    include_welcome_screen: Option<bool>,
+    memory_hard_kdf: Option<bool>,
+    acknowledge_weak: Option<bool>,
+    locale: Option<String>,
+) -> Result<String, ExportCommandError> {
     validate_passphrase(&passphrase)?;
+    let kdf = BrowserKdf::for_export(memory_hard_kdf.unwrap_or(false));
+    strength::assess_passphrase_export(&passphrase, kdf).require(acknowledge_weak.unwrap_or(false))?;
+    let locale = export_locale(locale)?;
     let doc = state.document.lock().map_err(|e| e.to_string())?;
-    export::generate_encrypted_html(&doc, &passphrase, include_welcome_screen.unwrap_or(false)).map_err(|e: export::ExportError| e.to_string())
+    let html = export::generate_encrypted_html(&doc, &passphrase, include_welcome_screen.unwrap_or(false), kdf, &export_theme()?, &locale).map_err(|e: export::ExportError| e.to_string())?;
+    Ok(sign_export(html)?)
 }
 
 #[tauri::command]
-fn export_html_with_questions(state: State<AppState>, passphrase: String, include_welcome_screen: bool) -> Result<String, String> {
+fn export_html_with_questions(
+    state: State<AppState>,
+    passphrase: String,
+    include_welcome_screen: bool,
+    memory_hard_kdf: Option<bool>,
+    acknowledge_weak: Option<bool>,
+    time_lock: Option<timelock::TimeLockSettings>,
+    locale: Option<String>,
+) -> Result<String, ExportCommandError> {
     validate_passphrase(&passphrase)?;
+    let kdf = BrowserKdf::for_export(memory_hard_kdf.unwrap_or(false));
+    let locale = export_locale(locale)?;
     let doc = state.document.lock().map_err(|e| e.to_string())?;
-    export::generate_encrypted_html_with_questions(&doc, &passphrase, include_welcome_screen).map_err(|e: export::ExportError| e.to_string())
+    require_question_strength(&doc, &passphrase, kdf, acknowledge_weak)?;
+    let time_lock = prepare_time_lock(time_lock)?;
+    let html = export::generate_encrypted_html_with_questions(&doc, &passphrase, include_welcome_screen, kdf, &export_theme()?, &locale, time_lock.as_ref().map(|(lock, _)| lock)).map_err(|e: export::ExportError| e.to_string())?;
+    let html = sign_export(html)?;
+    if let Some((_, escrowed)) = time_lock {
+        escrow_release_key(escrowed)?;
+    }
+    Ok(html)
+}
+
+fn today() -> chrono::NaiveDate {
+    chrono::Local::now().date_naive()
+}
+
+/// Creates the release key for a time-locked export; it is escrowed before the export is handed out
+fn prepare_time_lock(settings: Option<timelock::TimeLockSettings>) -> Result<Option<(timelock::TimeLock, timelock::EscrowedKey)>, String> {
+    settings
+        .map(|settings| timelock::create(&settings, today()).map_err(|e| e.to_string()))
+        .transpose()
+}
+
+/// Hands a release key to the local release service
+fn escrow_release_key(escrowed: timelock::EscrowedKey) -> Result<(), String> {
+    let mut store = storage::load_release_store().map_err(|e| e.to_string())?;
+    store.deposit(escrowed, today());
+    storage::save_release_store(&store).map_err(|e| e.to_string())
+}
+
+/// Drops the release key of an export that was never saved
+fn forget_release_key(id: &str) -> Result<(), String> {
+    let mut store = storage::load_release_store().map_err(|e| e.to_string())?;
+    store.remove(id).map_err(|e| e.to_string())?;
+    storage::save_release_store(&store).map_err(|e| e.to_string())
+}
+
+/// Checks answers and backup passphrase; a document without a welcome screen fails later in export
+fn require_question_strength(doc: &LegacyDocument, passphrase: &str, kdf: BrowserKdf, acknowledge_weak: Option<bool>) -> Result<(), ExportCommandError> {
+    match doc.welcome_screen {
+        Some(ref welcome) => strength::assess_question_export(welcome, passphrase, kdf).require(acknowledge_weak.unwrap_or(false)),
+        None => Ok(()),
+    }
 }
 
 #[tauri::command]
@@ -122,11 +248,23 @@ fn get_download_dir() -> Result<std::path::PathBuf, String> {
 }
 
 #[tauri::command]
-fn save_export(state: State<AppState>, passphrase: String, file_path: String, include_welcome_screen: Option<bool>) -> Result<(), String> {
+fn save_export(
+    state: State<AppState>,
+    passphrase: String,
+    file_path: String,
+    include_welcome_screen: Option<bool>,
+    memory_hard_kdf: Option<bool>,
+    acknowledge_weak: Option<bool>,
+    locale: Option<String>,
+) -> Result<(), ExportCommandError> {
     validate_passphrase(&passphrase)?;
+    let kdf = BrowserKdf::for_export(memory_hard_kdf.unwrap_or(false));
+    strength::assess_passphrase_export(&passphrase, kdf).require(acknowledge_weak.unwrap_or(false))?;
+    let locale = export_locale(locale)?;
     let doc = state.document.lock().map_err(|e| e.to_string())?;
-    let html = export::generate_encrypted_html(&doc, &passphrase, include_welcome_screen.unwrap_or(false)).map_err(|e: export::ExportError| e.to_string())?;
-    std::fs::write(&file_path, html).map_err(|_| "Failed to save file".to_string())
+    let html = export::generate_encrypted_html(&doc, &passphrase, include_welcome_screen.unwrap_or(false), kdf, &export_theme()?, &locale).map_err(|e: export::ExportError| e.to_string())?;
+    let html = sign_export(html)?;
+    Ok(std::fs::write(&file_path, html).map_err(|_| "Failed to save file".to_string())?)
 }
 
 #[tauri::command]
@@ -135,12 +273,19 @@ async fn save_export_with_dialog(
     state: State<'_, AppState>,
     passphrase: String,
     include_welcome_screen: bool,
-) -> Result<Option<String>, String> {
+    memory_hard_kdf: Option<bool>,
+    acknowledge_weak: Option<bool>,
+    locale: Option<String>,
+) -> Result<Option<String>, ExportCommandError> {
     use tauri_plugin_dialog::DialogExt;
 
     validate_passphrase(&passphrase)?;
+    let kdf = BrowserKdf::for_export(memory_hard_kdf.unwrap_or(false));
+    strength::assess_passphrase_export(&passphrase, kdf).require(acknowledge_weak.unwrap_or(false))?;
+    let locale = export_locale(locale)?;
     let doc = state.document.lock().map_err(|e| e.to_string())?;
-    let html = export::generate_encrypted_html(&doc, &passphrase, include_welcome_screen).map_err(|e: export::ExportError| e.to_string())?;
+    let html = export::generate_encrypted_html(&doc, &passphrase, include_welcome_screen, kdf, &export_theme()?, &locale).map_err(|e: export::ExportError| e.to_string())?;
+    let html = sign_export(html)?;
     drop(doc);
 
     let date = chrono::Local::now().format("%Y-%m-%d").to_string();
@@ -169,12 +314,21 @@ async fn save_export_with_questions(
     state: State<'_, AppState>,
     passphrase: String,
     include_welcome_screen: bool,
-) -> Result<Option<String>, String> {
+    memory_hard_kdf: Option<bool>,
+    acknowledge_weak: Option<bool>,
+    time_lock: Option<timelock::TimeLockSettings>,
+    locale: Option<String>,
+) -> Result<Option<String>, ExportCommandError> {
     use tauri_plugin_dialog::DialogExt;
 
     validate_passphrase(&passphrase)?;
+    let kdf = BrowserKdf::for_export(memory_hard_kdf.unwrap_or(false));
+    let locale = export_locale(locale)?;
     let doc = state.document.lock().map_err(|e| e.to_string())?;
-    let html = export::generate_encrypted_html_with_questions(&doc, &passphrase, include_welcome_screen).map_err(|e: export::ExportError| e.to_string())?;
+    require_question_strength(&doc, &passphrase, kdf, acknowledge_weak)?;
+    let time_lock = prepare_time_lock(time_lock)?;
+    let html = export::generate_encrypted_html_with_questions(&doc, &passphrase, include_welcome_screen, kdf, &export_theme()?, &locale, time_lock.as_ref().map(|(lock, _)| lock)).map_err(|e: export::ExportError| e.to_string())?;
+    let html = sign_export(html)?;
     drop(doc);
 
     let date = chrono::Local::now().format("%Y-%m-%d").to_string();
@@ -189,8 +343,21 @@ async fn save_export_with_questions(
     match file_path {
         Some(path) => {
             let path_str = path.to_string();
-            std::fs::write(&path_str, &html)
-                .map_err(|e| format!("Failed to save file: {}", e))?;
+            // Escrow first: a saved file whose release key was never kept only opens with the backup passphrase
+            let escrowed_id = match time_lock {
+                Some((_, escrowed)) => {
+                    let id = escrowed.id.clone();
+                    escrow_release_key(escrowed)?;
+                    Some(id)
+                }
+                None => None,
+            };
+            if let Err(e) = std::fs::write(&path_str, &html) {
+                if let Some(id) = escrowed_id {
+                    let _ = forget_release_key(&id);
+                }
+                return Err(format!("Failed to save file: {}", e).into());
+            }
             Ok(Some(path_str))
         }
         None => Ok(None),
@@ -198,71 +365,223 @@ async fn save_export_with_questions(
 }
 
 #[tauri::command]
-fn get_print_html(state: State<AppState>) -> Result<String, String> {
+fn save_archive_export(state: State<AppState>, passphrase: String, file_path: String, acknowledge_weak: Option<bool>) -> Result<(), ExportCommandError> {
+    validate_passphrase(&passphrase)?;
+    strength::assess_archive_export(&passphrase).require(acknowledge_weak.unwrap_or(false))?;
+    let doc = state.document.lock().map_err(|e| e.to_string())?;
+    let bytes = archive::export_archive(&doc, &passphrase).map_err(|e| e.to_string())?;
+    Ok(std::fs::write(&file_path, bytes).map_err(|_| "Failed to save file".to_string())?)
+}
+
+/// Mobile counterpart of `save_archive_export`; the frontend shares the returned path
+#[tauri::command]
+fn save_archive_to_downloads(state: State<AppState>, passphrase: String, file_name: String, acknowledge_weak: Option<bool>) -> Result<String, ExportCommandError> {
+    validate_passphrase(&passphrase)?;
+    strength::assess_archive_export(&passphrase).require(acknowledge_weak.unwrap_or(false))?;
+    let doc = state.document.lock().map_err(|e| e.to_string())?;
+    let bytes = archive::export_archive(&doc, &passphrase).map_err(|e| e.to_string())?;
+    drop(doc);
+
+    let dir = get_download_dir()?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create directory: {}", e))?;
+
+    let path = dir.join(&file_name);
+    std::fs::write(&path, bytes).map_err(|e| format!("Failed to write file: {}", e))?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// The saved export theme, used for every export and the printable page
+fn export_theme() -> Result<render::Theme, String> {
+    storage::load_export_theme().map_err(|e| e.to_string())
+}
+
+/// Signs a finished export with this install's key, so a changed copy no longer imports
+fn sign_export(html: String) -> Result<String, String> {
+    let key = storage::get_or_create_signing_key().map_err(|e| e.to_string())?;
+    key.sign_html(&html).map_err(|e| e.to_string())
+}
+
+/// The export language picked for this export; English when none is given
+fn export_locale(locale: Option<String>) -> Result<i18n::Locale, String> {
+    i18n::Locale::for_export(locale.as_deref()).map_err(|e| e.to_string())
+}
+
+/// Languages the export dialog offers for the recipient
+#[tauri::command]
+fn get_export_locales() -> Vec<i18n::LocaleInfo> {
+    i18n::available_locales()
+}
+
+#[tauri::command]
+fn get_export_theme() -> Result<render::Theme, String> {
+    export_theme()
+}
+
+/// Saves a theme for exports and printing; `None` goes back to the Honey Did look
+#[tauri::command]
+fn set_export_theme(theme: Option<render::Theme>) -> Result<render::Theme, String> {
+    match theme {
+        Some(theme) => {
+            theme.validate().map_err(|e| e.to_string())?;
+            storage::save_export_theme(&theme).map_err(|e| e.to_string())?;
+            Ok(theme)
+        }
+        None => {
+            storage::delete_export_theme().map_err(|e| e.to_string())?;
+            Ok(render::Theme::default())
+        }
+    }
+}
+
+/// Turns a picked image file (base64, like attachments) into a logo for `set_export_theme`
+#[tauri::command]
+fn theme_logo_data_uri(data: String) -> Result<String, String> {
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+
+    let bytes = BASE64.decode(data.trim()).map_err(|_| "Invalid image data".to_string())?;
+    render::logo_data_uri_from_file(&bytes).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_print_html(state: State<AppState>, locale: Option<String>) -> Result<String, String> {
+    let locale = export_locale(locale)?;
     let doc = state.document.lock().map_err(|e| e.to_string())?;
-    Ok(export::generate_print_html(&doc))
+    export::generate_print_html(&doc, &export_theme()?, &locale).map_err(|e| e.to_string())
 }
 
+/// Imports an exported file. Files not signed by this install's key are refused
+/// unless the user has confirmed them (`allow_unverified`).
 #[tauri::command]
-fn import_file(encrypted_html: String, passphrase: String) -> Result<LegacyDocument, String> {
+fn import_file(
+    encrypted_html: String,
+    passphrase: String,
+    allow_unverified: Option<bool>,
+) -> Result<LegacyDocument, String> {
     validate_html_content(&encrypted_html)?;
     validate_passphrase(&passphrase)?;
+    if !allow_unverified.unwrap_or(false) {
+        match export_signature_status(&encrypted_html)? {
+            signing::SignatureStatus::Own { .. } => {}
+            signing::SignatureStatus::Other { fingerprint } => {
+                return Err(format!(
+                    "This file was signed with a different key ({}), not this app's. Confirm the import to continue.",
+                    fingerprint
+                ));
+            }
+            signing::SignatureStatus::Unsigned => {
+                return Err("This file is not signed. Confirm the import to continue.".to_string());
+            }
+        }
+    }
     export::import_from_html(&encrypted_html, &passphrase).map_err(|e: export::ExportError| e.to_string())
 }
 
+fn export_signature_status(encrypted_html: &str) -> Result<signing::SignatureStatus, String> {
+    let key = storage::get_or_create_signing_key().map_err(|e| e.to_string())?;
+    signing::check_html(encrypted_html, &key).map_err(|e| e.to_string())
+}
+
+/// Checks an exported file's signature before import and compares the signing
+/// key with this install's own.
+#[tauri::command]
+fn check_export_signature(encrypted_html: String) -> Result<signing::SignatureStatus, String> {
+    validate_html_content(&encrypted_html)?;
+    export_signature_status(&encrypted_html)
+}
+
+/// The fingerprint of the key that signs this install's exports
+#[tauri::command]
+fn get_signing_fingerprint() -> Result<String, String> {
+    let key = storage::get_or_create_signing_key().map_err(|e| e.to_string())?;
+    key.fingerprint().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn import_archive(data_base64: String, passphrase: String) -> Result<LegacyDocument, String> {
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+
+    validate_passphrase(&passphrase)?;
+    let bytes = BASE64.decode(data_base64.trim()).map_err(|_| "File is not a valid archive".to_string())?;
+    validate_archive(&bytes)?;
+    archive::import_from_archive(&bytes, &passphrase).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 fn merge_document(state: State<AppState>, imported: LegacyDocument) -> Result<(), String> {
-    let mut doc = state.document.lock().map_err(|e| e.to_string())?;
-    *doc = imported;
-    storage::save_document(&doc).map_err(|e| e.to_string())?;
-    Ok(())
+    replace_document(&state, imported)
+}
+
+#[tauri::command]
+fn preview_csv_import(
+    state: State<AppState>,
+    content: String,
+    target: csv_import::CsvTarget,
+    mapping: Option<csv_import::ColumnMapping>,
+) -> Result<csv_import::CsvPreview, String> {
+    validate_text_import(&content)?;
+    let doc = state.document.lock().map_err(|e| e.to_string())?;
+    csv_import::preview_csv(&doc, &content, &target, mapping).map_err(|e| e.to_string())
+}
+
+/// Returns the document with the CSV rows appended; the frontend saves it with `merge_document`
+#[tauri::command]
+fn import_csv(
+    state: State<AppState>,
+    content: String,
+    target: csv_import::CsvTarget,
+    mapping: csv_import::ColumnMapping,
+) -> Result<(LegacyDocument, csv_import::CsvImportReport), String> {
+    validate_text_import(&content)?;
+    let mut doc = state.document.lock().map_err(|e| e.to_string())?.clone();
+    let report = csv_import::import_csv(&mut doc, &content, &target, &mapping).map_err(|e| e.to_string())?;
+    Ok((doc, report))
+}
+
+#[tauri::command]
+fn import_vcard(content: String) -> Result<Vec<models::Contact>, String> {
+    validate_text_import(&content)?;
+    vcard::parse_vcards(&content).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn export_contacts_vcf(state: State<AppState>) -> Result<String, String> {
+    let doc = state.document.lock().map_err(|e| e.to_string())?;
+    Ok(vcard::document_to_vcf(&doc))
+}
+
+#[tauri::command]
+fn get_calendar_report(state: State<AppState>) -> Result<calendar::CalendarReport, String> {
+    let doc = state.document.lock().map_err(|e| e.to_string())?;
+    Ok(calendar::generate_ics(&doc, chrono::Local::now().date_naive()).1)
+}
+
+#[tauri::command]
+fn preview_password_import(state: State<AppState>, content: String) -> Result<password_import::PasswordImportReport, String> {
+    validate_text_import(&content)?;
+    let mut doc = state.document.lock().map_err(|e| e.to_string())?.clone();
+    password_import::import_passwords(&mut doc, &content, false).map_err(|e| e.to_string())
+}
+
+/// Returns the document with the logins added; the frontend saves it with `merge_document`
+#[tauri::command]
+fn import_passwords(
+    state: State<AppState>,
+    content: String,
+    include_secrets: bool,
+) -> Result<(LegacyDocument, password_import::PasswordImportReport), String> {
+    validate_text_import(&content)?;
+    let mut doc = state.document.lock().map_err(|e| e.to_string())?.clone();
+    let report = password_import::import_passwords(&mut doc, &content, include_secrets).map_err(|e| e.to_string())?;
+    Ok((doc, report))
 }
 
+/// Diceware passphrase from the EFF large wordlist; defaults to six words joined by hyphens
 #[tauri::command]
-fn generate_passphrase() -> String {
-    use rand::seq::SliceRandom;
-    use rand::rngs::OsRng;
-
-    // Extended word list (256 words) for better entropy
-    // 6 words from 256 = log2(256^6) = 48 bits of entropy
-    let words: Vec<&str> = vec![
-        "apple", "arrow", "anchor", "autumn", "azure", "baker", "banana", "beacon",
-        "berry", "blade", "blanket", "blaze", "bloom", "bottle", "branch", "breeze",
-        "bridge", "bronze", "brush", "bucket", "butter", "cabin", "cactus", "candle",
-        "canvas", "canyon", "carpet", "carrot", "castle", "cedar", "chain", "chair",
-        "cherry", "cider", "circle", "cliff", "cloud", "clover", "coast", "cobalt",
-        "coffee", "comet", "coral", "cotton", "crayon", "creek", "cricket", "crown",
-        "crystal", "curtain", "dagger", "daisy", "dancer", "delta", "desert", "diamond",
-        "dolphin", "dragon", "dream", "drift", "drum", "eagle", "earth", "echo",
-        "elder", "ember", "engine", "fabric", "falcon", "feather", "fern", "field",
-        "fire", "flame", "flint", "flower", "forest", "forge", "fossil", "fountain",
-        "frost", "galaxy", "garden", "garnet", "geyser", "glacier", "globe", "golden",
-        "grain", "grape", "grass", "gravel", "grove", "hammer", "harbor", "harvest",
-        "hawk", "hazel", "heart", "heather", "helmet", "honey", "horizon", "hunter",
-        "ice", "indigo", "iris", "iron", "island", "ivory", "jade", "jasper",
-        "jet", "journal", "jungle", "kelp", "kernel", "kettle", "kingdom", "kitchen",
-        "kite", "lake", "lantern", "lapis", "lark", "lava", "leaf", "lemon",
-        "library", "light", "lily", "linen", "lion", "lotus", "lunar", "magnet",
-        "maple", "marble", "market", "meadow", "melon", "metal", "mirror", "mist",
-        "moon", "moss", "mountain", "mushroom", "nectar", "needle", "night", "north",
-        "oak", "oasis", "ocean", "olive", "onyx", "orange", "orchid", "osprey",
-        "otter", "palm", "panther", "paper", "parrot", "path", "pebble", "pepper",
-        "phoenix", "piano", "pillow", "pine", "planet", "plum", "pond", "poplar",
-        "prism", "pumpkin", "quartz", "queen", "quiet", "rabbit", "radish", "rain",
-        "rainbow", "raven", "reef", "ridge", "river", "robin", "rocket", "rose",
-        "ruby", "sage", "salmon", "sand", "sapphire", "scarlet", "scroll", "shadow",
-        "shell", "shore", "silver", "sky", "slate", "snow", "spark", "spirit",
-        "spruce", "star", "stone", "storm", "stream", "summer", "summit", "sunset",
-        "swift", "temple", "thistle", "thunder", "tiger", "timber", "torch", "trail",
-        "treasure", "tree", "trout", "tulip", "turtle", "twilight", "umbrella", "valley",
-        "velvet", "violet", "volcano", "wave", "wheat", "willow", "wind", "winter",
-        "wolf", "wonder", "woods", "yarn", "yellow", "zebra", "zenith", "zephyr",
-        "zinc", "amber", "aspen", "atlas", "basil", "birch", "brook", "canyon",
-    ];
-
-    let mut rng = OsRng;
-    let selected: Vec<&str> = words.choose_multiple(&mut rng, 6).cloned().collect();
-    selected.join("-")
+fn generate_passphrase(
+    options: Option<passphrase::PassphraseOptions>,
+) -> Result<passphrase::GeneratedPassphrase, String> {
+    passphrase::generate(&options.unwrap_or_default()).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -282,7 +601,186 @@ fn verify_app_password(password: String) -> Result<bool, String> {
     let hash = storage::load_password_hash()
         .map_err(|e| e.to_string())?
         .ok_or("No password set")?;
-    storage::verify_password(&password, &hash).map_err(|e| e.to_string())
+    let valid = storage::verify_password(&password, &hash).map_err(|e| e.to_string())?;
+    if valid {
+        // Unlocking the app checks in the dead man's switch, but not time-locked exports
+        let _ = record_check_in(timelock::CheckIn::AppUnlock);
+    }
+    Ok(valid)
+}
+
+fn record_check_in(kind: timelock::CheckIn) -> Result<timelock::ReleaseOverview, String> {
+    if let Some(mut switch) = storage::load_dead_man_switch().map_err(|e| e.to_string())? {
+        switch.check_in(today());
+        storage::save_dead_man_switch(&switch).map_err(|e| e.to_string())?;
+    }
+    let mut store = storage::load_release_store().map_err(|e| e.to_string())?;
+    if !store.keys.is_empty() && store.check_in(kind, today()) {
+        storage::save_release_store(&store).map_err(|e| e.to_string())?;
+    }
+    Ok(store.overview(today()))
+}
+
+#[tauri::command]
+fn get_time_locks() -> Result<timelock::ReleaseOverview, String> {
+    let store = storage::load_release_store().map_err(|e| e.to_string())?;
+    Ok(store.overview(today()))
+}
+
+#[tauri::command]
+fn time_lock_check_in() -> Result<timelock::ReleaseOverview, String> {
+    record_check_in(timelock::CheckIn::Explicit)
+}
+
+#[tauri::command]
+fn get_release_code(id: String) -> Result<String, String> {
+    let store = storage::load_release_store().map_err(|e| e.to_string())?;
+    store.release_code(&id, today()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_dead_man_switch() -> Result<Option<deadman::DeadManStatus>, String> {
+    let switch = storage::load_dead_man_switch().map_err(|e| e.to_string())?;
+    Ok(switch.map(|s| s.status()))
+}
+
+#[tauri::command]
+fn configure_dead_man_switch(settings: deadman::DeadManSettings) -> Result<deadman::DeadManStatus, ExportCommandError> {
+    if let Some(passphrase) = settings.passphrase.as_deref().filter(|p| !p.is_empty()) {
+        validate_passphrase(passphrase)?;
+        // Sent to someone else with nobody watching, so the same bar as a manual export
+        let kdf = BrowserKdf::for_export(settings.memory_hard_kdf);
+        strength::assess_passphrase_export(passphrase, kdf).require(settings.acknowledge_weak)?;
+    }
+    let existing = storage::load_dead_man_switch().map_err(|e| e.to_string())?;
+    let switch = deadman::DeadManSwitch::configure(existing, settings, today()).map_err(|e| e.to_string())?;
+    storage::save_dead_man_switch(&switch).map_err(|e| e.to_string())?;
+    Ok(switch.status())
+}
+
+#[tauri::command]
+fn delete_dead_man_switch() -> Result<(), String> {
+    storage::delete_dead_man_switch().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn dead_man_check_in() -> Result<Option<deadman::DeadManStatus>, String> {
+    record_check_in(timelock::CheckIn::Explicit)?;
+    get_dead_man_switch()
+}
+
+/// Sends an export through the configured transport right away, to test it
+#[tauri::command]
+async fn send_dead_man_test() -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(|| {
+        let switch = storage::load_dead_man_switch()
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| deadman::DeadManError::NotConfigured.to_string())?;
+        switch.deliver(today()).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+fn get_auto_export() -> Result<Option<autoexport::AutoExportStatus>, String> {
+    let auto_export = storage::load_auto_export().map_err(|e| e.to_string())?;
+    Ok(auto_export.map(|a| a.status()))
+}
+
+#[tauri::command]
+fn configure_auto_export(settings: autoexport::AutoExportSettings) -> Result<autoexport::AutoExportStatus, ExportCommandError> {
+    if let Some(passphrase) = settings.passphrase.as_deref().filter(|p| !p.is_empty()) {
+        validate_passphrase(passphrase)?;
+        // Written unattended into a synced folder, so the same bar as a manual export
+        let kdf = BrowserKdf::for_export(settings.memory_hard_kdf);
+        strength::assess_passphrase_export(passphrase, kdf).require(settings.acknowledge_weak)?;
+    }
+    let existing = storage::load_auto_export().map_err(|e| e.to_string())?;
+    let auto_export = autoexport::AutoExport::configure(existing, settings).map_err(|e| e.to_string())?;
+    storage::save_auto_export(&auto_export).map_err(|e| e.to_string())?;
+    Ok(auto_export.status())
+}
+
+#[tauri::command]
+fn delete_auto_export() -> Result<(), String> {
+    storage::delete_auto_export().map_err(|e| e.to_string())
+}
+
+/// Writes an automatic export right away, without waiting for a change
+#[tauri::command]
+async fn run_auto_export_now() -> Result<autoexport::AutoExportStatus, String> {
+    tauri::async_runtime::spawn_blocking(|| autoexport::run_now(chrono::Utc::now()).map_err(|e| e.to_string()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// The SMTP settings from the last emailed export, without the password
+#[tauri::command]
+fn get_email_settings() -> Result<Option<delivery::SmtpSettings>, String> {
+    let settings = storage::load_email_settings().map_err(|e| e.to_string())?;
+    Ok(settings.map(|s| delivery::SmtpSettings { password: None, ..s }))
+}
+
+/// Emails a passphrase export to the given recipients, with the hint sent separately
+#[tauri::command]
+async fn email_export(
+    state: State<'_, AppState>,
+    passphrase: String,
+    include_welcome_screen: Option<bool>,
+    memory_hard_kdf: Option<bool>,
+    acknowledge_weak: Option<bool>,
+    passphrase_hint: Option<String>,
+    smtp: delivery::SmtpSettings,
+    locale: Option<String>,
+) -> Result<String, ExportCommandError> {
+    validate_passphrase(&passphrase)?;
+    if let Some(hint) = passphrase_hint.as_deref() {
+        if hint.trim() == passphrase.trim() {
+            return Err("The hint can't be the passphrase itself".to_string().into());
+        }
+    }
+    let kdf = BrowserKdf::for_export(memory_hard_kdf.unwrap_or(false));
+    strength::assess_passphrase_export(&passphrase, kdf).require(acknowledge_weak.unwrap_or(false))?;
+    let locale = export_locale(locale)?;
+    let doc = state.document.lock().map_err(|e| e.to_string())?.clone();
+
+    let mut smtp = smtp;
+    if let Some(previous) = storage::load_email_settings().map_err(|e| e.to_string())? {
+        smtp.keep_password(&previous);
+    }
+    let sent = tauri::async_runtime::spawn_blocking(move || -> Result<String, String> {
+        use delivery::Transport;
+
+        let html = export::generate_encrypted_html(&doc, &passphrase, include_welcome_screen.unwrap_or(false), kdf, &export_theme()?, &locale)
+            .map_err(|e| e.to_string())?;
+        let html = sign_export(html)?;
+        let creator = doc.meta.creator_name.trim();
+        let creator = if creator.is_empty() { locale.t("email.owner") } else { creator };
+        let file_name = format!("honey-did-{}.html", today().format("%Y-%m-%d"));
+        let subject = locale.format("email.subject", &[("name", creator)]);
+        let delivery = delivery::Delivery {
+            passphrase_hint: delivery::HintEmail::new(&locale, &subject, &file_name, passphrase_hint.as_deref()),
+            file_name,
+            html,
+            subject,
+            message: locale.format("email.body", &[("name", creator)]),
+        };
+        let sent = smtp.deliver(&delivery).map_err(|e| e.to_string())?;
+        storage::save_email_settings(&smtp).map_err(|e| e.to_string())?;
+        Ok(sent)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    Ok(sent)
+}
+
+#[tauri::command]
+fn delete_time_lock(id: String) -> Result<timelock::ReleaseOverview, String> {
+    let mut store = storage::load_release_store().map_err(|e| e.to_string())?;
+    store.remove(&id).map_err(|e| e.to_string())?;
+    storage::save_release_store(&store).map_err(|e| e.to_string())?;
+    Ok(store.overview(today()))
 }
 
 #[tauri::command]
@@ -360,6 +858,11 @@ fn clear_all_data(app: tauri::AppHandle, state: State<AppState>, password: Strin
     storage::delete_document().map_err(|e| e.to_string())?;
     storage::delete_password_hash().map_err(|e| e.to_string())?;
     storage::delete_settings().map_err(|e| e.to_string())?;
+    storage::delete_dead_man_switch().map_err(|e| e.to_string())?;
+    storage::delete_email_settings().map_err(|e| e.to_string())?;
+    storage::delete_auto_export().map_err(|e| e.to_string())?;
+    storage::delete_export_theme().map_err(|e| e.to_string())?;
+    storage::delete_signing_key().map_err(|e| e.to_string())?;
     let mut doc = state.document.lock().map_err(|e| e.to_string())?;
     *doc = LegacyDocument::default();
     Ok(())
@@ -383,6 +886,11 @@ fn clear_all_data(state: State<AppState>, password: String) -> Result<(), String
     storage::delete_document().map_err(|e| e.to_string())?;
     storage::delete_password_hash().map_err(|e| e.to_string())?;
     storage::delete_settings().map_err(|e| e.to_string())?;
+    storage::delete_dead_man_switch().map_err(|e| e.to_string())?;
+    storage::delete_email_settings().map_err(|e| e.to_string())?;
+    storage::delete_auto_export().map_err(|e| e.to_string())?;
+    storage::delete_export_theme().map_err(|e| e.to_string())?;
+    storage::delete_signing_key().map_err(|e| e.to_string())?;
     let mut doc = state.document.lock().map_err(|e| e.to_string())?;
     *doc = LegacyDocument::default();
     Ok(())
@@ -403,6 +911,11 @@ fn force_clear_all_data(app: tauri::AppHandle, state: State<AppState>, confirmat
     storage::delete_document().map_err(|e| e.to_string())?;
     storage::delete_password_hash().map_err(|e| e.to_string())?;
     storage::delete_settings().map_err(|e| e.to_string())?;
+    storage::delete_dead_man_switch().map_err(|e| e.to_string())?;
+    storage::delete_email_settings().map_err(|e| e.to_string())?;
+    storage::delete_auto_export().map_err(|e| e.to_string())?;
+    storage::delete_export_theme().map_err(|e| e.to_string())?;
+    storage::delete_signing_key().map_err(|e| e.to_string())?;
     let mut doc = state.document.lock().map_err(|e| e.to_string())?;
     *doc = LegacyDocument::default();
     Ok(())
@@ -419,6 +932,11 @@ fn force_clear_all_data(state: State<AppState>, confirmation: String) -> Result<
     storage::delete_document().map_err(|e| e.to_string())?;
     storage::delete_password_hash().map_err(|e| e.to_string())?;
     storage::delete_settings().map_err(|e| e.to_string())?;
+    storage::delete_dead_man_switch().map_err(|e| e.to_string())?;
+    storage::delete_email_settings().map_err(|e| e.to_string())?;
+    storage::delete_auto_export().map_err(|e| e.to_string())?;
+    storage::delete_export_theme().map_err(|e| e.to_string())?;
+    storage::delete_signing_key().map_err(|e| e.to_string())?;
     let mut doc = state.document.lock().map_err(|e| e.to_string())?;
     *doc = LegacyDocument::default();
     Ok(())
@@ -439,6 +957,11 @@ fn clear_data_on_exit(state: State<AppState>) -> Result<(), String> {
     storage::delete_document().map_err(|e| e.to_string())?;
     storage::delete_password_hash().map_err(|e| e.to_string())?;
     storage::delete_settings().map_err(|e| e.to_string())?;
+    storage::delete_dead_man_switch().map_err(|e| e.to_string())?;
+    storage::delete_email_settings().map_err(|e| e.to_string())?;
+    storage::delete_auto_export().map_err(|e| e.to_string())?;
+    storage::delete_export_theme().map_err(|e| e.to_string())?;
+    // The signing key stays, so the fingerprint given to an executor keeps matching new exports
     let mut doc = state.document.lock().map_err(|e| e.to_string())?;
     *doc = LegacyDocument::default();
     Ok(())
@@ -600,11 +1123,14 @@ pub fn run() {
             if let Ok(mut doc) = state.document.lock() {
                 *doc = loaded;
             }
+            deadman::spawn_scheduler();
+            autoexport::spawn_scheduler();
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             get_document,
             update_document,
+            search_document,
             export_html,
             export_html_with_questions,
             save_html_to_downloads,
@@ -612,10 +1138,41 @@ pub fn run() {
             save_export,
             save_export_with_dialog,
             save_export_with_questions,
+            save_archive_export,
+            save_archive_to_downloads,
             get_print_html,
+            get_export_locales,
+            get_export_theme,
+            set_export_theme,
+            theme_logo_data_uri,
             import_file,
+            import_archive,
+            check_export_signature,
+            get_signing_fingerprint,
             merge_document,
+            preview_csv_import,
+            import_csv,
+            import_vcard,
+            export_contacts_vcf,
+            get_calendar_report,
+            preview_password_import,
+            import_passwords,
             generate_passphrase,
+            get_time_locks,
+            time_lock_check_in,
+            get_release_code,
+            delete_time_lock,
+            get_dead_man_switch,
+            configure_dead_man_switch,
+            delete_dead_man_switch,
+            dead_man_check_in,
+            send_dead_man_test,
+            get_email_settings,
+            email_export,
+            get_auto_export,
+            configure_auto_export,
+            delete_auto_export,
+            run_auto_export_now,
             set_app_password,
             verify_app_password,
             has_app_password,
@@ -0,0 +1,412 @@
+use crate::archive::attachment_path;
+use crate::models::{Contact, CustomSubsection, FieldType, FileAttachment, FormElement, LegacyDocument};
+
+/// Escapes characters that would otherwise be read as Markdown formatting
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.trim().chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Escapes link targets; archive paths only need spaces and parentheses encoded
+fn escape_link(path: &str) -> String {
+    path.replace(' ', "%20").replace('(', "%28").replace(')', "%29")
+}
+
+struct Markdown {
+    out: String,
+}
+
+impl Markdown {
+    fn heading(&mut self, level: usize, text: &str) {
+        self.out.push_str(&format!("\n{} {}\n\n", "#".repeat(level), escape(text)));
+    }
+
+    /// An entry with a bold title and one bullet per non-empty field
+    fn item(&mut self, title: &str, fields: &[(&str, &str)]) {
+        let title = if title.trim().is_empty() { "(untitled)" } else { title };
+        self.out.push_str(&format!("**{}**\n\n", escape(title)));
+        for (label, value) in fields {
+            self.field(label, value);
+        }
+        self.out.push('\n');
+    }
+
+    fn field(&mut self, label: &str, value: &str) {
+        if value.trim().is_empty() {
+            return;
+        }
+        // Keep multi-line values inside the list item
+        let value = escape(value).replace('\n', "  \n  ");
+        self.out.push_str(&format!("- {}: {}\n", label, value));
+    }
+
+    fn contact(&mut self, contact: &Contact, role_label: &str) {
+        if contact.name.trim().is_empty() {
+            return;
+        }
+        self.item(
+            &contact.name,
+            &[(role_label, &contact.relationship), ("Phone", &contact.phone), ("Email", &contact.email), ("Notes", &contact.notes)],
+        );
+    }
+
+    fn notes(&mut self, notes: &str) {
+        if notes.trim().is_empty() {
+            return;
+        }
+        for line in escape(notes).lines() {
+            self.out.push_str(&format!("> {}\n", line));
+        }
+        self.out.push('\n');
+    }
+
+    fn attachments(&mut self, section_key: &str, attachments: &[FileAttachment]) {
+        if attachments.is_empty() {
+            return;
+        }
+        self.out.push_str("Attachments:\n\n");
+        for a in attachments {
+            self.out.push_str(&format!("- [{}]({})\n", escape(&a.name), escape_link(&attachment_path(section_key, a))));
+        }
+        self.out.push('\n');
+    }
+
+    fn custom_subsection(&mut self, subsection: &CustomSubsection) {
+        if subsection.items.is_empty() {
+            return;
+        }
+        self.heading(3, &subsection.name);
+
+        let fields: Vec<(String, String, FieldType)> = if subsection.form_elements.is_empty() {
+            subsection.field_definitions.iter().map(|f| (f.id.clone(), f.name.clone(), f.field_type.clone())).collect()
+        } else {
+            subsection
+                .form_elements
+                .iter()
+                .filter_map(|el| match el {
+                    FormElement::Field { id, name, field_type } => Some((id.clone(), name.clone(), field_type.clone())),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        for item in &subsection.items {
+            let values: Vec<(&str, String)> = fields
+                .iter()
+                .filter_map(|(id, name, field_type)| {
+                    let value = item.values.get(id)?;
+                    let value = match field_type {
+                        FieldType::Boolean => if value == "true" { "Yes" } else { "No" }.to_string(),
+                        _ => value.clone(),
+                    };
+                    Some((name.as_str(), value))
+                })
+                .collect();
+            let title = values.first().map(|(_, v)| v.clone()).unwrap_or_default();
+            let mut rest: Vec<(&str, &str)> = values.iter().skip(1).map(|(k, v)| (*k, v.as_str())).collect();
+            if let Some(notes) = item.values.get("_notes") {
+                rest.push(("Notes", notes));
+            }
+            self.item(&title, &rest);
+        }
+    }
+}
+
+/// Renders the whole document as Markdown, for readers who want plain files
+/// instead of the self-decrypting HTML. Attachments link to their paths in the archive.
+pub fn render_markdown(document: &LegacyDocument) -> String {
+    let mut md = Markdown { out: String::new() };
+    md.out.push_str("# Honey Did - Legacy Document\n\n");
+    if !document.meta.creator_name.is_empty() {
+        md.out.push_str(&format!("Prepared by {}\n", escape(&document.meta.creator_name)));
+    }
+    if !document.meta.updated_at.is_empty() {
+        md.out.push_str(&format!("\nLast updated {}\n", escape(&document.meta.updated_at)));
+    }
+
+    let f = &document.financial;
+    md.heading(2, "Financial");
+    if !f.bank_accounts.is_empty() {
+        md.heading(3, "Bank Accounts");
+        for a in &f.bank_accounts {
+            md.item(&a.name, &[("Institution", &a.institution), ("Type", &a.account_type), ("Last 4", &a.last_four), ("Notes", &a.notes)]);
+        }
+    }
+    if !f.credit_cards.is_empty() {
+        md.heading(3, "Credit Cards");
+        for c in &f.credit_cards {
+            md.item(&c.name, &[("Issuer", &c.issuer), ("Last 4", &c.last_four), ("Notes", &c.notes)]);
+        }
+    }
+    if !f.investments.is_empty() {
+        md.heading(3, "Investments");
+        for i in &f.investments {
+            md.item(&i.name, &[("Institution", &i.institution), ("Type", &i.account_type), ("Notes", &i.notes)]);
+        }
+    }
+    if !f.debts.is_empty() {
+        md.heading(3, "Debts");
+        for d in &f.debts {
+            md.item(&d.name, &[("Lender", &d.lender), ("Notes", &d.notes)]);
+        }
+    }
+    md.notes(&f.notes);
+    md.attachments("financial", &f.attachments);
+
+    md.heading(2, "Insurance");
+    for p in &document.insurance.policies {
+        md.item(&p.policy_type, &[("Provider", &p.provider), ("Policy #", &p.policy_number), ("Contact", &p.contact), ("Notes", &p.notes)]);
+    }
+    md.notes(&document.insurance.notes);
+    md.attachments("insurance", &document.insurance.attachments);
+
+    md.heading(2, "Bills");
+    for b in &document.bills.bills {
+        let autopay = if b.autopay { "Yes" } else { "No" };
+        md.item(&b.name, &[("Provider", &b.provider), ("Amount", &b.amount), ("Due day", &b.due_day), ("Auto-pay", autopay), ("Notes", &b.notes)]);
+    }
+    md.notes(&document.bills.notes);
+    md.attachments("bills", &document.bills.attachments);
+
+    let p = &document.property;
+    md.heading(2, "Property");
+    if !p.properties.is_empty() {
+        md.heading(3, "Properties");
+        for x in &p.properties {
+            md.item(&x.name, &[("Address", &x.address), ("Notes", &x.notes)]);
+        }
+    }
+    if !p.vehicles.is_empty() {
+        md.heading(3, "Vehicles");
+        for x in &p.vehicles {
+            md.item(&x.name, &[("Details", &x.details), ("Notes", &x.notes)]);
+        }
+    }
+    if !p.valuables.is_empty() {
+        md.heading(3, "Valuables");
+        for x in &p.valuables {
+            md.item(&x.name, &[("Location", &x.location), ("Notes", &x.notes)]);
+        }
+    }
+    md.notes(&p.notes);
+    md.attachments("property", &p.attachments);
+
+    let l = &document.legal;
+    md.heading(2, "Legal");
+    md.field("Will location", &l.will_location);
+    md.field("Power of attorney", &l.power_of_attorney);
+    md.out.push('\n');
+    if !l.attorney.name.trim().is_empty() {
+        md.heading(3, "Attorney");
+        md.contact(&l.attorney, "Firm");
+    }
+    if !l.trusts.is_empty() {
+        md.heading(3, "Trusts");
+        for t in &l.trusts {
+            md.item(&t.name, &[("Trustee", &t.trustee), ("Notes", &t.notes)]);
+        }
+    }
+    md.notes(&l.notes);
+    md.attachments("legal", &l.attachments);
+
+    let d = &document.digital;
+    md.heading(2, "Digital Life");
+    for (title, accounts) in [
+        ("Email Accounts", &d.email_accounts),
+        ("Social Media", &d.social_media),
+        ("Other Online Accounts", &d.online_accounts),
+    ] {
+        if !accounts.is_empty() {
+            md.heading(3, title);
+            for a in accounts {
+                md.item(
+                    &a.name,
+                    &[("Website", &a.url), ("Username", &a.username), ("Password", &a.password), ("Recovery", &a.recovery_hint), ("Notes", &a.notes)],
+                );
+            }
+        }
+    }
+    let pm = &d.password_manager;
+    if !pm.name.trim().is_empty() {
+        md.heading(3, "Password Manager");
+        md.item(&pm.name, &[("Master password hint", &pm.master_password_hint), ("Recovery", &pm.recovery_method), ("Notes", &pm.notes)]);
+    }
+    md.notes(&d.notes);
+    md.attachments("digital", &d.attachments);
+
+    let h = &document.household;
+    md.heading(2, "Household");
+    if !h.maintenance_items.is_empty() {
+        md.heading(3, "Maintenance");
+        for m in &h.maintenance_items {
+            md.item(&m.name, &[("Frequency", &m.frequency), ("Last done", &m.last_done), ("Notes", &m.notes)]);
+        }
+    }
+    if !h.contractors.is_empty() {
+        md.heading(3, "Contractors");
+        for c in &h.contractors {
+            md.contact(c, "Service");
+        }
+    }
+    if !h.how_things_work.is_empty() {
+        md.heading(3, "How Things Work");
+        for x in &h.how_things_work {
+            md.item(&x.name, &[("Instructions", &x.instructions)]);
+        }
+    }
+    md.notes(&h.notes);
+    md.attachments("household", &h.attachments);
+
+    let pe = &document.personal;
+    md.heading(2, "Personal Wishes");
+    md.field("Funeral preferences", &pe.funeral_preferences);
+    md.field("Obituary notes", &pe.obituary_notes);
+    md.out.push('\n');
+    if !pe.messages.is_empty() {
+        md.heading(3, "Personal Messages");
+        for m in &pe.messages {
+            md.item(&format!("To: {}", m.recipient), &[("Message", &m.message)]);
+        }
+    }
+    md.notes(&pe.notes);
+    md.attachments("personal", &pe.attachments);
+
+    let c = &document.contacts;
+    md.heading(2, "Important Contacts");
+    for (title, list) in [("Emergency Contacts", &c.emergency_contacts), ("Family", &c.family), ("Professionals", &c.professionals)] {
+        if !list.is_empty() {
+            md.heading(3, title);
+            for contact in list {
+                md.contact(contact, "Relationship");
+            }
+        }
+    }
+    md.notes(&c.notes);
+    md.attachments("contacts", &c.attachments);
+
+    md.heading(2, "Medical");
+    for m in &document.medical.family_members {
+        md.heading(3, &m.name);
+        for doctor in &m.doctors {
+            md.contact(doctor, "Specialty");
+        }
+        for med in &m.medications {
+            md.item(&med.name, &[("Dosage", &med.dosage), ("Frequency", &med.frequency), ("Prescriber", &med.prescriber), ("Notes", &med.notes)]);
+        }
+        md.field("Conditions", &m.conditions.join(", "));
+        md.field("Allergies", &m.allergies.join(", "));
+        if !m.pharmacy.name.trim().is_empty() {
+            md.field("Pharmacy", &format!("{} {}", m.pharmacy.name, m.pharmacy.phone));
+        }
+        md.field("Notes", &m.notes);
+        md.out.push('\n');
+    }
+    md.notes(&document.medical.notes);
+    md.attachments("medical", &document.medical.attachments);
+
+    md.heading(2, "Pets");
+    for pet in &document.pets.pets {
+        md.item(&pet.name, &[("Species", &pet.species), ("Breed", &pet.breed), ("Feeding", &pet.feeding), ("Care notes", &pet.care_notes)]);
+        md.contact(&pet.vet, "Veterinarian");
+        for med in &pet.medications {
+            md.item(&med.name, &[("Dosage", &med.dosage), ("Frequency", &med.frequency), ("Notes", &med.notes)]);
+        }
+    }
+    md.notes(&document.pets.notes);
+    md.attachments("pets", &document.pets.attachments);
+
+    for section in &document.custom_sections {
+        md.heading(2, &section.name);
+        for sub in &section.subsections {
+            md.custom_subsection(sub);
+        }
+        md.attachments(&format!("custom/{}", section.id), &section.attachments);
+    }
+
+    // Drop headings of empty sections
+    remove_empty_headings(&md.out)
+}
+
+fn heading_level(line: &str) -> usize {
+    let hashes = line.chars().take_while(|c| *c == '#').count();
+    if hashes > 0 && line[hashes..].starts_with(' ') { hashes } else { 0 }
+}
+
+/// Removes section headings with nothing under them and collapses runs of blank lines
+fn remove_empty_headings(markdown: &str) -> String {
+    let mut lines: Vec<&str> = markdown.lines().collect();
+
+    // Removing an empty "###" can leave its "##" empty too, so repeat until nothing changes
+    loop {
+        let empty = (0..lines.len()).find(|&i| {
+            let level = heading_level(lines[i]);
+            level >= 2
+                && lines[i + 1..]
+                    .iter()
+                    .find(|l| !l.trim().is_empty())
+                    .is_none_or(|next| (1..=level).contains(&heading_level(next)))
+        });
+        match empty {
+            Some(i) => {
+                lines.remove(i);
+            }
+            None => break,
+        }
+    }
+
+    let mut out = String::new();
+    let mut previous_blank = false;
+    for line in lines {
+        let blank = line.trim().is_empty();
+        if !(blank && previous_blank) {
+            out.push_str(line);
+            out.push('\n');
+        }
+        previous_blank = blank;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BankAccount, CustomItem, CustomSection};
+
+    #[test]
+    fn test_renders_sections_and_skips_empty_ones() {
+        let mut doc = LegacyDocument::default();
+        doc.meta.creator_name = "Pat".into();
+        doc.financial.bank_accounts.push(BankAccount {
+            name: "Joint *checking*".into(),
+            institution: "First Bank".into(),
+            ..Default::default()
+        });
+        doc.financial.attachments.push(FileAttachment { id: "a1".into(), name: "statement (1).pdf".into(), ..Default::default() });
+        doc.custom_sections.push(CustomSection {
+            id: "c1".into(),
+            name: "Storage".into(),
+            subsections: vec![CustomSubsection {
+                id: "s1".into(),
+                name: "Units".into(),
+                form_elements: vec![FormElement::Field { id: "f1".into(), name: "Unit".into(), field_type: FieldType::Text }],
+                items: vec![CustomItem { id: "i1".into(), values: [("f1".to_string(), "B12".to_string())].into() }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        });
+
+        let md = render_markdown(&doc);
+
+        assert!(md.contains("## Financial\n\n### Bank Accounts\n\n**Joint \\*checking\\***\n\n- Institution: First Bank\n"));
+        assert!(md.contains("- [statement (1).pdf](attachments/financial/a1.pdf)"));
+        assert!(md.contains("## Storage\n\n### Units\n\n**B12**"));
+        assert!(!md.contains("## Insurance"));
+        assert!(!md.contains("## Pets"));
+    }
+}
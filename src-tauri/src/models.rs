@@ -183,6 +183,8 @@ pub struct Trust {
 pub struct DigitalSection {
     pub email_accounts: Vec<DigitalAccount>,
     pub social_media: Vec<DigitalAccount>,
+    #[serde(default)]
+    pub online_accounts: Vec<DigitalAccount>,
     pub password_manager: PasswordManagerInfo,
     pub notes: String,
     #[serde(default)]
@@ -195,6 +197,11 @@ pub struct DigitalAccount {
     pub username: String,
     pub recovery_hint: String,
     pub notes: String,
+    #[serde(default)]
+    pub url: String,
+    /// Only filled in when the user opts in to importing secrets from a password manager
+    #[serde(default)]
+    pub password: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -340,6 +347,9 @@ pub struct MessageSlide {
     pub text: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub answer: Option<String>,
+    /// Other spellings that also unlock, e.g. "Bustopher" for "Buster"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternate_answers: Vec<String>,
     pub transition: SlideTransition,
 }
 
@@ -364,6 +374,12 @@ pub struct WelcomeScreen {
     pub slides: Vec<MessageSlide>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub fallback_passphrase: Option<String>,
+    /// How many question answers unlock the file; `None` means all of them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required_answers: Option<usize>,
+    /// Wrong tries at the questions before the exported file only opens with the backup passphrase; `None` means no limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attempt_limit: Option<u32>,
 }
 
 // --- Custom Sections ---
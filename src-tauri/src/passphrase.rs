@@ -0,0 +1,202 @@
+use rand::rngs::OsRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// The EFF large diceware wordlist, all 7776 words, one per line without
+/// EFF's dice numbers. `parse_wordlist` skips a "11111<TAB>" prefix if one is there.
+const WORDLIST_TXT: &str = include_str!("eff_large_wordlist.txt");
+
+pub const MIN_WORDS: usize = 4;
+pub const MAX_WORDS: usize = 12;
+const MAX_SEPARATOR_CHARS: usize = 3;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PassphraseOptions {
+    pub word_count: usize,
+    pub separator: String,
+    /// Capitalize the first letter of every word
+    pub capitalize: bool,
+    /// Append a random digit to one randomly chosen word
+    pub include_digit: bool,
+}
+
+impl Default for PassphraseOptions {
+    fn default() -> Self {
+        Self {
+            word_count: 6,
+            separator: "-".to_string(),
+            capitalize: false,
+            include_digit: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GeneratedPassphrase {
+    pub passphrase: String,
+    /// Bits of entropy against an attacker who knows the wordlist and the options
+    pub entropy_bits: f64,
+}
+
+#[derive(Debug)]
+pub enum PassphraseError {
+    WordCount(usize),
+    Separator,
+}
+
+impl std::fmt::Display for PassphraseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PassphraseError::WordCount(count) => write!(
+                f,
+                "Passphrases need between {} and {} words (got {})",
+                MIN_WORDS, MAX_WORDS, count
+            ),
+            PassphraseError::Separator => write!(
+                f,
+                "Separator must be at most {} characters, with no letters or digits",
+                MAX_SEPARATOR_CHARS
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PassphraseError {}
+
+fn parse_wordlist(text: &str) -> Vec<&str> {
+    text.lines()
+        .filter_map(|line| line.split('\t').next_back())
+        .map(str::trim)
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+fn wordlist() -> &'static [&'static str] {
+    static WORDS: OnceLock<Vec<&'static str>> = OnceLock::new();
+    WORDS.get_or_init(|| parse_wordlist(WORDLIST_TXT))
+}
+
+/// Entropy of a passphrase generated with these options
+pub fn entropy_bits(options: &PassphraseOptions) -> f64 {
+    let mut bits = options.word_count as f64 * (wordlist().len() as f64).log2();
+    if options.include_digit {
+        // Which digit, and which word it's attached to
+        bits += 10f64.log2() + (options.word_count as f64).log2();
+    }
+    bits
+}
+
+fn capitalized(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+pub fn generate(options: &PassphraseOptions) -> Result<GeneratedPassphrase, PassphraseError> {
+    if !(MIN_WORDS..=MAX_WORDS).contains(&options.word_count) {
+        return Err(PassphraseError::WordCount(options.word_count));
+    }
+    // Letters or digits in the separator would blur word boundaries
+    if options.separator.chars().count() > MAX_SEPARATOR_CHARS
+        || options.separator.chars().any(char::is_alphanumeric)
+    {
+        return Err(PassphraseError::Separator);
+    }
+
+    let mut rng = OsRng;
+    let words = wordlist();
+    // Words are drawn independently, so repeats are possible and the entropy is exact
+    let mut chosen: Vec<String> = (0..options.word_count)
+        .map(|_| {
+            let word = words.choose(&mut rng).copied().unwrap_or_default();
+            if options.capitalize { capitalized(word) } else { word.to_string() }
+        })
+        .collect();
+
+    if options.include_digit {
+        let index = rng.gen_range(0..chosen.len());
+        let digit = rng.gen_range(0..10u8);
+        chosen[index].push(char::from(b'0' + digit));
+    }
+
+    Ok(GeneratedPassphrase {
+        passphrase: chosen.join(&options.separator),
+        entropy_bits: entropy_bits(options),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_wordlist_words_are_unique() {
+        let words = wordlist();
+        let unique: HashSet<&str> = words.iter().copied().collect();
+        assert_eq!(unique.len(), words.len());
+    }
+
+    #[test]
+    fn test_wordlist_is_large_and_lowercase() {
+        let words = wordlist();
+        assert_eq!(words.len(), 7776);
+        assert!(words.windows(2).all(|pair| pair[0] < pair[1]), "not sorted or has duplicates");
+        for word in words {
+            assert!((3..=9).contains(&word.len()), "{:?}", word);
+            assert!(word.chars().all(|c| c.is_ascii_lowercase() || c == '-'), "{:?}", word);
+        }
+    }
+
+    #[test]
+    fn test_wordlist_accepts_dice_numbers() {
+        assert_eq!(parse_wordlist("11111\tabacus\n11112\tabdomen\n"), vec!["abacus", "abdomen"]);
+    }
+
+    #[test]
+    fn test_default_passphrase() {
+        // A few EFF words contain hyphens, so split on a space instead of the default separator
+        let options = PassphraseOptions { separator: " ".to_string(), ..Default::default() };
+        let generated = generate(&options).unwrap();
+        let words: Vec<&str> = generated.passphrase.split(' ').collect();
+        assert_eq!(words.len(), 6);
+        assert!(words.iter().all(|w| wordlist().contains(w)));
+        assert!(generated.entropy_bits > 77.0);
+    }
+
+    #[test]
+    fn test_options_shape_passphrase() {
+        let options = PassphraseOptions {
+            word_count: 5,
+            separator: " ".to_string(),
+            capitalize: true,
+            include_digit: true,
+        };
+        let generated = generate(&options).unwrap();
+        let words: Vec<&str> = generated.passphrase.split(' ').collect();
+        assert_eq!(words.len(), 5);
+        assert!(words.iter().all(|w| w.starts_with(|c: char| c.is_ascii_uppercase())));
+        assert_eq!(generated.passphrase.chars().filter(|c| c.is_ascii_digit()).count(), 1);
+        let expected = 5.0 * (wordlist().len() as f64).log2() + 10f64.log2() + 5f64.log2();
+        assert!((generated.entropy_bits - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rejects_bad_options() {
+        let too_few = PassphraseOptions { word_count: 3, ..Default::default() };
+        assert!(matches!(generate(&too_few), Err(PassphraseError::WordCount(3))));
+        let too_many = PassphraseOptions { word_count: 13, ..Default::default() };
+        assert!(matches!(generate(&too_many), Err(PassphraseError::WordCount(13))));
+        let lettered = PassphraseOptions { separator: "x".to_string(), ..Default::default() };
+        assert!(matches!(generate(&lettered), Err(PassphraseError::Separator)));
+        let long = PassphraseOptions { separator: "----".to_string(), ..Default::default() };
+        assert!(matches!(generate(&long), Err(PassphraseError::Separator)));
+        let empty = PassphraseOptions { separator: String::new(), ..Default::default() };
+        assert!(generate(&empty).is_ok());
+    }
+}
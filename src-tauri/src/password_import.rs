@@ -0,0 +1,500 @@
+use crate::csv_import::{normalize_header, parse_csv, CsvImportError};
+use crate::models::{DigitalAccount, LegacyDocument};
+use serde::{Deserialize, Serialize};
+
+/// Domains and service names that go into `email_accounts`
+const EMAIL_SERVICES: &[&str] = &[
+    "gmail", "googlemail", "outlook", "hotmail", "live", "msn", "yahoo", "icloud", "me", "mac", "aol", "proton",
+    "protonmail", "fastmail", "zoho", "gmx", "yandex", "tutanota", "hey", "mail",
+];
+
+/// Domains and service names that go into `social_media`
+const SOCIAL_SERVICES: &[&str] = &[
+    "facebook", "instagram", "twitter", "x", "linkedin", "tiktok", "snapchat", "pinterest", "reddit", "tumblr",
+    "youtube", "threads", "mastodon", "bsky", "discord", "whatsapp", "telegram", "nextdoor", "flickr", "myspace",
+];
+
+#[derive(Debug)]
+pub enum PasswordImportError {
+    ParseError(String),
+    UnrecognizedFormat,
+    EncryptedExport,
+    NoEntries,
+}
+
+impl std::fmt::Display for PasswordImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PasswordImportError::ParseError(msg) => write!(f, "Could not read export file: {}", msg),
+            PasswordImportError::UnrecognizedFormat => {
+                write!(f, "Unrecognized file. Export from your password manager as CSV (or Bitwarden JSON)")
+            }
+            PasswordImportError::EncryptedExport => {
+                write!(f, "This export is encrypted. Export again without a password to import it")
+            }
+            PasswordImportError::NoEntries => write!(f, "No logins were found in the file"),
+        }
+    }
+}
+
+impl std::error::Error for PasswordImportError {}
+
+impl From<CsvImportError> for PasswordImportError {
+    fn from(e: CsvImportError) -> Self {
+        match e {
+            CsvImportError::EmptyFile => PasswordImportError::UnrecognizedFormat,
+            other => PasswordImportError::ParseError(other.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PasswordSource {
+    Bitwarden,
+    OnePassword,
+    Keepass,
+    Lastpass,
+    Browser,
+    Generic,
+}
+
+/// Where an imported entry ended up in the Digital Life section
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountCategory {
+    Email,
+    Social,
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportedAccount {
+    pub category: AccountCategory,
+    pub name: String,
+    pub username: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PasswordImportReport {
+    pub source: PasswordSource,
+    pub email_accounts: usize,
+    pub social_media: usize,
+    pub online_accounts: usize,
+    /// Already in the document with the same name and username
+    pub duplicates: usize,
+    /// Secure notes, cards, identities and rows with neither a name nor a URL
+    pub skipped: usize,
+    pub accounts: Vec<ImportedAccount>,
+}
+
+/// Words in a custom field's name that mark it as a secret even when it also
+/// looks like a hint, e.g. "Recovery code"
+const SECRET_FIELD_WORDS: &[&str] = &["code", "key", "secret", "password", "pin", "answer", "seed", "phrase", "token"];
+
+/// Words in a custom field's name that mark it as a recovery hint
+const HINT_FIELD_WORDS: &[&str] = &["recovery", "backup email", "backup phone", "security question"];
+
+/// One login as read from the export, before secrets are dropped
+#[derive(Debug, Default)]
+struct Login {
+    name: String,
+    url: String,
+    username: String,
+    password: String,
+    totp: String,
+    notes: String,
+    /// Custom fields as (name, value, hidden)
+    fields: Vec<(String, String, bool)>,
+}
+
+/// Whether a custom field holds a recovery hint (imported by default) rather than a secret.
+/// "Password hint" is a hint; "Recovery code" is not.
+fn is_hint_field(name: &str, hidden: bool) -> bool {
+    if hidden {
+        return false;
+    }
+    let name = name.to_lowercase();
+    name.contains("hint")
+        || (HINT_FIELD_WORDS.iter().any(|w| name.contains(w)) && !SECRET_FIELD_WORDS.iter().any(|w| name.contains(w)))
+}
+
+/// Bitwarden's CSV packs custom fields into one column as "name: value" lines
+fn parse_field_lines(text: &str) -> Vec<(String, String, bool)> {
+    text.lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string(), false))
+        .filter(|(name, value, _)| !name.is_empty() && !value.is_empty())
+        .collect()
+}
+
+// Bitwarden unencrypted JSON export
+#[derive(Deserialize)]
+struct BitwardenExport {
+    #[serde(default)]
+    encrypted: bool,
+    #[serde(default)]
+    items: Vec<BitwardenItem>,
+}
+
+#[derive(Deserialize)]
+struct BitwardenItem {
+    #[serde(rename = "type")]
+    item_type: u8,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    notes: Option<String>,
+    #[serde(default)]
+    login: Option<BitwardenLogin>,
+    #[serde(default)]
+    fields: Option<Vec<BitwardenField>>,
+}
+
+#[derive(Deserialize)]
+struct BitwardenField {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    value: Option<String>,
+    #[serde(rename = "type", default)]
+    field_type: u8,
+}
+
+#[derive(Deserialize, Default)]
+struct BitwardenLogin {
+    #[serde(default)]
+    username: Option<String>,
+    #[serde(default)]
+    password: Option<String>,
+    #[serde(default)]
+    totp: Option<String>,
+    #[serde(default)]
+    uris: Option<Vec<BitwardenUri>>,
+}
+
+#[derive(Deserialize)]
+struct BitwardenUri {
+    #[serde(default)]
+    uri: Option<String>,
+}
+
+const BITWARDEN_LOGIN_TYPE: u8 = 1;
+const BITWARDEN_HIDDEN_FIELD: u8 = 1;
+
+fn parse_bitwarden_json(content: &str) -> Result<(Vec<Login>, usize), PasswordImportError> {
+    let export: BitwardenExport =
+        serde_json::from_str(content).map_err(|e| PasswordImportError::ParseError(e.to_string()))?;
+    if export.encrypted {
+        return Err(PasswordImportError::EncryptedExport);
+    }
+
+    let mut logins = Vec::new();
+    let mut skipped = 0;
+    for item in export.items {
+        if item.item_type != BITWARDEN_LOGIN_TYPE {
+            skipped += 1;
+            continue;
+        }
+        let login = item.login.unwrap_or_default();
+        logins.push(Login {
+            name: item.name.unwrap_or_default(),
+            url: login.uris.unwrap_or_default().into_iter().find_map(|u| u.uri).unwrap_or_default(),
+            username: login.username.unwrap_or_default(),
+            password: login.password.unwrap_or_default(),
+            totp: login.totp.unwrap_or_default(),
+            notes: item.notes.unwrap_or_default(),
+            fields: item
+                .fields
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|f| Some((f.name?, f.value?, f.field_type == BITWARDEN_HIDDEN_FIELD)))
+                .collect(),
+        });
+    }
+    Ok((logins, skipped))
+}
+
+/// Guesses the exporting app from its distinctive column names
+fn detect_source(headers: &[String]) -> PasswordSource {
+    let has = |name: &str| headers.iter().any(|h| h == name);
+    if has("loginuri") || has("loginusername") {
+        PasswordSource::Bitwarden
+    } else if has("otpauth") || (has("title") && has("archived")) {
+        PasswordSource::OnePassword
+    } else if has("grouping") || has("extra") {
+        PasswordSource::Lastpass
+    } else if has("loginname") || (has("group") && has("title")) {
+        PasswordSource::Keepass
+    } else if has("httprealm") || (has("name") && has("url") && has("note")) {
+        PasswordSource::Browser
+    } else {
+        PasswordSource::Generic
+    }
+}
+
+/// Column names used by Bitwarden, 1Password, KeePass/KeePassXC, LastPass, Chrome, Edge and Firefox
+fn column_aliases(field: &str) -> &'static [&'static str] {
+    match field {
+        "name" => &["name", "title", "account", "service"],
+        "url" => &["loginuri", "url", "website", "web site", "uri", "loginurl", "origin"],
+        "username" => &["loginusername", "username", "loginname", "user", "email", "login"],
+        "password" => &["loginpassword", "password"],
+        "totp" => &["logintotp", "totp", "otpauth", "onetimepassword"],
+        "notes" => &["notes", "note", "comments", "extra"],
+        "fields" => &["fields"],
+        "type" => &["type"],
+        _ => &[],
+    }
+}
+
+fn parse_password_csv(content: &str) -> Result<(PasswordSource, Vec<Login>, usize), PasswordImportError> {
+    let parsed = parse_csv(content)?;
+    let headers: Vec<String> = parsed.headers.iter().map(|h| normalize_header(h)).collect();
+    let column = |field: &str| {
+        column_aliases(field).iter().find_map(|alias| {
+            let alias = normalize_header(alias);
+            headers.iter().position(|h| *h == alias)
+        })
+    };
+
+    let (name, url, username) = (column("name"), column("url"), column("username"));
+    if name.is_none() && url.is_none() {
+        return Err(PasswordImportError::UnrecognizedFormat);
+    }
+    if username.is_none() && column("password").is_none() {
+        return Err(PasswordImportError::UnrecognizedFormat);
+    }
+    let (password, totp, notes, item_type) = (column("password"), column("totp"), column("notes"), column("type"));
+    let fields = column("fields");
+    // Columns of their own, such as "Recovery email" added to a KeePass export
+    let known = [name, url, username, password, totp, notes, item_type, fields];
+    let hint_columns: Vec<usize> = (0..parsed.headers.len())
+        .filter(|c| !known.contains(&Some(*c)) && is_hint_field(&parsed.headers[*c], false))
+        .collect();
+
+    let mut logins = Vec::new();
+    let mut skipped = 0;
+    for (_, row) in &parsed.rows {
+        let get = |col: Option<usize>| col.and_then(|c| row.get(c)).cloned().unwrap_or_default();
+        // Bitwarden puts secure notes, cards and identities in the same file
+        let kind = get(item_type);
+        if !kind.is_empty() && !kind.eq_ignore_ascii_case("login") {
+            skipped += 1;
+            continue;
+        }
+        logins.push(Login {
+            name: get(name),
+            url: get(url),
+            username: get(username),
+            password: get(password),
+            totp: get(totp),
+            notes: get(notes),
+            fields: parse_field_lines(&get(fields))
+                .into_iter()
+                .chain(hint_columns.iter().map(|&c| (parsed.headers[c].trim().to_string(), get(Some(c)), false)))
+                .collect(),
+        });
+    }
+    Ok((detect_source(&headers), logins, skipped))
+}
+
+/// Host part of a URL without scheme, port, path or a leading "www."
+fn url_host(url: &str) -> String {
+    let rest = url.split_once("://").map(|(_, r)| r).unwrap_or(url);
+    let host = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host = host.rsplit('@').next().unwrap_or(host);
+    let host = host.split(':').next().unwrap_or(host);
+    host.trim_start_matches("www.").to_lowercase()
+}
+
+/// Second-level label of a host, e.g. "mail.google.com" -> "google", "bbc.co.uk" -> "bbc"
+fn service_label(host: &str) -> &str {
+    let labels: Vec<&str> = host.split('.').filter(|l| !l.is_empty()).collect();
+    match labels.len() {
+        0 => "",
+        1 => labels[0],
+        n if labels[n - 1].len() == 2 && labels[n - 2].len() <= 3 && n >= 3 => labels[n - 3],
+        n => labels[n - 2],
+    }
+}
+
+fn categorize(login: &Login) -> AccountCategory {
+    let host = url_host(&login.url);
+    let label = service_label(&host);
+    let name = login.name.trim().to_lowercase();
+
+    // Gmail logins are usually saved against accounts.google.com
+    if EMAIL_SERVICES.contains(&label)
+        || host.starts_with("mail.")
+        || host == "accounts.google.com"
+        || EMAIL_SERVICES.contains(&name.as_str())
+    {
+        AccountCategory::Email
+    } else if SOCIAL_SERVICES.contains(&label) || SOCIAL_SERVICES.contains(&name.as_str()) {
+        AccountCategory::Social
+    } else {
+        AccountCategory::Other
+    }
+}
+
+fn to_account(login: Login, include_secrets: bool) -> Option<DigitalAccount> {
+    let name = if login.name.trim().is_empty() { url_host(&login.url) } else { login.name.trim().to_string() };
+    if name.is_empty() {
+        return None;
+    }
+
+    let (hints, other_fields): (Vec<_>, Vec<_>) = login
+        .fields
+        .into_iter()
+        .filter(|(_, value, _)| !value.trim().is_empty())
+        .partition(|(field, _, hidden)| is_hint_field(field, *hidden));
+    let mut account = DigitalAccount {
+        name,
+        username: login.username,
+        url: login.url,
+        recovery_hint: hints.iter().map(|(field, value, _)| format!("{}: {}", field, value.trim())).collect::<Vec<_>>().join("\n"),
+        ..Default::default()
+    };
+    // Free-text notes often hold recovery codes, and there's no telling them apart
+    // from harmless ones, so notes and other custom fields count as secrets too
+    if include_secrets {
+        account.password = login.password;
+        let mut notes: Vec<String> = vec![login.notes].into_iter().filter(|n| !n.is_empty()).collect();
+        notes.extend(other_fields.iter().map(|(field, value, _)| format!("{}: {}", field, value.trim())));
+        if !login.totp.is_empty() {
+            notes.push(format!("One-time code secret: {}", login.totp));
+        }
+        account.notes = notes.join("\n");
+    }
+    Some(account)
+}
+
+fn account_key(account: &DigitalAccount) -> (String, String) {
+    (account.name.trim().to_lowercase(), account.username.trim().to_lowercase())
+}
+
+/// Adds the logins from a password-manager export to the Digital Life section.
+/// Only names, usernames, URLs and recovery hints from custom fields are kept
+/// unless `include_secrets` is set.
+pub fn import_passwords(
+    document: &mut LegacyDocument,
+    content: &str,
+    include_secrets: bool,
+) -> Result<PasswordImportReport, PasswordImportError> {
+    let trimmed = content.trim_start_matches('\u{feff}').trim_start();
+    let (source, logins, skipped) = if trimmed.starts_with('{') {
+        let (logins, skipped) = parse_bitwarden_json(trimmed)?;
+        (PasswordSource::Bitwarden, logins, skipped)
+    } else {
+        parse_password_csv(content)?
+    };
+
+    let mut report = PasswordImportReport {
+        source,
+        email_accounts: 0,
+        social_media: 0,
+        online_accounts: 0,
+        duplicates: 0,
+        skipped,
+        accounts: Vec::new(),
+    };
+
+    let digital = &mut document.digital;
+    for login in logins {
+        let category = categorize(&login);
+        let Some(account) = to_account(login, include_secrets) else {
+            report.skipped += 1;
+            continue;
+        };
+
+        let (list, count) = match category {
+            AccountCategory::Email => (&mut digital.email_accounts, &mut report.email_accounts),
+            AccountCategory::Social => (&mut digital.social_media, &mut report.social_media),
+            AccountCategory::Other => (&mut digital.online_accounts, &mut report.online_accounts),
+        };
+        let key = account_key(&account);
+        if list.iter().any(|existing| account_key(existing) == key) {
+            report.duplicates += 1;
+            continue;
+        }
+
+        report.accounts.push(ImportedAccount { category, name: account.name.clone(), username: account.username.clone() });
+        list.push(account);
+        *count += 1;
+    }
+
+    if report.accounts.is_empty() && report.duplicates == 0 {
+        return Err(PasswordImportError::NoEntries);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BITWARDEN_CSV: &str = "folder,favorite,type,name,notes,fields,reprompt,login_uri,login_username,login_password,login_totp\n\
+,,login,Gmail,backup codes 1234,\"Recovery email: pat@fastmail.com\nRecovery code: 9876\",0,https://accounts.google.com,pat@gmail.com,hunter2,JBSWY3DP\n\
+,,login,,,,0,https://www.facebook.com/login,pat.lee,pw,\n\
+Shopping,,login,Costco,,,0,https://www.costco.com,pat,pw,\n\
+,,note,Safe combination,12-34-56,,0,,,,\n";
+
+    #[test]
+    fn test_bitwarden_csv_sorts_accounts_and_drops_secrets() {
+        let mut doc = LegacyDocument::default();
+        let report = import_passwords(&mut doc, BITWARDEN_CSV, false).unwrap();
+
+        assert_eq!(report.source, PasswordSource::Bitwarden);
+        assert_eq!((report.email_accounts, report.social_media, report.online_accounts, report.skipped), (1, 1, 1, 1));
+        assert_eq!(doc.digital.email_accounts[0].username, "pat@gmail.com");
+        assert_eq!(doc.digital.social_media[0].name, "facebook.com", "name falls back to the host");
+        assert_eq!(doc.digital.online_accounts[0].url, "https://www.costco.com");
+        assert!(doc.digital.email_accounts[0].password.is_empty());
+        assert!(doc.digital.email_accounts[0].notes.is_empty());
+        assert_eq!(doc.digital.email_accounts[0].recovery_hint, "Recovery email: pat@fastmail.com");
+    }
+
+    #[test]
+    fn test_secrets_only_when_opted_in_and_duplicates_skipped() {
+        let mut doc = LegacyDocument::default();
+        import_passwords(&mut doc, BITWARDEN_CSV, true).unwrap();
+        let gmail = &doc.digital.email_accounts[0];
+        assert_eq!(gmail.password, "hunter2");
+        assert_eq!(gmail.notes, "backup codes 1234\nRecovery code: 9876\nOne-time code secret: JBSWY3DP");
+
+        let again = import_passwords(&mut doc, BITWARDEN_CSV, true).unwrap();
+        assert_eq!(again.duplicates, 3);
+        assert_eq!(doc.digital.email_accounts.len(), 1);
+    }
+
+    #[test]
+    fn test_other_formats() {
+        let keepass = "\"Group\",\"Title\",\"Username\",\"Password\",\"URL\",\"Notes\",\"TOTP\",\"Recovery Email\"\n\
+\"Root\",\"Bank\",\"pat\",\"x\",\"https://bank.example.co.uk/\",\"\",\"\",\"pat@example.com\"\n";
+        let mut doc = LegacyDocument::default();
+        let report = import_passwords(&mut doc, keepass, false).unwrap();
+        assert_eq!(report.source, PasswordSource::Keepass);
+        assert_eq!(doc.digital.online_accounts[0].name, "Bank");
+        assert_eq!(doc.digital.online_accounts[0].recovery_hint, "Recovery Email: pat@example.com");
+
+        let chrome = "name,url,username,password,note\nlinkedin.com,https://www.linkedin.com/,pat,x,\n";
+        let report = import_passwords(&mut doc, chrome, false).unwrap();
+        assert_eq!(report.source, PasswordSource::Browser);
+        assert_eq!(report.social_media, 1);
+
+        let json = r#"{"encrypted":false,"items":[{"type":1,"name":"Outlook","login":{"username":"pat@outlook.com","password":"x","uris":[{"uri":"https://outlook.live.com"}]},"fields":[{"name":"Password hint","value":"first dog","type":0},{"name":"Recovery phone","value":"555-0100","type":1}]},{"type":3,"name":"Visa"}]}"#;
+        let report = import_passwords(&mut doc, json, false).unwrap();
+        assert_eq!((report.email_accounts, report.skipped), (1, 1));
+        assert_eq!(doc.digital.email_accounts[0].recovery_hint, "Password hint: first dog", "hidden fields stay out");
+
+        assert!(matches!(
+            import_passwords(&mut doc, r#"{"encrypted":true,"items":[]}"#, false),
+            Err(PasswordImportError::EncryptedExport)
+        ));
+        assert!(matches!(
+            import_passwords(&mut doc, "date,amount\n2024-01-01,5\n", false),
+            Err(PasswordImportError::UnrecognizedFormat)
+        ));
+    }
+}
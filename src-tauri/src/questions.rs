@@ -0,0 +1,152 @@
+use unicode_normalization::UnicodeNormalization;
+
+/// Upper bound on key wrappings from alternative answers; each one costs a full PBKDF2 run at export
+pub const MAX_ANSWER_COMBINATIONS: usize = 64;
+
+/// Browser-side twin of `normalize_answer` and `question_key_passphrase`.
+/// Both implementations are checked against `answer_normalization_vectors.json`.
+pub const QUESTION_JS_NORMALIZE: &str = include_str!("answer_normalization.js");
+
+/// Canonical form of a security-question answer, so that case, punctuation,
+/// extra spaces and compatibility characters (full-width letters, ligatures)
+/// don't stop a correct answer from unlocking the file.
+///
+/// NFKC, then lowercase, then drop everything that isn't a letter, digit or
+/// whitespace, then collapse whitespace runs to a single space and trim.
+pub fn normalize_answer(answer: &str) -> String {
+    let lowered = answer.nfkc().collect::<String>().to_lowercase();
+    let kept: String = lowered.chars().filter(|c| c.is_alphanumeric() || c.is_whitespace()).collect();
+    kept.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Joins normalized answers into the passphrase for the question key. Each
+/// answer is prefixed with its UTF-8 length, so "ab" + "c" and "a" + "bc"
+/// produce different keys.
+pub fn question_key_passphrase<S: AsRef<str>>(answers: &[S]) -> String {
+    answers
+        .iter()
+        .map(|a| {
+            let normalized = normalize_answer(a.as_ref());
+            format!("{}:{}", normalized.len(), normalized)
+        })
+        .collect()
+}
+
+/// The accepted answers for one question: the main answer first, then the
+/// alternatives, skipping any that normalize to an empty or repeated value
+pub fn accepted_answers<'a>(answer: &'a str, alternates: &'a [String]) -> Vec<&'a str> {
+    let mut seen = Vec::new();
+    let mut accepted = Vec::new();
+    for candidate in std::iter::once(answer).chain(alternates.iter().map(String::as_str)) {
+        let normalized = normalize_answer(candidate);
+        if !normalized.is_empty() && !seen.contains(&normalized) {
+            seen.push(normalized);
+            accepted.push(candidate);
+        }
+    }
+    accepted
+}
+
+/// Every way of picking one accepted answer per question, starting with all main answers
+pub fn answer_combinations<'a>(options: &[Vec<&'a str>]) -> Vec<Vec<&'a str>> {
+    options.iter().fold(vec![Vec::new()], |combinations, choices| {
+        combinations
+            .iter()
+            .flat_map(|prefix| {
+                choices.iter().map(move |choice| {
+                    let mut next = prefix.clone();
+                    next.push(*choice);
+                    next
+                })
+            })
+            .collect()
+    })
+}
+
+/// Every way of choosing `k` of `n` question indices, in lexicographic order
+pub fn answer_subsets(n: usize, k: usize) -> Vec<Vec<usize>> {
+    if k == 0 || k > n {
+        return Vec::new();
+    }
+    let mut subsets = Vec::new();
+    let mut current: Vec<usize> = (0..k).collect();
+    loop {
+        subsets.push(current.clone());
+        // Advance the rightmost index that still has room to move
+        let Some(i) = (0..k).rev().find(|&i| current[i] < n - k + i) else {
+            return subsets;
+        };
+        current[i] += 1;
+        for j in i + 1..k {
+            current[j] = current[j - 1] + 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Vectors {
+        normalize: Vec<NormalizeVector>,
+        passphrase: Vec<PassphraseVector>,
+    }
+
+    #[derive(Deserialize)]
+    struct NormalizeVector {
+        input: String,
+        expected: String,
+    }
+
+    #[derive(Deserialize)]
+    struct PassphraseVector {
+        answers: Vec<String>,
+        expected: String,
+    }
+
+    fn vectors() -> Vectors {
+        serde_json::from_str(include_str!("answer_normalization_vectors.json")).unwrap()
+    }
+
+    #[test]
+    fn test_normalization_vectors() {
+        for v in vectors().normalize {
+            assert_eq!(normalize_answer(&v.input), v.expected, "input {:?}", v.input);
+        }
+    }
+
+    #[test]
+    fn test_passphrase_vectors() {
+        for v in vectors().passphrase {
+            assert_eq!(question_key_passphrase(&v.answers), v.expected, "answers {:?}", v.answers);
+        }
+    }
+
+    #[test]
+    fn test_answer_subsets() {
+        assert_eq!(answer_subsets(4, 3), vec![vec![0, 1, 2], vec![0, 1, 3], vec![0, 2, 3], vec![1, 2, 3]]);
+        assert_eq!(answer_subsets(5, 3).len(), 10);
+        assert_eq!(answer_subsets(3, 3), vec![vec![0, 1, 2]]);
+        assert!(answer_subsets(2, 3).is_empty());
+    }
+
+    #[test]
+    fn test_accepted_answer_combinations() {
+        let alternates = vec!["Bustopher".to_string(), "BUSTER!".to_string(), " ".to_string()];
+        let first = accepted_answers("Buster", &alternates);
+        assert_eq!(first, vec!["Buster", "Bustopher"]);
+
+        let combinations = answer_combinations(&[first, vec!["Blue"], vec!["Paris", "France"]]);
+        assert_eq!(combinations.len(), 4);
+        assert_eq!(combinations[0], vec!["Buster", "Blue", "Paris"]);
+        assert!(combinations.contains(&vec!["Bustopher", "Blue", "France"]));
+    }
+
+    #[test]
+    fn test_split_answers_no_longer_collide() {
+        assert_ne!(question_key_passphrase(&["ab", "c"]), question_key_passphrase(&["a", "bc"]));
+        assert_eq!(question_key_passphrase(&["St. Louis", "O'Brien"]), question_key_passphrase(&["st  louis", "OBrien "]));
+    }
+}
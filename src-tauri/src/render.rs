@@ -0,0 +1,634 @@
+use crate::i18n::Locale;
+use crate::models::LegacyDocument;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use minijinja::value::Kwargs;
+use minijinja::{context, Environment, HtmlEscape, Value};
+use serde::{Deserialize, Serialize};
+
+/// The app logo PNG, embedded at compile time.
+const LOGO_PNG_BYTES: &[u8] = include_bytes!("../icons/icon-no-text.png");
+
+/// Largest logo a theme may embed, before base64
+const MAX_LOGO_BYTES: usize = 512 * 1024;
+
+/// Image types a theme logo may use; all of them are inert inside `<img>`
+const LOGO_MIME_TYPES: &[&str] = &["image/png", "image/jpeg", "image/gif", "image/webp", "image/svg+xml"];
+
+/// Templates shared by the exported viewer and the printable page
+const TEMPLATES: &[(&str, &str)] = &[
+    ("macros.html", include_str!("templates/macros.html")),
+    ("section.html", include_str!("templates/section.html")),
+    ("sidebar_header.html", include_str!("templates/sidebar_header.html")),
+    ("nav.html", include_str!("templates/nav.html")),
+    ("footer.html", include_str!("templates/footer.html")),
+    ("viewer.html", include_str!("templates/viewer.html")),
+    ("print.html", include_str!("templates/print.html")),
+    ("financial.html", include_str!("templates/financial.html")),
+    ("insurance.html", include_str!("templates/insurance.html")),
+    ("bills.html", include_str!("templates/bills.html")),
+    ("property.html", include_str!("templates/property.html")),
+    ("legal.html", include_str!("templates/legal.html")),
+    ("digital.html", include_str!("templates/digital.html")),
+    ("household.html", include_str!("templates/household.html")),
+    ("personal.html", include_str!("templates/personal.html")),
+    ("contacts.html", include_str!("templates/contacts.html")),
+    ("medical.html", include_str!("templates/medical.html")),
+    ("pets.html", include_str!("templates/pets.html")),
+    ("custom.html", include_str!("templates/custom.html")),
+];
+
+/// Built-in sections in display order: key (also the template name and catalog prefix) and icon
+const SECTIONS: &[(&str, &str)] = &[
+    ("financial", "💰"),
+    ("insurance", "🛡️"),
+    ("bills", "📄"),
+    ("property", "🏠"),
+    ("legal", "⚖️"),
+    ("digital", "💻"),
+    ("household", "🔧"),
+    ("personal", "👤"),
+    ("contacts", "📇"),
+    ("medical", "🏥"),
+    ("pets", "🐾"),
+];
+
+/// Shown before the title of custom sections
+const CUSTOM_ICON: &str = "📋";
+
+#[derive(Debug)]
+pub enum RenderError {
+    InvalidTheme(String),
+    TemplateError(String),
+}
+
+impl std::fmt::Display for RenderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RenderError::InvalidTheme(msg) => write!(f, "Invalid theme: {}", msg),
+            RenderError::TemplateError(msg) => write!(f, "Template error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+impl From<minijinja::Error> for RenderError {
+    fn from(e: minijinja::Error) -> Self {
+        RenderError::TemplateError(e.to_string())
+    }
+}
+
+/// Branding for exported and printed documents. Every field has a default, so a
+/// theme file only needs the values it changes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    /// Shown on the lock screen and in the sidebar
+    pub name: String,
+    /// The page title and printed heading; "<name> - Legacy Document" (translated) when unset
+    pub title: Option<String>,
+    /// A `data:image/...;base64,` URI; the Honey Did logo when unset
+    pub logo: Option<String>,
+    /// Replaces "This document was prepared by ..." under the lock screen heading
+    pub lock_message: Option<String>,
+    /// Replaces the license notice at the bottom, e.g. with the firm's name and phone number
+    pub footer: String,
+    pub colors: ThemeColors,
+}
+
+/// CSS colors as `#rgb` or `#rrggbb`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeColors {
+    /// Headings, buttons and borders
+    pub primary: String,
+    /// Hover state of primary buttons and the welcome screen gradient
+    pub primary_dark: String,
+    /// Subheadings and secondary text
+    pub accent: String,
+    /// Page background
+    pub background: String,
+    /// Dividers and input borders
+    pub border: String,
+    /// Hints and other quiet text
+    pub muted: String,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            name: "Honey Did".into(),
+            title: None,
+            logo: None,
+            lock_message: None,
+            footer: String::new(),
+            colors: ThemeColors::default(),
+        }
+    }
+}
+
+impl Default for ThemeColors {
+    fn default() -> Self {
+        ThemeColors {
+            primary: "#283618".into(),
+            primary_dark: "#1a2410".into(),
+            accent: "#606C38".into(),
+            background: "#F0EFEB".into(),
+            border: "#D4D4D4".into(),
+            muted: "#B7B7A4".into(),
+        }
+    }
+}
+
+impl Theme {
+    /// Checks everything that ends up in CSS or an `src` attribute, which escaping alone doesn't cover
+    pub fn validate(&self) -> Result<(), RenderError> {
+        if self.name.trim().is_empty() || self.name.chars().count() > 80 {
+            return Err(RenderError::InvalidTheme("name must be 1 to 80 characters".into()));
+        }
+        if self.title.as_ref().is_some_and(|t| t.trim().is_empty() || t.chars().count() > 120) {
+            return Err(RenderError::InvalidTheme("title must be 1 to 120 characters".into()));
+        }
+        if self.lock_message.as_ref().is_some_and(|m| m.chars().count() > 500) {
+            return Err(RenderError::InvalidTheme("lock screen message must be at most 500 characters".into()));
+        }
+        if self.footer.chars().count() > 200 {
+            return Err(RenderError::InvalidTheme("footer must be at most 200 characters".into()));
+        }
+        for (name, color) in self.colors.entries() {
+            if !is_hex_color(color) {
+                return Err(RenderError::InvalidTheme(format!("{} must be a hex color like #283618", name)));
+            }
+        }
+        if let Some(ref logo) = self.logo {
+            let (mime, data) = logo
+                .strip_prefix("data:")
+                .and_then(|rest| rest.split_once(";base64,"))
+                .ok_or_else(|| RenderError::InvalidTheme("logo must be a base64 data URI".into()))?;
+            if !LOGO_MIME_TYPES.contains(&mime) {
+                return Err(RenderError::InvalidTheme(format!("logo type {} is not supported", mime)));
+            }
+            let bytes = BASE64.decode(data).map_err(|_| RenderError::InvalidTheme("logo is not valid base64".into()))?;
+            if bytes.len() > MAX_LOGO_BYTES {
+                return Err(RenderError::InvalidTheme("logo must be 512 KB or smaller".into()));
+            }
+            if sniff_image_type(&bytes) != Some(mime) {
+                return Err(RenderError::InvalidTheme(format!("logo content is not {}", mime)));
+            }
+        }
+        Ok(())
+    }
+
+    /// The theme's logo, or the Honey Did logo
+    pub fn logo_data_uri(&self) -> String {
+        match self.logo {
+            Some(ref logo) => logo.clone(),
+            None => format!("data:image/png;base64,{}", BASE64.encode(LOGO_PNG_BYTES)),
+        }
+    }
+
+    /// The name, escaped for use in HTML text or attributes
+    pub fn escaped_name(&self) -> String {
+        HtmlEscape(&self.name).to_string()
+    }
+
+    /// The page title and printed heading
+    pub fn document_title(&self, locale: &Locale) -> String {
+        match self.title {
+            Some(ref title) => title.clone(),
+            None => locale.format("document.title", &[("name", &self.name)]),
+        }
+    }
+
+    pub fn escaped_title(&self, locale: &Locale) -> String {
+        HtmlEscape(&self.document_title(locale)).to_string()
+    }
+
+    /// The lock screen text as HTML, keeping the message's line breaks. A theme's
+    /// message is used as written, whatever the export language.
+    pub fn lock_message_html(&self, creator_name: &str, locale: &Locale) -> String {
+        match self.lock_message {
+            Some(ref message) => message.lines().map(|line| HtmlEscape(line).to_string()).collect::<Vec<_>>().join("<br>"),
+            None => locale.html("lock.message", &[("name", creator_name)]),
+        }
+    }
+
+    /// Returns the logo as an `<img>` tag for the lock and unlocking screens
+    pub fn logo_img_tag(&self, css_class: &str, size_px: u32) -> String {
+        format!(
+            r#"<img class="{}" src="{}" alt="{}" style="width:{}px;height:{}px;" />"#,
+            css_class, self.logo_data_uri(), self.escaped_name(), size_px, size_px
+        )
+    }
+
+    /// Custom properties read by the stylesheets. Only valid once `validate` has passed.
+    pub fn css_variables(&self) -> String {
+        let vars: String = self.colors.entries().iter().map(|(name, color)| format!(" --hd-{}: {};", name.replace('_', "-"), color)).collect();
+        format!(":root {{{} }}", vars)
+    }
+}
+
+impl ThemeColors {
+    fn entries(&self) -> [(&'static str, &str); 6] {
+        [
+            ("primary", &self.primary),
+            ("primary_dark", &self.primary_dark),
+            ("accent", &self.accent),
+            ("background", &self.background),
+            ("border", &self.border),
+            ("muted", &self.muted),
+        ]
+    }
+}
+
+/// Turns an image file into a data URI for `Theme::logo`, going by its content rather than its name
+pub fn logo_data_uri_from_file(bytes: &[u8]) -> Result<String, RenderError> {
+    if bytes.len() > MAX_LOGO_BYTES {
+        return Err(RenderError::InvalidTheme("logo must be 512 KB or smaller".into()));
+    }
+    let mime = sniff_image_type(bytes)
+        .ok_or_else(|| RenderError::InvalidTheme("logo must be a PNG, JPEG, GIF, WebP or SVG image".into()))?;
+    Ok(format!("data:{};base64,{}", mime, BASE64.encode(bytes)))
+}
+
+/// The image type from its leading bytes, limited to `LOGO_MIME_TYPES`
+fn sniff_image_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        let text = std::str::from_utf8(bytes).ok()?.trim_start_matches('\u{feff}').trim_start();
+        (text.starts_with("<svg") || (text.starts_with("<?xml") && text.contains("<svg"))).then_some("image/svg+xml")
+    }
+}
+
+fn is_hex_color(color: &str) -> bool {
+    color.strip_prefix('#').is_some_and(|hex| matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Links the exported viewer can offer, which depend on the files bundled with the export
+#[derive(Default)]
+pub struct ViewerLinks<'a> {
+    /// Keys of `export_extras.contact_cards`, see `vcard::contact_card_key`
+    pub contact_cards: Vec<&'a str>,
+    pub contacts_bundle: bool,
+    pub calendar: bool,
+}
+
+/// The exported viewer, inserted by `renderDocument` once the file is unlocked
+#[derive(Debug, Serialize)]
+pub struct RenderedDocument {
+    /// The whole viewer: toolbar, sidebar with search and contents, and the sections
+    pub page: String,
+    #[serde(skip)]
+    pub sidebar_header: String,
+    #[serde(skip)]
+    pub nav: String,
+    #[serde(skip)]
+    pub footer: String,
+    #[serde(skip)]
+    pub content: String,
+}
+
+/// A rendered section and, for built-in and top-level custom sections, its sidebar entry
+struct RenderedSection {
+    id: String,
+    icon: &'static str,
+    nav_label: Option<String>,
+    html: String,
+}
+
+/// Renders the document for the exported viewer
+pub fn render_viewer(document: &LegacyDocument, theme: &Theme, locale: &Locale, links: &ViewerLinks) -> Result<RenderedDocument, RenderError> {
+    theme.validate()?;
+    let env = environment(theme, locale, Some(links));
+    let sections = render_sections(&env, locale, document)?;
+    let nav: Vec<Value> = sections
+        .iter()
+        .filter_map(|s| s.nav_label.as_ref().map(|label| context! { id => s.id, icon => s.icon, label => label }))
+        .collect();
+    let creator_name = &document.meta.creator_name;
+    let sidebar_header = env.get_template("sidebar_header.html")?.render(context! { creator_name })?;
+    let nav = env.get_template("nav.html")?.render(context! { entries => nav })?;
+    let footer = env.get_template("footer.html")?.render(context! {})?;
+    let content: String = sections.into_iter().map(|s| s.html).collect();
+    let page = env.get_template("viewer.html")?.render(context! {
+        sidebar_header => Value::from_safe_string(sidebar_header.clone()),
+        nav => Value::from_safe_string(nav.clone()),
+        footer => Value::from_safe_string(footer.clone()),
+        content => Value::from_safe_string(content.clone()),
+    })?;
+    Ok(RenderedDocument { page, sidebar_header, nav, footer, content })
+}
+
+/// Renders the whole document as a standalone page for printing
+pub fn render_print(document: &LegacyDocument, theme: &Theme, locale: &Locale) -> Result<String, RenderError> {
+    theme.validate()?;
+    let env = environment(theme, locale, None);
+    let content: String = render_sections(&env, locale, document)?.into_iter().map(|s| s.html).collect();
+    Ok(env.get_template("print.html")?.render(context! {
+        creator_name => document.meta.creator_name,
+        css_variables => Value::from_safe_string(theme.css_variables()),
+        content => Value::from_safe_string(content),
+    })?)
+}
+
+/// Sets up the templates; `links` is `None` for printing, which leaves out anything clickable.
+/// Templates look up their text with `t("key")`, or `t("key", name=value)` for placeholders.
+fn environment(theme: &Theme, locale: &Locale, links: Option<&ViewerLinks>) -> Environment<'static> {
+    let mut env = Environment::new();
+    env.set_trim_blocks(true);
+    env.set_lstrip_blocks(true);
+    for (name, source) in TEMPLATES {
+        env.add_template(name, source).expect("built-in templates parse");
+    }
+    env.add_filter("file_size", file_size);
+    env.add_filter("attr_id", |value: String| attr_id(&value));
+    let messages = locale.clone();
+    env.add_function("t", move |key: &str, params: Kwargs| -> Result<String, minijinja::Error> {
+        let mut text = messages.t(key).to_string();
+        for name in params.args() {
+            let value: Value = params.get(name)?;
+            text = text.replace(&format!("{{{}}}", name), &value.to_string());
+        }
+        Ok(text)
+    });
+    let dates = locale.clone();
+    env.add_filter("date", move |value: String| dates.format_date_str(&value));
+    env.add_global("locale", context! { code => locale.code, dir => locale.dir() });
+    env.add_global("theme", context! {
+        name => theme.name,
+        title => theme.document_title(locale),
+        footer => theme.footer,
+        // Only base64 characters after a fixed prefix, once validated
+        logo => Value::from_safe_string(theme.logo_data_uri()),
+    });
+    env.add_global("viewer", links.is_some());
+    let links = links.map(|l| (l.contact_cards.clone(), l.contacts_bundle, l.calendar)).unwrap_or_default();
+    env.add_global("contact_cards", Value::from_serialize(&links.0));
+    env.add_global("contacts_bundle", links.1);
+    env.add_global("calendar", links.2);
+    env
+}
+
+fn render_sections(env: &Environment, locale: &Locale, document: &LegacyDocument) -> Result<Vec<RenderedSection>, RenderError> {
+    let data = Value::from_serialize(document);
+    let mut sections = Vec::new();
+
+    for (key, icon) in SECTIONS {
+        let content = env.get_template(&format!("{}.html", key))?.render(context! { s => data.get_attr(key)? })?;
+        let title = format!("section.{}.title", key);
+        if let Some(html) = wrap_section(env, key, icon, locale.t(&title), &content)? {
+            let nav_label = locale.t(&format!("section.{}.nav", key)).to_string();
+            sections.push(RenderedSection { id: key.to_string(), icon, nav_label: Some(nav_label), html });
+        }
+    }
+
+    let custom = env.get_template("custom.html")?;
+    for section in document.custom_sections.iter().filter(|s| s.parent.is_none()) {
+        let content = custom.render(context! { sections => vec![Value::from_serialize(section)], attachments => true })?;
+        let id = format!("custom-{}", section.id);
+        if let Some(html) = wrap_section(env, &id, CUSTOM_ICON, &section.name, &content)? {
+            sections.push(RenderedSection { id: attr_id(&id), icon: CUSTOM_ICON, nav_label: Some(section.name.clone()), html });
+        }
+    }
+
+    // Custom subsections added under built-in sections, grouped by parent in first-seen order
+    let mut by_parent: Vec<(&str, Vec<Value>)> = Vec::new();
+    for section in &document.custom_sections {
+        let Some(ref parent) = section.parent else { continue };
+        match by_parent.iter_mut().find(|(p, _)| p == parent) {
+            Some((_, group)) => group.push(Value::from_serialize(section)),
+            None => by_parent.push((parent, vec![Value::from_serialize(section)])),
+        }
+    }
+    for (parent, group) in by_parent {
+        let content = custom.render(context! { sections => group, attachments => false })?;
+        let key = format!("section.{}.custom", parent);
+        let label = match locale.t(&key) {
+            label if label != key => label.to_string(),
+            _ => locale.format("section.custom", &[("name", parent)]),
+        };
+        let id = format!("custom-{}", parent);
+        if let Some(html) = wrap_section(env, &id, CUSTOM_ICON, &label, &content)? {
+            sections.push(RenderedSection { id: attr_id(&id), icon: CUSTOM_ICON, nav_label: None, html });
+        }
+    }
+
+    Ok(sections)
+}
+
+/// Puts a heading around a section's content, or returns `None` if there is nothing to show.
+/// The icon is hidden from screen readers, which would otherwise read out the emoji's name.
+fn wrap_section(env: &Environment, id: &str, icon: &str, title: &str, content: &str) -> Result<Option<String>, RenderError> {
+    if content.trim().is_empty() {
+        return Ok(None);
+    }
+    let html = env.get_template("section.html")?.render(context! {
+        id => attr_id(id),
+        icon => icon,
+        title => title,
+        content => Value::from_safe_string(content.to_string()),
+    })?;
+    Ok(Some(html))
+}
+
+/// Keeps only characters that are safe in an `id` or `href="#..."`
+fn attr_id(value: &str) -> String {
+    value.chars().filter(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '-').collect()
+}
+
+/// Formats an attachment size like the viewer always has: "512 B", "1.5 KB", "2.0 MB"
+fn file_size(bytes: u64) -> String {
+    match bytes {
+        0 => String::new(),
+        b if b < 1024 => format!("{} B", b),
+        b if b < 1024 * 1024 => format!("{:.1} KB", b as f64 / 1024.0),
+        b => format!("{:.1} MB", b as f64 / (1024.0 * 1024.0)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BankAccount, Bill, Contact, CustomItem, CustomSection, CustomSubsection, FieldType, FileAttachment, FormElement};
+
+    fn sample_document() -> LegacyDocument {
+        let mut doc = LegacyDocument::default();
+        doc.meta.creator_name = "Pat <Lee>".into();
+        doc.financial.bank_accounts.push(BankAccount { name: "Joint & checking".into(), institution: "First Bank".into(), ..Default::default() });
+        doc.bills.bills.push(Bill { name: "Electric".into(), due_day: "15".into(), autopay: true, ..Default::default() });
+        doc.contacts.family.push(Contact { name: "Ann Lee".into(), phone: "555-0100".into(), ..Default::default() });
+        doc.financial.attachments.push(FileAttachment { id: "a1".into(), name: "statement.pdf".into(), size: 1536, ..Default::default() });
+        doc
+    }
+
+    fn custom_section(id: &str, parent: Option<&str>) -> CustomSection {
+        let mut item = CustomItem::default();
+        item.values.insert("f1".into(), "true".into());
+        item.values.insert("_notes".into(), "Keep the spare key".into());
+        CustomSection {
+            id: id.into(),
+            name: "Garage".into(),
+            parent: parent.map(String::from),
+            subsections: vec![CustomSubsection {
+                name: "Tools".into(),
+                form_elements: vec![FormElement::Field { id: "f1".into(), name: "Insured".into(), field_type: FieldType::Boolean }],
+                items: vec![item],
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_viewer_renders_sections_and_nav() {
+        let doc = sample_document();
+        let key = crate::vcard::contact_card_key(&doc.contacts.family[0]);
+        let links = ViewerLinks { contact_cards: vec![&key], contacts_bundle: true, calendar: true };
+        let rendered = render_viewer(&doc, &Theme::default(), &Locale::default(), &links).unwrap();
+
+        assert!(rendered.content.contains(r#"<section class="section" id="financial" aria-labelledby="financial-title"><h2 class="section-title" id="financial-title"><span aria-hidden="true">💰</span> Financial Information</h2>"#));
+        assert!(rendered.content.contains("Joint &amp; checking"));
+        assert!(rendered.content.contains("Auto-pay: Yes"));
+        assert!(rendered.content.contains(r#"id="att-dl-a1" data-attached="attached file">statement.pdf</a> <span class="attachment-export-size">(1.5 KB)</span>"#));
+        assert!(rendered.content.contains(r#"data-vcard="Ann Lee|555-0100|""#));
+        assert!(rendered.content.contains(r#"class="calendar-link""#));
+        assert!(rendered.content.contains(r#"id="vcardBundle""#));
+        assert!(!rendered.content.contains(r#"id="insurance""#));
+        assert!(rendered.nav.contains(r##"<a href="#bills" data-click="closeSidebarOnMobile"><span aria-hidden="true">📄</span> Bills</a>"##));
+        assert!(!rendered.nav.contains("#pets"));
+        assert!(rendered.sidebar_header.contains("By Pat &lt;Lee&gt;"));
+    }
+
+    #[test]
+    fn test_print_leaves_out_viewer_links() {
+        let html = render_print(&sample_document(), &Theme::default(), &Locale::default()).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("Prepared by Pat &lt;Lee&gt;"));
+        assert!(html.contains("Joint &amp; checking"));
+        assert!(html.contains("statement.pdf"));
+        assert!(!html.contains("att-dl-") && !html.contains("data-vcard") && !html.contains("calendar-link"));
+        assert!(!html.contains("<script"));
+    }
+
+    #[test]
+    fn test_custom_sections() {
+        let mut doc = LegacyDocument::default();
+        doc.custom_sections.push(custom_section("c<1>", None));
+        doc.custom_sections.push(custom_section("c2", Some("property")));
+        let rendered = render_viewer(&doc, &Theme::default(), &Locale::default(), &ViewerLinks::default()).unwrap();
+
+        assert!(rendered.content.contains(r#"<section class="section" id="custom-c1" aria-labelledby="custom-c1-title"><h2 class="section-title" id="custom-c1-title"><span aria-hidden="true">📋</span> Garage</h2>"#));
+        assert!(rendered.content.contains("<strong>Insured:</strong> Yes"));
+        assert!(rendered.content.contains(r#"<div class="notes">Keep the spare key</div>"#));
+        assert!(rendered.content.contains(r#"id="custom-property-title"><span aria-hidden="true">📋</span> Property (Custom)</h2>"#));
+        assert!(rendered.nav.contains("#custom-c1"));
+        assert!(!rendered.nav.contains("#custom-property"));
+    }
+
+    #[test]
+    fn test_theme_applies_to_both_outputs() {
+        let theme = Theme {
+            name: "Smith & Co. Estate Planning".into(),
+            logo: Some("data:image/svg+xml;base64,PHN2Zy8+".into()),
+            footer: "Questions? Call 555-0199".into(),
+            colors: ThemeColors { primary: "#123456".into(), ..Default::default() },
+            ..Theme::default()
+        };
+        let rendered = render_viewer(&sample_document(), &theme, &Locale::default(), &ViewerLinks::default()).unwrap();
+        assert!(rendered.sidebar_header.contains("Smith &amp; Co. Estate Planning"));
+        assert!(rendered.sidebar_header.contains("data:image/svg+xml;base64,PHN2Zy8+"));
+        assert!(rendered.footer.contains("Questions? Call 555-0199"));
+
+        let html = render_print(&sample_document(), &theme, &Locale::default()).unwrap();
+        assert!(html.contains("--hd-primary: #123456;"));
+        assert!(html.contains("<title>Smith &amp; Co. Estate Planning - Legacy Document</title>"));
+    }
+
+    #[test]
+    fn test_title_lock_message_and_footer() {
+        let theme = Theme {
+            title: Some("Estate Binder <2026>".into()),
+            lock_message: Some("Prepared with Smith & Co.\nCall us first".into()),
+            footer: "Smith & Co. Estate Planning".into(),
+            ..Theme::default()
+        };
+        assert_eq!(theme.escaped_title(&Locale::default()), "Estate Binder &lt;2026&gt;");
+        assert_eq!(theme.lock_message_html("Pat", &Locale::default()), "Prepared with Smith &amp; Co.<br>Call us first");
+        assert_eq!(Theme::default().lock_message_html("<Pat>", &Locale::default()), "This document was prepared by &lt;Pat&gt;<br>to help you in their absence.");
+
+        let html = render_print(&sample_document(), &theme, &Locale::default()).unwrap();
+        assert!(html.contains("<h1>Estate Binder &lt;2026&gt;</h1>"));
+        assert!(html.contains("Smith &amp; Co. Estate Planning"));
+        assert!(!html.contains("scafidi.dev"));
+        assert!(render_print(&sample_document(), &Theme::default(), &Locale::default()).unwrap().contains("scafidi.dev"));
+    }
+
+    #[test]
+    fn test_other_languages() {
+        let mut doc = sample_document();
+        doc.custom_sections.push(custom_section("c2", Some("property")));
+        doc.custom_sections[0].subsections[0].form_elements.push(FormElement::Field { id: "f2".into(), name: "Bought".into(), field_type: FieldType::Date });
+        doc.custom_sections[0].subsections[0].items[0].values.insert("f2".into(), "2019-08-14".into());
+        let spanish = Locale::load("es").unwrap();
+        let rendered = render_viewer(&doc, &Theme::default(), &spanish, &ViewerLinks::default()).unwrap();
+        assert!(rendered.content.contains(r#"<span aria-hidden="true">💰</span> Información financiera</h2>"#));
+        assert!(rendered.content.contains("Entidad: First Bank"));
+        assert!(rendered.content.contains("Pago automático: Sí"));
+        assert!(rendered.content.contains("</span> Bienes (personalizado)</h2>"));
+        assert!(rendered.content.contains("<strong>Bought:</strong> 14 de agosto de 2019"));
+        assert!(rendered.content.contains(r#"data-attached="archivo adjunto""#));
+        assert!(rendered.nav.contains("</span> Facturas</a>"));
+        assert!(rendered.sidebar_header.contains("De Pat &lt;Lee&gt;"));
+
+        let html = render_print(&doc, &Theme::default(), &Locale::load("ar").unwrap()).unwrap();
+        assert!(html.contains(r#"<html lang="ar" dir="rtl">"#));
+        assert!(html.contains("<title>Honey Did - وثيقة الإرث</title>"));
+        assert!(html.contains("14 أغسطس 2019"));
+        assert!(render_print(&doc, &Theme::default(), &Locale::default()).unwrap().contains(r#"<html lang="en" dir="ltr">"#));
+    }
+
+    #[test]
+    fn test_logo_from_file_checks_content() {
+        assert!(logo_data_uri_from_file(LOGO_PNG_BYTES).unwrap().starts_with("data:image/png;base64,iVBOR"));
+        assert!(logo_data_uri_from_file(b"<?xml version=\"1.0\"?><svg xmlns=\"http://www.w3.org/2000/svg\"/>").unwrap().starts_with("data:image/svg+xml;"));
+        assert!(logo_data_uri_from_file(b"<html><script>alert(1)</script>").is_err());
+        assert!(logo_data_uri_from_file(&vec![0xFF; MAX_LOGO_BYTES + 1]).is_err());
+
+        // A declared type has to match the bytes
+        let mislabeled = Theme { logo: Some(format!("data:image/gif;base64,{}", BASE64.encode(LOGO_PNG_BYTES))), ..Theme::default() };
+        assert!(mislabeled.validate().is_err());
+    }
+
+    #[test]
+    fn test_theme_validation() {
+        assert!(Theme::default().validate().is_ok());
+        let bad_color = Theme { colors: ThemeColors { accent: "red;}body{display:none".into(), ..Default::default() }, ..Default::default() };
+        assert!(bad_color.validate().is_err());
+        let remote_logo = Theme { logo: Some("https://example.com/logo.png".into()), ..Default::default() };
+        assert!(remote_logo.validate().is_err());
+        let html_logo = Theme { logo: Some("data:text/html;base64,PHNjcmlwdD4=".into()), ..Default::default() };
+        assert!(html_logo.validate().is_err());
+        assert!(Theme { title: Some(String::new()), ..Default::default() }.validate().is_err());
+        assert!(Theme { lock_message: Some("x".repeat(501)), ..Default::default() }.validate().is_err());
+        assert!(Theme { name: " ".into(), ..Default::default() }.validate().is_err());
+
+        let partial: Theme = serde_json::from_str(r##"{"name": "Acme", "colors": {"primary": "#000"}}"##).unwrap();
+        assert_eq!(partial.colors.accent, ThemeColors::default().accent);
+        assert!(partial.validate().is_ok());
+    }
+
+    #[test]
+    fn test_file_size_matches_viewer() {
+        assert_eq!(file_size(0), "");
+        assert_eq!(file_size(512), "512 B");
+        assert_eq!(file_size(1536), "1.5 KB");
+        assert_eq!(file_size(3 * 1024 * 1024), "3.0 MB");
+    }
+}
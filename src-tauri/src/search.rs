@@ -0,0 +1,1114 @@
+use crate::models::{FileAttachment, LegacyDocument};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Browser-side twin of `tokenize`, `stem`, `double_metaphone` and
+/// `Query::classify`. Both implementations are checked against `search_vectors.json`.
+pub const SEARCH_JS_MATCHING: &str = include_str!("search_matching.js");
+
+/// Bumped whenever tokenizing or phonetic codes change
+pub const INDEX_VERSION: u32 = 1;
+
+/// Double Metaphone codes are cut to this many characters
+const CODE_LENGTH: usize = 4;
+
+/// Longest stretch of a TXT or CSV attachment that `search_document` reads
+const MAX_ATTACHMENT_TEXT: usize = 1024 * 1024;
+
+/// Characters of context kept on each side of a hit
+const SNIPPET_CONTEXT: usize = 40;
+
+/// Document fields that are never shown as text, or only on the welcome screen.
+/// Imported account passwords are skipped so a hit's text and snippet never show one.
+const SKIPPED_KEYS: &[&str] = &[
+    "id", "data", "mime_type", "size", "group", "parent", "type", "field_type", "transition", "welcome_screen",
+    "created_at", "updated_at", "password",
+];
+
+/// How a word matched, best first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    /// The same word, or another form of it ("payment" for "payments")
+    Exact,
+    /// The word contains the search term
+    Contains,
+    /// A small spelling difference
+    #[serde(alias = "fuzzy")]
+    Spelling,
+    /// Sounds alike
+    Phonetic,
+}
+
+impl MatchKind {
+    pub const ALL: [MatchKind; 4] = [MatchKind::Exact, MatchKind::Contains, MatchKind::Spelling, MatchKind::Phonetic];
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric()
+}
+
+fn is_apostrophe(c: char) -> bool {
+    c == '\'' || c == '\u{2019}'
+}
+
+/// A word found by `tokens`, with its byte range in the original text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub word: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Splits text into lower-cased words of at least two characters. A word is a
+/// run of letters and digits, joined across single apostrophes ("o'brien").
+/// Same as the regex /[\p{Alphabetic}\p{N}]+(?:['’][\p{Alphabetic}\p{N}]+)*/gu.
+pub fn tokens(text: &str) -> Vec<Token> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let offset = |i: usize| chars.get(i).map_or(text.len(), |(at, _)| *at);
+    let mut found = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if !is_word_char(chars[i].1) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        loop {
+            while i < chars.len() && is_word_char(chars[i].1) {
+                i += 1;
+            }
+            if i + 1 < chars.len() && is_apostrophe(chars[i].1) && is_word_char(chars[i + 1].1) {
+                i += 1;
+            } else {
+                break;
+            }
+        }
+        if i - start >= 2 {
+            let (start, end) = (offset(start), offset(i));
+            found.push(Token { word: normalize_word(&text[start..end]), start, end });
+        }
+    }
+    found
+}
+
+/// The words of `text`, as `tokens` finds them
+pub fn tokenize(text: &str) -> Vec<String> {
+    tokens(text).into_iter().map(|t| t.word).collect()
+}
+
+fn normalize_word(word: &str) -> String {
+    word.to_lowercase().replace('\u{2019}', "'")
+}
+
+fn ends_with(word: &[char], suffix: &str) -> bool {
+    let suffix: Vec<char> = suffix.chars().collect();
+    word.len() >= suffix.len() && word[word.len() - suffix.len()..] == suffix[..]
+}
+
+fn has_vowel(word: &[char]) -> bool {
+    word.iter().any(|c| "aeiouy".contains(*c))
+}
+
+/// A light English stemmer: plurals, possessives, -ing, -ed and -ly. Words
+/// with anything but ASCII letters and apostrophes are left alone.
+pub fn stem(word: &str) -> String {
+    let mut w: Vec<char> = word.chars().collect();
+    if w.len() <= 3 || !w.iter().all(|c| c.is_ascii_lowercase() || *c == '\'') {
+        return word.to_string();
+    }
+    if ends_with(&w, "'s") {
+        w.truncate(w.len() - 2);
+    } else if ends_with(&w, "'") {
+        w.truncate(w.len() - 1);
+    }
+
+    if ["sses", "xes", "ches", "shes"].iter().any(|s| ends_with(&w, s)) {
+        w.truncate(w.len() - 2);
+    } else if ends_with(&w, "ies") && w.len() > 4 {
+        w.truncate(w.len() - 3);
+        w.push('y');
+    } else if ends_with(&w, "s") && !ends_with(&w, "ss") && !ends_with(&w, "us") && !ends_with(&w, "is") && w.len() > 3 {
+        w.truncate(w.len() - 1);
+    }
+
+    for suffix in ["ingly", "edly", "ing", "ed", "ly"] {
+        let cut = w.len().saturating_sub(suffix.chars().count());
+        if ends_with(&w, suffix) && cut >= 3 && has_vowel(&w[..cut]) {
+            w.truncate(cut);
+            let n = w.len();
+            if suffix != "ly" && w[n - 1] == w[n - 2] && !"lsz".contains(w[n - 1]) && !"aeiouy".contains(w[n - 1]) {
+                w.truncate(n - 1);
+            }
+            break;
+        }
+    }
+    w.into_iter().collect()
+}
+
+/// Primary and alternate Double Metaphone codes, with the builder's length cap
+struct Codes {
+    primary: String,
+    alternate: String,
+}
+
+impl Codes {
+    fn add(&mut self, primary: &str, alternate: &str) {
+        for c in primary.chars() {
+            if self.primary.chars().count() < CODE_LENGTH {
+                self.primary.push(c);
+            }
+        }
+        for c in alternate.chars() {
+            if self.alternate.chars().count() < CODE_LENGTH {
+                self.alternate.push(c);
+            }
+        }
+    }
+
+    fn both(&mut self, code: &str) {
+        self.add(code, code);
+    }
+
+    fn is_complete(&self) -> bool {
+        self.primary.chars().count() >= CODE_LENGTH && self.alternate.chars().count() >= CODE_LENGTH
+    }
+}
+
+/// Lawrence Philips' Double Metaphone, following the Apache Commons Codec
+/// structure. Returns the primary and alternate codes, at most four characters.
+pub fn double_metaphone(word: &str) -> (String, String) {
+    let v: Vec<char> = word.to_uppercase().chars().collect();
+    let len = v.len() as isize;
+    let at = |i: isize| -> char { if i < 0 || i >= len { '\0' } else { v[i as usize] } };
+    let is_vowel = |c: char| "AEIOUY".contains(c) && c != '\0';
+    // True when the `n` characters from `start` are one of `options`
+    let matches = |start: isize, n: isize, options: &[&str]| -> bool {
+        if start < 0 || start + n > len {
+            return false;
+        }
+        let slice: String = v[start as usize..(start + n) as usize].iter().collect();
+        options.contains(&slice.as_str())
+    };
+    let text: String = v.iter().collect();
+    let slavo_germanic = text.contains('W') || text.contains('K') || text.contains("CZ") || text.contains("WITZ");
+
+    let mut codes = Codes { primary: String::new(), alternate: String::new() };
+    let mut i: isize = if matches(0, 2, &["GN", "KN", "PN", "WR", "PS"]) { 1 } else { 0 };
+
+    while !codes.is_complete() && i < len {
+        let c = at(i);
+        match c {
+            'A' | 'E' | 'I' | 'O' | 'U' | 'Y' => {
+                if i == 0 {
+                    codes.both("A");
+                }
+                i += 1;
+            }
+            'B' => {
+                codes.both("P");
+                i += if at(i + 1) == 'B' { 2 } else { 1 };
+            }
+            'Ç' => {
+                codes.both("S");
+                i += 1;
+            }
+            'C' => {
+                let germanic_ch = matches(i, 4, &["CHIA"])
+                    || (i > 1
+                        && !is_vowel(at(i - 2))
+                        && matches(i - 1, 3, &["ACH"])
+                        && ((at(i + 2) != 'I' && at(i + 2) != 'E') || matches(i - 2, 6, &["BACHER", "MACHER"])));
+                if germanic_ch {
+                    codes.both("K");
+                    i += 2;
+                } else if i == 0 && matches(i, 6, &["CAESAR"]) {
+                    codes.both("S");
+                    i += 2;
+                } else if matches(i, 2, &["CH"]) {
+                    if i > 0 && matches(i, 4, &["CHAE"]) {
+                        codes.add("K", "X");
+                    } else if (i == 0
+                        && (matches(i + 1, 5, &["HARAC", "HARIS"]) || matches(i + 1, 3, &["HOR", "HYM", "HIA", "HEM"]))
+                        && !matches(0, 5, &["CHORE"]))
+                        || matches(0, 4, &["VAN ", "VON "])
+                        || matches(0, 3, &["SCH"])
+                        || matches(i - 2, 6, &["ORCHES", "ARCHIT", "ORCHID"])
+                        || matches(i + 2, 1, &["T", "S"])
+                        || ((matches(i - 1, 1, &["A", "O", "U", "E"]) || i == 0)
+                            && (matches(i + 2, 1, &["L", "R", "N", "M", "B", "H", "F", "V", "W", " "]) || i + 1 == len - 1))
+                    {
+                        // Greek roots ("chorus") and Germanic "ch"
+                        codes.both("K");
+                    } else if i > 0 {
+                        if matches(0, 2, &["MC"]) {
+                            codes.both("K");
+                        } else {
+                            codes.add("X", "K");
+                        }
+                    } else {
+                        codes.both("X");
+                    }
+                    i += 2;
+                } else if matches(i, 2, &["CZ"]) && !matches(i - 2, 4, &["WICZ"]) {
+                    codes.add("S", "X");
+                    i += 2;
+                } else if matches(i + 1, 3, &["CIA"]) {
+                    codes.both("X");
+                    i += 3;
+                } else if matches(i, 2, &["CC"]) && !(i == 1 && at(0) == 'M') {
+                    if matches(i + 2, 1, &["I", "E", "H"]) && !matches(i + 2, 2, &["HU"]) {
+                        if (i == 1 && at(i - 1) == 'A') || matches(i - 1, 5, &["UCCEE", "UCCES"]) {
+                            codes.both("KS");
+                        } else {
+                            codes.both("X");
+                        }
+                        i += 3;
+                    } else {
+                        codes.both("K");
+                        i += 2;
+                    }
+                } else if matches(i, 2, &["CK", "CG", "CQ"]) {
+                    codes.both("K");
+                    i += 2;
+                } else if matches(i, 2, &["CI", "CE", "CY"]) {
+                    if matches(i, 3, &["CIO", "CIE", "CIA"]) {
+                        codes.add("S", "X");
+                    } else {
+                        codes.both("S");
+                    }
+                    i += 2;
+                } else {
+                    codes.both("K");
+                    if matches(i + 1, 2, &[" C", " Q", " G"]) {
+                        i += 3;
+                    } else if matches(i + 1, 1, &["C", "K", "Q"]) && !matches(i + 1, 2, &["CE", "CI"]) {
+                        i += 2;
+                    } else {
+                        i += 1;
+                    }
+                }
+            }
+            'D' => {
+                if matches(i, 2, &["DG"]) {
+                    if matches(i + 2, 1, &["I", "E", "Y"]) {
+                        codes.both("J");
+                        i += 3;
+                    } else {
+                        codes.both("TK");
+                        i += 2;
+                    }
+                } else if matches(i, 2, &["DT", "DD"]) {
+                    codes.both("T");
+                    i += 2;
+                } else {
+                    codes.both("T");
+                    i += 1;
+                }
+            }
+            'F' => {
+                codes.both("F");
+                i += if at(i + 1) == 'F' { 2 } else { 1 };
+            }
+            'G' => {
+                if at(i + 1) == 'H' {
+                    if i > 0 && !is_vowel(at(i - 1)) {
+                        codes.both("K");
+                    } else if i == 0 {
+                        codes.both(if at(i + 2) == 'I' { "J" } else { "K" });
+                    } else if (i > 1 && matches(i - 2, 1, &["B", "H", "D"]))
+                        || (i > 2 && matches(i - 3, 1, &["B", "H", "D"]))
+                        || (i > 3 && matches(i - 4, 1, &["B", "H"]))
+                    {
+                        // Parker's rule: "hugh"
+                    } else if i > 2 && at(i - 1) == 'U' && matches(i - 3, 1, &["C", "G", "L", "R", "T"]) {
+                        codes.both("F");
+                    } else if i > 0 && at(i - 1) != 'I' {
+                        codes.both("K");
+                    }
+                    i += 2;
+                } else if at(i + 1) == 'N' {
+                    if i == 1 && is_vowel(at(0)) && !slavo_germanic {
+                        codes.add("KN", "N");
+                    } else if !matches(i + 2, 2, &["EY"]) && at(i + 1) != 'Y' && !slavo_germanic {
+                        codes.add("N", "KN");
+                    } else {
+                        codes.both("KN");
+                    }
+                    i += 2;
+                } else if matches(i + 1, 2, &["LI"]) && !slavo_germanic {
+                    codes.add("KL", "L");
+                    i += 2;
+                } else if (i == 0
+                    && (at(i + 1) == 'Y'
+                        || matches(i + 1, 2, &["ES", "EP", "EB", "EL", "EY", "IB", "IL", "IN", "IE", "EI", "ER"])))
+                    || ((matches(i + 1, 2, &["ER"]) || at(i + 1) == 'Y')
+                        && !matches(0, 6, &["DANGER", "RANGER", "MANGER"])
+                        && !matches(i - 1, 1, &["E", "I"])
+                        && !matches(i - 1, 3, &["RGY", "OGY"]))
+                {
+                    // "ges-", "gie-" at the start, "-ger-", "-gy-"
+                    codes.add("K", "J");
+                    i += 2;
+                } else if matches(i + 1, 1, &["E", "I", "Y"]) || matches(i - 1, 4, &["AGGI", "OGGI"]) {
+                    if matches(0, 4, &["VAN ", "VON "]) || matches(0, 3, &["SCH"]) || matches(i + 1, 2, &["ET"]) {
+                        codes.both("K");
+                    } else if matches(i + 1, 3, &["IER"]) {
+                        codes.both("J");
+                    } else {
+                        codes.add("J", "K");
+                    }
+                    i += 2;
+                } else if at(i + 1) == 'G' {
+                    codes.both("K");
+                    i += 2;
+                } else {
+                    codes.both("K");
+                    i += 1;
+                }
+            }
+            'H' => {
+                if (i == 0 || is_vowel(at(i - 1))) && is_vowel(at(i + 1)) {
+                    codes.both("H");
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            'J' => {
+                if matches(i, 4, &["JOSE"]) || matches(0, 4, &["SAN "]) {
+                    if (i == 0 && at(i + 4) == ' ') || len == 4 || matches(0, 4, &["SAN "]) {
+                        codes.both("H");
+                    } else {
+                        codes.add("J", "H");
+                    }
+                    i += 1;
+                } else {
+                    if i == 0 {
+                        codes.add("J", "A");
+                    } else if is_vowel(at(i - 1)) && !slavo_germanic && (at(i + 1) == 'A' || at(i + 1) == 'O') {
+                        codes.add("J", "H");
+                    } else if i == len - 1 {
+                        codes.add("J", "");
+                    } else if !matches(i + 1, 1, &["L", "T", "K", "S", "N", "M", "B", "Z"])
+                        && !matches(i - 1, 1, &["S", "K", "L"])
+                    {
+                        codes.both("J");
+                    }
+                    i += if at(i + 1) == 'J' { 2 } else { 1 };
+                }
+            }
+            'K' => {
+                codes.both("K");
+                i += if at(i + 1) == 'K' { 2 } else { 1 };
+            }
+            'L' => {
+                if at(i + 1) == 'L' {
+                    let spanish = (i == len - 3 && matches(i - 1, 4, &["ILLO", "ILLA", "ALLE"]))
+                        || ((matches(len - 2, 2, &["AS", "OS"]) || matches(len - 1, 1, &["A", "O"]))
+                            && matches(i - 1, 4, &["ALLE"]));
+                    if spanish {
+                        codes.add("L", "");
+                    } else {
+                        codes.both("L");
+                    }
+                    i += 2;
+                } else {
+                    codes.both("L");
+                    i += 1;
+                }
+            }
+            'M' => {
+                codes.both("M");
+                let double = at(i + 1) == 'M'
+                    || (matches(i - 1, 3, &["UMB"]) && (i + 1 == len - 1 || matches(i + 2, 2, &["ER"])));
+                i += if double { 2 } else { 1 };
+            }
+            'N' => {
+                codes.both("N");
+                i += if at(i + 1) == 'N' { 2 } else { 1 };
+            }
+            'Ñ' => {
+                codes.both("N");
+                i += 1;
+            }
+            'P' => {
+                if at(i + 1) == 'H' {
+                    codes.both("F");
+                    i += 2;
+                } else {
+                    codes.both("P");
+                    i += if matches(i + 1, 1, &["P", "B"]) { 2 } else { 1 };
+                }
+            }
+            'Q' => {
+                codes.both("K");
+                i += if at(i + 1) == 'Q' { 2 } else { 1 };
+            }
+            'R' => {
+                if i == len - 1 && !slavo_germanic && matches(i - 2, 2, &["IE"]) && !matches(i - 4, 2, &["ME", "MA"]) {
+                    codes.add("", "R");
+                } else {
+                    codes.both("R");
+                }
+                i += if at(i + 1) == 'R' { 2 } else { 1 };
+            }
+            'S' => {
+                if matches(i - 1, 3, &["ISL", "YSL"]) {
+                    i += 1;
+                } else if i == 0 && matches(i, 5, &["SUGAR"]) {
+                    codes.add("X", "S");
+                    i += 1;
+                } else if matches(i, 2, &["SH"]) {
+                    if matches(i + 1, 4, &["HEIM", "HOEK", "HOLM", "HOLZ"]) {
+                        codes.both("S");
+                    } else {
+                        codes.both("X");
+                    }
+                    i += 2;
+                } else if matches(i, 3, &["SIO", "SIA"]) || matches(i, 4, &["SIAN"]) {
+                    if slavo_germanic {
+                        codes.both("S");
+                    } else {
+                        codes.add("S", "X");
+                    }
+                    i += 3;
+                } else if (i == 0 && matches(i + 1, 1, &["M", "N", "L", "W"])) || matches(i + 1, 1, &["Z"]) {
+                    codes.add("S", "X");
+                    i += if matches(i + 1, 1, &["Z"]) { 2 } else { 1 };
+                } else if matches(i, 2, &["SC"]) {
+                    if at(i + 2) == 'H' {
+                        if matches(i + 3, 2, &["OO", "ER", "EN", "UY", "ED", "EM"]) {
+                            if matches(i + 3, 2, &["ER", "EN"]) {
+                                codes.add("X", "SK");
+                            } else {
+                                codes.both("SK");
+                            }
+                        } else if i == 0 && !is_vowel(at(3)) && at(3) != 'W' {
+                            codes.add("X", "S");
+                        } else {
+                            codes.both("X");
+                        }
+                    } else if matches(i + 2, 1, &["I", "E", "Y"]) {
+                        codes.both("S");
+                    } else {
+                        codes.both("SK");
+                    }
+                    i += 3;
+                } else {
+                    if i == len - 1 && matches(i - 2, 2, &["AI", "OI"]) {
+                        codes.add("", "S");
+                    } else {
+                        codes.both("S");
+                    }
+                    i += if matches(i + 1, 1, &["S", "Z"]) { 2 } else { 1 };
+                }
+            }
+            'T' => {
+                if matches(i, 4, &["TION"]) || matches(i, 3, &["TIA", "TCH"]) {
+                    codes.both("X");
+                    i += 3;
+                } else if matches(i, 2, &["TH"]) || matches(i, 3, &["TTH"]) {
+                    if matches(i + 2, 2, &["OM", "AM"]) || matches(0, 4, &["VAN ", "VON "]) || matches(0, 3, &["SCH"]) {
+                        codes.both("T");
+                    } else {
+                        codes.add("0", "T");
+                    }
+                    i += 2;
+                } else {
+                    codes.both("T");
+                    i += if matches(i + 1, 1, &["T", "D"]) { 2 } else { 1 };
+                }
+            }
+            'V' => {
+                codes.both("F");
+                i += if at(i + 1) == 'V' { 2 } else { 1 };
+            }
+            'W' => {
+                if matches(i, 2, &["WR"]) {
+                    codes.both("R");
+                    i += 2;
+                } else if i == 0 && (is_vowel(at(i + 1)) || matches(i, 2, &["WH"])) {
+                    if is_vowel(at(i + 1)) {
+                        codes.add("A", "F");
+                    } else {
+                        codes.both("A");
+                    }
+                    i += 1;
+                } else if (i == len - 1 && is_vowel(at(i - 1)))
+                    || matches(i - 1, 5, &["EWSKI", "EWSKY", "OWSKI", "OWSKY"])
+                    || matches(0, 3, &["SCH"])
+                {
+                    codes.add("", "F");
+                    i += 1;
+                } else if matches(i, 4, &["WICZ", "WITZ"]) {
+                    codes.add("TS", "FX");
+                    i += 4;
+                } else {
+                    i += 1;
+                }
+            }
+            'X' => {
+                if i == 0 {
+                    codes.both("S");
+                    i += 1;
+                } else {
+                    let french = i == len - 1 && (matches(i - 3, 3, &["IAU", "EAU"]) || matches(i - 2, 2, &["AU", "OU"]));
+                    if !french {
+                        codes.both("KS");
+                    }
+                    i += if matches(i + 1, 1, &["C", "X"]) { 2 } else { 1 };
+                }
+            }
+            'Z' => {
+                if at(i + 1) == 'H' {
+                    codes.both("J");
+                    i += 2;
+                } else {
+                    if matches(i + 1, 2, &["ZO", "ZI", "ZA"]) || (slavo_germanic && i > 0 && at(i - 1) != 'T') {
+                        codes.add("S", "TS");
+                    } else {
+                        codes.both("S");
+                    }
+                    i += if at(i + 1) == 'Z' { 2 } else { 1 };
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    (codes.primary, codes.alternate)
+}
+
+/// A word with everything a search compares it by
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Term {
+    pub word: String,
+    pub stem: String,
+    pub primary: String,
+    pub alternate: String,
+}
+
+impl Term {
+    /// `word` must already be a token from `tokenize`
+    pub fn new(word: &str) -> Self {
+        let (primary, alternate) = double_metaphone(word);
+        Term { word: word.to_string(), stem: stem(word), primary, alternate }
+    }
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            current[j + 1] = if ca == cb {
+                previous[j]
+            } else {
+                1 + previous[j].min(previous[j + 1]).min(current[j])
+            };
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// A search term, prepared once and compared against many words
+pub struct Query {
+    term: Term,
+    chars: Vec<char>,
+}
+
+impl Query {
+    /// `None` for terms shorter than two characters
+    pub fn new(text: &str) -> Option<Self> {
+        let word = normalize_word(text.trim());
+        let chars: Vec<char> = word.chars().collect();
+        if chars.len() < 2 {
+            return None;
+        }
+        Some(Query { term: Term::new(&word), chars })
+    }
+
+    pub fn classify(&self, term: &Term) -> Option<MatchKind> {
+        let query = &self.term;
+        if term.word == query.word || term.stem == query.stem {
+            return Some(MatchKind::Exact);
+        }
+        if self.chars.len() >= 3 && term.word.contains(&query.word) {
+            return Some(MatchKind::Contains);
+        }
+        let word: Vec<char> = term.word.chars().collect();
+        if word.first() == self.chars.first() {
+            let max_distance = match self.chars.len() {
+                n if n >= 8 => 3,
+                n if n >= 5 => 2,
+                _ => 1,
+            };
+            let distance = levenshtein(&word, &self.chars);
+            if distance > 0 && distance <= max_distance {
+                return Some(MatchKind::Spelling);
+            }
+        }
+        if self.chars.len() >= 3 && !query.primary.is_empty() && !term.primary.is_empty() {
+            let ours = [&query.primary, &query.alternate];
+            let theirs = [&term.primary, &term.alternate];
+            if ours.iter().any(|code| !code.is_empty() && theirs.contains(code)) {
+                return Some(MatchKind::Phonetic);
+            }
+        }
+        None
+    }
+}
+
+/// Every distinct word in a document with its stem and phonetic codes, stored
+/// as parallel lists to keep the export small. A stem equal to its word is
+/// stored empty, and the phonetic codes as "PRIMARY" or "PRIMARY|ALTERNATE".
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchIndex {
+    pub version: u32,
+    pub words: Vec<String>,
+    pub stems: Vec<String>,
+    pub phonetic: Vec<String>,
+}
+
+impl SearchIndex {
+    pub fn from_document(document: &LegacyDocument) -> Self {
+        let mut words = std::collections::BTreeSet::new();
+        for_each_text(document, |_, text| words.extend(tokenize(text)));
+
+        let mut index = SearchIndex { version: INDEX_VERSION, ..Default::default() };
+        for word in words {
+            let term = Term::new(&word);
+            index.stems.push(if term.stem == term.word { String::new() } else { term.stem });
+            index.phonetic.push(if term.alternate == term.primary {
+                term.primary
+            } else {
+                format!("{}|{}", term.primary, term.alternate)
+            });
+            index.words.push(word);
+        }
+        index
+    }
+
+    #[cfg(test)]
+    pub fn terms(&self) -> impl Iterator<Item = Term> + '_ {
+        self.words.iter().zip(&self.stems).zip(&self.phonetic).map(|((word, stem), phonetic)| {
+            let (primary, alternate) = phonetic.split_once('|').unwrap_or((phonetic, phonetic));
+            Term {
+                word: word.clone(),
+                stem: if stem.is_empty() { word.clone() } else { stem.clone() },
+                primary: primary.to_string(),
+                alternate: alternate.to_string(),
+            }
+        })
+    }
+}
+
+/// Where a piece of text sits in the document
+#[derive(Debug, Clone, Copy)]
+pub struct TextField<'a> {
+    /// JSON path, e.g. "financial.bank_accounts[0].name"
+    pub path: &'a str,
+    /// `id` of the innermost custom section, subsection, item or attachment
+    pub entry_id: Option<&'a str>,
+}
+
+/// Calls `visit` with the location and value of every text field a reader
+/// would see
+pub fn for_each_text(document: &LegacyDocument, mut visit: impl FnMut(TextField, &str)) {
+    fn walk(value: &serde_json::Value, path: &mut String, entry_id: Option<&str>, visit: &mut dyn FnMut(TextField, &str)) {
+        match value {
+            serde_json::Value::String(text) if !text.trim().is_empty() => visit(TextField { path, entry_id }, text),
+            serde_json::Value::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    let len = path.len();
+                    path.push_str(&format!("[{}]", i));
+                    walk(item, path, entry_id, visit);
+                    path.truncate(len);
+                }
+            }
+            serde_json::Value::Object(fields) => {
+                let entry_id = fields.get("id").and_then(|id| id.as_str()).or(entry_id);
+                for (key, item) in fields {
+                    if SKIPPED_KEYS.contains(&key.as_str()) {
+                        continue;
+                    }
+                    let len = path.len();
+                    if !path.is_empty() {
+                        path.push('.');
+                    }
+                    path.push_str(key);
+                    walk(item, path, entry_id, visit);
+                    path.truncate(len);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let Ok(value) = serde_json::to_value(document) {
+        walk(&value, &mut String::new(), None, &mut visit);
+    }
+}
+
+/// One matching word, found by `search_document`
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    /// The sidebar section: a built-in key such as "financial", or
+    /// "custom-<id>" for a top-level custom section
+    pub section: String,
+    /// Id of the custom item, subsection or attachment holding the text. Built-in
+    /// entries have no id; their index is in `field_path`.
+    pub entry_id: Option<String>,
+    /// JSON path of the field, e.g. "financial.bank_accounts[0].notes". Text
+    /// inside an attachment ends in ".content".
+    pub field_path: String,
+    /// The word as written
+    pub text: String,
+    pub kind: MatchKind,
+    /// The word with some surrounding text
+    pub snippet: String,
+}
+
+/// Every attachment with the JSON path of its list
+fn attachment_lists(document: &LegacyDocument) -> Vec<(String, &Vec<FileAttachment>)> {
+    let mut lists = vec![
+        ("financial.attachments".to_string(), &document.financial.attachments),
+        ("insurance.attachments".to_string(), &document.insurance.attachments),
+        ("bills.attachments".to_string(), &document.bills.attachments),
+        ("property.attachments".to_string(), &document.property.attachments),
+        ("legal.attachments".to_string(), &document.legal.attachments),
+        ("digital.attachments".to_string(), &document.digital.attachments),
+        ("household.attachments".to_string(), &document.household.attachments),
+        ("personal.attachments".to_string(), &document.personal.attachments),
+        ("contacts.attachments".to_string(), &document.contacts.attachments),
+        ("medical.attachments".to_string(), &document.medical.attachments),
+        ("pets.attachments".to_string(), &document.pets.attachments),
+    ];
+    for (i, section) in document.custom_sections.iter().enumerate() {
+        lists.push((format!("custom_sections[{}].attachments", i), &section.attachments));
+    }
+    lists
+}
+
+/// The text of a TXT or CSV attachment, cut to `MAX_ATTACHMENT_TEXT` bytes
+fn attachment_text(attachment: &FileAttachment) -> Option<String> {
+    let name = attachment.name.to_lowercase();
+    let is_text = matches!(attachment.mime_type.as_str(), "text/plain" | "text/csv")
+        || name.ends_with(".txt")
+        || name.ends_with(".csv");
+    if !is_text {
+        return None;
+    }
+    let mut bytes = BASE64.decode(attachment.data.trim()).ok()?;
+    bytes.truncate(MAX_ATTACHMENT_TEXT);
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// The sidebar section a JSON path belongs to, `None` outside any section
+fn section_for_path(document: &LegacyDocument, path: &str) -> Option<String> {
+    let key = path.split(['.', '[']).next()?;
+    match key {
+        "meta" => None,
+        "custom_sections" => {
+            let index: usize = path["custom_sections[".len()..].split(']').next()?.parse().ok()?;
+            let section = document.custom_sections.get(index)?;
+            Some(section.parent.clone().unwrap_or_else(|| format!("custom-{}", section.id)))
+        }
+        _ => Some(key.to_string()),
+    }
+}
+
+/// `text[start..end]` with up to `SNIPPET_CONTEXT` characters either side, on one line
+fn snippet(text: &str, start: usize, end: usize) -> String {
+    let before: Vec<char> = text[..start].chars().collect();
+    let after: Vec<char> = text[end..].chars().collect();
+    let from = before.len().saturating_sub(SNIPPET_CONTEXT);
+    let to = after.len().min(SNIPPET_CONTEXT);
+    let snippet = format!(
+        "{}{}{}{}{}",
+        if from > 0 { "…" } else { "" },
+        before[from..].iter().collect::<String>(),
+        &text[start..end],
+        after[..to].iter().collect::<String>(),
+        if to < after.len() { "…" } else { "" },
+    );
+    snippet.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Searches the document with the same matching as the export's search box.
+/// Each distinct word gives one hit per field, best kinds first, and only the
+/// kinds in `kinds` are returned.
+pub fn search_document(document: &LegacyDocument, query: &Query, kinds: &[MatchKind]) -> Vec<SearchHit> {
+    let mut classified: HashMap<String, Option<MatchKind>> = HashMap::new();
+    let mut hits = Vec::new();
+    let mut search_text = |field: TextField, text: &str| {
+        let Some(section) = section_for_path(document, field.path) else {
+            return;
+        };
+        let mut seen = std::collections::HashSet::new();
+        for token in tokens(text) {
+            let kind = *classified
+                .entry(token.word.clone())
+                .or_insert_with(|| query.classify(&Term::new(&token.word)));
+            let Some(kind) = kind.filter(|k| kinds.contains(k)) else {
+                continue;
+            };
+            if !seen.insert(token.word) {
+                continue;
+            }
+            hits.push(SearchHit {
+                section: section.clone(),
+                entry_id: field.entry_id.map(str::to_string),
+                field_path: field.path.to_string(),
+                text: text[token.start..token.end].to_string(),
+                kind,
+                snippet: snippet(text, token.start, token.end),
+            });
+        }
+    };
+
+    for_each_text(document, &mut search_text);
+    for (list_path, attachments) in attachment_lists(document) {
+        for (i, attachment) in attachments.iter().enumerate() {
+            if let Some(text) = attachment_text(attachment) {
+                let path = format!("{}[{}].content", list_path, i);
+                search_text(TextField { path: &path, entry_id: Some(&attachment.id) }, &text);
+            }
+        }
+    }
+    hits.sort_by_key(|hit| hit.kind);
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify(query: &str, word: &str) -> Option<MatchKind> {
+        Query::new(query).unwrap().classify(&Term::new(word))
+    }
+
+    #[derive(Deserialize)]
+    struct Vectors {
+        tokenize: Vec<Vector<Vec<String>>>,
+        stem: Vec<Vector<String>>,
+        metaphone: Vec<Vector<(String, String)>>,
+        classify: Vec<ClassifyVector>,
+    }
+
+    #[derive(Deserialize)]
+    struct Vector<T> {
+        input: String,
+        expected: T,
+    }
+
+    #[derive(Deserialize)]
+    struct ClassifyVector {
+        query: String,
+        word: String,
+        expected: Option<MatchKind>,
+    }
+
+    fn vectors() -> Vectors {
+        serde_json::from_str(include_str!("search_vectors.json")).unwrap()
+    }
+
+    #[test]
+    fn test_search_vectors() {
+        let vectors = vectors();
+        for v in vectors.tokenize {
+            assert_eq!(tokenize(&v.input), v.expected, "tokenize {:?}", v.input);
+        }
+        for v in vectors.stem {
+            assert_eq!(stem(&v.input), v.expected, "stem {:?}", v.input);
+        }
+        for v in vectors.metaphone {
+            assert_eq!(double_metaphone(&v.input), v.expected, "metaphone {:?}", v.input);
+        }
+        for v in vectors.classify {
+            assert_eq!(classify(&v.query, &v.word), v.expected, "{:?} against {:?}", v.query, v.word);
+        }
+    }
+
+    #[test]
+    fn test_js_index_version_matches() {
+        assert!(SEARCH_JS_MATCHING.contains(&format!("const SEARCH_INDEX_VERSION = {};", INDEX_VERSION)));
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("O'Brien’s 2 accounts: José-Luis, a x99"),
+            vec!["o'brien's", "accounts", "josé", "luis", "x99"]
+        );
+        assert!(tokenize("  ").is_empty());
+    }
+
+    #[test]
+    fn test_stem() {
+        assert_eq!(stem("payments"), "payment");
+        assert_eq!(stem("policies"), "policy");
+        assert_eq!(stem("running"), "run");
+        assert_eq!(stem("filled"), "fill");
+        assert_eq!(stem("brother's"), "brother");
+        assert_eq!(stem("business"), "business");
+        assert_eq!(stem("bus"), "bus");
+        assert_eq!(stem("josé"), "josé");
+        assert_eq!(stem("boxes"), "box");
+    }
+
+    #[test]
+    fn test_double_metaphone() {
+        let code = |w: &str| double_metaphone(w);
+        assert_eq!(code("smith"), ("SM0".into(), "XMT".into()));
+        assert_eq!(code("schmidt"), ("XMT".into(), "SMT".into()));
+        assert_eq!(code("thompson"), ("TMPS".into(), "TMPS".into()));
+        assert_eq!(code("knight"), ("NT".into(), "NT".into()));
+        assert_eq!(code("catherine"), ("K0RN".into(), "KTRN".into()));
+        assert_eq!(code("jose"), ("HS".into(), "HS".into()));
+        assert_eq!(code("caesar"), ("SSR".into(), "SSR".into()));
+        assert_eq!(code("xavier"), ("SF".into(), "SFR".into()));
+        assert_eq!(code(""), (String::new(), String::new()));
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify("payment", "payment"), Some(MatchKind::Exact));
+        assert_eq!(classify("payment", "payments"), Some(MatchKind::Exact));
+        assert_eq!(classify("bank", "banking"), Some(MatchKind::Exact));
+        assert_eq!(classify("pay", "repayment"), Some(MatchKind::Contains));
+        assert_eq!(classify("insurence", "insurance"), Some(MatchKind::Spelling));
+        assert_eq!(classify("smyth", "smith"), Some(MatchKind::Spelling));
+        assert_eq!(classify("catherine", "kathryn"), Some(MatchKind::Phonetic));
+        assert_eq!(classify("dog", "cat"), None);
+        assert!(Query::new(" a ").is_none());
+    }
+
+    fn search(document: &LegacyDocument, query: &str) -> Vec<SearchHit> {
+        search_document(document, &Query::new(query).unwrap(), &MatchKind::ALL)
+    }
+
+    fn text_attachment(id: &str, name: &str, text: &str) -> FileAttachment {
+        FileAttachment {
+            id: id.into(),
+            name: name.into(),
+            mime_type: "text/plain".into(),
+            size: text.len() as u64,
+            data: BASE64.encode(text),
+            group: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_search_document_builtin_fields() {
+        let mut document = LegacyDocument::default();
+        document.meta.creator_name = "Chase Walker".into();
+        document.financial.bank_accounts.push(crate::models::BankAccount {
+            name: "Joint checking".into(),
+            institution: "Chase".into(),
+            ..Default::default()
+        });
+        document.bills.notes = "Card is with Chace, see the drawer".into();
+
+        let hits = search(&document, "chase");
+        assert_eq!(hits.len(), 2, "{:?}", hits);
+        assert_eq!(hits[0].section, "financial");
+        assert_eq!(hits[0].field_path, "financial.bank_accounts[0].institution");
+        assert_eq!(hits[0].entry_id, None);
+        assert_eq!(hits[0].kind, MatchKind::Exact);
+        assert_eq!(hits[1].field_path, "bills.notes");
+        assert_eq!(hits[1].text, "Chace");
+        assert_eq!(hits[1].kind, MatchKind::Spelling);
+        assert_eq!(hits[1].snippet, "Card is with Chace, see the drawer");
+
+        let exact_only = search_document(&document, &Query::new("chase").unwrap(), &[MatchKind::Exact]);
+        assert_eq!(exact_only.len(), 1);
+    }
+
+    #[test]
+    fn test_search_never_shows_passwords() {
+        let mut document = LegacyDocument::default();
+        document.digital.online_accounts.push(crate::models::DigitalAccount {
+            name: "Gmail".into(),
+            username: "pat@example.com".into(),
+            password: "hunter2 sunflower".into(),
+            ..Default::default()
+        });
+
+        for query in ["hunter2", "sunflower", "sunflowr"] {
+            assert!(search(&document, query).is_empty(), "{}", query);
+        }
+        assert!(SearchIndex::from_document(&document).words.iter().all(|w| w != "sunflower"));
+        assert_eq!(search(&document, "gmail")[0].snippet, "Gmail");
+    }
+
+    #[test]
+    fn test_search_document_custom_sections_and_attachments() {
+        let mut document = LegacyDocument::default();
+        let mut values = HashMap::new();
+        values.insert("f1".to_string(), "Storage unit 42 at Shurgard".to_string());
+        document.custom_sections.push(crate::models::CustomSection {
+            id: "s1".into(),
+            name: "Storage".into(),
+            parent: None,
+            subsections: vec![crate::models::CustomSubsection {
+                id: "sub1".into(),
+                name: "Units".into(),
+                items: vec![crate::models::CustomItem { id: "item1".into(), values }],
+                ..Default::default()
+            }],
+            attachments: vec![text_attachment("a1", "storage-contract.txt", "Gate code is in the storage office")],
+        });
+        document.custom_sections.push(crate::models::CustomSection {
+            id: "s2".into(),
+            name: "Crypto".into(),
+            parent: Some("financial".into()),
+            ..Default::default()
+        });
+        document.legal.attachments.push(FileAttachment {
+            data: BASE64.encode("storage,fee\nunit 42,30"),
+            mime_type: "text/csv".into(),
+            ..text_attachment("a2", "fees.csv", "")
+        });
+
+        let hits = search(&document, "storage");
+        let find = |path: &str| hits.iter().find(|h| h.field_path == path).unwrap_or_else(|| panic!("{} in {:?}", path, hits));
+
+        let section_name = find("custom_sections[0].name");
+        assert_eq!((section_name.section.as_str(), section_name.entry_id.as_deref()), ("custom-s1", Some("s1")));
+        let item = find("custom_sections[0].subsections[0].items[0].values.f1");
+        assert_eq!(item.entry_id.as_deref(), Some("item1"));
+        let filename = find("custom_sections[0].attachments[0].name");
+        assert_eq!(filename.entry_id.as_deref(), Some("a1"));
+        let content = find("custom_sections[0].attachments[0].content");
+        assert_eq!((content.entry_id.as_deref(), content.text.as_str()), (Some("a1"), "storage"));
+        let csv = find("legal.attachments[0].content");
+        assert_eq!((csv.section.as_str(), csv.entry_id.as_deref()), ("legal", Some("a2")));
+
+        assert_eq!(search(&document, "crypto")[0].section, "financial");
+    }
+
+    #[test]
+    fn test_fuzzy_is_spelling() {
+        let kinds: Vec<MatchKind> = serde_json::from_str(r#"["exact", "fuzzy", "phonetic"]"#).unwrap();
+        assert_eq!(kinds, vec![MatchKind::Exact, MatchKind::Spelling, MatchKind::Phonetic]);
+    }
+
+    #[test]
+    fn test_index_round_trips_terms() {
+        let mut document = LegacyDocument::default();
+        document.meta.creator_name = "Catherine Smith".into();
+        document.financial.notes = "Payments to Smith & Sons".into();
+        let index = SearchIndex::from_document(&document);
+        assert_eq!(index.version, INDEX_VERSION);
+        assert!(index.words.contains(&"catherine".to_string()));
+        let terms: Vec<Term> = index.terms().collect();
+        for term in &terms {
+            assert_eq!(term, &Term::new(&term.word));
+        }
+        let query = Query::new("kathryn").unwrap();
+        assert!(terms.iter().any(|t| t.word == "catherine" && query.classify(t) == Some(MatchKind::Phonetic)));
+    }
+}
@@ -0,0 +1,434 @@
+        // Must match tokenize(), stem(), double_metaphone() and Query::classify() in search.rs
+        const SEARCH_INDEX_VERSION = 1;
+        const SEARCH_WORD = /[\p{Alphabetic}\p{N}]+(?:['’][\p{Alphabetic}\p{N}]+)*/gu;
+
+        function normalizeSearchWord(word) {
+            return word.toLowerCase().replace(/’/g, "'");
+        }
+
+        // Words of two or more characters with their offsets in `text`
+        function searchTokens(text) {
+            const tokens = [];
+            const pattern = new RegExp(SEARCH_WORD.source, 'gu');
+            let m;
+            while ((m = pattern.exec(text))) {
+                if (Array.from(m[0]).length >= 2) {
+                    tokens.push({ text: m[0], word: normalizeSearchWord(m[0]), start: m.index, end: m.index + m[0].length });
+                }
+            }
+            return tokens;
+        }
+
+        function searchStem(word) {
+            let w = Array.from(word);
+            if (w.length <= 3 || !w.every(c => (c >= 'a' && c <= 'z') || c === "'")) return word;
+            const endsWith = s => w.length >= s.length && w.slice(w.length - s.length).join('') === s;
+            if (endsWith("'s")) w = w.slice(0, -2);
+            else if (endsWith("'")) w = w.slice(0, -1);
+
+            if (['sses', 'xes', 'ches', 'shes'].some(endsWith)) w = w.slice(0, -2);
+            else if (endsWith('ies') && w.length > 4) w = w.slice(0, -3).concat('y');
+            else if (endsWith('s') && !endsWith('ss') && !endsWith('us') && !endsWith('is') && w.length > 3) w = w.slice(0, -1);
+
+            for (const suffix of ['ingly', 'edly', 'ing', 'ed', 'ly']) {
+                const cut = Math.max(0, w.length - suffix.length);
+                if (endsWith(suffix) && cut >= 3 && w.slice(0, cut).some(c => 'aeiouy'.includes(c))) {
+                    w = w.slice(0, cut);
+                    const n = w.length;
+                    if (suffix !== 'ly' && w[n - 1] === w[n - 2] && !'lsz'.includes(w[n - 1]) && !'aeiouy'.includes(w[n - 1])) {
+                        w = w.slice(0, -1);
+                    }
+                    break;
+                }
+            }
+            return w.join('');
+        }
+
+        function doubleMetaphone(word) {
+            const v = Array.from(word.toUpperCase());
+            const len = v.length;
+            const at = i => (i < 0 || i >= len) ? '\0' : v[i];
+            const isVowel = c => c !== '\0' && 'AEIOUY'.includes(c);
+            const matches = (start, n, options) => start >= 0 && start + n <= len && options.includes(v.slice(start, start + n).join(''));
+            const text = v.join('');
+            const slavoGermanic = text.includes('W') || text.includes('K') || text.includes('CZ') || text.includes('WITZ');
+
+            let primary = '';
+            let alternate = '';
+            const add = (p, a) => {
+                primary = (primary + p).slice(0, 4);
+                alternate = (alternate + a).slice(0, 4);
+            };
+            const both = code => add(code, code);
+
+            let i = matches(0, 2, ['GN', 'KN', 'PN', 'WR', 'PS']) ? 1 : 0;
+            while (!(primary.length >= 4 && alternate.length >= 4) && i < len) {
+                switch (at(i)) {
+                    case 'A': case 'E': case 'I': case 'O': case 'U': case 'Y':
+                        if (i === 0) both('A');
+                        i += 1;
+                        break;
+                    case 'B':
+                        both('P');
+                        i += at(i + 1) === 'B' ? 2 : 1;
+                        break;
+                    case 'Ç':
+                        both('S');
+                        i += 1;
+                        break;
+                    case 'C': {
+                        const germanicCh = matches(i, 4, ['CHIA'])
+                            || (i > 1
+                                && !isVowel(at(i - 2))
+                                && matches(i - 1, 3, ['ACH'])
+                                && ((at(i + 2) !== 'I' && at(i + 2) !== 'E') || matches(i - 2, 6, ['BACHER', 'MACHER'])));
+                        if (germanicCh) {
+                            both('K');
+                            i += 2;
+                        } else if (i === 0 && matches(i, 6, ['CAESAR'])) {
+                            both('S');
+                            i += 2;
+                        } else if (matches(i, 2, ['CH'])) {
+                            if (i > 0 && matches(i, 4, ['CHAE'])) {
+                                add('K', 'X');
+                            } else if ((i === 0
+                                && (matches(i + 1, 5, ['HARAC', 'HARIS']) || matches(i + 1, 3, ['HOR', 'HYM', 'HIA', 'HEM']))
+                                && !matches(0, 5, ['CHORE']))
+                                || matches(0, 4, ['VAN ', 'VON '])
+                                || matches(0, 3, ['SCH'])
+                                || matches(i - 2, 6, ['ORCHES', 'ARCHIT', 'ORCHID'])
+                                || matches(i + 2, 1, ['T', 'S'])
+                                || ((matches(i - 1, 1, ['A', 'O', 'U', 'E']) || i === 0)
+                                    && (matches(i + 2, 1, ['L', 'R', 'N', 'M', 'B', 'H', 'F', 'V', 'W', ' ']) || i + 1 === len - 1))) {
+                                // Greek roots ("chorus") and Germanic "ch"
+                                both('K');
+                            } else if (i > 0) {
+                                if (matches(0, 2, ['MC'])) both('K');
+                                else add('X', 'K');
+                            } else {
+                                both('X');
+                            }
+                            i += 2;
+                        } else if (matches(i, 2, ['CZ']) && !matches(i - 2, 4, ['WICZ'])) {
+                            add('S', 'X');
+                            i += 2;
+                        } else if (matches(i + 1, 3, ['CIA'])) {
+                            both('X');
+                            i += 3;
+                        } else if (matches(i, 2, ['CC']) && !(i === 1 && at(0) === 'M')) {
+                            if (matches(i + 2, 1, ['I', 'E', 'H']) && !matches(i + 2, 2, ['HU'])) {
+                                if ((i === 1 && at(i - 1) === 'A') || matches(i - 1, 5, ['UCCEE', 'UCCES'])) both('KS');
+                                else both('X');
+                                i += 3;
+                            } else {
+                                both('K');
+                                i += 2;
+                            }
+                        } else if (matches(i, 2, ['CK', 'CG', 'CQ'])) {
+                            both('K');
+                            i += 2;
+                        } else if (matches(i, 2, ['CI', 'CE', 'CY'])) {
+                            if (matches(i, 3, ['CIO', 'CIE', 'CIA'])) add('S', 'X');
+                            else both('S');
+                            i += 2;
+                        } else {
+                            both('K');
+                            if (matches(i + 1, 2, [' C', ' Q', ' G'])) i += 3;
+                            else if (matches(i + 1, 1, ['C', 'K', 'Q']) && !matches(i + 1, 2, ['CE', 'CI'])) i += 2;
+                            else i += 1;
+                        }
+                        break;
+                    }
+                    case 'D':
+                        if (matches(i, 2, ['DG'])) {
+                            if (matches(i + 2, 1, ['I', 'E', 'Y'])) {
+                                both('J');
+                                i += 3;
+                            } else {
+                                both('TK');
+                                i += 2;
+                            }
+                        } else if (matches(i, 2, ['DT', 'DD'])) {
+                            both('T');
+                            i += 2;
+                        } else {
+                            both('T');
+                            i += 1;
+                        }
+                        break;
+                    case 'F':
+                        both('F');
+                        i += at(i + 1) === 'F' ? 2 : 1;
+                        break;
+                    case 'G':
+                        if (at(i + 1) === 'H') {
+                            if (i > 0 && !isVowel(at(i - 1))) {
+                                both('K');
+                            } else if (i === 0) {
+                                both(at(i + 2) === 'I' ? 'J' : 'K');
+                            } else if ((i > 1 && matches(i - 2, 1, ['B', 'H', 'D']))
+                                || (i > 2 && matches(i - 3, 1, ['B', 'H', 'D']))
+                                || (i > 3 && matches(i - 4, 1, ['B', 'H']))) {
+                                // Parker's rule: "hugh"
+                            } else if (i > 2 && at(i - 1) === 'U' && matches(i - 3, 1, ['C', 'G', 'L', 'R', 'T'])) {
+                                both('F');
+                            } else if (i > 0 && at(i - 1) !== 'I') {
+                                both('K');
+                            }
+                            i += 2;
+                        } else if (at(i + 1) === 'N') {
+                            if (i === 1 && isVowel(at(0)) && !slavoGermanic) add('KN', 'N');
+                            else if (!matches(i + 2, 2, ['EY']) && at(i + 1) !== 'Y' && !slavoGermanic) add('N', 'KN');
+                            else both('KN');
+                            i += 2;
+                        } else if (matches(i + 1, 2, ['LI']) && !slavoGermanic) {
+                            add('KL', 'L');
+                            i += 2;
+                        } else if ((i === 0
+                            && (at(i + 1) === 'Y'
+                                || matches(i + 1, 2, ['ES', 'EP', 'EB', 'EL', 'EY', 'IB', 'IL', 'IN', 'IE', 'EI', 'ER'])))
+                            || ((matches(i + 1, 2, ['ER']) || at(i + 1) === 'Y')
+                                && !matches(0, 6, ['DANGER', 'RANGER', 'MANGER'])
+                                && !matches(i - 1, 1, ['E', 'I'])
+                                && !matches(i - 1, 3, ['RGY', 'OGY']))) {
+                            // "ges-", "gie-" at the start, "-ger-", "-gy-"
+                            add('K', 'J');
+                            i += 2;
+                        } else if (matches(i + 1, 1, ['E', 'I', 'Y']) || matches(i - 1, 4, ['AGGI', 'OGGI'])) {
+                            if (matches(0, 4, ['VAN ', 'VON ']) || matches(0, 3, ['SCH']) || matches(i + 1, 2, ['ET'])) both('K');
+                            else if (matches(i + 1, 3, ['IER'])) both('J');
+                            else add('J', 'K');
+                            i += 2;
+                        } else if (at(i + 1) === 'G') {
+                            both('K');
+                            i += 2;
+                        } else {
+                            both('K');
+                            i += 1;
+                        }
+                        break;
+                    case 'H':
+                        if ((i === 0 || isVowel(at(i - 1))) && isVowel(at(i + 1))) {
+                            both('H');
+                            i += 2;
+                        } else {
+                            i += 1;
+                        }
+                        break;
+                    case 'J':
+                        if (matches(i, 4, ['JOSE']) || matches(0, 4, ['SAN '])) {
+                            if ((i === 0 && at(i + 4) === ' ') || len === 4 || matches(0, 4, ['SAN '])) both('H');
+                            else add('J', 'H');
+                            i += 1;
+                        } else {
+                            if (i === 0) {
+                                add('J', 'A');
+                            } else if (isVowel(at(i - 1)) && !slavoGermanic && (at(i + 1) === 'A' || at(i + 1) === 'O')) {
+                                add('J', 'H');
+                            } else if (i === len - 1) {
+                                add('J', '');
+                            } else if (!matches(i + 1, 1, ['L', 'T', 'K', 'S', 'N', 'M', 'B', 'Z'])
+                                && !matches(i - 1, 1, ['S', 'K', 'L'])) {
+                                both('J');
+                            }
+                            i += at(i + 1) === 'J' ? 2 : 1;
+                        }
+                        break;
+                    case 'K':
+                        both('K');
+                        i += at(i + 1) === 'K' ? 2 : 1;
+                        break;
+                    case 'L':
+                        if (at(i + 1) === 'L') {
+                            const spanish = (i === len - 3 && matches(i - 1, 4, ['ILLO', 'ILLA', 'ALLE']))
+                                || ((matches(len - 2, 2, ['AS', 'OS']) || matches(len - 1, 1, ['A', 'O']))
+                                    && matches(i - 1, 4, ['ALLE']));
+                            if (spanish) add('L', '');
+                            else both('L');
+                            i += 2;
+                        } else {
+                            both('L');
+                            i += 1;
+                        }
+                        break;
+                    case 'M': {
+                        both('M');
+                        const double = at(i + 1) === 'M'
+                            || (matches(i - 1, 3, ['UMB']) && (i + 1 === len - 1 || matches(i + 2, 2, ['ER'])));
+                        i += double ? 2 : 1;
+                        break;
+                    }
+                    case 'N':
+                        both('N');
+                        i += at(i + 1) === 'N' ? 2 : 1;
+                        break;
+                    case 'Ñ':
+                        both('N');
+                        i += 1;
+                        break;
+                    case 'P':
+                        if (at(i + 1) === 'H') {
+                            both('F');
+                            i += 2;
+                        } else {
+                            both('P');
+                            i += matches(i + 1, 1, ['P', 'B']) ? 2 : 1;
+                        }
+                        break;
+                    case 'Q':
+                        both('K');
+                        i += at(i + 1) === 'Q' ? 2 : 1;
+                        break;
+                    case 'R':
+                        if (i === len - 1 && !slavoGermanic && matches(i - 2, 2, ['IE']) && !matches(i - 4, 2, ['ME', 'MA'])) add('', 'R');
+                        else both('R');
+                        i += at(i + 1) === 'R' ? 2 : 1;
+                        break;
+                    case 'S':
+                        if (matches(i - 1, 3, ['ISL', 'YSL'])) {
+                            i += 1;
+                        } else if (i === 0 && matches(i, 5, ['SUGAR'])) {
+                            add('X', 'S');
+                            i += 1;
+                        } else if (matches(i, 2, ['SH'])) {
+                            if (matches(i + 1, 4, ['HEIM', 'HOEK', 'HOLM', 'HOLZ'])) both('S');
+                            else both('X');
+                            i += 2;
+                        } else if (matches(i, 3, ['SIO', 'SIA']) || matches(i, 4, ['SIAN'])) {
+                            if (slavoGermanic) both('S');
+                            else add('S', 'X');
+                            i += 3;
+                        } else if ((i === 0 && matches(i + 1, 1, ['M', 'N', 'L', 'W'])) || matches(i + 1, 1, ['Z'])) {
+                            add('S', 'X');
+                            i += matches(i + 1, 1, ['Z']) ? 2 : 1;
+                        } else if (matches(i, 2, ['SC'])) {
+                            if (at(i + 2) === 'H') {
+                                if (matches(i + 3, 2, ['OO', 'ER', 'EN', 'UY', 'ED', 'EM'])) {
+                                    if (matches(i + 3, 2, ['ER', 'EN'])) add('X', 'SK');
+                                    else both('SK');
+                                } else if (i === 0 && !isVowel(at(3)) && at(3) !== 'W') {
+                                    add('X', 'S');
+                                } else {
+                                    both('X');
+                                }
+                            } else if (matches(i + 2, 1, ['I', 'E', 'Y'])) {
+                                both('S');
+                            } else {
+                                both('SK');
+                            }
+                            i += 3;
+                        } else {
+                            if (i === len - 1 && matches(i - 2, 2, ['AI', 'OI'])) add('', 'S');
+                            else both('S');
+                            i += matches(i + 1, 1, ['S', 'Z']) ? 2 : 1;
+                        }
+                        break;
+                    case 'T':
+                        if (matches(i, 4, ['TION']) || matches(i, 3, ['TIA', 'TCH'])) {
+                            both('X');
+                            i += 3;
+                        } else if (matches(i, 2, ['TH']) || matches(i, 3, ['TTH'])) {
+                            if (matches(i + 2, 2, ['OM', 'AM']) || matches(0, 4, ['VAN ', 'VON ']) || matches(0, 3, ['SCH'])) both('T');
+                            else add('0', 'T');
+                            i += 2;
+                        } else {
+                            both('T');
+                            i += matches(i + 1, 1, ['T', 'D']) ? 2 : 1;
+                        }
+                        break;
+                    case 'V':
+                        both('F');
+                        i += at(i + 1) === 'V' ? 2 : 1;
+                        break;
+                    case 'W':
+                        if (matches(i, 2, ['WR'])) {
+                            both('R');
+                            i += 2;
+                        } else if (i === 0 && (isVowel(at(i + 1)) || matches(i, 2, ['WH']))) {
+                            if (isVowel(at(i + 1))) add('A', 'F');
+                            else both('A');
+                            i += 1;
+                        } else if ((i === len - 1 && isVowel(at(i - 1)))
+                            || matches(i - 1, 5, ['EWSKI', 'EWSKY', 'OWSKI', 'OWSKY'])
+                            || matches(0, 3, ['SCH'])) {
+                            add('', 'F');
+                            i += 1;
+                        } else if (matches(i, 4, ['WICZ', 'WITZ'])) {
+                            add('TS', 'FX');
+                            i += 4;
+                        } else {
+                            i += 1;
+                        }
+                        break;
+                    case 'X':
+                        if (i === 0) {
+                            both('S');
+                            i += 1;
+                        } else {
+                            const french = i === len - 1 && (matches(i - 3, 3, ['IAU', 'EAU']) || matches(i - 2, 2, ['AU', 'OU']));
+                            if (!french) both('KS');
+                            i += matches(i + 1, 1, ['C', 'X']) ? 2 : 1;
+                        }
+                        break;
+                    case 'Z':
+                        if (at(i + 1) === 'H') {
+                            both('J');
+                            i += 2;
+                        } else {
+                            if (matches(i + 1, 2, ['ZO', 'ZI', 'ZA']) || (slavoGermanic && i > 0 && at(i - 1) !== 'T')) add('S', 'TS');
+                            else both('S');
+                            i += at(i + 1) === 'Z' ? 2 : 1;
+                        }
+                        break;
+                    default:
+                        i += 1;
+                }
+            }
+            return [primary, alternate];
+        }
+
+        function searchLevenshtein(a, b) {
+            let previous = Array.from({ length: b.length + 1 }, (_, j) => j);
+            for (let i = 0; i < a.length; i++) {
+                const current = new Array(b.length + 1).fill(i + 1);
+                for (let j = 0; j < b.length; j++) {
+                    current[j + 1] = a[i] === b[j]
+                        ? previous[j]
+                        : 1 + Math.min(previous[j], previous[j + 1], current[j]);
+                }
+                previous = current;
+            }
+            return previous[b.length];
+        }
+
+        function makeSearchTerm(word) {
+            const codes = doubleMetaphone(word);
+            return { word: word, stem: searchStem(word), primary: codes[0], alternate: codes[1] };
+        }
+
+        // null for terms shorter than two characters
+        function searchQuery(text) {
+            const word = normalizeSearchWord(text.trim());
+            const chars = Array.from(word);
+            if (chars.length < 2) return null;
+            return { term: makeSearchTerm(word), chars: chars };
+        }
+
+        // 'exact', 'contains', 'spelling', 'phonetic' or null
+        function classifySearchMatch(query, term) {
+            const q = query.term;
+            if (term.word === q.word || term.stem === q.stem) return 'exact';
+            if (query.chars.length >= 3 && term.word.includes(q.word)) return 'contains';
+            const word = Array.from(term.word);
+            if (word[0] === query.chars[0]) {
+                const n = query.chars.length;
+                const maxDistance = n >= 8 ? 3 : (n >= 5 ? 2 : 1);
+                const distance = searchLevenshtein(word, query.chars);
+                if (distance > 0 && distance <= maxDistance) return 'spelling';
+            }
+            if (query.chars.length >= 3 && q.primary && term.primary) {
+                const theirs = [term.primary, term.alternate];
+                if ([q.primary, q.alternate].some(code => code && theirs.includes(code))) return 'phonetic';
+            }
+            return null;
+        }
@@ -0,0 +1,234 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ring::digest;
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+
+// Export signatures. Each install holds one Ed25519 key, kept encrypted with
+// the local key, and signs every exported HTML file with it: the template,
+// the script and the encrypted payload alike. The signature and public key
+// sit in a <meta> tag in the file's <head>; the signed bytes are the file
+// with that tag left empty, so verifying only needs the file itself.
+//
+// A changed file can't vouch for itself, because whoever changed it could
+// also change the page's script, re-sign it with their own key or strip the
+// signature. The lock screen shows the key's fingerprint so the owner can
+// read it out to the recipient, and on import the app compares the signing
+// key with its own and asks before taking in anything it didn't sign.
+
+/// The signature tag as the export templates write it, before signing
+pub const SIGNATURE_PLACEHOLDER: &str = r#"<meta name="honey-did-signature" content="">"#;
+
+const SIGNATURE_TAG_START: &str = r#"<meta name="honey-did-signature" content=""#;
+const SIGNATURE_SCHEME: &str = "ed25519";
+/// Bytes of the public key's SHA-256 shown as the fingerprint
+const FINGERPRINT_BYTES: usize = 16;
+const FINGERPRINT_GROUP: usize = 4;
+
+#[derive(Debug)]
+pub enum SigningError {
+    KeyGeneration,
+    InvalidKey,
+    MissingPlaceholder,
+    Malformed,
+    Mismatch,
+}
+
+impl std::fmt::Display for SigningError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SigningError::KeyGeneration => write!(f, "Failed to create the export signing key"),
+            SigningError::InvalidKey => write!(f, "The export signing key is damaged"),
+            SigningError::MissingPlaceholder => write!(f, "The export has no place for a signature"),
+            SigningError::Malformed => write!(f, "The file's signature is unreadable"),
+            SigningError::Mismatch => write!(f, "The file's signature doesn't match: it was changed after it was exported"),
+        }
+    }
+}
+
+impl std::error::Error for SigningError {}
+
+/// This install's export signing key, stored as its PKCS#8 document
+#[derive(Serialize, Deserialize)]
+pub struct SigningKey {
+    pkcs8: String,
+}
+
+impl SigningKey {
+    pub fn generate() -> Result<Self, SigningError> {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).map_err(|_| SigningError::KeyGeneration)?;
+        Ok(SigningKey { pkcs8: BASE64.encode(pkcs8.as_ref()) })
+    }
+
+    fn key_pair(&self) -> Result<Ed25519KeyPair, SigningError> {
+        let pkcs8 = BASE64.decode(&self.pkcs8).map_err(|_| SigningError::InvalidKey)?;
+        Ed25519KeyPair::from_pkcs8(&pkcs8).map_err(|_| SigningError::InvalidKey)
+    }
+
+    pub fn fingerprint(&self) -> Result<String, SigningError> {
+        Ok(fingerprint(self.key_pair()?.public_key().as_ref()))
+    }
+
+    /// Fills in the file's signature tag
+    pub fn sign_html(&self, html: &str) -> Result<String, SigningError> {
+        let position = html.find(SIGNATURE_PLACEHOLDER).ok_or(SigningError::MissingPlaceholder)?;
+        if html.matches(SIGNATURE_TAG_START).count() > 1 {
+            return Err(SigningError::Malformed);
+        }
+        let pair = self.key_pair()?;
+        let signature = pair.sign(html.as_bytes());
+        let tag = format!(
+            r#"{}{}:{}:{}">"#,
+            SIGNATURE_TAG_START,
+            SIGNATURE_SCHEME,
+            BASE64.encode(pair.public_key().as_ref()),
+            BASE64.encode(signature.as_ref())
+        );
+        let mut signed = html.to_string();
+        signed.replace_range(position..position + SIGNATURE_PLACEHOLDER.len(), &tag);
+        Ok(signed)
+    }
+}
+
+/// How an imported file's signature relates to this install's key
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SignatureStatus {
+    /// Signed with this install's key
+    Own { fingerprint: String },
+    /// Signed with another key: another install's export, or a copy someone changed and re-signed
+    Other { fingerprint: String },
+    /// Exported before files were signed, or the signature was removed
+    Unsigned,
+}
+
+/// Verifies an exported file and compares its signing key with `own`
+pub fn check_html(html: &str, own: &SigningKey) -> Result<SignatureStatus, SigningError> {
+    let own_fingerprint = own.fingerprint()?;
+    Ok(match verify_html(html)? {
+        None => SignatureStatus::Unsigned,
+        Some(fingerprint) if fingerprint == own_fingerprint => SignatureStatus::Own { fingerprint },
+        Some(fingerprint) => SignatureStatus::Other { fingerprint },
+    })
+}
+
+/// SHA-256 of a public key, shortened and grouped so it can be read over the phone
+pub fn fingerprint(public_key: &[u8]) -> String {
+    let hash = digest::digest(&digest::SHA256, public_key);
+    let hex: String = hash.as_ref()[..FINGERPRINT_BYTES].iter().map(|b| format!("{:02X}", b)).collect();
+    hex.as_bytes()
+        .chunks(FINGERPRINT_GROUP)
+        .map(|group| std::str::from_utf8(group).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Checks an exported file's signature and returns the signing key's fingerprint.
+/// Files from before signatures (an empty or missing tag) give `None`.
+pub fn verify_html(html: &str) -> Result<Option<String>, SigningError> {
+    let Some(start) = html.find(SIGNATURE_TAG_START) else {
+        return Ok(None);
+    };
+    // A second tag could hide the real one behind an empty one
+    if html.matches(SIGNATURE_TAG_START).count() > 1 {
+        return Err(SigningError::Malformed);
+    }
+    let value_start = start + SIGNATURE_TAG_START.len();
+    let value_len = html[value_start..].find("\">").ok_or(SigningError::Malformed)?;
+    let value = &html[value_start..value_start + value_len];
+    if value.is_empty() {
+        return Ok(None);
+    }
+
+    let mut parts = value.split(':');
+    let (Some(SIGNATURE_SCHEME), Some(public_key), Some(signature), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+        return Err(SigningError::Malformed);
+    };
+    let public_key = BASE64.decode(public_key).map_err(|_| SigningError::Malformed)?;
+    let signature = BASE64.decode(signature).map_err(|_| SigningError::Malformed)?;
+
+    let mut unsigned = html.to_string();
+    unsigned.replace_range(start..value_start + value_len + "\">".len(), SIGNATURE_PLACEHOLDER);
+    UnparsedPublicKey::new(&ED25519, &public_key)
+        .verify(unsigned.as_bytes(), &signature)
+        .map_err(|_| SigningError::Mismatch)?;
+    Ok(Some(fingerprint(&public_key)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(body: &str) -> String {
+        format!("<html><head>{}</head><body>{}</body></html>", SIGNATURE_PLACEHOLDER, body)
+    }
+
+    #[test]
+    fn test_signed_file_verifies_with_its_fingerprint() {
+        let key = SigningKey::generate().unwrap();
+        let signed = key.sign_html(&page("const ENCRYPTED_DATA = {};")).unwrap();
+        assert!(!signed.contains(SIGNATURE_PLACEHOLDER));
+        assert_eq!(verify_html(&signed).unwrap(), Some(key.fingerprint().unwrap()));
+
+        let fingerprint = key.fingerprint().unwrap();
+        assert_eq!(fingerprint.len(), 39);
+        assert!(fingerprint.split(' ').all(|g| g.len() == 4 && g.chars().all(|c| c.is_ascii_hexdigit())));
+    }
+
+    #[test]
+    fn test_changed_or_resigned_parts_are_rejected() {
+        let key = SigningKey::generate().unwrap();
+        let signed = key.sign_html(&page("const ENCRYPTED_DATA = {};")).unwrap();
+        let tampered = signed.replace("ENCRYPTED_DATA = {}", "ENCRYPTED_DATA = []");
+        assert!(matches!(verify_html(&tampered), Err(SigningError::Mismatch)));
+
+        // Another key's signature moved onto this file doesn't fit it either
+        let other = SigningKey::generate().unwrap().sign_html(&page("other")).unwrap();
+        let tag = |html: &str| {
+            let start = html.find(SIGNATURE_TAG_START).unwrap();
+            html[start..start + html[start..].find("\">").unwrap() + 2].to_string()
+        };
+        let swapped = signed.replace(&tag(&signed), &tag(&other));
+        assert!(matches!(verify_html(&swapped), Err(SigningError::Mismatch)));
+
+        assert!(matches!(verify_html(&signed.replace("ed25519:", "rsa:")), Err(SigningError::Malformed)));
+        assert!(matches!(verify_html(&signed.replace("<head>", &format!("<head>{}", SIGNATURE_PLACEHOLDER))), Err(SigningError::Malformed)));
+    }
+
+    #[test]
+    fn test_unsigned_files_still_open() {
+        assert_eq!(verify_html("<html><body>older export</body></html>").unwrap(), None);
+        assert_eq!(verify_html(&page("not signed")).unwrap(), None);
+        assert!(matches!(SigningKey::generate().unwrap().sign_html("<html></html>"), Err(SigningError::MissingPlaceholder)));
+    }
+
+    #[test]
+    fn test_check_compares_with_own_key() {
+        let own = SigningKey::generate().unwrap();
+        let other = SigningKey::generate().unwrap();
+        let html = page("const ENCRYPTED_DATA = {};");
+
+        let ours = own.sign_html(&html).unwrap();
+        assert_eq!(check_html(&ours, &own).unwrap(), SignatureStatus::Own { fingerprint: own.fingerprint().unwrap() });
+
+        // A swapped file re-signed with another key verifies, but isn't ours
+        let swapped = other.sign_html(&page("const ENCRYPTED_DATA = [];")).unwrap();
+        let status = check_html(&swapped, &own).unwrap();
+        assert_eq!(status, SignatureStatus::Other { fingerprint: other.fingerprint().unwrap() });
+        assert!(!matches!(status, SignatureStatus::Own { .. }));
+
+        // Stripping the signature leaves an unsigned file, not a trusted one
+        let start = ours.find(SIGNATURE_TAG_START).unwrap();
+        let end = start + ours[start..].find("\">").unwrap() + 2;
+        let stripped = format!("{}{}", &ours[..start], &ours[end..]);
+        assert_eq!(check_html(&stripped, &own).unwrap(), SignatureStatus::Unsigned);
+        assert!(matches!(check_html(&ours.replace("{}", "[]"), &own), Err(SigningError::Mismatch)));
+    }
+
+    #[test]
+    fn test_stored_key_keeps_its_fingerprint() {
+        let key = SigningKey::generate().unwrap();
+        let stored: SigningKey = serde_json::from_str(&serde_json::to_string(&key).unwrap()).unwrap();
+        assert_eq!(stored.fingerprint().unwrap(), key.fingerprint().unwrap());
+    }
+}
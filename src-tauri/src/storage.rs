@@ -1,5 +1,11 @@
+use crate::autoexport::AutoExport;
+use crate::deadman::DeadManSwitch;
+use crate::delivery::SmtpSettings;
 use crate::encryption::{decrypt, encrypt, EncryptedPayload, EncryptionError};
 use crate::models::LegacyDocument;
+use crate::render::Theme;
+use crate::signing::SigningKey;
+use crate::timelock::ReleaseStore;
 use argon2::{
     password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
     Argon2,
@@ -248,6 +254,179 @@ pub fn delete_document() -> Result<(), StorageError> {
     Ok(())
 }
 
+/// Writes a value as JSON encrypted with the local key, like the document
+fn save_encrypted_json<T: serde::Serialize>(file_name: &str, value: &T) -> Result<(), StorageError> {
+    let data_dir = get_data_dir()?;
+    fs::create_dir_all(&data_dir)
+        .map_err(|e| StorageError::IoError(e.to_string()))?;
+
+    let json = serde_json::to_string(value)
+        .map_err(|e| StorageError::SerializationError(e.to_string()))?;
+
+    let local_key = get_or_create_local_key()?;
+    let encrypted = encrypt(&json, &local_key)?;
+
+    let encrypted_json = serde_json::to_string(&encrypted)
+        .map_err(|e| StorageError::SerializationError(e.to_string()))?;
+
+    fs::write(data_dir.join(file_name), encrypted_json)
+        .map_err(|e| StorageError::IoError(e.to_string()))?;
+
+    Ok(())
+}
+
+/// Reads a value written by `save_encrypted_json`, if the file exists
+fn load_encrypted_json<T: serde::de::DeserializeOwned>(file_name: &str) -> Result<Option<T>, StorageError> {
+    let data_dir = get_data_dir()?;
+    let file_path = data_dir.join(file_name);
+
+    if !file_path.exists() {
+        return Ok(None);
+    }
+
+    let encrypted_json = fs::read_to_string(&file_path)
+        .map_err(|e| StorageError::IoError(e.to_string()))?;
+
+    let encrypted: EncryptedPayload = serde_json::from_str(&encrypted_json)
+        .map_err(|e| StorageError::SerializationError(e.to_string()))?;
+
+    let local_key = get_or_create_local_key()?;
+    let json = decrypt(&encrypted, &local_key)?;
+
+    serde_json::from_str(&json)
+        .map(Some)
+        .map_err(|e| StorageError::SerializationError(e.to_string()))
+}
+
+/// Saves the local release service's escrowed keys
+pub fn save_release_store(store: &ReleaseStore) -> Result<(), StorageError> {
+    save_encrypted_json("release_escrow.encrypted", store)
+}
+
+/// Loads the escrowed release keys, or an empty store if there are none
+pub fn load_release_store() -> Result<ReleaseStore, StorageError> {
+    Ok(load_encrypted_json("release_escrow.encrypted")?.unwrap_or_default())
+}
+
+/// Loads this install's export signing key, creating it on first use. It stays
+/// the same across exports so its fingerprint can be shared once.
+pub fn get_or_create_signing_key() -> Result<SigningKey, StorageError> {
+    if let Some(key) = load_encrypted_json("signing_key.encrypted")? {
+        return Ok(key);
+    }
+    let key = SigningKey::generate().map_err(|e| StorageError::KeyringError(e.to_string()))?;
+    save_encrypted_json("signing_key.encrypted", &key)?;
+    Ok(key)
+}
+
+/// Deletes the export signing key; the next export creates a new one with a new fingerprint
+pub fn delete_signing_key() -> Result<(), StorageError> {
+    let data_dir = get_data_dir()?;
+    let file_path = data_dir.join("signing_key.encrypted");
+    if file_path.exists() {
+        fs::remove_file(&file_path)
+            .map_err(|e| StorageError::IoError(e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Saves the dead man's switch, including the passphrase for its exports
+pub fn save_dead_man_switch(switch: &DeadManSwitch) -> Result<(), StorageError> {
+    save_encrypted_json("dead_man_switch.encrypted", switch)
+}
+
+/// Loads the dead man's switch if one has been set up
+pub fn load_dead_man_switch() -> Result<Option<DeadManSwitch>, StorageError> {
+    load_encrypted_json("dead_man_switch.encrypted")
+}
+
+/// Removes the dead man's switch and its stored passphrase
+pub fn delete_dead_man_switch() -> Result<(), StorageError> {
+    let data_dir = get_data_dir()?;
+    let file_path = data_dir.join("dead_man_switch.encrypted");
+    if file_path.exists() {
+        fs::remove_file(&file_path)
+            .map_err(|e| StorageError::IoError(e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Saves the automatic export settings, including the passphrase for its exports
+pub fn save_auto_export(auto_export: &AutoExport) -> Result<(), StorageError> {
+    save_encrypted_json("auto_export.encrypted", auto_export)
+}
+
+/// Loads the automatic export settings if they have been set up
+pub fn load_auto_export() -> Result<Option<AutoExport>, StorageError> {
+    load_encrypted_json("auto_export.encrypted")
+}
+
+/// Deletes the automatic export settings and their stored passphrase
+pub fn delete_auto_export() -> Result<(), StorageError> {
+    let data_dir = get_data_dir()?;
+    let file_path = data_dir.join("auto_export.encrypted");
+    if file_path.exists() {
+        fs::remove_file(&file_path)
+            .map_err(|e| StorageError::IoError(e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Saves the SMTP settings used for emailed exports, including any password
+pub fn save_email_settings(settings: &SmtpSettings) -> Result<(), StorageError> {
+    save_encrypted_json("email_settings.encrypted", settings)
+}
+
+/// Loads the SMTP settings from the last emailed export
+pub fn load_email_settings() -> Result<Option<SmtpSettings>, StorageError> {
+    load_encrypted_json("email_settings.encrypted")
+}
+
+/// Deletes the saved SMTP settings
+pub fn delete_email_settings() -> Result<(), StorageError> {
+    let data_dir = get_data_dir()?;
+    let file_path = data_dir.join("email_settings.encrypted");
+    if file_path.exists() {
+        fs::remove_file(&file_path)
+            .map_err(|e| StorageError::IoError(e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Saves the export theme. Branding isn't private, so it's plain JSON like settings.json.
+pub fn save_export_theme(theme: &Theme) -> Result<(), StorageError> {
+    let data_dir = get_data_dir()?;
+    fs::create_dir_all(&data_dir)
+        .map_err(|e| StorageError::IoError(e.to_string()))?;
+    let json = serde_json::to_string_pretty(theme)
+        .map_err(|e| StorageError::SerializationError(e.to_string()))?;
+    fs::write(data_dir.join("export_theme.json"), json)
+        .map_err(|e| StorageError::IoError(e.to_string()))
+}
+
+/// Loads the export theme, or the Honey Did look if none is set
+pub fn load_export_theme() -> Result<Theme, StorageError> {
+    let data_dir = get_data_dir()?;
+    let file_path = data_dir.join("export_theme.json");
+    if !file_path.exists() {
+        return Ok(Theme::default());
+    }
+    let json = fs::read_to_string(&file_path)
+        .map_err(|e| StorageError::IoError(e.to_string()))?;
+    serde_json::from_str(&json).map_err(|e| StorageError::SerializationError(e.to_string()))
+}
+
+/// Deletes the export theme, going back to the Honey Did look
+pub fn delete_export_theme() -> Result<(), StorageError> {
+    let data_dir = get_data_dir()?;
+    let file_path = data_dir.join("export_theme.json");
+    if file_path.exists() {
+        fs::remove_file(&file_path)
+            .map_err(|e| StorageError::IoError(e.to_string()))?;
+    }
+    Ok(())
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Default)]
 pub struct AppSettings {
     pub clear_on_exit: bool,
@@ -0,0 +1,407 @@
+use crate::encryption::BrowserKdf;
+use crate::models::{SlideType, WelcomeScreen};
+use crate::questions::{accepted_answers, normalize_answer};
+use serde::Serialize;
+
+// Guesses per second for an offline attacker with about a hundred high-end
+// GPUs. Rough figures from published hashcat benchmarks, scaled to our costs.
+const PBKDF2_GUESSES_PER_SECOND: f64 = 1.5e6;
+const ARGON2_GUESSES_PER_SECOND: f64 = 2.0e5;
+// ZIP AES keys come from PBKDF2-HMAC-SHA1 with 1,000 iterations
+const ZIP_AES_GUESSES_PER_SECOND: f64 = 1.0e9;
+
+// Units for crack-time scores and descriptions
+const SECONDS_PER_DAY: f64 = 86_400.0;
+const SECONDS_PER_YEAR: f64 = 365.25 * SECONDS_PER_DAY;
+
+/// Scores below this are refused by the export commands until acknowledged
+const MIN_ACCEPTABLE_SCORE: u8 = 2;
+
+// Roughly log2 of the vocabulary an attacker would try for a single word
+const WORD_BITS: f64 = 11.0;
+const NAME_OR_PLACE_BITS: f64 = 12.0;
+const EXTRA_ANSWER_WORD_BITS: f64 = 6.0;
+
+/// Most common leaked passwords, most common first
+const COMMON_PASSWORDS: &[&str] = &[
+    "123456", "password", "12345678", "qwerty", "123456789", "12345", "1234", "111111", "1234567", "dragon",
+    "123123", "baseball", "abc123", "football", "monkey", "letmein", "696969", "shadow", "master", "666666",
+    "qwertyuiop", "123321", "mustang", "1234567890", "michael", "654321", "superman", "1qaz2wsx", "7777777",
+    "121212", "000000", "qazwsx", "123qwe", "killer", "trustno1", "jordan", "jennifer", "zxcvbnm", "asdfgh",
+    "hunter", "buster", "soccer", "harley", "batman", "andrew", "tigger", "sunshine", "iloveyou", "2000",
+    "charlie", "robert", "thomas", "hockey", "ranger", "daniel", "starwars", "klaster", "112233", "george",
+    "computer", "michelle", "jessica", "pepper", "1111", "zxcvbn", "555555", "11111111", "131313", "freedom",
+    "777777", "pass", "maggie", "159753", "aaaaaa", "ginger", "princess", "joshua", "cheese", "amanda",
+    "summer", "love", "ashley", "nicole", "chelsea", "biteme", "matthew", "access", "yankees", "987654321",
+    "dallas", "austin", "thunder", "taylor", "matrix", "welcome", "admin", "passw0rd", "password1", "login",
+    "secret", "family", "honey", "money", "changeme",
+];
+
+/// Typical answers to "pet's name", "favorite color", "city you were born
+/// in", "mother's maiden name" and similar questions
+const COMMON_ANSWERS: &[&str] = &[
+    // Pets
+    "max", "bella", "charlie", "luna", "lucy", "cooper", "buddy", "daisy", "rocky", "molly", "bailey", "sadie",
+    "duke", "lola", "bear", "maggie", "tucker", "sophie", "jack", "chloe", "toby", "coco", "buster", "fluffy",
+    "spot", "rex", "lady", "shadow", "tiger", "smokey", "oreo", "milo", "oliver", "simba", "kitty", "princess",
+    "pepper", "ginger", "sam", "jake", "zeus", "penny", "rosie", "murphy", "sasha", "misty", "boots", "patches",
+    // Colors
+    "red", "blue", "green", "yellow", "purple", "orange", "pink", "black", "white", "brown", "gray", "grey",
+    "teal", "navy", "silver", "gold",
+    // Places
+    "new york", "los angeles", "chicago", "houston", "phoenix", "philadelphia", "san antonio", "san diego",
+    "dallas", "san jose", "austin", "boston", "seattle", "denver", "detroit", "miami", "atlanta", "st louis",
+    "saint louis", "london", "paris", "toronto", "mexico city", "madrid", "rome", "berlin", "sydney",
+    "california", "texas", "florida", "ohio", "michigan",
+    // Surnames
+    "smith", "johnson", "williams", "brown", "jones", "garcia", "miller", "davis", "rodriguez", "martinez",
+    "hernandez", "lopez", "gonzalez", "wilson", "anderson", "thomas", "taylor", "moore", "jackson", "martin",
+    "lee", "thompson", "white", "harris", "clark", "lewis", "robinson", "walker", "young", "allen",
+    // Foods, cars, schools and teams
+    "pizza", "pasta", "tacos", "sushi", "chocolate", "ice cream", "steak", "burger", "ford", "chevy",
+    "chevrolet", "toyota", "honda", "dodge", "mustang", "camaro", "civic", "corolla", "lincoln", "washington",
+    "jefferson", "roosevelt", "yankees", "cowboys", "packers", "lakers", "patriots", "cubs",
+];
+
+const KEYBOARD_ROWS: &[&str] = &["qwertyuiop", "asdfghjkl", "zxcvbnm", "1234567890", "abcdefghijklmnopqrstuvwxyz"];
+
+/// Estimated cost of guessing one secret
+#[derive(Debug, Clone, Serialize)]
+pub struct StrengthEstimate {
+    pub entropy_bits: f64,
+    /// Average seconds for an offline attacker to find it
+    pub crack_seconds: f64,
+    /// `crack_seconds` for people, e.g. "under a minute", "3 hours" or "centuries"
+    pub crack_time: String,
+    /// 0 (trivial) to 4 (strong)
+    pub score: u8,
+}
+
+/// What the export commands report about the file's protection. The file is
+/// only as strong as its weakest way in, so `score` is the lower of the two.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportStrength {
+    pub score: u8,
+    pub weak: bool,
+    pub passphrase: Option<StrengthEstimate>,
+    pub answers: Option<StrengthEstimate>,
+    pub warnings: Vec<String>,
+}
+
+/// Error from an export command: a plain failure, or a refusal because the
+/// protection is weak and the user hasn't acknowledged it yet
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ExportCommandError {
+    Failed { message: String },
+    WeakProtection { strength: Box<ExportStrength> },
+}
+
+impl std::fmt::Display for ExportCommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportCommandError::Failed { message } => write!(f, "{}", message),
+            ExportCommandError::WeakProtection { strength } => {
+                write!(f, "Weak protection: {}", strength.warnings.join(" "))
+            }
+        }
+    }
+}
+
+impl From<String> for ExportCommandError {
+    fn from(message: String) -> Self {
+        ExportCommandError::Failed { message }
+    }
+}
+
+impl ExportStrength {
+    /// Refuses weak protection unless the user has already seen the warning
+    pub fn require(self, acknowledged: bool) -> Result<(), ExportCommandError> {
+        if self.weak && !acknowledged {
+            Err(ExportCommandError::WeakProtection { strength: Box::new(self) })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn guesses_per_second(kdf: BrowserKdf) -> f64 {
+    match kdf {
+        BrowserKdf::Pbkdf2 => PBKDF2_GUESSES_PER_SECOND,
+        BrowserKdf::Argon2id { .. } => ARGON2_GUESSES_PER_SECOND,
+    }
+}
+
+fn estimate(entropy_bits: f64, guesses_per_second: f64) -> StrengthEstimate {
+    // On average the attacker searches half the space
+    let crack_seconds = 2f64.powf(entropy_bits - 1.0).max(1.0) / guesses_per_second;
+    let score = if crack_seconds < SECONDS_PER_DAY {
+        0
+    } else if crack_seconds < SECONDS_PER_YEAR {
+        1
+    } else if crack_seconds < 100.0 * SECONDS_PER_YEAR {
+        2
+    } else if crack_seconds < 100_000.0 * SECONDS_PER_YEAR {
+        3
+    } else {
+        4
+    };
+    StrengthEstimate { entropy_bits, crack_seconds, crack_time: describe_duration(crack_seconds), score }
+}
+
+fn describe_duration(seconds: f64) -> String {
+    let units = [
+        (SECONDS_PER_YEAR, "year"),
+        (SECONDS_PER_DAY, "day"),
+        (3600.0, "hour"),
+        (60.0, "minute"),
+    ];
+    if seconds >= 1000.0 * SECONDS_PER_YEAR {
+        return "centuries".to_string();
+    }
+    for (size, name) in units {
+        if seconds >= size {
+            let count = (seconds / size).round() as u64;
+            return format!("{} {}{}", count, name, if count == 1 { "" } else { "s" });
+        }
+    }
+    "under a minute".to_string()
+}
+
+fn common_rank(list: &[&str], value: &str) -> Option<usize> {
+    list.iter().position(|entry| *entry == value)
+}
+
+/// Bits for a string guessed character by character. Repeats, runs like
+/// "abcd" or "4321" and keyboard walks like "qwerty" cost almost nothing.
+fn character_bits(value: &str) -> f64 {
+    let chars: Vec<char> = value.chars().collect();
+    let mut pool = 0u32;
+    if chars.iter().any(|c| c.is_ascii_lowercase()) { pool += 26; }
+    if chars.iter().any(|c| c.is_ascii_uppercase()) { pool += 26; }
+    if chars.iter().any(|c| c.is_ascii_digit()) { pool += 10; }
+    if chars.iter().any(|c| c.is_ascii_punctuation() || *c == ' ') { pool += 33; }
+    if chars.iter().any(|c| !c.is_ascii()) { pool += 100; }
+    let per_char = f64::from(pool.max(1)).log2();
+
+    let lowered: Vec<char> = value.to_lowercase().chars().collect();
+    let mut bits = 0.0;
+    for (i, c) in lowered.iter().enumerate() {
+        let predictable = i > 0 && {
+            let prev = lowered[i - 1];
+            prev == *c || (*c as i64 - prev as i64).abs() == 1 || KEYBOARD_ROWS.iter().any(|row| {
+                let pair: String = [prev, *c].iter().collect();
+                let reversed: String = [*c, prev].iter().collect();
+                row.contains(&pair) || row.contains(&reversed)
+            })
+        };
+        bits += if predictable { 1.0 } else { per_char };
+    }
+    bits
+}
+
+/// Entropy estimate for an export passphrase, with what made it weak
+fn passphrase_bits(passphrase: &str) -> (f64, Option<String>) {
+    let lowered = passphrase.to_lowercase();
+    if let Some(rank) = common_rank(COMMON_PASSWORDS, &lowered) {
+        return ((rank as f64 + 2.0).log2(), Some("The passphrase is one of the most common passwords.".to_string()));
+    }
+
+    let mut best = character_bits(passphrase);
+    let mut reason = None;
+
+    // A common password dressed up with capitals, digits or symbols, e.g. "Password1!"
+    let trimmed = lowered.trim_end_matches(|c: char| !c.is_alphabetic());
+    let core: String = trimmed.chars().filter(|c| c.is_alphanumeric()).collect();
+    if let Some(rank) = common_rank(COMMON_PASSWORDS, &core) {
+        // One bit for capitalization, plus whatever was tacked on the end
+        let decoration = character_bits(&lowered[trimmed.len()..]) + 1.0;
+        let bits = (rank as f64 + 2.0).log2() + decoration;
+        if bits < best {
+            best = bits;
+            reason = Some("The passphrase is a common password with a few characters added.".to_string());
+        }
+    }
+
+    // Words separated by spaces or punctuation are guessed a word at a time
+    let words: Vec<&str> = passphrase.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()).collect();
+    if words.len() >= 2 && words.iter().all(|w| w.chars().all(char::is_alphabetic)) {
+        let bits = words.len() as f64 * WORD_BITS;
+        if bits < best {
+            best = bits;
+            reason = None;
+        }
+    }
+
+    (best, reason)
+}
+
+/// Entropy estimate for one normalized security-question answer
+fn answer_bits(normalized: &str) -> (f64, bool) {
+    if normalized.is_empty() {
+        return (0.0, false);
+    }
+    if common_rank(COMMON_ANSWERS, normalized).is_some() {
+        return ((COMMON_ANSWERS.len() as f64).log2(), true);
+    }
+    if normalized.chars().all(|c| c.is_ascii_digit()) {
+        let is_year = normalized.len() == 4 && (normalized.starts_with("19") || normalized.starts_with("20"));
+        let bits = if is_year { 200f64.log2() } else { normalized.len() as f64 * 10f64.log2() };
+        return (bits, false);
+    }
+    let words = normalized.split(' ').count() as f64;
+    let vocabulary = NAME_OR_PLACE_BITS + (words - 1.0) * EXTRA_ANSWER_WORD_BITS;
+    (vocabulary.min(character_bits(normalized)), false)
+}
+
+/// Strength of a passphrase-protected export
+pub fn assess_passphrase_export(passphrase: &str, kdf: BrowserKdf) -> ExportStrength {
+    assess_passphrase(passphrase, guesses_per_second(kdf))
+}
+
+/// Strength of an encrypted archive; ZIP AES is far cheaper to attack than the HTML exports
+pub fn assess_archive_export(passphrase: &str) -> ExportStrength {
+    assess_passphrase(passphrase, ZIP_AES_GUESSES_PER_SECOND)
+}
+
+fn assess_passphrase(passphrase: &str, rate: f64) -> ExportStrength {
+    let (bits, reason) = passphrase_bits(passphrase);
+    let passphrase = estimate(bits, rate);
+    let mut warnings: Vec<String> = reason.into_iter().collect();
+    if passphrase.score < MIN_ACCEPTABLE_SCORE {
+        warnings.push(format!("An attacker with a copy of the file could guess the passphrase in {}.", passphrase.crack_time));
+    }
+    ExportStrength {
+        score: passphrase.score,
+        weak: passphrase.score < MIN_ACCEPTABLE_SCORE,
+        passphrase: Some(passphrase),
+        answers: None,
+        warnings,
+    }
+}
+
+/// Strength of a question-unlock export. An attacker only needs the weakest
+/// `required` answers, and any accepted alternative counts, so those set the
+/// answers' strength. The backup passphrase is a second way in.
+pub fn assess_question_export(welcome: &WelcomeScreen, passphrase: &str, kdf: BrowserKdf) -> ExportStrength {
+    let rate = guesses_per_second(kdf);
+    let mut warnings = Vec::new();
+
+    let mut per_question: Vec<f64> = Vec::new();
+    for slide in welcome.slides.iter().filter(|s| s.slide_type == SlideType::Question) {
+        let accepted = accepted_answers(slide.answer.as_deref().unwrap_or(""), &slide.alternate_answers);
+        let mut weakest = f64::INFINITY;
+        for answer in &accepted {
+            let normalized = normalize_answer(answer);
+            let (bits, common) = answer_bits(&normalized);
+            if common {
+                warnings.push(format!("\"{}\" is one of the first answers an attacker would try.", answer.trim()));
+            }
+            weakest = weakest.min(bits);
+        }
+        // Each extra accepted answer is one more chance to guess right
+        per_question.push((weakest - (accepted.len().max(1) as f64).log2()).max(0.0));
+    }
+
+    per_question.sort_by(|a, b| a.total_cmp(b));
+    let required = welcome.required_answers.unwrap_or(per_question.len()).min(per_question.len());
+    let answers = estimate(per_question.iter().take(required).sum(), rate);
+    if answers.score < MIN_ACCEPTABLE_SCORE {
+        warnings.push(format!(
+            "An attacker with a copy of the file could guess the answers in {}. Choose answers only the recipient would know, or add more questions.",
+            answers.crack_time
+        ));
+    }
+
+    let mut score = answers.score;
+    let passphrase = if passphrase.is_empty() {
+        None
+    } else {
+        let backup = assess_passphrase(passphrase, rate);
+        score = score.min(backup.score);
+        warnings.extend(backup.warnings);
+        backup.passphrase
+    };
+
+    ExportStrength {
+        score,
+        weak: score < MIN_ACCEPTABLE_SCORE,
+        passphrase,
+        answers: Some(answers),
+        warnings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::MessageSlide;
+
+    fn questions(answers: &[&str], required: Option<usize>) -> WelcomeScreen {
+        WelcomeScreen {
+            enabled: true,
+            slides: answers
+                .iter()
+                .enumerate()
+                .map(|(i, a)| MessageSlide {
+                    id: format!("q{}", i),
+                    slide_type: SlideType::Question,
+                    answer: Some(a.to_string()),
+                    ..Default::default()
+                })
+                .collect(),
+            fallback_passphrase: None,
+            required_answers: required,
+            attempt_limit: None,
+        }
+    }
+
+    #[test]
+    fn test_common_passwords_are_weak() {
+        for passphrase in ["password", "Password1!", "qwertyuiop", "aaaaaaaaaaaa"] {
+            let strength = assess_passphrase_export(passphrase, BrowserKdf::Pbkdf2);
+            assert!(strength.weak, "{} should be weak", passphrase);
+        }
+    }
+
+    #[test]
+    fn test_generated_style_passphrases_pass() {
+        let strength = assess_passphrase_export("cobalt-cricket-anchor-meadow-violet-harbor", BrowserKdf::Pbkdf2);
+        assert!(!strength.weak);
+        assert!(strength.warnings.is_empty());
+
+        // Memory-hard derivation buys time for the same passphrase
+        let pbkdf2 = assess_passphrase_export("Tr0ub4dor&3x", BrowserKdf::Pbkdf2);
+        let argon2 = assess_passphrase_export("Tr0ub4dor&3x", BrowserKdf::ARGON2ID);
+        assert!(argon2.passphrase.unwrap().crack_seconds > pbkdf2.passphrase.unwrap().crack_seconds);
+    }
+
+    #[test]
+    fn test_pet_name_answers_push_back() {
+        let welcome = questions(&["Fluffy", "Blue", "St. Louis"], None);
+        let strength = assess_question_export(&welcome, "", BrowserKdf::ARGON2ID);
+        assert!(strength.weak);
+        assert_eq!(strength.score, 0);
+        assert!(strength.warnings.iter().any(|w| w.contains("\"Fluffy\"")));
+        assert!(strength.require(false).is_err());
+    }
+
+    #[test]
+    fn test_required_answers_count_only_the_weakest() {
+        let all = questions(&["Fluffy", "grandmas kitchen in duluth 1987", "my first bicycle was a schwinn"], None);
+        let any_two = questions(&["Fluffy", "grandmas kitchen in duluth 1987", "my first bicycle was a schwinn"], Some(2));
+        let all = assess_question_export(&all, "", BrowserKdf::Pbkdf2).answers.unwrap();
+        let any_two = assess_question_export(&any_two, "", BrowserKdf::Pbkdf2).answers.unwrap();
+        assert!(any_two.entropy_bits < all.entropy_bits);
+    }
+
+    #[test]
+    fn test_weak_backup_passphrase_weakens_question_export() {
+        let welcome = questions(&["the blue house on elm street", "uncle bernard's old tractor", "aunt maya's lemon cake"], None);
+        let strong = assess_question_export(&welcome, "cobalt-cricket-anchor-meadow-violet-harbor", BrowserKdf::ARGON2ID);
+        assert!(!strong.weak);
+        let weak = assess_question_export(&welcome, "letmein", BrowserKdf::ARGON2ID);
+        assert!(weak.weak);
+        assert!(weak.require(true).is_ok());
+    }
+}
@@ -0,0 +1,456 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{Days, NaiveDate};
+use rand::RngCore;
+use ring::digest;
+use serde::{Deserialize, Serialize};
+
+// Time-locked question exports. Answering the questions only recovers the
+// document key XOR a random release key; the release key is escrowed with a
+// release service that hands it out as a release code once the release date
+// arrives, or once the owner has gone too long without checking in. The
+// backup passphrase still unwraps the document key directly, so an executor
+// who holds it is never locked out.
+//
+// The release service here is a local stand-in: escrowed keys live in this
+// app's encrypted data directory, and the owner (or whoever has the device)
+// reads out a code only after it has been released.
+
+/// Longest check-in interval, about ten years
+pub const MAX_CHECK_IN_DAYS: u32 = 3650;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const RELEASE_CODE_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const RELEASE_CODE_LENGTH: usize = 52;
+const RELEASE_CODE_GROUP: usize = 4;
+
+#[derive(Debug)]
+pub enum TimeLockError {
+    NoCondition,
+    InvalidDate(String),
+    DateNotInFuture,
+    InvalidCheckInDays(u32),
+    NotFound,
+    StillLocked(Option<NaiveDate>),
+    InvalidReleaseCode,
+}
+
+impl std::fmt::Display for TimeLockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeLockError::NoCondition => write!(f, "Choose a release date, a check-in interval, or both"),
+            TimeLockError::InvalidDate(date) => write!(f, "Invalid release date: {}", date),
+            TimeLockError::DateNotInFuture => write!(f, "The release date must be in the future"),
+            TimeLockError::InvalidCheckInDays(days) => {
+                write!(f, "Check-in interval must be between 1 and {} days (got {})", MAX_CHECK_IN_DAYS, days)
+            }
+            TimeLockError::NotFound => write!(f, "No time lock with that id"),
+            TimeLockError::StillLocked(Some(date)) => {
+                write!(f, "This export stays locked until {}", date.format(DATE_FORMAT))
+            }
+            TimeLockError::StillLocked(None) => write!(f, "This export is still locked"),
+            TimeLockError::InvalidReleaseCode => write!(f, "Invalid release code"),
+        }
+    }
+}
+
+impl std::error::Error for TimeLockError {}
+
+/// What prompted a check-in. Unlocking the app doesn't count: whoever holds
+/// the device has to unlock it to read a release code, and that must not
+/// push the release back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckIn {
+    AppUnlock,
+    Explicit,
+}
+
+/// Time lock chosen in the export dialog
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TimeLockSettings {
+    /// YYYY-MM-DD; the export opens on this day
+    pub release_after: Option<String>,
+    /// The export also opens after this many days without a check-in
+    pub check_in_days: Option<u32>,
+    pub label: Option<String>,
+}
+
+/// What the exported file records about its time lock. `check` lets the page
+/// tell a mistyped release code apart from wrong answers.
+#[derive(Debug, Clone, Serialize)]
+pub struct TimeLockInfo {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release_after: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub check_in_days: Option<u32>,
+    pub check: String,
+}
+
+/// A time lock for one export: the release key and what the file says about it
+pub struct TimeLock {
+    pub release_key: [u8; 32],
+    pub info: TimeLockInfo,
+}
+
+impl TimeLock {
+    /// The key the questions unlock: the document key XOR the release key
+    pub fn question_share(&self, document_key: &[u8; 32]) -> [u8; 32] {
+        let mut share = [0u8; 32];
+        for (i, byte) in share.iter_mut().enumerate() {
+            *byte = document_key[i] ^ self.release_key[i];
+        }
+        share
+    }
+}
+
+/// A release key held by the release service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscrowedKey {
+    pub id: String,
+    pub label: String,
+    pub created: String,
+    pub release_after: Option<String>,
+    pub check_in_days: Option<u32>,
+    release_key: String,
+}
+
+/// What the settings screen shows for an escrowed key; never the key itself
+#[derive(Debug, Clone, Serialize)]
+pub struct EscrowSummary {
+    pub id: String,
+    pub label: String,
+    pub created: String,
+    pub release_after: Option<String>,
+    pub check_in_days: Option<u32>,
+    /// The day the release code becomes available, given the last check-in
+    pub releases_on: Option<String>,
+    pub released: bool,
+}
+
+/// The local release service's state, stored encrypted next to the document
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReleaseStore {
+    pub last_check_in: Option<String>,
+    #[serde(default)]
+    pub keys: Vec<EscrowedKey>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReleaseOverview {
+    pub last_check_in: Option<String>,
+    pub keys: Vec<EscrowSummary>,
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, TimeLockError> {
+    NaiveDate::parse_from_str(value.trim(), DATE_FORMAT).map_err(|_| TimeLockError::InvalidDate(value.to_string()))
+}
+
+fn format_date(date: NaiveDate) -> String {
+    date.format(DATE_FORMAT).to_string()
+}
+
+fn release_check(release_key: &[u8; 32]) -> String {
+    BASE64.encode(&digest::digest(&digest::SHA256, release_key).as_ref()[..8])
+}
+
+/// Creates a time lock for a new export and the matching escrow entry
+pub fn create(settings: &TimeLockSettings, today: NaiveDate) -> Result<(TimeLock, EscrowedKey), TimeLockError> {
+    let release_after = match settings.release_after.as_deref().filter(|d| !d.trim().is_empty()) {
+        Some(value) => {
+            let date = parse_date(value)?;
+            if date <= today {
+                return Err(TimeLockError::DateNotInFuture);
+            }
+            Some(format_date(date))
+        }
+        None => None,
+    };
+    if let Some(days) = settings.check_in_days {
+        if days == 0 || days > MAX_CHECK_IN_DAYS {
+            return Err(TimeLockError::InvalidCheckInDays(days));
+        }
+    }
+    if release_after.is_none() && settings.check_in_days.is_none() {
+        return Err(TimeLockError::NoCondition);
+    }
+
+    let mut release_key = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut release_key);
+    let mut id_bytes = [0u8; 8];
+    rand::thread_rng().fill_bytes(&mut id_bytes);
+    let id: String = id_bytes.iter().map(|b| format!("{:02x}", b)).collect();
+
+    let label = settings
+        .label
+        .as_deref()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("Export of {}", format_date(today)));
+
+    let info = TimeLockInfo {
+        id: id.clone(),
+        release_after: release_after.clone(),
+        check_in_days: settings.check_in_days,
+        check: release_check(&release_key),
+    };
+    let escrowed = EscrowedKey {
+        id,
+        label,
+        created: format_date(today),
+        release_after,
+        check_in_days: settings.check_in_days,
+        release_key: BASE64.encode(release_key),
+    };
+    Ok((TimeLock { release_key, info }, escrowed))
+}
+
+impl EscrowedKey {
+    /// The earlier of the release date and the end of the check-in window
+    fn releases_on(&self, last_check_in: Option<NaiveDate>) -> Option<NaiveDate> {
+        let by_date = self.release_after.as_deref().and_then(|d| parse_date(d).ok());
+        let since = last_check_in.or_else(|| parse_date(&self.created).ok());
+        let by_silence = self
+            .check_in_days
+            .zip(since)
+            .and_then(|(days, since)| since.checked_add_days(Days::new(u64::from(days))));
+        match (by_date, by_silence) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+impl ReleaseStore {
+    fn last_check_in_date(&self) -> Option<NaiveDate> {
+        self.last_check_in.as_deref().and_then(|d| parse_date(d).ok())
+    }
+
+    /// Keeps a new release key; the first one starts the check-in clock
+    pub fn deposit(&mut self, key: EscrowedKey, today: NaiveDate) {
+        if self.last_check_in.is_none() {
+            self.last_check_in = Some(format_date(today));
+        }
+        self.keys.push(key);
+    }
+
+    /// Records that the owner is still around, pushing back check-in releases.
+    /// Returns whether the store changed; app unlocks leave it alone.
+    pub fn check_in(&mut self, kind: CheckIn, today: NaiveDate) -> bool {
+        if kind == CheckIn::AppUnlock {
+            return false;
+        }
+        self.last_check_in = Some(format_date(today));
+        true
+    }
+
+    pub fn remove(&mut self, id: &str) -> Result<(), TimeLockError> {
+        let before = self.keys.len();
+        self.keys.retain(|k| k.id != id);
+        if self.keys.len() == before { Err(TimeLockError::NotFound) } else { Ok(()) }
+    }
+
+    pub fn overview(&self, today: NaiveDate) -> ReleaseOverview {
+        let last = self.last_check_in_date();
+        let keys = self
+            .keys
+            .iter()
+            .map(|key| {
+                let releases_on = key.releases_on(last);
+                EscrowSummary {
+                    id: key.id.clone(),
+                    label: key.label.clone(),
+                    created: key.created.clone(),
+                    release_after: key.release_after.clone(),
+                    check_in_days: key.check_in_days,
+                    releases_on: releases_on.map(format_date),
+                    released: releases_on.is_some_and(|d| today >= d),
+                }
+            })
+            .collect();
+        ReleaseOverview { last_check_in: self.last_check_in.clone(), keys }
+    }
+
+    /// The release code for an export, once it has been released
+    pub fn release_code(&self, id: &str, today: NaiveDate) -> Result<String, TimeLockError> {
+        let key = self.keys.iter().find(|k| k.id == id).ok_or(TimeLockError::NotFound)?;
+        let releases_on = key.releases_on(self.last_check_in_date());
+        if releases_on.is_none_or(|d| today < d) {
+            return Err(TimeLockError::StillLocked(releases_on));
+        }
+        let bytes = BASE64.decode(&key.release_key).map_err(|_| TimeLockError::InvalidReleaseCode)?;
+        let release_key: [u8; 32] = bytes.try_into().map_err(|_| TimeLockError::InvalidReleaseCode)?;
+        Ok(encode_release_code(&release_key))
+    }
+}
+
+/// Crockford base32 in groups of four, e.g. "7K2M-Q9XD-...", easy to read aloud
+pub fn encode_release_code(release_key: &[u8; 32]) -> String {
+    let mut symbols = Vec::with_capacity(RELEASE_CODE_LENGTH);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for &byte in release_key {
+        buffer = (buffer << 8) | u32::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            symbols.push(RELEASE_CODE_ALPHABET[((buffer >> bits) & 31) as usize]);
+        }
+        buffer &= (1 << bits) - 1;
+    }
+    if bits > 0 {
+        symbols.push(RELEASE_CODE_ALPHABET[((buffer << (5 - bits)) & 31) as usize]);
+    }
+    symbols
+        .chunks(RELEASE_CODE_GROUP)
+        .map(|group| String::from_utf8_lossy(group).into_owned())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Reads a release code back, forgiving case, spacing and look-alike letters
+pub fn decode_release_code(code: &str) -> Result<[u8; 32], TimeLockError> {
+    let mut values = Vec::with_capacity(RELEASE_CODE_LENGTH);
+    for c in code.chars().filter(|c| !c.is_whitespace() && *c != '-') {
+        let c = match c.to_ascii_uppercase() {
+            'O' => '0',
+            'I' | 'L' => '1',
+            other => other,
+        };
+        let value = RELEASE_CODE_ALPHABET
+            .iter()
+            .position(|&symbol| char::from(symbol) == c)
+            .ok_or(TimeLockError::InvalidReleaseCode)?;
+        values.push(value as u32);
+    }
+    if values.len() != RELEASE_CODE_LENGTH {
+        return Err(TimeLockError::InvalidReleaseCode);
+    }
+
+    let mut key = [0u8; 32];
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    let mut index = 0;
+    for value in values {
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            if index == key.len() {
+                return Err(TimeLockError::InvalidReleaseCode);
+            }
+            key[index] = (buffer >> bits) as u8;
+            index += 1;
+        }
+        buffer &= (1 << bits) - 1;
+    }
+    // The last symbol carries four padding bits, which must be zero
+    if index != key.len() || buffer != 0 {
+        return Err(TimeLockError::InvalidReleaseCode);
+    }
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        parse_date(value).unwrap()
+    }
+
+    fn settings(release_after: Option<&str>, check_in_days: Option<u32>) -> TimeLockSettings {
+        TimeLockSettings {
+            release_after: release_after.map(str::to_string),
+            check_in_days,
+            label: None,
+        }
+    }
+
+    #[test]
+    fn test_release_code_roundtrip() {
+        let key: [u8; 32] = std::array::from_fn(|i| (i * 37 + 11) as u8);
+        let code = encode_release_code(&key);
+        assert_eq!(code.replace('-', "").len(), RELEASE_CODE_LENGTH);
+        assert_eq!(decode_release_code(&code).unwrap(), key);
+        // Lowercase, spaces and look-alikes still work
+        let sloppy = code.to_lowercase().replace('-', " ").replace('0', "o").replace('1', "l");
+        assert_eq!(decode_release_code(&sloppy).unwrap(), key);
+    }
+
+    #[test]
+    fn test_release_code_rejects_garbage() {
+        assert!(decode_release_code("").is_err());
+        assert!(decode_release_code("ABCD-EFGH").is_err());
+        let code = encode_release_code(&[0xff; 32]);
+        assert!(decode_release_code(&format!("{}U", &code[..code.len() - 1])).is_err());
+    }
+
+    #[test]
+    fn test_question_share_needs_release_key() {
+        let (lock, _) = create(&settings(Some("2030-01-01"), None), date("2026-01-01")).unwrap();
+        let document_key = [7u8; 32];
+        let share = lock.question_share(&document_key);
+        assert_ne!(share, document_key);
+        assert_eq!(lock.question_share(&share), document_key);
+    }
+
+    #[test]
+    fn test_create_validates_settings() {
+        let today = date("2026-06-01");
+        assert!(matches!(create(&settings(None, None), today), Err(TimeLockError::NoCondition)));
+        assert!(matches!(create(&settings(Some("2026-06-01"), None), today), Err(TimeLockError::DateNotInFuture)));
+        assert!(matches!(create(&settings(Some("June 2027"), None), today), Err(TimeLockError::InvalidDate(_))));
+        assert!(matches!(create(&settings(None, Some(0)), today), Err(TimeLockError::InvalidCheckInDays(0))));
+        assert!(create(&settings(None, Some(90)), today).is_ok());
+    }
+
+    #[test]
+    fn test_release_by_date() {
+        let mut store = ReleaseStore::default();
+        let (lock, escrowed) = create(&settings(Some("2027-03-01"), None), date("2026-06-01")).unwrap();
+        let id = escrowed.id.clone();
+        store.deposit(escrowed, date("2026-06-01"));
+
+        assert!(matches!(store.release_code(&id, date("2027-02-28")), Err(TimeLockError::StillLocked(_))));
+        let code = store.release_code(&id, date("2027-03-01")).unwrap();
+        assert_eq!(decode_release_code(&code).unwrap(), lock.release_key);
+        assert!(store.overview(date("2027-03-01")).keys[0].released);
+    }
+
+    #[test]
+    fn test_release_after_missed_check_ins() {
+        let mut store = ReleaseStore::default();
+        let (_, escrowed) = create(&settings(Some("2040-01-01"), Some(30)), date("2026-06-01")).unwrap();
+        let id = escrowed.id.clone();
+        store.deposit(escrowed, date("2026-06-01"));
+
+        assert!(store.check_in(CheckIn::Explicit, date("2026-06-20")));
+        assert!(store.release_code(&id, date("2026-07-15")).is_err());
+        assert_eq!(store.overview(date("2026-07-15")).keys[0].releases_on.as_deref(), Some("2026-07-20"));
+        assert!(store.release_code(&id, date("2026-07-20")).is_ok());
+    }
+
+    #[test]
+    fn test_unlocking_the_app_does_not_hold_back_release() {
+        let mut store = ReleaseStore::default();
+        let (_, escrowed) = create(&settings(None, Some(30)), date("2026-06-01")).unwrap();
+        let id = escrowed.id.clone();
+        store.deposit(escrowed, date("2026-06-01"));
+
+        // The executor unlocks the app after the owner has gone quiet, to read the code
+        assert!(!store.check_in(CheckIn::AppUnlock, date("2026-07-05")));
+        assert_eq!(store.last_check_in.as_deref(), Some("2026-06-01"));
+        assert!(store.release_code(&id, date("2026-07-05")).is_ok());
+    }
+
+    #[test]
+    fn test_remove_escrowed_key() {
+        let mut store = ReleaseStore::default();
+        let (_, escrowed) = create(&settings(None, Some(7)), date("2026-06-01")).unwrap();
+        let id = escrowed.id.clone();
+        store.deposit(escrowed, date("2026-06-01"));
+        assert!(store.remove(&id).is_ok());
+        assert!(matches!(store.remove(&id), Err(TimeLockError::NotFound)));
+    }
+}
@@ -0,0 +1,434 @@
+use crate::models::{Contact, LegacyDocument};
+use std::collections::BTreeMap;
+
+/// Maximum line length in octets before folding (RFC 6350 §3.2)
+const MAX_LINE_OCTETS: usize = 75;
+
+const PRODID: &str = "-//scafidi.dev//Honey Did//EN";
+
+#[derive(Debug)]
+pub enum VcardError {
+    NoContacts,
+}
+
+impl std::fmt::Display for VcardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VcardError::NoContacts => write!(f, "No contacts found in vCard file"),
+        }
+    }
+}
+
+impl std::error::Error for VcardError {}
+
+/// Escapes a text value (RFC 6350 §3.4; iCalendar uses the same rules)
+pub(crate) fn escape_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ',' => out.push_str("\\,"),
+            ';' => out.push_str("\\;"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Folds a content line at 75 octets without splitting a UTF-8 character
+pub(crate) fn fold_line(line: &str, out: &mut String) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            // The leading space counts toward the next line's length
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+/// Splits a display name into the structured N property (family;given;additional;prefix;suffix)
+fn structured_name(name: &str) -> String {
+    let mut parts: Vec<&str> = name.split_whitespace().collect();
+    let family = if parts.len() > 1 { parts.pop().unwrap_or("") } else { "" };
+    format!("{};{};;;", escape_text(family), escape_text(&parts.join(" ")))
+}
+
+/// Writes a single contact as a vCard 4.0 object.
+/// `category` names the list the contact came from, e.g. "Family" or "Veterinarian".
+pub fn contact_to_vcard(contact: &Contact, category: &str) -> String {
+    let mut out = String::new();
+    let mut line = |l: String| fold_line(&l, &mut out);
+
+    line("BEGIN:VCARD".into());
+    line("VERSION:4.0".into());
+    line(format!("PRODID:{}", PRODID));
+    line(format!("FN:{}", escape_text(contact.name.trim())));
+    line(format!("N:{}", structured_name(contact.name.trim())));
+    if !contact.relationship.trim().is_empty() {
+        line(format!("ROLE:{}", escape_text(contact.relationship.trim())));
+    }
+    if !contact.phone.trim().is_empty() {
+        line(format!("TEL;TYPE=voice:{}", escape_text(contact.phone.trim())));
+    }
+    if !contact.email.trim().is_empty() {
+        line(format!("EMAIL:{}", escape_text(contact.email.trim())));
+    }
+    if !contact.notes.trim().is_empty() {
+        line(format!("NOTE:{}", escape_text(contact.notes.trim())));
+    }
+    if !category.is_empty() {
+        line(format!("CATEGORIES:{}", escape_text(category)));
+    }
+    line("END:VCARD".into());
+    out
+}
+
+/// Returns every named contact in the document with the category it is exported under
+pub fn document_contacts(document: &LegacyDocument) -> Vec<(&Contact, String)> {
+    let mut contacts: Vec<(&Contact, String)> = Vec::new();
+    for c in &document.contacts.emergency_contacts {
+        contacts.push((c, "Emergency Contact".into()));
+    }
+    for c in &document.contacts.family {
+        contacts.push((c, "Family".into()));
+    }
+    for c in &document.contacts.professionals {
+        contacts.push((c, "Professional".into()));
+    }
+    contacts.push((&document.legal.attorney, "Attorney".into()));
+    for c in &document.household.contractors {
+        contacts.push((c, "Contractor".into()));
+    }
+    for member in &document.medical.family_members {
+        for c in &member.doctors {
+            contacts.push((c, format!("Doctor ({})", member.name)));
+        }
+        contacts.push((&member.pharmacy, format!("Pharmacy ({})", member.name)));
+    }
+    for pet in &document.pets.pets {
+        contacts.push((&pet.vet, format!("Veterinarian ({})", pet.name)));
+    }
+
+    contacts.retain(|(c, _)| !c.name.trim().is_empty());
+    contacts
+}
+
+/// Key the exported HTML uses to find the card for a rendered contact
+pub fn contact_card_key(contact: &Contact) -> String {
+    format!("{}|{}|{}", contact.name, contact.phone, contact.email)
+}
+
+/// Writes all contacts in the document as one `.vcf` bundle
+pub fn document_to_vcf(document: &LegacyDocument) -> String {
+    document_contacts(document)
+        .into_iter()
+        .map(|(c, category)| contact_to_vcard(c, &category))
+        .collect()
+}
+
+/// Per-contact cards keyed by `contact_card_key`, for the "Save contact" links in the export
+pub fn document_contact_cards(document: &LegacyDocument) -> BTreeMap<String, String> {
+    document_contacts(document)
+        .into_iter()
+        .map(|(c, category)| (contact_card_key(c), contact_to_vcard(c, &category)))
+        .collect()
+}
+
+/// Reverses `escape_text`; also accepts the unescaped commas older writers emit
+fn unescape_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => out.push('\\'),
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Decodes vCard 2.1 QUOTED-PRINTABLE values, as written by many phone address books
+fn decode_quoted_printable(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'=' {
+            let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Joins folded lines. Quoted-printable soft line breaks ("=" at end of line) are joined too.
+fn unfold(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut qp_continuation = false;
+    for raw in content.split('\n') {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        if qp_continuation {
+            if let Some(last) = lines.last_mut() {
+                last.pop(); // soft line break "="
+                last.push_str(raw);
+            }
+        } else if raw.starts_with(' ') || raw.starts_with('\t') {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(&raw[1..]);
+            }
+        } else {
+            lines.push(raw.to_string());
+        }
+        qp_continuation = lines
+            .last()
+            .map(|l| l.ends_with('=') && l.to_uppercase().contains("QUOTED-PRINTABLE"))
+            .unwrap_or(false);
+    }
+    lines
+}
+
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn has_param(&self, key: &str, value: &str) -> bool {
+        self.params
+            .iter()
+            .any(|(k, v)| k == key && v.split(',').any(|part| part.trim_matches('"').eq_ignore_ascii_case(value)))
+    }
+
+    /// The value with any transfer encoding removed, still escaped
+    fn decoded(&self) -> String {
+        if self.has_param("ENCODING", "QUOTED-PRINTABLE") || self.has_param("TYPE", "QUOTED-PRINTABLE") {
+            decode_quoted_printable(&self.value)
+        } else {
+            self.value.clone()
+        }
+    }
+
+    fn text(&self) -> String {
+        unescape_text(&self.decoded())
+    }
+}
+
+/// Splits "item1.TEL;TYPE=cell:+1 555" into its name, parameters and value
+fn parse_property(line: &str) -> Option<Property> {
+    let mut in_quotes = false;
+    let colon = line.char_indices().find(|(_, c)| {
+        if *c == '"' {
+            in_quotes = !in_quotes;
+        }
+        *c == ':' && !in_quotes
+    })?.0;
+
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?.rsplit('.').next()?.trim().to_uppercase();
+    let params = parts
+        .map(|p| match p.split_once('=') {
+            Some((k, v)) => (k.trim().to_uppercase(), v.trim().to_string()),
+            // vCard 2.1 allows bare parameters: TEL;CELL;PREF:...
+            None => ("TYPE".to_string(), p.trim().to_string()),
+        })
+        .collect();
+
+    Some(Property { name, params, value: value.to_string() })
+}
+
+/// Picks the preferred value among repeated properties (TEL, EMAIL)
+fn preferred(props: &[&Property]) -> String {
+    props
+        .iter()
+        .find(|p| p.has_param("TYPE", "pref") || p.params.iter().any(|(k, _)| k == "PREF"))
+        .or_else(|| props.first())
+        .map(|p| p.text())
+        .unwrap_or_default()
+}
+
+fn card_to_contact(props: &[Property]) -> Contact {
+    let find = |name: &str| props.iter().find(|p| p.name == name).map(|p| p.text()).unwrap_or_default();
+
+    let mut name = find("FN").trim().to_string();
+    if name.is_empty() {
+        // N is family;given;additional;prefix;suffix
+        let n = props.iter().find(|p| p.name == "N").map(|p| p.decoded()).unwrap_or_default();
+        let parts: Vec<String> = n.split(';').map(unescape_text).collect();
+        let ordered = [parts.get(3), parts.get(1), parts.get(2), parts.first(), parts.get(4)];
+        name = ordered
+            .iter()
+            .flatten()
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+    }
+    let org = find("ORG").split(';').next().unwrap_or("").trim().to_string();
+    if name.is_empty() {
+        name = org.clone();
+    }
+
+    let phones: Vec<&Property> = props.iter().filter(|p| p.name == "TEL").collect();
+    let emails: Vec<&Property> = props.iter().filter(|p| p.name == "EMAIL").collect();
+    let phone = preferred(&phones);
+    let phone = phone.strip_prefix("tel:").unwrap_or(&phone).to_string();
+    let email = preferred(&emails);
+    let email = email.strip_prefix("mailto:").unwrap_or(&email).to_string();
+
+    let relationship = [find("ROLE"), find("TITLE"), find("X-HONEYDID-RELATIONSHIP")]
+        .into_iter()
+        .find(|r| !r.trim().is_empty())
+        .unwrap_or_default();
+
+    let mut notes = find("NOTE");
+    if !org.is_empty() && org != name {
+        notes = if notes.is_empty() { org } else { format!("{}\n{}", org, notes) };
+    }
+
+    Contact {
+        name,
+        relationship: relationship.trim().to_string(),
+        phone: phone.trim().to_string(),
+        email: email.trim().to_string(),
+        notes: notes.trim().to_string(),
+    }
+}
+
+/// Parses every card in a `.vcf` file (vCard 2.1, 3.0 and 4.0).
+/// Cards without a name, phone or email are ignored.
+pub fn parse_vcards(content: &str) -> Result<Vec<Contact>, VcardError> {
+    let content = content.trim_start_matches('\u{feff}');
+    let mut contacts = Vec::new();
+    let mut current: Option<Vec<Property>> = None;
+
+    for line in unfold(content) {
+        let Some(prop) = parse_property(&line) else { continue };
+        match (prop.name.as_str(), prop.value.trim().to_uppercase().as_str()) {
+            ("BEGIN", "VCARD") => current = Some(Vec::new()),
+            ("END", "VCARD") => {
+                if let Some(props) = current.take() {
+                    let contact = card_to_contact(&props);
+                    if !contact.name.is_empty() || !contact.phone.is_empty() || !contact.email.is_empty() {
+                        contacts.push(contact);
+                    }
+                }
+            }
+            _ => {
+                if let Some(props) = current.as_mut() {
+                    props.push(prop);
+                }
+            }
+        }
+    }
+
+    if contacts.is_empty() {
+        return Err(VcardError::NoContacts);
+    }
+    Ok(contacts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_contact() -> Contact {
+        Contact {
+            name: "María José García".into(),
+            relationship: "Estate attorney".into(),
+            phone: "+1 555-0100".into(),
+            email: "mj@example.com".into(),
+            notes: "Has the original will; call weekdays, 9-5.\nAsk for the file number.".into(),
+        }
+    }
+
+    #[test]
+    fn test_vcard_roundtrip() {
+        let contact = sample_contact();
+        let card = contact_to_vcard(&contact, "Attorney");
+
+        assert!(card.starts_with("BEGIN:VCARD\r\nVERSION:4.0\r\n"));
+        assert!(card.contains("N:García;María José;;;\r\n"));
+        assert!(card.contains("CATEGORIES:Attorney\r\n"));
+
+        let parsed = parse_vcards(&card).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].name, contact.name);
+        assert_eq!(parsed[0].relationship, contact.relationship);
+        assert_eq!(parsed[0].phone, contact.phone);
+        assert_eq!(parsed[0].email, contact.email);
+        assert_eq!(parsed[0].notes, contact.notes);
+    }
+
+    #[test]
+    fn test_long_lines_are_folded_at_75_octets() {
+        let mut contact = sample_contact();
+        contact.notes = "ü".repeat(100);
+        let card = contact_to_vcard(&contact, "Family");
+
+        assert!(card.split("\r\n").all(|l| l.len() <= MAX_LINE_OCTETS));
+        assert_eq!(parse_vcards(&card).unwrap()[0].notes, contact.notes);
+    }
+
+    #[test]
+    fn test_parses_phone_address_book_exports() {
+        let vcf = "BEGIN:VCARD\r\n\
+                   VERSION:2.1\r\n\
+                   N;CHARSET=UTF-8;ENCODING=QUOTED-PRINTABLE:M=C3=BCller;J=C3=BCrgen;;;\r\n\
+                   TEL;HOME:555-0100\r\n\
+                   TEL;CELL;PREF:555-0199\r\n\
+                   END:VCARD\r\n\
+                   BEGIN:VCARD\n\
+                   VERSION:3.0\n\
+                   FN:Dr. Smith\n\
+                   ORG:Lakeside Clinic;Cardiology\n\
+                   item1.EMAIL;type=INTERNET;type=pref:smith@\n clinic.example\n\
+                   TITLE:Cardiologist\n\
+                   END:VCARD\n\
+                   BEGIN:VCARD\nVERSION:3.0\nEND:VCARD\n";
+        let contacts = parse_vcards(vcf).unwrap();
+
+        assert_eq!(contacts.len(), 2);
+        assert_eq!(contacts[0].name, "Jürgen Müller");
+        assert_eq!(contacts[0].phone, "555-0199");
+        assert_eq!(contacts[1].email, "smith@clinic.example");
+        assert_eq!(contacts[1].relationship, "Cardiologist");
+        assert_eq!(contacts[1].notes, "Lakeside Clinic");
+    }
+
+    #[test]
+    fn test_document_bundle_includes_embedded_contacts() {
+        let mut doc = LegacyDocument::default();
+        doc.contacts.family.push(Contact { name: "Ann Lee".into(), ..Default::default() });
+        doc.legal.attorney = Contact { name: "Bob Law".into(), ..Default::default() };
+        doc.pets.pets.push(crate::models::Pet {
+            name: "Rex".into(),
+            vet: Contact { name: "Paws Clinic".into(), ..Default::default() },
+            ..Default::default()
+        });
+
+        let vcf = document_to_vcf(&doc);
+        assert_eq!(parse_vcards(&vcf).unwrap().len(), 3);
+        assert!(vcf.contains("CATEGORIES:Veterinarian (Rex)"));
+        assert!(document_contact_cards(&doc).contains_key("Paws Clinic||"));
+        assert!(matches!(parse_vcards(""), Err(VcardError::NoContacts)));
+    }
+}
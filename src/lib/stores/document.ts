@@ -82,6 +82,7 @@ export interface LegalSection {
 export interface DigitalSection {
   email_accounts: any[];
   social_media: any[];
+  online_accounts?: any[];
   password_manager: any;
   notes: string;
   attachments: FileAttachment[];
@@ -130,6 +131,7 @@ export interface MessageSlide {
   type: SlideType;
   text: string;
   answer?: string;
+  alternate_answers?: string[];
   transition: { type: 'click' } | { type: 'auto'; seconds: number };
 }
 
@@ -137,6 +139,10 @@ export interface WelcomeScreen {
   enabled: boolean;
   slides: MessageSlide[];
   fallback_passphrase?: string;
+  /** How many question answers unlock the file; unset means all of them */
+  required_answers?: number;
+  /** Wrong tries at the questions before the file only opens with the backup passphrase; unset means no limit */
+  attempt_limit?: number;
 }
 
 // Custom Sections
@@ -227,7 +233,7 @@ function createEmptyDocument(): LegacyDocument {
     bills: { bills: [], notes: '', attachments: [] },
     property: { properties: [], vehicles: [], valuables: [], notes: '', attachments: [] },
     legal: { will_location: '', attorney: { name: '', relationship: '', phone: '', email: '', notes: '' }, power_of_attorney: '', trusts: [], notes: '', attachments: [] },
-    digital: { email_accounts: [], social_media: [], password_manager: { name: '', master_password_hint: '', recovery_method: '', notes: '' }, notes: '', attachments: [] },
+    digital: { email_accounts: [], social_media: [], online_accounts: [], password_manager: { name: '', master_password_hint: '', recovery_method: '', notes: '' }, notes: '', attachments: [] },
     household: { maintenance_items: [], contractors: [], how_things_work: [], notes: '', attachments: [] },
     personal: { funeral_preferences: '', obituary_notes: '', messages: [], notes: '', attachments: [] },
     contacts: { emergency_contacts: [], family: [], professionals: [], notes: '', attachments: [] },
@@ -398,7 +404,8 @@ export function isDocumentEmpty(doc: LegacyDocument | null): boolean {
     !!doc.legal.will_location ||
     !!doc.legal.power_of_attorney;
   const hasDigital = doc.digital.email_accounts.length > 0 ||
-    doc.digital.social_media.length > 0;
+    doc.digital.social_media.length > 0 ||
+    (doc.digital.online_accounts?.length ?? 0) > 0;
   const hasHousehold = doc.household.maintenance_items.length > 0 ||
     doc.household.contractors.length > 0 ||
     doc.household.how_things_work.length > 0;
@@ -444,7 +451,7 @@ export const insuranceStore = createSectionStore('insurance', { policies: [], no
 export const billsStore = createSectionStore('bills', { bills: [], notes: '', attachments: [] });
 export const propertyStore = createSectionStore('property', { properties: [], vehicles: [], valuables: [], notes: '', attachments: [] });
 export const legalStore = createSectionStore('legal', { will_location: '', attorney: { name: '', relationship: '', phone: '', email: '', notes: '' }, power_of_attorney: '', trusts: [], notes: '', attachments: [] });
-export const digitalStore = createSectionStore('digital', { email_accounts: [], social_media: [], password_manager: { name: '', master_password_hint: '', recovery_method: '', notes: '' }, notes: '', attachments: [] });
+export const digitalStore = createSectionStore('digital', { email_accounts: [], social_media: [], online_accounts: [], password_manager: { name: '', master_password_hint: '', recovery_method: '', notes: '' }, notes: '', attachments: [] });
 export const householdStore = createSectionStore('household', { maintenance_items: [], contractors: [], how_things_work: [], notes: '', attachments: [] });
 export const personalStore = createSectionStore('personal', { funeral_preferences: '', obituary_notes: '', messages: [], notes: '', attachments: [] });
 export const contactsStore = createSectionStore('contacts', { emergency_contacts: [], family: [], professionals: [], notes: '', attachments: [] });
@@ -0,0 +1,26 @@
+import { describe, it, expect } from 'vitest';
+import { readFileSync } from 'fs';
+import { resolve } from 'path';
+
+// The exported HTML embeds this script; the Rust side (questions.rs) checks the same vectors
+const script = readFileSync(resolve(__dirname, '../../src-tauri/src/answer_normalization.js'), 'utf-8');
+const vectors = JSON.parse(
+  readFileSync(resolve(__dirname, '../../src-tauri/src/answer_normalization_vectors.json'), 'utf-8')
+);
+
+const { normalizeAnswer, questionKeyPassphrase } = new Function(
+  `${script}\nreturn { normalizeAnswer, questionKeyPassphrase };`
+)() as {
+  normalizeAnswer: (answer: string) => string;
+  questionKeyPassphrase: (answers: string[]) => string;
+};
+
+describe('question answer normalization', () => {
+  it.each(vectors.normalize)('normalizes $input', ({ input, expected }) => {
+    expect(normalizeAnswer(input)).toBe(expected);
+  });
+
+  it.each(vectors.passphrase)('joins $answers', ({ answers, expected }) => {
+    expect(questionKeyPassphrase(answers)).toBe(expected);
+  });
+});
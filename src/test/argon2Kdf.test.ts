@@ -0,0 +1,39 @@
+import { describe, it, expect } from 'vitest';
+import { readFileSync } from 'fs';
+import { resolve } from 'path';
+
+// The exported HTML embeds this script and module; encryption.rs checks the same vectors with the argon2 crate
+const srcDir = resolve(__dirname, '../../src-tauri/src');
+const script = readFileSync(resolve(srcDir, 'argon2_kdf.js'), 'utf-8');
+const wasmBase64 = readFileSync(resolve(srcDir, 'argon2.wasm')).toString('base64');
+const vectors = JSON.parse(readFileSync(resolve(srcDir, 'argon2_kdf_vectors.json'), 'utf-8'));
+
+interface Argon2Params {
+  memory_kib: number;
+  iterations: number;
+  parallelism: number;
+}
+
+const { argon2id } = new Function('ARGON2_WASM', `${script}\nreturn { argon2id };`)(wasmBase64) as {
+  argon2id: (password: Uint8Array, salt: Uint8Array, kdf: Argon2Params) => Promise<Uint8Array>;
+};
+
+const fromHex = (hex: string) => Uint8Array.from(hex.match(/../g) ?? [], (byte) => parseInt(byte, 16));
+const toHex = (bytes: Uint8Array) => Array.from(bytes, (byte) => byte.toString(16).padStart(2, '0')).join('');
+
+describe('embedded Argon2id', () => {
+  it.each(vectors.argon2id)(
+    'derives the same key as the app for $memory_kib KiB, $iterations passes',
+    async (v) => {
+      const key = await argon2id(new TextEncoder().encode(v.passphrase), fromHex(v.salt), v);
+      expect(toHex(key)).toBe(v.key);
+    },
+    30000
+  );
+
+  it('rejects more than one lane', async () => {
+    await expect(
+      argon2id(new Uint8Array(), fromHex('0001020304050607'), { memory_kib: 64, iterations: 1, parallelism: 2 })
+    ).rejects.toThrow();
+  });
+});
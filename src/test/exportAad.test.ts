@@ -0,0 +1,22 @@
+import { describe, it, expect } from 'vitest';
+import { readFileSync } from 'fs';
+import { resolve } from 'path';
+
+// The exported HTML embeds this script; the Rust side (export.rs) checks the same vectors
+const script = readFileSync(resolve(__dirname, '../../src-tauri/src/export_aad.js'), 'utf-8');
+const vectors = JSON.parse(readFileSync(resolve(__dirname, '../../src-tauri/src/export_aad_vectors.json'), 'utf-8'));
+
+const { exportAssociatedData } = new Function(`${script}\nreturn { exportAssociatedData };`)() as {
+  exportAssociatedData: (header: { format: number; created: string; creator: string; lock_screen?: string }, slot: string) => string;
+};
+
+describe('export associated data', () => {
+  it.each(vectors.associated_data)('binds $header.creator to the $slot slot', ({ header, slot, expected }) => {
+    expect(exportAssociatedData(header, slot)).toBe(expected);
+  });
+
+  it('binds every edit to the lock screen', () => {
+    const expected = vectors.associated_data.map((v: { expected: string }) => v.expected);
+    expect(new Set(expected).size).toBe(expected.length);
+  });
+});
@@ -0,0 +1,52 @@
+import { describe, it, expect } from 'vitest';
+import { readFileSync } from 'fs';
+import { resolve } from 'path';
+
+// The exported HTML embeds this script; the Rust side (search.rs) checks the same vectors
+const script = readFileSync(resolve(__dirname, '../../src-tauri/src/search_matching.js'), 'utf-8');
+const vectors = JSON.parse(readFileSync(resolve(__dirname, '../../src-tauri/src/search_vectors.json'), 'utf-8'));
+
+interface SearchTerm {
+  word: string;
+  stem: string;
+  primary: string;
+  alternate: string;
+}
+
+const { searchTokens, searchStem, doubleMetaphone, searchQuery, makeSearchTerm, classifySearchMatch } = new Function(
+  `${script}\nreturn { searchTokens, searchStem, doubleMetaphone, searchQuery, makeSearchTerm, classifySearchMatch };`
+)() as {
+  searchTokens: (text: string) => { text: string; word: string; start: number; end: number }[];
+  searchStem: (word: string) => string;
+  doubleMetaphone: (word: string) => [string, string];
+  searchQuery: (text: string) => object | null;
+  makeSearchTerm: (word: string) => SearchTerm;
+  classifySearchMatch: (query: object, term: SearchTerm) => string | null;
+};
+
+describe('export search matching', () => {
+  it.each(vectors.tokenize)('tokenizes $input', ({ input, expected }) => {
+    expect(searchTokens(input).map((t) => t.word)).toEqual(expected);
+  });
+
+  it.each(vectors.stem)('stems $input', ({ input, expected }) => {
+    expect(searchStem(input)).toBe(expected);
+  });
+
+  it.each(vectors.metaphone)('encodes $input', ({ input, expected }) => {
+    expect(doubleMetaphone(input)).toEqual(expected);
+  });
+
+  it.each(vectors.classify)('classifies $word for $query', ({ query, word, expected }) => {
+    expect(classifySearchMatch(searchQuery(query)!, makeSearchTerm(word))).toBe(expected);
+  });
+
+  it('reports token offsets in the original text', () => {
+    const text = 'Call José’s bank';
+    expect(searchTokens(text).map((t) => text.slice(t.start, t.end))).toEqual(['Call', 'José’s', 'bank']);
+  });
+
+  it('ignores terms shorter than two characters', () => {
+    expect(searchQuery(' a ')).toBeNull();
+  });
+});